@@ -0,0 +1,48 @@
+//! Compares rendering with the boxed pipeline of `Synth::new` against the
+//! statically dispatched one of `Synth::new_static`, for short sounds, where
+//! building the pipeline takes a large part of the render, and for every
+//! example.
+//!
+//! Usage: `cargo bench --bench dispatch`
+
+use std::time::Instant;
+
+use jfxr::parameter::*;
+use jfxr::sound::Example;
+use jfxr::{Sound, Synth};
+
+const ROUNDS: usize = 200;
+
+fn time<T>(mut f: impl FnMut() -> T) -> f64 {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        std::hint::black_box(f());
+    }
+    start.elapsed().as_secs_f64() / ROUNDS as f64
+}
+
+fn compare(name: &str, sound: &Sound) {
+    let dynamic = time(|| Synth::new(sound).generate());
+    let fixed = time(|| Synth::new_static(sound).generate());
+    println!(
+        "{name:<12} {:>7} samples: dynamic {:>9.1} us, static {:>9.1} us ({:.2}x)",
+        sound.num_samples(),
+        dynamic * 1e6,
+        fixed * 1e6,
+        dynamic / fixed
+    );
+}
+
+fn main() {
+    for sustain in [0.001, 0.01, 0.1] {
+        let sound = Sound {
+            waveform: Waveform::Square,
+            sustain: Sustain(sustain),
+            ..Default::default()
+        };
+        compare(&format!("{} ms", sustain * 1000.0), &sound);
+    }
+    for example in Example::ALL {
+        compare(example.name(), &Sound::example(*example));
+    }
+}
@@ -0,0 +1,48 @@
+//! Compares evaluating the model one time at a time against the vectorized
+//! variants, for the frequency and amplitude curves of every example.
+//!
+//! Usage: `cargo bench --bench model`
+
+use std::time::Instant;
+
+use jfxr::sound::Example;
+
+const ROUNDS: usize = 20;
+
+fn time<T>(mut f: impl FnMut() -> T) -> f64 {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        std::hint::black_box(f());
+    }
+    start.elapsed().as_secs_f64() / ROUNDS as f64
+}
+
+fn main() {
+    for example in Example::ALL {
+        let mut sound = jfxr::Sound::example(*example);
+        sound.vibrato_depth.0 = 10.0;
+        sound.tremolo_depth.0 = 50.0;
+        sound.repeat_decay.0 = 20.0;
+        let n = sound.num_samples();
+        let duration = sound.duration();
+        let times: Vec<f64> = (0..n).map(|i| i as f64 * duration / n as f64).collect();
+        let mut out = vec![0.0; n];
+
+        let scalar = time(|| times.iter().map(|time| sound.frequency_at(*time) + sound.amplitude_at(*time)).sum::<f64>());
+        let vectorized = time(|| {
+            sound.frequencies_at(&times, &mut out);
+            let frequencies = out.iter().sum::<f64>();
+            sound.amplitudes_at(&times, &mut out);
+            frequencies + out.iter().sum::<f64>()
+        });
+        let between = time(|| (sound.frequencies_between(0.0, duration, n), sound.amplitudes_between(0.0, duration, n)));
+        println!(
+            "{:<10} {:>7} samples: scalar {:>8.3} ms, at {:>8.3} ms, between {:>8.3} ms",
+            example.name(),
+            n,
+            scalar * 1e3,
+            vectorized * 1e3,
+            between * 1e3,
+        );
+    }
+}
@@ -0,0 +1,71 @@
+//! Measures the render throughput of every waveform, with 0 and 5
+//! harmonics, of sine waves with up to 32 harmonics, and of a sound that
+//! uses every filter, as a baseline for changes to the speed of the engine.
+//! Outside of criterion, [`jfxr::bench::bench_render`] takes the same
+//! measurements, along with the number of allocations.
+//!
+//! Usage: `cargo bench --bench render`
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use jfxr::parameter::*;
+use jfxr::Sound;
+
+fn base() -> Sound {
+    Sound {
+        sustain: Sustain(0.5),
+        ..Default::default()
+    }
+}
+
+fn bench_sound(c: &mut Criterion, group: &str, parameter: impl std::fmt::Display, sound: &Sound) {
+    let mut group = c.benchmark_group(group);
+    group.throughput(Throughput::Elements(sound.num_samples() as u64));
+    group.bench_with_input(BenchmarkId::from_parameter(parameter), sound, |b, sound| b.iter(|| jfxr::generate(std::hint::black_box(sound))));
+    group.finish();
+}
+
+fn waveforms(c: &mut Criterion) {
+    for waveform in Waveform::VALUES {
+        for harmonics in [0, 5] {
+            let sound = Sound {
+                waveform: *waveform,
+                harmonics: Harmonics(harmonics),
+                ..base()
+            };
+            bench_sound(c, waveform.name(), format_args!("harmonics {harmonics}"), &sound);
+        }
+    }
+}
+
+fn additive(c: &mut Criterion) {
+    // Sine waves with more than 5 harmonics are summed in a single pass.
+    for harmonics in [16, 32] {
+        let sound = Sound {
+            waveform: Waveform::Sine,
+            harmonics: Harmonics(harmonics),
+            ..base()
+        };
+        bench_sound(c, "sine", format_args!("harmonics {harmonics}"), &sound);
+    }
+}
+
+fn filtered(c: &mut Criterion) {
+    let sound = Sound {
+        flanger_offset: FlangerOffset(5.0),
+        flanger_offset_sweep: FlangerOffsetSweep(5.0),
+        chorus_voices: ChorusVoices(3),
+        bit_crush: BitCrush(8),
+        bit_crush_sweep: BitCrushSweep(4),
+        low_pass_cutoff: LowPassCutoff(2000.0),
+        low_pass_cutoff_sweep: LowPassCutoffSweep(5000.0),
+        high_pass_cutoff: HighPassCutoff(100.0),
+        high_pass_cutoff_sweep: HighPassCutoffSweep(500.0),
+        compression: Compression(0.5),
+        reverb_amount: ReverbAmount(50.0),
+        ..base()
+    };
+    bench_sound(c, "filtered", "every filter", &sound);
+}
+
+criterion_group!(benches, waveforms, additive, filtered);
+criterion_main!(benches);
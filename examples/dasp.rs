@@ -0,0 +1,37 @@
+//! Chains a sound into a `dasp` signal graph, which runs it through a
+//! one-pole low-pass filter before writing it to a WAV file.
+//!
+//! Usage: `cargo run --example dasp --features dasp [output.wav]`
+
+use dasp::Signal;
+
+fn main() -> std::io::Result<()> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "dasp.wav".to_string());
+
+    let sound = jfxr::Sound {
+        name: "Buzz".to_string(),
+        waveform: jfxr::parameter::Waveform::Sawtooth,
+        frequency: jfxr::parameter::Frequency(220.0),
+        sustain: jfxr::parameter::Sustain(1.0),
+        decay: jfxr::parameter::Decay(0.5),
+        ..Default::default()
+    };
+
+    // `dasp` has no filters of its own, so the low-pass is a simple closure
+    // that keeps its state between samples.
+    let cutoff = 800.0;
+    let alpha = 1.0 - (-2.0 * std::f64::consts::PI * cutoff / sound.sample_rate.0).exp();
+    let mut state = 0.0;
+    let signal = jfxr::Synth::new(&sound).stream().samples();
+    let filtered = Signal::map(signal, move |sample| {
+        state += alpha * (sample - state);
+        state
+    })
+    .until_exhausted();
+
+    let samples: Vec<f64> = filtered.collect();
+    let file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+    jfxr::wav::write_wav(&samples, sound.sample_rate.0 as u32, file)?;
+    println!("Wrote {} samples to {}", samples.len(), path);
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! A minimal sound editor, which re-renders the sound whenever a parameter
+//! changes and shows the resulting waveform.
+//!
+//! Usage: `cargo run --example editor --features egui,eframe`
+
+use eframe::egui;
+
+struct Editor {
+    sound: jfxr::Sound,
+    samples: Vec<f64>,
+}
+
+impl Editor {
+    fn new() -> Self {
+        let sound = jfxr::Sound::default();
+        let samples = jfxr::try_generate(&sound).unwrap_or_default();
+        Self { sound, samples }
+    }
+
+    fn waveform(&self, ui: &mut egui::Ui) {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 100.0), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+        let columns = rect.width().max(1.0) as usize;
+        let stroke = egui::Stroke::new(1.0, ui.visuals().text_color());
+        for column in 0..columns {
+            let start = column * self.samples.len() / columns;
+            let end = ((column + 1) * self.samples.len() / columns).max(start + 1).min(self.samples.len());
+            let (min, max) = self.samples[start..end]
+                .iter()
+                .fold((0.0f64, 0.0f64), |(min, max), sample| (min.min(*sample), max.max(*sample)));
+            let x = rect.left() + column as f32;
+            let y = |value: f64| rect.center().y - value as f32 * rect.height() / 2.0;
+            painter.line_segment([egui::pos2(x, y(max)), egui::pos2(x, y(min))], stroke);
+        }
+    }
+}
+
+impl eframe::App for Editor {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::TopBottomPanel::top("waveform").show(ctx, |ui| {
+            ui.label(format!("{:.2} s, {} samples", self.sound.duration(), self.samples.len()));
+            self.waveform(ui);
+        });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                if jfxr::ui::sound_editor(ui, &mut self.sound) {
+                    // Sounds can be edited into states that cannot be
+                    // generated, such as a zero duration.
+                    self.samples = jfxr::try_generate(&self.sound).unwrap_or_default();
+                }
+            });
+        });
+    }
+}
+
+fn main() -> eframe::Result {
+    eframe::run_native("jfxr", eframe::NativeOptions::default(), Box::new(|_| Ok(Box::new(Editor::new()))))
+}
@@ -0,0 +1,31 @@
+//! Renders a minute of brown noise wind straight to a WAV file, without ever
+//! holding the whole sound in memory.
+//!
+//! Usage: `cargo run --example wind [output.wav]`
+
+fn main() -> std::io::Result<()> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "wind.wav".to_string());
+
+    let sound = jfxr::Sound {
+        name: "Wind".to_string(),
+        waveform: jfxr::parameter::Waveform::Brownnoise,
+        frequency: jfxr::parameter::Frequency(800.0),
+        attack: jfxr::parameter::Attack(2.0),
+        sustain: jfxr::parameter::Sustain(56.0),
+        decay: jfxr::parameter::Decay(2.0),
+        tremolo_depth: jfxr::parameter::TremoloDepth(40.0),
+        tremolo_frequency: jfxr::parameter::TremoloFrequency(0.2),
+        low_pass_cutoff: jfxr::parameter::LowPassCutoff(2000.0),
+        ..Default::default()
+    };
+
+    let mut stream = jfxr::Synth::new(&sound).stream();
+    let file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+    let mut writer = jfxr::wav::StreamWriter::new(file, sound.sample_rate.0 as u32)?;
+    while let Some(block) = stream.next_block() {
+        writer.write_block(block)?;
+    }
+    writer.finalize()?;
+    println!("Wrote {} samples to {}", stream.num_samples(), path);
+    Ok(())
+}
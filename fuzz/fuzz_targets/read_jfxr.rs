@@ -0,0 +1,27 @@
+//! Parses arbitrary input as an untrusted `.jfxr` file and renders the
+//! result, which must neither panic nor produce non-finite samples. Peeking
+//! at the version of the input must not panic either.
+//!
+//! Usage: `cargo +nightly fuzz run read_jfxr`
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(data) = std::str::from_utf8(data) else {
+        return;
+    };
+    let _ = jfxr::jfxr::peek_version(data);
+    let Ok(sound) = jfxr::jfxr::read_jfxr_untrusted(data, &jfxr::jfxr::Limits::default()) else {
+        return;
+    };
+    let Ok(mut synth) = jfxr::Synth::try_new(&sound) else {
+        return;
+    };
+    // Keep each run short; longer sounds only take more time.
+    synth.set_max_duration(1.0);
+    if let Ok(samples) = synth.try_generate() {
+        assert!(samples.iter().all(|sample| sample.is_finite()));
+    }
+});
@@ -0,0 +1,405 @@
+//! Measurements of the level, clicks and pitch of rendered samples.
+
+/// Length of the gating blocks of [`LoudnessMeter`], in seconds.
+const BLOCK_DURATION: f64 = 0.4;
+
+/// Number of steps into which each gating block is divided; consecutive
+/// blocks overlap by all but one step.
+const BLOCK_STEPS: usize = 4;
+
+/// Blocks quieter than this are ignored by [`LoudnessMeter`], in LUFS.
+const ABSOLUTE_GATE: f64 = -70.0;
+
+/// Blocks quieter than this, relative to the loudness of the blocks that
+/// pass the absolute gate, are ignored by [`LoudnessMeter`], in LU.
+const RELATIVE_GATE: f64 = -10.0;
+
+/// Number of samples on each side of a jump that [`detect_clicks`] compares
+/// it to, which covers a full period of waveforms down to about 20 Hz at
+/// 44100 Hz.
+const CLICK_CONTEXT: usize = 2048;
+
+/// Factor by which a jump must exceed the largest jump around it for
+/// [`detect_clicks`] to report it.
+const CLICK_RATIO: f64 = 2.0;
+
+/// Largest value of the cumulative mean normalized difference at which
+/// [`detect_pitch`] accepts a period, as proposed for YIN. Aperiodic
+/// windows, such as noise, stay above it.
+const PITCH_THRESHOLD: f64 = 0.1;
+
+/// Threshold of [`detect_clicks`] used by [`crate::Synth::render_report`]:
+/// a jump of a tenth of full scale, about -20 dBFS.
+pub const DEFAULT_CLICK_THRESHOLD: f64 = 0.1;
+
+/// Peak level, in dBFS, below which [`crate::Synth::render_report`] treats a
+/// render as silent.
+pub const SILENCE_THRESHOLD: f64 = -60.0;
+
+/// Converts a level in decibels into a linear gain.
+pub fn db_to_gain(db: f64) -> f64 {
+    crate::math::pow(10.0, db / 20.0)
+}
+
+/// Converts a linear gain into a level in decibels. Silence is negative
+/// infinity.
+pub fn gain_to_db(gain: f64) -> f64 {
+    20.0 * crate::math::log10(gain)
+}
+
+/// Returns the largest absolute value of the samples.
+pub fn peak(samples: &[f64]) -> f64 {
+    samples.iter().fold(0.0, |peak, sample| peak.max(sample.abs()))
+}
+
+/// A jump in the samples that is likely audible as a click, as found by
+/// [`detect_clicks`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClickReport {
+    /// Index of the sample after the jump. A click at the start of the
+    /// samples has index 0, and a click at the end has the number of
+    /// samples as its index.
+    pub index: usize,
+    /// Absolute difference between the samples on either side of the jump.
+    pub magnitude: f64,
+}
+
+/// Finds jumps between consecutive samples that are larger than the
+/// threshold, which is relative to full scale, and are not explained by the
+/// waveform. The samples are taken to be surrounded by silence, so a first
+/// or last sample that is larger than the threshold is a click at the start
+/// or end, as the playback jumps from or to zero. Any other jump is only
+/// reported if it is more than twice as large as every jump within 2048
+/// samples before and after it, so that the regular edges of square and
+/// sawtooth waves, and noise, are not reported. The jumps right next to it
+/// are left out of the comparison, so that both jumps of a single-sample
+/// spike are found.
+///
+/// ```rust
+/// use jfxr::analysis::{detect_clicks, DEFAULT_CLICK_THRESHOLD};
+/// use jfxr::parameter::Waveform;
+///
+/// let mut sound = jfxr::Sound::default();
+/// sound.waveform = Waveform::Square;
+/// sound.sustain.0 = 0.2;
+/// sound.decay.0 = 0.1;
+/// let clicks = detect_clicks(&jfxr::generate(&sound), DEFAULT_CLICK_THRESHOLD);
+/// assert_eq!(clicks.len(), 1);
+/// assert_eq!(clicks[0].index, 0);
+///
+/// // A 5 ms attack fades the sound in without a click.
+/// sound.attack.0 = 0.005;
+/// assert!(detect_clicks(&jfxr::generate(&sound), DEFAULT_CLICK_THRESHOLD).is_empty());
+///
+/// // A jump in the middle of a quiet sine.
+/// let mut samples: Vec<f64> = (0..10000).map(|i| 0.2 * (i as f64 * 0.05).sin()).collect();
+/// samples[5000] += 0.5;
+/// let indices: Vec<usize> = detect_clicks(&samples, DEFAULT_CLICK_THRESHOLD).iter().map(|click| click.index).collect();
+/// assert_eq!(indices, [5000, 5001]);
+/// ```
+pub fn detect_clicks(samples: &[f64], threshold: f64) -> Vec<ClickReport> {
+    let mut clicks = Vec::new();
+    let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+        return clicks;
+    };
+    if first.abs() > threshold {
+        clicks.push(ClickReport { index: 0, magnitude: first.abs() });
+    }
+    let jumps: Vec<f64> = samples.windows(2).map(|pair| (pair[1] - pair[0]).abs()).collect();
+    let before = max_of_previous(&jumps, CLICK_CONTEXT);
+    let mut after = max_of_previous(&jumps.iter().rev().copied().collect::<Vec<_>>(), CLICK_CONTEXT);
+    after.reverse();
+    for (i, jump) in jumps.iter().enumerate() {
+        let before = if i > 0 { before[i - 1] } else { 0.0 };
+        let after = after.get(i + 1).copied().unwrap_or(0.0);
+        if *jump > threshold && *jump > CLICK_RATIO * before.max(after) {
+            clicks.push(ClickReport { index: i + 1, magnitude: *jump });
+        }
+    }
+    if last.abs() > threshold {
+        clicks.push(ClickReport { index: samples.len(), magnitude: last.abs() });
+    }
+    clicks
+}
+
+/// Returns, for each value, the largest of the `window` values before it,
+/// or 0 if there are none.
+fn max_of_previous(values: &[f64], window: usize) -> Vec<f64> {
+    // Indices of candidates for the maximum, with decreasing values.
+    let mut candidates = std::collections::VecDeque::new();
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            while candidates.front().is_some_and(|&j: &usize| j + window < i) {
+                candidates.pop_front();
+            }
+            let max = candidates.front().map_or(0.0, |&j| values[j]);
+            while candidates.back().is_some_and(|&j| values[j] <= *value) {
+                candidates.pop_back();
+            }
+            candidates.push_back(i);
+            max
+        })
+        .collect()
+}
+
+/// Returns the root mean square of the samples, which is 0 if there are
+/// none.
+pub fn rms(samples: &[f64]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|sample| sample * sample).sum::<f64>() / samples.len() as f64).sqrt()
+}
+
+/// Returns the root mean square of the samples in decibels relative to full
+/// scale, where a full-scale square wave is 0 dB.
+///
+/// ```rust
+/// let sine: Vec<f64> = (0..44100).map(|i| (i as f64 * 0.1).sin()).collect();
+/// assert!((jfxr::analysis::rms_db(&sine) + 3.01).abs() < 0.01);
+/// ```
+pub fn rms_db(samples: &[f64]) -> f64 {
+    gain_to_db(rms(samples))
+}
+
+/// Returns the integrated loudness of the samples in LUFS, as measured by
+/// [`LoudnessMeter`].
+pub fn lufs(samples: &[f64], sample_rate: f64) -> f64 {
+    let mut meter = LoudnessMeter::new(sample_rate);
+    meter.push(samples);
+    meter.integrated_loudness()
+}
+
+/// Estimates the pitch of the samples over time with the YIN algorithm. The
+/// samples are split into consecutive frames of twice the window, in
+/// samples. For each frame, this returns the time in seconds, at the middle
+/// of the samples that the pitch was measured from, and the frequency in
+/// Hz, or `None` and the center of the frame if the frame has no clear
+/// period, as for noise or silence. Periods up to the length of the window
+/// are found, so the lowest pitch that can be detected is the sample rate
+/// divided by the window. Sweeps are averaged over each frame, so shorter
+/// windows follow fast changes more closely.
+///
+/// ```rust
+/// use jfxr::analysis::detect_pitch;
+/// use jfxr::parameter::{EnumParameter, Waveform};
+///
+/// let cents = |a: f64, b: f64| 1200.0 * (a / b).log2();
+/// let mut sound = jfxr::Sound::default();
+/// sound.sustain.0 = 0.3;
+/// sound.frequency.0 = 440.0;
+/// for waveform in Waveform::VALUES.iter().filter(|waveform| !waveform.is_noise()) {
+///     sound.waveform = *waveform;
+///     let pitches = detect_pitch(&jfxr::generate(&sound), 44100.0, 1024);
+///     for (_, hz) in &pitches {
+///         assert!(cents(hz.unwrap(), 440.0).abs() < 5.0, "{}: {hz:?}", waveform.name());
+///     }
+/// }
+///
+/// // A sweep from 440 Hz follows the model of the sound, a little less
+/// // closely, since the period changes within each frame.
+/// sound.waveform = Waveform::Square;
+/// sound.frequency_sweep.0 = 600.0;
+/// for (time, hz) in detect_pitch(&jfxr::generate(&sound), 44100.0, 512) {
+///     assert!(cents(hz.unwrap(), sound.frequency_at(time)).abs() < 10.0, "{time}: {hz:?}");
+/// }
+///
+/// // So does vibrato, with a window short enough to follow it.
+/// sound.waveform = Waveform::Sine;
+/// sound.frequency_sweep.0 = 0.0;
+/// sound.vibrato_depth.0 = 100.0;
+/// sound.vibrato_frequency.0 = 5.0;
+/// for (time, hz) in detect_pitch(&jfxr::generate(&sound), 44100.0, 256) {
+///     assert!(cents(hz.unwrap(), sound.frequency_at(time)).abs() < 5.0, "{time}: {hz:?}");
+/// }
+///
+/// // Noise has no pitch.
+/// for waveform in Waveform::VALUES.iter().filter(|waveform| waveform.is_noise()) {
+///     sound.waveform = *waveform;
+///     let pitches = detect_pitch(&jfxr::generate(&sound), 44100.0, 1024);
+///     assert!(pitches.iter().all(|(_, hz)| hz.is_none()), "{}: {pitches:?}", waveform.name());
+/// }
+/// ```
+pub fn detect_pitch(samples: &[f64], sample_rate: f64, window: usize) -> Vec<(f64, Option<f64>)> {
+    if window < 2 {
+        return Vec::new();
+    }
+    samples
+        .chunks_exact(2 * window)
+        .enumerate()
+        .map(|(i, frame)| {
+            let start = (2 * i * window) as f64;
+            match frame_period(frame, window) {
+                // The samples that were compared span the window and one
+                // period after it.
+                Some(period) => ((start + 0.5 * (window as f64 + period)) / sample_rate, Some(sample_rate / period)),
+                None => ((start + window as f64) / sample_rate, None),
+            }
+        })
+        .collect()
+}
+
+/// Returns the period of the frame in samples, with YIN: the squared
+/// difference between the first `window` samples and the samples a period
+/// later, normalized by its mean over shorter periods, is lowest at the
+/// period.
+fn frame_period(frame: &[f64], window: usize) -> Option<f64> {
+    let mut differences = vec![1.0; window];
+    let mut sum = 0.0;
+    for period in 1..window {
+        let difference: f64 = (0..window).map(|i| frame[i] - frame[i + period]).map(|delta| delta * delta).sum();
+        sum += difference;
+        differences[period] = if sum > 0.0 { difference * period as f64 / sum } else { 1.0 };
+    }
+    // The lowest point after the first dip below the threshold, up to half
+    // an octave further, which skips the dips at multiples of the period.
+    // Strong overtones, such as those of whistle waves, ripple the
+    // difference around the period, so the first dip may be a side lobe.
+    let start = (2..window).find(|period| differences[*period] < PITCH_THRESHOLD)?;
+    let period = (start..(start + start / 2).min(window)).min_by(|a, b| differences[*a].total_cmp(&differences[*b]))?;
+    if period + 1 == window {
+        return None;
+    }
+    // The minimum of a parabola through the lowest point and its neighbors.
+    let (before, at, after) = (differences[period - 1], differences[period], differences[period + 1]);
+    let curvature = before - 2.0 * at + after;
+    let offset = if curvature > 0.0 { 0.5 * (before - after) / curvature } else { 0.0 };
+    Some(period as f64 + offset)
+}
+
+/// A biquad filter in direct form I.
+#[derive(Clone)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1] - self.a[0] * self.y[0] - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+/// Measures the integrated loudness of a single channel following ITU-R
+/// BS.1770: the samples are K-weighted, and the mean power is taken over
+/// overlapping blocks of 400 ms, leaving out blocks that are silent or much
+/// quieter than the rest. A full-scale 997 Hz sine measures about -3 LUFS.
+///
+/// Sound effects are often shorter than a single block. If no block is
+/// complete, the mean power of all samples is used instead.
+///
+/// Samples can be pushed in any number of calls, so the meter can follow a
+/// [`crate::Stream`].
+///
+/// ```rust
+/// let sine = |amplitude: f64| -> Vec<f64> {
+///     (0..48000).map(|i| amplitude * (2.0 * std::f64::consts::PI * 997.0 * i as f64 / 48000.0).sin()).collect()
+/// };
+/// assert!((jfxr::analysis::lufs(&sine(1.0), 48000.0) + 3.01).abs() < 0.05);
+/// assert!((jfxr::analysis::lufs(&sine(0.1), 48000.0) + 23.01).abs() < 0.05);
+/// ```
+#[derive(Clone)]
+pub struct LoudnessMeter {
+    filters: [Biquad; 2],
+    step_len: usize,
+    step_energy: f64,
+    step_pos: usize,
+    /// Sum of the squared, K-weighted samples of each completed step.
+    steps: Vec<f64>,
+    total_energy: f64,
+    total_len: usize,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: f64) -> Self {
+        // K-weighting: a high shelf modelling the head, followed by a high
+        // pass, with the coefficients derived for any sample rate.
+        let shelf = {
+            let k = crate::math::tan(std::f64::consts::PI * 1681.974450955533 / sample_rate);
+            let q = 0.7071752369554196;
+            let vh = crate::math::pow(10.0, 3.999843853973347 / 20.0);
+            let vb = crate::math::pow(vh, 0.4996667741545416);
+            let a0 = 1.0 + k / q + k * k;
+            Biquad {
+                b: [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+                a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+                x: [0.0; 2],
+                y: [0.0; 2],
+            }
+        };
+        let high_pass = {
+            let k = crate::math::tan(std::f64::consts::PI * 38.13547087602444 / sample_rate);
+            let q = 0.5003270373238773;
+            let a0 = 1.0 + k / q + k * k;
+            Biquad {
+                b: [1.0, -2.0, 1.0],
+                a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+                x: [0.0; 2],
+                y: [0.0; 2],
+            }
+        };
+        Self {
+            filters: [shelf, high_pass],
+            step_len: ((BLOCK_DURATION / BLOCK_STEPS as f64 * sample_rate).round() as usize).max(1),
+            step_energy: 0.0,
+            step_pos: 0,
+            steps: Vec::new(),
+            total_energy: 0.0,
+            total_len: 0,
+        }
+    }
+
+    /// Adds samples to the measurement.
+    pub fn push(&mut self, samples: &[f64]) {
+        for sample in samples {
+            let weighted = self.filters.iter_mut().fold(*sample, |x, filter| filter.process(x));
+            let energy = weighted * weighted;
+            self.step_energy += energy;
+            self.total_energy += energy;
+            self.total_len += 1;
+            self.step_pos += 1;
+            if self.step_pos == self.step_len {
+                self.steps.push(self.step_energy);
+                self.step_energy = 0.0;
+                self.step_pos = 0;
+            }
+        }
+    }
+
+    /// Returns the integrated loudness of the samples pushed so far, in
+    /// LUFS. Silence is negative infinity.
+    pub fn integrated_loudness(&self) -> f64 {
+        let block_len = (self.step_len * BLOCK_STEPS) as f64;
+        let blocks: Vec<f64> = self
+            .steps
+            .windows(BLOCK_STEPS)
+            .map(|steps| steps.iter().sum::<f64>() / block_len)
+            .collect();
+        if blocks.is_empty() {
+            if self.total_len == 0 {
+                return f64::NEG_INFINITY;
+            }
+            return power_to_lufs(self.total_energy / self.total_len as f64);
+        }
+        let mean_above = |threshold: f64| {
+            let gated: Vec<f64> = blocks.iter().copied().filter(|power| power_to_lufs(*power) > threshold).collect();
+            if gated.is_empty() {
+                return 0.0;
+            }
+            gated.iter().sum::<f64>() / gated.len() as f64
+        };
+        let relative_threshold = power_to_lufs(mean_above(ABSOLUTE_GATE)) + RELATIVE_GATE;
+        power_to_lufs(mean_above(relative_threshold.max(ABSOLUTE_GATE)))
+    }
+}
+
+fn power_to_lufs(power: f64) -> f64 {
+    -0.691 + 10.0 * crate::math::log10(power)
+}
@@ -0,0 +1,138 @@
+//! Baking of several variations of a sound into a single buffer, so that a
+//! game engine can load one file and play a random variation by starting
+//! at one of the offsets.
+//!
+//! ```rust
+//! use jfxr::bake::VariationStrategy;
+//! use jfxr::sound::Example;
+//!
+//! let sound = jfxr::Sound::example(Example::Explosion);
+//! let strategy = VariationStrategy::Both { amount: 0.05 };
+//! let baked = jfxr::bake::bake_variations(&sound, 4, strategy);
+//!
+//! // The offsets cover the buffer exactly, one variation after another.
+//! assert_eq!(baked.offsets.len(), 4);
+//! assert_eq!(baked.offsets[0].start, 0);
+//! assert!(baked.offsets.windows(2).all(|pair| pair[0].end == pair[1].start));
+//! assert_eq!(baked.offsets[3].end, baked.samples.len());
+//!
+//! // Each segment is the variation rendered on its own.
+//! for (index, range) in baked.offsets.iter().enumerate() {
+//!     let variation = jfxr::bake::render_variation(&sound, index, strategy);
+//!     assert_eq!(baked.samples[range.clone()], variation[..]);
+//! }
+//!
+//! // Variations do not depend on how many are baked.
+//! let fewer = jfxr::bake::bake_variations(&sound, 2, strategy);
+//! assert_eq!(fewer.samples, baked.samples[..baked.offsets[1].end]);
+//! ```
+
+use std::ops::Range;
+
+use crate::oscillator::Random;
+use crate::{Sound, Synth, SynthError};
+
+/// How the variations of a baked sound differ from each other. Variation
+/// `index` is the same whatever the number of variations baked, and on
+/// every platform.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VariationStrategy {
+    /// Only the random elements of the sound differ, as selected by
+    /// [`Synth::set_variation`] with the index of the variation. Variation 0
+    /// is the sound itself, and sounds without noise do not vary.
+    NoiseSeed,
+    /// The parameters of the sound are changed with [`Sound::mutate`] by the
+    /// given amount, with a random number generator seeded with the index of
+    /// the variation.
+    Mutate { amount: f64 },
+    /// Both of the above.
+    Both { amount: f64 },
+}
+
+impl VariationStrategy {
+    /// Returns the sound of the given variation, and the variation to pass
+    /// to [`Synth::set_variation`].
+    fn apply(self, sound: &Sound, index: usize) -> (Sound, u64) {
+        let mut sound = sound.clone();
+        let (amount, variation) = match self {
+            Self::NoiseSeed => (None, index as u64),
+            Self::Mutate { amount } => (Some(amount), 0),
+            Self::Both { amount } => (Some(amount), index as u64),
+        };
+        if let Some(amount) = amount {
+            sound.mutate(&mut Random::new(index as u32), amount);
+        }
+        (sound, variation)
+    }
+}
+
+/// Variations of a sound joined end to end, as returned by
+/// [`bake_variations`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Baked {
+    /// The samples of every variation, without gaps.
+    pub samples: Vec<f64>,
+    /// The range of samples of each variation, in order.
+    pub offsets: Vec<Range<usize>>,
+}
+
+impl Baked {
+    /// Writes the samples as a WAV file, like [`crate::wav::write_wav`],
+    /// with a cue point at the start of each variation.
+    #[cfg(feature = "wav")]
+    pub fn write_wav(&self, sample_rate: u32, writer: impl std::io::Write) -> std::io::Result<()> {
+        let cues: Vec<usize> = self.offsets.iter().map(|range| range.start).collect();
+        crate::wav::write_wav_with_cues(&self.samples, sample_rate, &cues, writer)
+    }
+}
+
+/// Renders a single variation of the sound, as it appears in the result of
+/// [`bake_variations`].
+///
+/// # Panics
+///
+/// Panics if the variation cannot be generated, like [`crate::generate`].
+pub fn render_variation(sound: &Sound, index: usize, strategy: VariationStrategy) -> Vec<f64> {
+    match try_render_variation(sound, index, strategy) {
+        Ok(samples) => samples,
+        Err(error) => panic!("cannot generate sound: {error}"),
+    }
+}
+
+/// Renders a single variation of the sound, like [`render_variation`], or
+/// returns an error if it cannot be generated.
+pub fn try_render_variation(sound: &Sound, index: usize, strategy: VariationStrategy) -> Result<Vec<f64>, SynthError> {
+    let (sound, variation) = strategy.apply(sound, index);
+    let mut synth = Synth::try_new(&sound)?;
+    synth.set_variation(variation);
+    synth.try_generate()
+}
+
+/// Renders `n` variations of the sound, numbered from 0, and joins them
+/// without gaps or crossfades. Variations made with
+/// [`VariationStrategy::Mutate`] may differ in length.
+///
+/// # Panics
+///
+/// Panics if any of the variations cannot be generated, like
+/// [`crate::generate`].
+pub fn bake_variations(sound: &Sound, n: usize, strategy: VariationStrategy) -> Baked {
+    match try_bake_variations(sound, n, strategy) {
+        Ok(baked) => baked,
+        Err(error) => panic!("cannot generate sound: {error}"),
+    }
+}
+
+/// Renders `n` variations of the sound, like [`bake_variations`], or returns
+/// the error of the first variation that cannot be generated. Mutation can
+/// make a sound invalid, for example by shortening it to nothing.
+pub fn try_bake_variations(sound: &Sound, n: usize, strategy: VariationStrategy) -> Result<Baked, SynthError> {
+    let mut baked = Baked::default();
+    for index in 0..n {
+        let samples = try_render_variation(sound, index, strategy)?;
+        let start = baked.samples.len();
+        baked.samples.extend_from_slice(&samples);
+        baked.offsets.push(start..baked.samples.len());
+    }
+    Ok(baked)
+}
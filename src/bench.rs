@@ -0,0 +1,136 @@
+//! Measuring the speed of rendering, to track it from one release of the
+//! engine to the next. Requires the `bench` feature.
+//!
+//! The allocations made while rendering are only counted if the program
+//! uses [`CountingAllocator`] as its global allocator:
+//!
+//! ```rust
+//! #[global_allocator]
+//! static ALLOCATOR: jfxr::bench::CountingAllocator = jfxr::bench::CountingAllocator;
+//!
+//! fn main() {
+//!     let sound = jfxr::Sound::example(jfxr::sound::Example::Coin);
+//!     let result = jfxr::bench::bench_render(&sound, 3);
+//!     assert_eq!(result.samples, 3 * sound.num_samples());
+//!     assert!(result.samples_per_sec > 0.0);
+//!     assert!(result.allocations.unwrap() > 0);
+//!     println!("{result}");
+//! }
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::Sound;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static COUNTING: AtomicBool = AtomicBool::new(false);
+
+/// Global allocator that allocates with [`System`] and counts the number of
+/// allocations, for [`BenchResult::allocations`] and for
+/// `testing::assert_allocation_free`. Allocations on every thread are
+/// counted.
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    /// Returns the number of allocations and reallocations made so far, on
+    /// any thread.
+    pub fn allocations(&self) -> u64 {
+        ALLOCATIONS.load(Ordering::Relaxed)
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc_zeroed(layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+fn count_allocation() {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    COUNTING.store(true, Ordering::Relaxed);
+}
+
+/// Returns the number of allocations so far, if [`CountingAllocator`] is the
+/// global allocator.
+fn allocations() -> Option<u64> {
+    // Any program allocates before it renders, so the counter has started
+    // if the allocator is in use.
+    COUNTING
+        .load(Ordering::Relaxed)
+        .then(|| ALLOCATIONS.load(Ordering::Relaxed))
+}
+
+/// Measurements of [`bench_render`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchResult {
+    /// Number of times the sound was rendered.
+    pub iterations: usize,
+    /// Number of samples rendered over all iterations.
+    pub samples: usize,
+    /// Time taken by all iterations.
+    pub wall_time: Duration,
+    /// Number of samples rendered per second.
+    pub samples_per_sec: f64,
+    /// Number of allocations made by all iterations, or `None` if
+    /// [`CountingAllocator`] is not the global allocator.
+    pub allocations: Option<u64>,
+}
+
+impl std::fmt::Display for BenchResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} renders in {:.3} ms, {:.0} samples/s",
+            self.iterations,
+            self.wall_time.as_secs_f64() * 1000.0,
+            self.samples_per_sec
+        )?;
+        if let Some(allocations) = self.allocations {
+            write!(f, ", {allocations} allocations")?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders the sound `iterations` times with [`crate::generate`], after one
+/// render to warm up caches, and measures the time taken. Renders run on
+/// the current thread, and should be the only work of the program while
+/// they run, so that the time and the allocations are only theirs.
+///
+/// # Panics
+///
+/// Panics if the sound cannot be generated, like [`crate::generate`].
+pub fn bench_render(sound: &Sound, iterations: usize) -> BenchResult {
+    std::hint::black_box(crate::generate(sound));
+    let allocations_before = allocations();
+    let start = Instant::now();
+    let mut samples = 0;
+    for _ in 0..iterations {
+        samples += std::hint::black_box(crate::generate(std::hint::black_box(sound))).len();
+    }
+    let wall_time = start.elapsed();
+    let allocations = allocations()
+        .zip(allocations_before)
+        .map(|(after, before)| after - before);
+    BenchResult {
+        iterations,
+        samples,
+        wall_time,
+        samples_per_sec: samples as f64 / wall_time.as_secs_f64(),
+        allocations,
+    }
+}
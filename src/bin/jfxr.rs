@@ -0,0 +1,576 @@
+//! Command line interface for creating sounds in scripts and build
+//! pipelines. Requires the `cli` feature.
+//!
+//! Usage: `cargo run --features cli -- help`
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage:
+    jfxr preset <kind> [--seed <n>] [-o <file>] [--render]
+        Generates a random sound of the given kind: coin, laser, explosion,
+        jump, hit, powerup or blip. Writes <kind>-<seed>.jfxr by default.
+
+    jfxr mutate <file> [--amount <x>] [--count <n>] [--seed <n>] [--out-dir <dir>] [--render]
+        Writes <count> mutated variants of the sound, using the seeds <seed>,
+        <seed>+1 and so on, as <name>-<seed>.jfxr in the output directory,
+        which defaults to the directory of the file. The amount is the
+        largest change of each parameter, as a fraction of its range.
+
+    jfxr watch <dir> [--out <dir>] [--debounce <ms>]
+        Renders every .jfxr file in the directory to a .wav file in the
+        output directory, which defaults to the same directory, and renders
+        each file again whenever it changes, until interrupted.
+
+    jfxr export <file> [--lang <c|rust>] [--name <name>] [--bits <8|16>] [--line-width <n>] [-o <file>]
+        Renders the sound and writes its samples as a C array or a Rust
+        static, named after the name, which defaults to the name of the
+        file. Writes to standard output by default.
+
+    jfxr apply-patch <patch> <file>... [--render]
+        Applies the parameter values of the patch, a JSON object with the
+        keys of the .jfxr format, to each sound, and writes the sound back
+        to its file.
+
+    jfxr diff <old> <new>
+        Prints the parameters that differ between the two sounds, one per
+        line, with their old and new values.
+
+    jfxr validate <path>... [--format <text|json>]
+        Reads every .jfxr file, searching directories recursively, with the
+        limits for untrusted input, and renders it with the safety limits of
+        the synth. Reports the duration and peak level of each sound, along
+        with errors, warnings for out-of-range values that were clamped,
+        unknown fields, clipping and clicks, and notes for missing fields that were
+        set to their defaults. Exits with 1 if any file has errors, with 3
+        if there are only warnings, and with 0 otherwise.
+
+    jfxr snapshot make <dir> [--snapshot <dir>]
+        Renders every .jfxr file in the directory and its subdirectories,
+        and writes each render as a 16-bit .wav file to the snapshot
+        directory, which defaults to <dir>/snapshot, along with a manifest
+        of their hashes.
+
+    jfxr snapshot verify <dir> [--snapshot <dir>] [--tolerance <x>]
+        Renders the sounds again and compares them with the snapshot.
+        Reports the files that were added, removed or sound different, and
+        exits with 1 if there are any.
+
+    jfxr --capabilities
+        Prints the version, the enabled features and other capabilities of
+        this build as JSON.
+
+Options:
+    --seed <n>       Seed of the random number generator, from 0 to 4294967295 [default: 0]
+    --amount <x>     Amount of mutation, from 0 to 1 [default: 0.1]
+    --count <n>      Number of variants [default: 1]
+    --render         Also writes a .wav file next to each .jfxr file
+    --debounce <ms>  Time to wait for further changes before rendering [default: 100]
+    --lang <lang>    Language of the exported source code [default: c]
+    --bits <n>       Bits per exported sample [default: 16]
+    --format <fmt>   Format of the validation report [default: text]
+    --tolerance <x>  Largest difference of a sample from the snapshot, or exact [default: one 16-bit step]";
+
+/// Error that ends the program.
+enum Error {
+    /// The command line is invalid; the usage is printed.
+    Usage(String),
+    /// The command could not be completed.
+    Failed(String),
+    /// The command was completed, but reported warnings.
+    Warnings(String),
+}
+
+fn main() -> ExitCode {
+    // Logs of the library, such as a summary of each render with
+    // `RUST_LOG=jfxr=debug`.
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::IsTerminal::is_terminal(&std::io::stderr()))
+        .init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Error::Usage(message)) => {
+            eprintln!("jfxr: {message}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+        Err(Error::Failed(message)) => {
+            eprintln!("jfxr: {message}");
+            ExitCode::FAILURE
+        }
+        Err(Error::Warnings(message)) => {
+            eprintln!("jfxr: {message}");
+            ExitCode::from(3)
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), Error> {
+    match args.first().map(String::as_str) {
+        Some("preset") => preset(&Args::parse(&args[1..], &["--seed", "-o", "--output"], &["--render"])?),
+        Some("mutate") => mutate(&Args::parse(&args[1..], &["--amount", "--count", "--seed", "--out-dir"], &["--render"])?),
+        Some("watch") => watch(&Args::parse(&args[1..], &["--out", "--debounce"], &[])?),
+        Some("export") => export(&Args::parse(&args[1..], &["--lang", "--name", "--bits", "--line-width", "-o", "--output"], &[])?),
+        Some("apply-patch") => apply_patch(&Args::parse(&args[1..], &[], &["--render"])?),
+        Some("diff") => diff(&Args::parse(&args[1..], &[], &[])?),
+        Some("validate") => validate(&Args::parse(&args[1..], &["--format"], &[])?),
+        Some("snapshot") => match args.get(1).map(String::as_str) {
+            Some("make") => snapshot_make(&Args::parse(&args[2..], &["--snapshot"], &[])?),
+            Some("verify") => snapshot_verify(&Args::parse(&args[2..], &["--snapshot", "--tolerance"], &[])?),
+            Some(command) => Err(Error::Usage(format!("unknown snapshot command \"{command}\", expected make or verify"))),
+            None => Err(Error::Usage("expected make or verify".to_string())),
+        },
+        Some("--capabilities") => {
+            println!("{}", jfxr::capabilities().to_json().pretty(2));
+            Ok(())
+        }
+        Some("help" | "-h" | "--help") => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Some(command) => Err(Error::Usage(format!("unknown command \"{command}\""))),
+        None => Err(Error::Usage("missing command".to_string())),
+    }
+}
+
+fn preset(args: &Args) -> Result<(), Error> {
+    let [kind] = args.positional(["kind"])?;
+    let example = jfxr::sound::Example::from_name(kind).ok_or_else(|| {
+        let kinds: Vec<String> = jfxr::sound::Example::ALL.iter().map(|example| example.name().to_lowercase()).collect();
+        Error::Failed(format!("unknown preset \"{kind}\", expected one of: {}", kinds.join(", ")))
+    })?;
+    let seed = args.value("--seed")?.unwrap_or(0);
+    let path = match args.value::<PathBuf>("-o")?.or(args.value("--output")?) {
+        Some(path) => path,
+        None => PathBuf::from(format!("{}-{seed}.jfxr", example.name().to_lowercase())),
+    };
+    let sound = jfxr::presets::random(example, &mut jfxr::Random::new(seed));
+    write_sound(&sound, &path, args.flag("--render"))
+}
+
+fn mutate(args: &Args) -> Result<(), Error> {
+    let [file] = args.positional(["file"])?;
+    let amount = args.value("--amount")?.unwrap_or(0.1);
+    if !(0.0..=1.0).contains(&amount) {
+        return Err(Error::Usage(format!("amount must be between 0 and 1, but it is {amount}")));
+    }
+    let count: u32 = args.value("--count")?.unwrap_or(1);
+    let first_seed: u32 = args.value("--seed")?.unwrap_or(0);
+    let path = Path::new(file);
+    let out_dir = match args.value::<PathBuf>("--out-dir")? {
+        Some(out_dir) => out_dir,
+        None => path.parent().unwrap_or(Path::new("")).to_path_buf(),
+    };
+    let stem = path.file_stem().map_or("sound".into(), |stem| stem.to_string_lossy());
+
+    let data = std::fs::read_to_string(path).map_err(|error| Error::Failed(format!("cannot read {}: {error}", path.display())))?;
+    let base = jfxr::read_jfxr(&data).map_err(|error| Error::Failed(format!("cannot parse {}: {error}", path.display())))?;
+    if !out_dir.as_os_str().is_empty() {
+        std::fs::create_dir_all(&out_dir).map_err(|error| Error::Failed(format!("cannot create directory {}: {error}", out_dir.display())))?;
+    }
+    for i in 0..count {
+        let seed = first_seed.checked_add(i).ok_or_else(|| Error::Usage("seeds exceed 4294967295".to_string()))?;
+        let mut sound = base.clone();
+        sound.name = format!("{} {seed}", base.name);
+        sound.mutate(&mut jfxr::Random::new(seed), amount);
+        write_sound(&sound, &out_dir.join(format!("{stem}-{seed}.jfxr")), args.flag("--render"))?;
+    }
+    Ok(())
+}
+
+fn watch(args: &Args) -> Result<(), Error> {
+    let [dir] = args.positional(["dir"])?;
+    let mut config = jfxr::watch::WatchConfig::new(dir, args.value::<PathBuf>("--out")?.unwrap_or_else(|| PathBuf::from(dir)));
+    if let Some(debounce) = args.value("--debounce")? {
+        config.debounce = std::time::Duration::from_millis(debounce);
+    }
+    jfxr::watch::watch_and_render(&config, |event| {
+        match event {
+            jfxr::watch::WatchEvent::Rendered { output, .. } => println!("{}", output.display()),
+            jfxr::watch::WatchEvent::Failed { input, error } => eprintln!("jfxr: cannot render {}: {error}", input.display()),
+        }
+        std::ops::ControlFlow::Continue(())
+    })
+    .map_err(|error| Error::Failed(error.to_string()))
+}
+
+fn export(args: &Args) -> Result<(), Error> {
+    let [file] = args.positional(["file"])?;
+    let path = Path::new(file);
+    let mut options = jfxr::export::ExportOptions::new(&path.file_stem().unwrap_or_default().to_string_lossy());
+    if let Some(name) = args.value::<String>("--name")? {
+        options.name = name;
+    }
+    options.bit_depth = match args.value("--bits")?.unwrap_or(16) {
+        8 => jfxr::export::BitDepth::Eight,
+        16 => jfxr::export::BitDepth::Sixteen,
+        bits => return Err(Error::Usage(format!("bits must be 8 or 16, but it is {bits}"))),
+    };
+    if let Some(line_width) = args.value("--line-width")? {
+        options.line_width = line_width;
+    }
+    let to_source = match args.value::<String>("--lang")?.as_deref().unwrap_or("c") {
+        "c" => jfxr::export::to_c_array,
+        "rust" => jfxr::export::to_rust_static,
+        lang => return Err(Error::Usage(format!("unknown language \"{lang}\", expected c or rust"))),
+    };
+
+    let data = std::fs::read_to_string(path).map_err(|error| Error::Failed(format!("cannot read {}: {error}", path.display())))?;
+    let sound = jfxr::read_jfxr(&data).map_err(|error| Error::Failed(format!("cannot parse {}: {error}", path.display())))?;
+    let samples = jfxr::try_generate(&sound).map_err(|error| Error::Failed(format!("cannot render {}: {error}", path.display())))?;
+    let source = to_source(&samples, &options);
+    match args.value::<PathBuf>("-o")?.or(args.value("--output")?) {
+        Some(output) => std::fs::write(&output, source).map_err(|error| Error::Failed(format!("cannot write {}: {error}", output.display()))),
+        None => std::io::Write::write_all(&mut std::io::stdout(), source.as_bytes()).map_err(|error| Error::Failed(format!("cannot write to standard output: {error}"))),
+    }
+}
+
+fn apply_patch(args: &Args) -> Result<(), Error> {
+    let Some((patch_file, files)) = args.positional.split_first().filter(|(_, files)| !files.is_empty()) else {
+        return Err(Error::Usage("expected <patch> <file>...".to_string()));
+    };
+    let patch_path = Path::new(patch_file);
+    let data = std::fs::read_to_string(patch_path).map_err(|error| Error::Failed(format!("cannot read {}: {error}", patch_path.display())))?;
+    let patch = jfxr::jfxr::read_patch(&data).map_err(|error| Error::Failed(format!("cannot parse {}: {error}", patch_path.display())))?;
+    for file in files {
+        let path = Path::new(file);
+        let data = std::fs::read_to_string(path).map_err(|error| Error::Failed(format!("cannot read {}: {error}", path.display())))?;
+        let mut sound = jfxr::read_jfxr(&data).map_err(|error| Error::Failed(format!("cannot parse {}: {error}", path.display())))?;
+        patch.apply(&mut sound);
+        write_sound(&sound, path, args.flag("--render"))?;
+    }
+    Ok(())
+}
+
+fn diff(args: &Args) -> Result<(), Error> {
+    let [old, new] = args.positional(["old", "new"])?;
+    let read = |file: &str| {
+        let path = Path::new(file);
+        let data = std::fs::read_to_string(path).map_err(|error| Error::Failed(format!("cannot read {}: {error}", path.display())))?;
+        jfxr::read_jfxr(&data).map_err(|error| Error::Failed(format!("cannot parse {}: {error}", path.display())))
+    };
+    let (old, new) = (read(old)?, read(new)?);
+    for change in old.diff(&new) {
+        println!("{change}");
+    }
+    Ok(())
+}
+
+fn validate(args: &Args) -> Result<(), Error> {
+    if args.positional.is_empty() {
+        return Err(Error::Usage("expected <path>...".to_string()));
+    }
+    let json_format = match args.value::<String>("--format")?.as_deref().unwrap_or("text") {
+        "text" => false,
+        "json" => true,
+        format => return Err(Error::Usage(format!("unknown format \"{format}\", expected text or json"))),
+    };
+    let mut paths = Vec::new();
+    for path in &args.positional {
+        find_sounds(Path::new(path), &mut paths)?;
+    }
+
+    let reports: Vec<Report> = paths.iter().map(|path| Report::new(path)).collect();
+    let count = |severity| reports.iter().flat_map(|report| &report.diagnostics).filter(|diagnostic| diagnostic.severity == severity).count();
+    let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+    if json_format {
+        let mut report = json::object! {
+            "files": [],
+            "summary": { "files": reports.len(), "errors": errors, "warnings": warnings },
+        };
+        for file in &reports {
+            report["files"].push(file.to_json()).unwrap();
+        }
+        println!("{}", report.pretty(2));
+    } else {
+        for file in &reports {
+            file.print();
+        }
+        println!("{}, {}, {}", plural(reports.len(), "file"), plural(errors, "error"), plural(warnings, "warning"));
+    }
+    if errors > 0 {
+        let files = reports.iter().filter(|report| report.has(Severity::Error)).count();
+        Err(Error::Failed(format!("{} in {}", plural(errors, "error"), plural(files, "file"))))
+    } else if warnings > 0 {
+        let files = reports.iter().filter(|report| report.has(Severity::Warning)).count();
+        Err(Error::Warnings(format!("{} in {}", plural(warnings, "warning"), plural(files, "file"))))
+    } else {
+        Ok(())
+    }
+}
+
+fn snapshot_make(args: &Args) -> Result<(), Error> {
+    let [dir] = args.positional(["dir"])?;
+    let snapshot_dir = args.value::<PathBuf>("--snapshot")?.unwrap_or_else(|| Path::new(dir).join("snapshot"));
+    let manifest = jfxr::snapshot::make(Path::new(dir), &snapshot_dir).map_err(|error| Error::Failed(error.to_string()))?;
+    println!("{} in {}", plural(manifest.files.len(), "file"), snapshot_dir.display());
+    Ok(())
+}
+
+fn snapshot_verify(args: &Args) -> Result<(), Error> {
+    let [dir] = args.positional(["dir"])?;
+    let snapshot_dir = args.value::<PathBuf>("--snapshot")?.unwrap_or_else(|| Path::new(dir).join("snapshot"));
+    let tolerance = match args.value::<String>("--tolerance")?.as_deref() {
+        None => jfxr::snapshot::Tolerance::default(),
+        Some("exact") => jfxr::snapshot::Tolerance::Exact,
+        Some(tolerance) => match tolerance.parse() {
+            Ok(tolerance) if tolerance >= 0.0 => jfxr::snapshot::Tolerance::MaxAbsDiff(tolerance),
+            _ => return Err(Error::Usage(format!("tolerance must be exact or a number of at least 0, but it is \"{tolerance}\""))),
+        },
+    };
+    let mismatches = jfxr::snapshot::verify(Path::new(dir), &snapshot_dir, tolerance).map_err(|error| Error::Failed(error.to_string()))?;
+    for mismatch in &mismatches {
+        println!("{mismatch}");
+    }
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Failed(format!("{} in the snapshot", plural(mismatches.len(), "difference"))))
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    format!("{count} {noun}{}", if count == 1 { "" } else { "s" })
+}
+
+/// Adds the path to the list if it is a file, or the `.jfxr` files in it,
+/// sorted by name, if it is a directory.
+fn find_sounds(path: &Path, paths: &mut Vec<PathBuf>) -> Result<(), Error> {
+    if !path.is_dir() {
+        paths.push(path.to_path_buf());
+        return Ok(());
+    }
+    let entries = std::fs::read_dir(path).and_then(|entries| entries.map(|entry| entry.map(|entry| entry.path())).collect::<std::io::Result<Vec<_>>>());
+    let mut entries = entries.map_err(|error| Error::Failed(format!("cannot read directory {}: {error}", path.display())))?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() || entry.extension().is_some_and(|extension| extension == "jfxr") {
+            find_sounds(&entry, paths)?;
+        }
+    }
+    Ok(())
+}
+
+/// Fields of a `.jfxr` file that are not parameters, apart from the
+/// metadata fields starting with an underscore.
+const OTHER_FIELDS: &[&str] = &["harmonicsCustomAmplitudes", "interpolateNoise"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Error,
+    Warning,
+    /// Does not affect the exit code.
+    Note,
+}
+
+impl Severity {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Note => "note",
+        }
+    }
+}
+
+struct Diagnostic {
+    severity: Severity,
+    message: String,
+}
+
+/// Result of validating a single file.
+struct Report {
+    path: PathBuf,
+    diagnostics: Vec<Diagnostic>,
+    /// Length of the sound, and of the render including the tail of the
+    /// effects, in seconds, and the measurements of the render.
+    render: Option<(f64, f64, jfxr::RenderReport)>,
+}
+
+impl Report {
+    fn new(path: &Path) -> Self {
+        let mut report = Self {
+            path: path.to_path_buf(),
+            diagnostics: Vec::new(),
+            render: None,
+        };
+        if let Err(message) = report.check() {
+            report.push(Severity::Error, message);
+        }
+        report
+    }
+
+    fn check(&mut self) -> Result<(), String> {
+        let data = std::fs::read_to_string(&self.path).map_err(|error| format!("cannot read file: {error}"))?;
+        let sound = jfxr::jfxr::read_jfxr_untrusted(&data, &jfxr::jfxr::Limits::default()).map_err(|error| format!("cannot parse file: {error}"))?;
+        // The sound above is clamped; read the fields again as they are in
+        // the file to find the ones that are out of range.
+        let object = json::parse(&data).map_err(|error| format!("cannot parse file: {error}"))?;
+        let unclamped = jfxr::jfxr::from_json_value(&object).map_err(|error| format!("cannot parse file: {error}"))?;
+
+        for (key, _) in object.entries() {
+            if !key.starts_with('_') && jfxr::Sound::param(key).is_none() && !OTHER_FIELDS.contains(&key) {
+                self.push(Severity::Warning, format!("unknown field \"{key}\" is ignored"));
+            }
+        }
+        // The noise interpolation of files from the web tool is read from
+        // `interpolateNoise` instead.
+        let missing: Vec<&str> = jfxr::Sound::params()
+            .iter()
+            .map(|param| param.key)
+            .filter(|key| !object.has_key(key) && (*key != "noiseInterpolation" || !object.has_key("interpolateNoise")))
+            .collect();
+        if !missing.is_empty() {
+            self.push(Severity::Note, format!("missing fields are set to their defaults: {}", missing.join(", ")));
+        }
+        for param in jfxr::Sound::params() {
+            let (value, clamped) = (param.get(&unclamped), param.get(&sound));
+            if value != clamped {
+                self.push(Severity::Warning, format!("field \"{}\" is out of range: {} is clamped to {}", param.key, param.format_value(value), param.format_value(clamped)));
+            }
+        }
+
+        for diagnostic in sound.diagnose() {
+            self.push(Severity::Warning, diagnostic.to_string());
+        }
+
+        let render = || -> Result<(usize, jfxr::RenderReport), jfxr::SynthError> {
+            let mut synth = jfxr::Synth::try_new(&sound)?;
+            while !synth.try_generate_block()? {}
+            Ok((synth.generated().len(), synth.render_report()))
+        };
+        let (len, report) = render().map_err(|error| format!("cannot render: {error}"))?;
+        if report.is_silent() {
+            self.push(Severity::Warning, format!("render is silent, with a peak level of {:.1} dBFS", jfxr::analysis::gain_to_db(report.peak)));
+        }
+        if report.clipped > 0 {
+            self.push(Severity::Warning, format!("peak level of {:.1} dBFS clips {} samples", jfxr::analysis::gain_to_db(report.peak), report.clipped));
+        }
+        for click in &report.clicks {
+            let position = match click.index {
+                0 => "at the start".to_string(),
+                index if index == len => "at the end".to_string(),
+                index => format!("at {:.3} s", index as f64 / sound.sample_rate.0),
+            };
+            self.push(Severity::Warning, format!("click {position}, jumping by {:.2}", click.magnitude));
+        }
+        self.render = Some((sound.duration(), len as f64 / sound.sample_rate.0, report));
+        Ok(())
+    }
+
+    fn push(&mut self, severity: Severity, message: String) {
+        self.diagnostics.push(Diagnostic { severity, message });
+    }
+
+    fn has(&self, severity: Severity) -> bool {
+        self.diagnostics.iter().any(|diagnostic| diagnostic.severity == severity)
+    }
+
+    fn print(&self) {
+        match &self.render {
+            Some((duration, rendered_duration, report)) => println!(
+                "{}: {duration:.3} s ({rendered_duration:.3} s rendered), peak {:.1} dBFS",
+                self.path.display(),
+                jfxr::analysis::gain_to_db(report.peak)
+            ),
+            None => println!("{}:", self.path.display()),
+        }
+        for diagnostic in &self.diagnostics {
+            println!("    {}: {}", diagnostic.severity.name(), diagnostic.message);
+        }
+    }
+
+    fn to_json(&self) -> json::JsonValue {
+        let mut json = json::object! {
+            "path": self.path.to_string_lossy().as_ref(),
+            "diagnostics": [],
+        };
+        for diagnostic in &self.diagnostics {
+            json["diagnostics"].push(json::object! { "severity": diagnostic.severity.name(), "message": diagnostic.message.as_str() }).unwrap();
+        }
+        if let Some((duration, rendered_duration, report)) = &self.render {
+            json["duration"] = (*duration).into();
+            json["renderedDuration"] = (*rendered_duration).into();
+            json["peak"] = report.peak.into();
+            // Silence has no level in decibels, which JSON cannot represent.
+            json["peakDb"] = if report.peak > 0.0 { jfxr::analysis::gain_to_db(report.peak).into() } else { json::JsonValue::Null };
+            json["clipped"] = report.clipped.into();
+            json["clicks"] = report.clicks.iter().map(|click| json::object! { "index": click.index, "magnitude": click.magnitude }).collect::<Vec<_>>().into();
+        }
+        json
+    }
+}
+
+/// Writes the sound as a `.jfxr` file, and if requested, renders it to a
+/// `.wav` file next to it.
+fn write_sound(sound: &jfxr::Sound, path: &Path, render: bool) -> Result<(), Error> {
+    std::fs::write(path, jfxr::write_jfxr(sound.clone())).map_err(|error| Error::Failed(format!("cannot write {}: {error}", path.display())))?;
+    println!("{}", path.display());
+    if render {
+        let wav_path = path.with_extension("wav");
+        let samples = jfxr::try_generate(sound).map_err(|error| Error::Failed(format!("cannot render {}: {error}", path.display())))?;
+        let write = || -> std::io::Result<()> {
+            let file = std::io::BufWriter::new(std::fs::File::create(&wav_path)?);
+            jfxr::wav::write_wav(&samples, sound.sample_rate.0 as u32, file)
+        };
+        write().map_err(|error| Error::Failed(format!("cannot write {}: {error}", wav_path.display())))?;
+        println!("{}", wav_path.display());
+    }
+    Ok(())
+}
+
+/// Command line arguments of a subcommand.
+struct Args {
+    positional: Vec<String>,
+    values: Vec<(String, String)>,
+    flags: Vec<String>,
+}
+
+impl Args {
+    /// Splits the arguments into positional arguments, options that take a
+    /// value and flags, rejecting options that are not in either list.
+    fn parse(args: &[String], value_options: &[&str], flag_options: &[&str]) -> Result<Self, Error> {
+        let mut parsed = Self {
+            positional: Vec::new(),
+            values: Vec::new(),
+            flags: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if value_options.contains(&arg.as_str()) {
+                let value = args.next().ok_or_else(|| Error::Usage(format!("missing value for {arg}")))?;
+                parsed.values.push((arg.clone(), value.clone()));
+            } else if flag_options.contains(&arg.as_str()) {
+                parsed.flags.push(arg.clone());
+            } else if arg.starts_with('-') && arg.len() > 1 {
+                return Err(Error::Usage(format!("unknown option {arg}")));
+            } else {
+                parsed.positional.push(arg.clone());
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Returns the positional arguments, which must match the given names.
+    fn positional<const N: usize>(&self, names: [&str; N]) -> Result<[&str; N], Error> {
+        if self.positional.len() != N {
+            return Err(Error::Usage(format!("expected {}", names.map(|name| format!("<{name}>")).join(" "))));
+        }
+        Ok(std::array::from_fn(|i| self.positional[i].as_str()))
+    }
+
+    /// Returns the last value of the given option, if it was given.
+    fn value<T: std::str::FromStr>(&self, option: &str) -> Result<Option<T>, Error> {
+        match self.values.iter().rev().find(|(name, _)| name == option) {
+            Some((_, value)) => value.parse().map(Some).map_err(|_| Error::Usage(format!("invalid value for {option}: \"{value}\""))),
+            None => Ok(None),
+        }
+    }
+
+    fn flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|name| name == flag)
+    }
+}
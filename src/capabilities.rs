@@ -0,0 +1,114 @@
+//! What the linked build of the crate supports, for host applications that
+//! enable parts of their interface depending on it.
+
+/// The Cargo features of the crate, and whether each is enabled in this
+/// build.
+const FEATURES: &[(&str, bool)] = &[
+    ("bench", cfg!(feature = "bench")),
+    ("cli", cfg!(feature = "cli")),
+    ("dasp", cfg!(feature = "dasp")),
+    ("deterministic", cfg!(feature = "deterministic")),
+    ("edit", cfg!(feature = "edit")),
+    ("egui", cfg!(feature = "egui")),
+    ("json", cfg!(feature = "json")),
+    ("serde", cfg!(feature = "serde")),
+    ("test-util", cfg!(feature = "test-util")),
+    ("tracing", cfg!(feature = "tracing")),
+    ("wasm", cfg!(feature = "wasm")),
+    ("watch", cfg!(feature = "watch")),
+    ("wav", cfg!(feature = "wav")),
+];
+
+/// What this build of the crate supports, as returned by [`capabilities`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Version of the crate.
+    pub version: &'static str,
+    /// The enabled Cargo features, sorted by name.
+    pub features: Vec<&'static str>,
+    /// The format version of the `.jfxr` files that are written, or `None`
+    /// without the `json` feature, when files cannot be read or written.
+    pub format_version: Option<u32>,
+    /// Whether renders use the pure Rust math library of the
+    /// `deterministic` feature, which makes them bit-exact on every
+    /// platform.
+    pub deterministic_math: bool,
+    /// The SIMD code path used by the synth. The synth has no SIMD code, so
+    /// this is always `"scalar"`.
+    pub simd: &'static str,
+}
+
+impl Capabilities {
+    /// Returns whether the Cargo feature with the given name is enabled.
+    pub fn has_feature(&self, name: &str) -> bool {
+        self.features.contains(&name)
+    }
+
+    /// Returns every Cargo feature of the crate, whether enabled or not.
+    pub fn all_features() -> impl Iterator<Item = &'static str> {
+        FEATURES.iter().map(|(name, _)| *name)
+    }
+
+    /// Returns the capabilities as a JSON object with the fields `version`,
+    /// `features`, `formatVersion` (`null` without the `json` feature),
+    /// `deterministicMath` and `simd`.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> json::JsonValue {
+        json::object! {
+            "version": self.version,
+            "features": self.features.clone(),
+            "formatVersion": self.format_version,
+            "deterministicMath": self.deterministic_math,
+            "simd": self.simd,
+        }
+    }
+}
+
+/// Returns what this build of the crate supports.
+///
+/// ```rust
+/// let capabilities = jfxr::capabilities();
+/// assert_eq!(capabilities.version, env!("CARGO_PKG_VERSION"));
+/// // The doctests are built with the same features as the crate.
+/// for feature in jfxr::Capabilities::all_features() {
+///     let enabled = match feature {
+///         "bench" => cfg!(feature = "bench"),
+///         "cli" => cfg!(feature = "cli"),
+///         "dasp" => cfg!(feature = "dasp"),
+///         "deterministic" => cfg!(feature = "deterministic"),
+///         "edit" => cfg!(feature = "edit"),
+///         "egui" => cfg!(feature = "egui"),
+///         "json" => cfg!(feature = "json"),
+///         "serde" => cfg!(feature = "serde"),
+///         "test-util" => cfg!(feature = "test-util"),
+///         "tracing" => cfg!(feature = "tracing"),
+///         "wasm" => cfg!(feature = "wasm"),
+///         "watch" => cfg!(feature = "watch"),
+///         "wav" => cfg!(feature = "wav"),
+///         _ => panic!("feature {feature} is not checked"),
+///     };
+///     assert_eq!(capabilities.has_feature(feature), enabled, "{feature}");
+/// }
+///
+/// // Every feature of the manifest is listed.
+/// let manifest = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"));
+/// let features = manifest.split("[features]\n").nth(1).unwrap().split("\n\n").next().unwrap();
+/// let mut names: Vec<_> = features.lines().filter_map(|line| line.split(" = ").next()).filter(|name| *name != "default").collect();
+/// names.sort();
+/// assert_eq!(names, jfxr::Capabilities::all_features().collect::<Vec<_>>());
+/// assert_eq!(capabilities.deterministic_math, cfg!(feature = "deterministic"));
+/// assert_eq!(capabilities.format_version.is_some(), cfg!(feature = "json"));
+/// ```
+pub fn capabilities() -> Capabilities {
+    #[cfg(feature = "json")]
+    let format_version = Some(crate::format_version());
+    #[cfg(not(feature = "json"))]
+    let format_version = None;
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        features: FEATURES.iter().filter(|(_, enabled)| *enabled).map(|(name, _)| *name).collect(),
+        format_version,
+        deterministic_math: cfg!(feature = "deterministic"),
+        simd: "scalar",
+    }
+}
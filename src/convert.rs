@@ -0,0 +1,491 @@
+//! Utilities for converting rendered samples.
+
+use std::f64::consts::PI;
+
+/// Number of zero crossings of the sinc function on each side of the kernel
+/// used by [`ResampleQuality::WindowedSinc`].
+const SINC_ZERO_CROSSINGS: usize = 16;
+
+/// Algorithm used by [`resample`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResampleQuality {
+    /// Linear interpolation between neighbouring samples. This is fast, but
+    /// does not filter out frequencies above the target Nyquist frequency
+    /// when downsampling, so these alias back into the audible range.
+    Linear,
+    /// Band-limited interpolation with a Blackman-windowed sinc kernel.
+    #[default]
+    WindowedSinc,
+}
+
+/// Converts samples from one sample rate to another. The output has
+/// `ceil(samples.len() * to_rate / from_rate)` samples; samples outside of
+/// the input are treated as silence. If the rates are equal, the samples are
+/// copied unchanged.
+///
+/// ```rust
+/// use jfxr::convert::{resample, ResampleQuality};
+/// let input: Vec<f64> = (0..44100)
+///     .map(|i| (2.0 * std::f64::consts::PI * 1000.0 * i as f64 / 44100.0).sin())
+///     .collect();
+/// let output = resample(&input, 44100.0, 22050.0, ResampleQuality::WindowedSinc);
+/// assert_eq!(output.len(), 22050);
+///
+/// // Power at the given frequency, skipping the edges of the buffer.
+/// let power = |frequency: f64| {
+///     let (mut re, mut im) = (0.0, 0.0);
+///     for (i, sample) in output.iter().enumerate().take(21050).skip(1000) {
+///         let angle = 2.0 * std::f64::consts::PI * frequency * i as f64 / 22050.0;
+///         re += sample * angle.cos();
+///         im += sample * angle.sin();
+///     }
+///     re * re + im * im
+/// };
+/// // The fundamental is still at 1 kHz, ...
+/// assert!(power(1000.0) > 100.0 * power(990.0));
+/// assert!(power(1000.0) > 100.0 * power(1010.0));
+/// // ... with very little harmonic distortion.
+/// let distortion: f64 = (2..=10).map(|n| power(1000.0 * n as f64)).sum();
+/// assert!((distortion / power(1000.0)).sqrt() < 1e-3);
+/// ```
+pub fn resample(samples: &[f64], from_rate: f64, to_rate: f64, quality: ResampleQuality) -> Vec<f64> {
+    assert!(from_rate > 0.0 && to_rate > 0.0, "sample rates must be positive");
+    if from_rate == to_rate {
+        return samples.to_vec();
+    }
+    let ratio = to_rate / from_rate;
+    let output_length = (samples.len() as f64 * ratio).ceil() as usize;
+    let sample = |index: isize| {
+        if index < 0 {
+            return 0.0;
+        }
+        samples.get(index as usize).copied().unwrap_or(0.0)
+    };
+    match quality {
+        ResampleQuality::Linear => (0..output_length)
+            .map(|i| {
+                let position = i as f64 / ratio;
+                let index = position.floor();
+                let fraction = position - index;
+                let index = index as isize;
+                (1.0 - fraction) * sample(index) + fraction * sample(index + 1)
+            })
+            .collect(),
+        ResampleQuality::WindowedSinc => {
+            // When downsampling, lower the cutoff to the new Nyquist
+            // frequency, which widens the kernel accordingly.
+            let cutoff = ratio.min(1.0);
+            let half_width = SINC_ZERO_CROSSINGS as f64 / cutoff;
+            (0..output_length)
+                .map(|i| {
+                    let position = i as f64 / ratio;
+                    let start = (position - half_width).ceil().max(0.0) as isize;
+                    let end = ((position + half_width).floor() as isize).min(samples.len() as isize - 1);
+                    let mut sum = 0.0;
+                    for index in start..=end {
+                        let x = index as f64 - position;
+                        sum += sample(index) * cutoff * sinc(cutoff * x) * blackman(x / half_width);
+                    }
+                    sum
+                })
+                .collect()
+        }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        return 1.0;
+    }
+    crate::math::sin(PI * x) / (PI * x)
+}
+
+/// Blackman window, centered at 0 and reaching zero at -1 and 1.
+fn blackman(t: f64) -> f64 {
+    0.42 + 0.5 * crate::math::cos(PI * t) + 0.08 * crate::math::cos(2.0 * PI * t)
+}
+
+/// Length of the grains used by [`stretch`], in samples.
+const GRAIN_LENGTH: usize = 1024;
+
+/// Maximum distance by which [`stretch`] moves a grain from its nominal
+/// position to line it up with the previous grain, in samples.
+const GRAIN_TOLERANCE: usize = 256;
+
+/// Changes the duration of samples by the given factor without changing the
+/// pitch, so a factor of 1.2 makes the sound 20% longer. The output has
+/// `round(samples.len() * factor)` samples.
+///
+/// This uses WSOLA (waveform similarity overlap-add): the output is built
+/// from overlapping grains of the input, crossfaded with a Hann window, and
+/// each grain is shifted slightly so that it lines up with the previous one
+/// to avoid phase cancellation. This is meant as an offline utility for
+/// rendered sounds; it is not part of the synth and is not bit-exact across
+/// versions. Transients may be smeared or doubled.
+///
+/// ```rust
+/// let input: Vec<f64> = (0..44100)
+///     .map(|i| (2.0 * std::f64::consts::PI * 440.0 * i as f64 / 44100.0).sin())
+///     .collect();
+/// assert_eq!(jfxr::convert::stretch(&input, 1.2).len(), 52920);
+/// assert_eq!(jfxr::convert::stretch(&input, 0.5).len(), 22050);
+/// ```
+pub fn stretch(samples: &[f64], factor: f64) -> Vec<f64> {
+    assert!(factor > 0.0, "stretch factor must be positive");
+    if factor == 1.0 {
+        return samples.to_vec();
+    }
+    let output_length = (samples.len() as f64 * factor).round() as usize;
+    let hop = GRAIN_LENGTH / 2;
+    let sample = |index: usize| samples.get(index).copied().unwrap_or(0.0);
+    let window: Vec<f64> = (0..GRAIN_LENGTH)
+        .map(|i| 0.5 - 0.5 * crate::math::cos(2.0 * PI * i as f64 / GRAIN_LENGTH as f64))
+        .collect();
+
+    let mut output = vec![0.0; output_length + GRAIN_LENGTH];
+    let mut previous = 0;
+    for grain in 0..output_length.div_ceil(hop) {
+        let output_position = grain * hop;
+        let nominal = (output_position as f64 / factor).round() as usize;
+        let position = if grain == 0 {
+            0
+        } else {
+            // Find the grain that best continues the previous one.
+            let continuation = previous + hop;
+            let start = nominal.saturating_sub(GRAIN_TOLERANCE);
+            let end = (nominal + GRAIN_TOLERANCE).min(samples.len().max(1) - 1).max(start);
+            (start..=end)
+                .map(|candidate| {
+                    let correlation: f64 = (0..hop).map(|i| sample(candidate + i) * sample(continuation + i)).sum();
+                    (candidate, correlation)
+                })
+                .fold((nominal, f64::NEG_INFINITY), |best, candidate| if candidate.1 > best.1 { candidate } else { best })
+                .0
+        };
+        for (i, weight) in window.iter().enumerate() {
+            // The first grain has nothing to crossfade with.
+            let weight = if grain == 0 && i < hop { 1.0 } else { *weight };
+            output[output_position + i] += weight * sample(position + i);
+        }
+        previous = position;
+    }
+    output.truncate(output_length);
+    output
+}
+
+/// Changes the pitch of samples by the given number of semitones without
+/// changing the duration, by stretching and then resampling. Like
+/// [`stretch`], this is meant as an offline utility for rendered sounds.
+///
+/// ```rust
+/// let input: Vec<f64> = (0..44100)
+///     .map(|i| (2.0 * std::f64::consts::PI * 440.0 * i as f64 / 44100.0).sin())
+///     .collect();
+/// let output = jfxr::convert::pitch_shift(&input, 12.0);
+/// assert_eq!(output.len(), input.len());
+/// // Count zero crossings in the middle of the sound to find the pitch.
+/// let crossings = output[4410..39690].windows(2).filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0)).count();
+/// let frequency = crossings as f64 / 2.0 / 0.8;
+/// assert!((frequency - 880.0).abs() < 880.0 * 0.02, "{}", frequency);
+/// ```
+pub fn pitch_shift(samples: &[f64], semitones: f64) -> Vec<f64> {
+    let ratio = crate::math::exp2(semitones / 12.0);
+    let stretched = stretch(samples, ratio);
+    let mut output = resample(&stretched, ratio, 1.0, ResampleQuality::WindowedSinc);
+    output.resize(samples.len(), 0.0);
+    output
+}
+
+/// Lowest playback rate allowed by [`apply_rate_curve`]: three octaves down.
+const MIN_RATE: f64 = 0.125;
+
+/// Highest playback rate allowed by [`apply_rate_curve`]: three octaves up.
+const MAX_RATE: f64 = 8.0;
+
+/// Speed of sound in air, in metres per second, used by [`doppler`].
+const SPEED_OF_SOUND: f64 = 343.0;
+
+/// Plays samples back at a rate that varies over time, which changes both
+/// their pitch and their duration: at a rate of 2, the sound is an octave
+/// higher and half as long. `rate` gives the playback rate as a function of
+/// the time in the input, in seconds; rates are clamped to between 1/8 and
+/// 8, and NaN is treated as 1/8. The output ends when the whole input has
+/// been played, so its length is the integral of the inverse rate over the
+/// input.
+///
+/// Samples are read with cubic interpolation, treating samples outside of
+/// the input as silence, so the resampling adds no discontinuities of its
+/// own. It does not filter out frequencies above the Nyquist frequency,
+/// which alias at high rates. Like [`stretch`], this is meant as an offline
+/// utility for rendered sounds.
+///
+/// ```rust
+/// let input: Vec<f64> = (0..44100)
+///     .map(|i| (2.0 * std::f64::consts::PI * 440.0 * i as f64 / 44100.0).sin())
+///     .collect();
+/// let output = jfxr::convert::apply_rate_curve(&input, 44100.0, |_| 2.0);
+/// assert_eq!(output.len(), 22050);
+/// // Count zero crossings in the middle of the sound to find the pitch.
+/// let crossings = output[2205..19845].windows(2).filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0)).count();
+/// let frequency = crossings as f64 / 2.0 / 0.4;
+/// assert!((frequency - 880.0).abs() < 880.0 * 0.02, "{}", frequency);
+/// ```
+pub fn apply_rate_curve(samples: &[f64], sample_rate: f64, rate: impl Fn(f64) -> f64) -> Vec<f64> {
+    assert!(sample_rate > 0.0, "sample rate must be positive");
+    let sample = |index: isize| {
+        if index < 0 {
+            return 0.0;
+        }
+        samples.get(index as usize).copied().unwrap_or(0.0)
+    };
+    let length = samples.len() as f64;
+    let mut output = Vec::with_capacity(samples.len());
+    let mut position = 0.0;
+    while position < length {
+        let index = position.floor();
+        let t = position - index;
+        let index = index as isize;
+        // Catmull-Rom spline through the four nearest samples.
+        let (p0, p1, p2, p3) = (sample(index - 1), sample(index), sample(index + 1), sample(index + 2));
+        output.push(p1 + 0.5 * t * (p2 - p0 + t * (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3 + t * (3.0 * (p1 - p2) + p3 - p0))));
+        let rate = rate(position / sample_rate);
+        position += if rate.is_nan() { MIN_RATE } else { rate.clamp(MIN_RATE, MAX_RATE) };
+    }
+    output
+}
+
+/// Simulates the Doppler effect of a sound source that moves past the
+/// listener in a straight line at `source_speed` metres per second, coming
+/// closest, at `pass_distance` metres, halfway through the sound. The pitch
+/// falls as the source passes, as in a drive-by. The loudness is left
+/// unchanged. See [`apply_rate_curve`], which this uses, for the limits on
+/// the playback rate.
+///
+/// ```rust
+/// let input: Vec<f64> = (0..44100)
+///     .map(|i| (2.0 * std::f64::consts::PI * 440.0 * i as f64 / 44100.0).sin())
+///     .collect();
+/// let output = jfxr::convert::doppler(&input, 44100.0, 30.0, 5.0);
+/// let crossings = |samples: &[f64]| samples.windows(2).filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0)).count();
+/// // Approaching at 30 m/s raises the pitch by about 9.6%, and receding
+/// // lowers it by about 8%.
+/// let start = crossings(&output[..4410]) as f64 / 2.0 / 0.1;
+/// let end = crossings(&output[output.len() - 4410..]) as f64 / 2.0 / 0.1;
+/// assert!((start - 482.0).abs() < 10.0 && (end - 405.0).abs() < 10.0, "{start} {end}");
+/// ```
+pub fn doppler(samples: &[f64], sample_rate: f64, source_speed: f64, pass_distance: f64) -> Vec<f64> {
+    let pass_time = samples.len() as f64 / sample_rate / 2.0;
+    apply_rate_curve(samples, sample_rate, |time| {
+        let along = source_speed * (time - pass_time);
+        let distance = crate::math::hypot(along, pass_distance);
+        // Speed at which the source moves away from the listener.
+        let radial_speed = if distance == 0.0 { 0.0 } else { source_speed * along / distance };
+        1.0 / (1.0 + radial_speed / SPEED_OF_SOUND)
+    })
+}
+
+/// Joins parts end to end, crossfading each part into the next over the
+/// given time, so that there is no click at the joints. The crossfade is
+/// equal-power: the outgoing part fades with a quarter cosine and the
+/// incoming part with a quarter sine, which keeps the loudness steady when
+/// the parts are uncorrelated.
+///
+/// Each crossfade is shortened to the length of the shorter of the two parts
+/// it joins, so the output has the total length of the parts minus the
+/// length of all crossfades. Empty parts are skipped, and a zero crossfade
+/// concatenates the parts unchanged.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// let charge = vec![1.0; 1000];
+/// let fire = vec![-1.0; 2000];
+/// let joined = jfxr::convert::concat(&[&charge, &[], &fire, &[0.5; 10]], Duration::from_millis(10), 44100.0);
+/// // Crossfades of 441 samples, and of 10 samples for the short last part.
+/// assert_eq!(joined.len(), 1000 + 2000 + 10 - 441 - 10);
+/// assert_eq!(joined[..559], charge[..559]);
+///
+/// // Without a crossfade, the jump from 1 to -1 is a click. With one, the
+/// // largest step between samples is a small fraction of that.
+/// let largest_step = |samples: &[f64]| samples.windows(2).map(|pair| (pair[1] - pair[0]).abs()).fold(0.0, f64::max);
+/// let plain = jfxr::convert::concat(&[&charge, &fire], Duration::ZERO, 44100.0);
+/// assert_eq!(plain.len(), 3000);
+/// assert_eq!(largest_step(&plain), 2.0);
+/// assert!(largest_step(&joined[..2500]) < std::f64::consts::PI / 441.0);
+/// ```
+pub fn concat(parts: &[&[f64]], crossfade: std::time::Duration, sample_rate: f64) -> Vec<f64> {
+    let crossfade = (crossfade.as_secs_f64() * sample_rate).round() as usize;
+    let mut output = Vec::with_capacity(parts.iter().map(|part| part.len()).sum());
+    let mut previous_len = 0;
+    for part in parts.iter().filter(|part| !part.is_empty()) {
+        let overlap = crossfade.min(previous_len).min(part.len());
+        let start = output.len() - overlap;
+        for (i, sample) in part[..overlap].iter().enumerate() {
+            let angle = 0.5 * PI * (i as f64 + 0.5) / overlap as f64;
+            output[start + i] = output[start + i] * crate::math::cos(angle) + sample * crate::math::sin(angle);
+        }
+        output.extend_from_slice(&part[overlap..]);
+        previous_len = part.len();
+    }
+    output
+}
+
+/// Shape of the crossfade of [`morph_render`], which gives the weights of
+/// the two sounds over the course of the morph.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MorphCurve {
+    /// The weights change at a constant rate.
+    #[default]
+    Linear,
+    /// The weights change slowly at the start and the end, and fastest
+    /// halfway, following a smoothstep.
+    Smooth,
+    /// The weights follow a quarter cosine and a quarter sine, like the
+    /// crossfades of [`concat`].
+    EqualPower,
+}
+
+impl MorphCurve {
+    /// Returns the weights of the first and the second sound at the given
+    /// position in the morph, from 0 to 1.
+    fn weights(self, position: f64) -> (f64, f64) {
+        match self {
+            Self::Linear => (1.0 - position, position),
+            Self::Smooth => {
+                let smooth = position * position * (3.0 - 2.0 * position);
+                (1.0 - smooth, smooth)
+            }
+            Self::EqualPower => {
+                let angle = 0.5 * PI * position;
+                (crate::math::cos(angle), crate::math::sin(angle))
+            }
+        }
+    }
+}
+
+/// How [`morph_render`] lines up sounds whose renders differ in length.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MorphLength {
+    /// The shorter render is stretched with [`stretch`] to the length of the
+    /// longer one, which keeps its pitch.
+    #[default]
+    Stretch,
+    /// The shorter render is padded with silence at the end.
+    Pad,
+}
+
+/// Length of the blocks over which [`morph_render`] measures the loudness,
+/// in samples.
+const MORPH_BLOCK: usize = 1024;
+
+/// Largest gain that [`morph_render`] applies to keep the loudness steady,
+/// for blends in which the two sounds cancel out.
+const MAX_MORPH_GAIN: f64 = 4.0;
+
+/// Renders a morph from sound `a` to sound `b`, in the audio domain: both
+/// sounds are rendered, lined up as given by `length`, and crossfaded over
+/// the whole length following `curve`. Unlike interpolating between the
+/// parameters, this sounds smooth even when the waveforms differ, at the
+/// cost of hearing both sounds at once halfway. The output has the sample
+/// rate of `a`; `b` is resampled if its sample rate differs.
+///
+/// The blend is normalized so that the loudness moves steadily from that of
+/// `a` to that of `b`, rather than dipping or bulging where both are mixed.
+/// The root mean square of every block of 1024 samples is brought to that of
+/// the two sounds at that point, weighted by their share of the power, with
+/// the gain interpolated between the blocks and limited to 4.
+///
+/// ```rust
+/// use jfxr::convert::{morph_render, MorphCurve, MorphLength};
+/// use jfxr::parameter::Waveform;
+///
+/// let mut a = jfxr::Sound::default();
+/// (a.waveform, a.frequency.0, a.sustain.0) = (Waveform::Square, 440.0, 1.0);
+/// let mut b = a.clone();
+/// (b.waveform, b.frequency.0, b.amplification.0) = (Waveform::Sine, 660.0, 25.0);
+/// let rms = |samples: &[f64]| (samples.iter().map(|sample| sample * sample).sum::<f64>() / samples.len() as f64).sqrt();
+/// let (first, second) = (a.render(), b.render());
+///
+/// for curve in [MorphCurve::Linear, MorphCurve::Smooth, MorphCurve::EqualPower] {
+///     let morph = morph_render(&a, &b, curve, MorphLength::Stretch);
+///     assert_eq!(morph.len(), first.len());
+///     // It starts as `a` and ends as `b`, ...
+///     let end = morph.len() - 10;
+///     assert!(morph[..10].iter().zip(&first).all(|(x, y)| (x - y).abs() < 0.02), "{curve:?}");
+///     assert!(morph[end..].iter().zip(&second[end..]).all(|(x, y)| (x - y).abs() < 0.02), "{curve:?}");
+///     // ... with the loudness falling steadily in between.
+///     let levels: Vec<f64> = morph.chunks(4410).map(rms).collect();
+///     assert!(levels.windows(2).all(|pair| pair[1] <= pair[0] + 1e-3), "{curve:?}: {levels:?}");
+///     assert!((levels[0] - rms(&first[..4410])).abs() < 0.02);
+///     assert!((levels[9] - rms(&second[39690..])).abs() < 0.02);
+/// }
+///
+/// // A morph of a sound into itself is the sound.
+/// let morph = morph_render(&a, &a, MorphCurve::Smooth, MorphLength::Pad);
+/// assert!(morph.iter().zip(&first).all(|(x, y)| (x - y).abs() < 1e-9));
+///
+/// // A shorter sound is stretched, or followed by silence.
+/// b.sustain.0 = 0.5;
+/// let stretched = morph_render(&a, &b, MorphCurve::Linear, MorphLength::Stretch);
+/// let padded = morph_render(&a, &b, MorphCurve::Linear, MorphLength::Pad);
+/// assert_eq!((stretched.len(), padded.len()), (first.len(), first.len()));
+/// assert!(rms(&stretched[40000..]) > 0.1);
+/// assert!(rms(&padded[40000..]) < 0.01);
+/// ```
+///
+/// # Panics
+///
+/// Panics if either sound cannot be generated, like [`crate::generate`].
+pub fn morph_render(a: &crate::sound::Sound, b: &crate::sound::Sound, curve: MorphCurve, length: MorphLength) -> Vec<f64> {
+    let first = a.render();
+    let mut second = b.render();
+    if b.sample_rate.0 != a.sample_rate.0 {
+        second = resample(&second, b.sample_rate.0, a.sample_rate.0, ResampleQuality::WindowedSinc);
+    }
+    let len = first.len().max(second.len());
+    let fit = |samples: Vec<f64>| {
+        let mut samples = match length {
+            MorphLength::Stretch if !samples.is_empty() && samples.len() < len => stretch(&samples, len as f64 / samples.len() as f64),
+            _ => samples,
+        };
+        samples.resize(len, 0.0);
+        samples
+    };
+    let (first, second) = (fit(first), fit(second));
+    let position = |index: f64| if len > 1 { index / (len - 1) as f64 } else { 0.0 };
+    let mut output: Vec<f64> = (0..len)
+        .map(|i| {
+            let (weight_a, weight_b) = curve.weights(position(i as f64));
+            weight_a * first[i] + weight_b * second[i]
+        })
+        .collect();
+
+    // The gain of each block, at its center.
+    let rms = |samples: &[f64]| (samples.iter().map(|sample| sample * sample).sum::<f64>() / samples.len() as f64).sqrt();
+    let gains: Vec<(f64, f64)> = (0..len)
+        .step_by(MORPH_BLOCK)
+        .map(|start| {
+            let end = (start + MORPH_BLOCK).min(len);
+            let center = (start + end - 1) as f64 / 2.0;
+            let (weight_a, weight_b) = curve.weights(position(center));
+            let share = weight_b * weight_b / (weight_a * weight_a + weight_b * weight_b);
+            let target = (1.0 - share) * rms(&first[start..end]) + share * rms(&second[start..end]);
+            let blend = rms(&output[start..end]);
+            let gain = if blend > 0.0 { (target / blend).min(MAX_MORPH_GAIN) } else { 1.0 };
+            (center, gain)
+        })
+        .collect();
+    let mut block = 0;
+    for (i, sample) in output.iter_mut().enumerate() {
+        let i = i as f64;
+        while block + 1 < gains.len() && gains[block + 1].0 <= i {
+            block += 1;
+        }
+        let (center, gain) = gains[block];
+        *sample *= match gains.get(block + 1) {
+            Some((next_center, next_gain)) if i > center => gain + (next_gain - gain) * (i - center) / (next_center - center),
+            _ => gain,
+        };
+    }
+    output
+}
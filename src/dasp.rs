@@ -0,0 +1,64 @@
+//! Integration with the [`dasp`] digital signal processing crates.
+//!
+//! [`Samples`] implements [`dasp::Signal`] with `f64` frames, so a sound can
+//! be fed into a `dasp` signal graph directly:
+//!
+//! ```rust
+//! use dasp::Signal;
+//!
+//! let mut sound = jfxr::Sound::default();
+//! sound.sustain.0 = 0.3;
+//! let signal = jfxr::Synth::new(&sound).stream().samples();
+//! let quieter: Vec<f64> = signal.scale_amp(0.5).until_exhausted().collect();
+//! assert_eq!(quieter.len(), sound.num_samples());
+//! ```
+//!
+//! [`Samples`] is also an [`Iterator`], so methods that exist on both traits,
+//! such as `map`, must be called as `Signal::map(samples, ...)` when
+//! [`dasp::Signal`] is in scope.
+
+use dasp::sample::FromSample;
+use dasp::{Frame, Sample};
+
+use crate::synth::Samples;
+
+impl dasp::Signal for Samples<'_> {
+    type Frame = f64;
+
+    /// Returns the next sample, or silence once the sound has ended.
+    fn next(&mut self) -> f64 {
+        Iterator::next(self).unwrap_or(0.0)
+    }
+
+    fn is_exhausted(&self) -> bool {
+        Samples::is_exhausted(self)
+    }
+}
+
+/// Converts single-channel samples into frames of any `dasp` frame type,
+/// copying each sample into every channel and converting it to the sample
+/// type of the frame.
+///
+/// ```rust
+/// let frames: Vec<[f32; 2]> = jfxr::dasp::to_frames(&[0.5, -1.0]);
+/// assert_eq!(frames, vec![[0.5, 0.5], [-1.0, -1.0]]);
+/// let frames: Vec<i16> = jfxr::dasp::to_frames(&[0.5]);
+/// assert_eq!(frames, vec![16384]);
+/// ```
+pub fn to_frames<F>(samples: &[f64]) -> Vec<F>
+where
+    F: Frame,
+    F::Sample: FromSample<f64>,
+{
+    samples
+        .iter()
+        .map(|sample| F::from_fn(|_| sample.to_sample()))
+        .collect()
+}
+
+/// Converts single-channel samples into two-channel `f32` frames, with the
+/// sound centred between both channels. This is the frame format expected
+/// by most audio output libraries.
+pub fn to_stereo_frames(samples: &[f64]) -> Vec<[f32; 2]> {
+    to_frames(samples)
+}
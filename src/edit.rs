@@ -0,0 +1,196 @@
+//! Editing of sounds with undo and redo, for sound editors. Requires the
+//! `edit` feature.
+//!
+//! An [`EditableSound`] keeps its sound in an `Arc`, so that snapshots for
+//! rendering threads and [`Checkpoint`]s cost a reference count. Edits are
+//! made to a copy, which leaves the snapshots unchanged. Every
+//! edit goes through the parameters of [`Sound::params`] and is recorded in
+//! a journal, from which it can be undone and redone:
+//!
+//! ```rust
+//! use jfxr::edit::EditableSound;
+//! use jfxr::parameter::{EnumParameter, ParamId, Value, Waveform};
+//! use jfxr::sound::Example;
+//!
+//! let laser = jfxr::Sound::example(Example::Laser);
+//! let mut sound = EditableSound::new(laser.clone());
+//! let checkpoint = sound.checkpoint();
+//! assert!(sound.set("frequency", 880.0.into()));
+//! assert!(sound.set("waveform", Value::Enum(Waveform::Whistle.index())));
+//! assert!(!sound.set("frequency", 880.0.into()));
+//! let ids: Vec<_> = sound.diff_since(&checkpoint).iter().filter_map(|change| change.id).collect();
+//! assert_eq!(ids, [ParamId::Frequency, ParamId::Waveform]);
+//!
+//! sound.undo();
+//! assert!(sound.sound().waveform == laser.waveform);
+//! assert_eq!(sound.sound().frequency.0, 880.0);
+//! sound.undo();
+//! assert!(sound.diff_since(&checkpoint).is_empty());
+//! assert!(!sound.undo());
+//!
+//! sound.redo();
+//! sound.redo();
+//! assert!(sound.sound().waveform == Waveform::Whistle);
+//! assert_eq!(sound.journal().count(), 2);
+//!
+//! // A new edit after undoing discards the steps that could be redone.
+//! sound.undo();
+//! sound.set("frequency", 440.0.into());
+//! assert!(!sound.can_redo());
+//! assert!(sound.sound().waveform == laser.waveform);
+//! ```
+
+use std::sync::Arc;
+
+use crate::parameter::{Param, Value};
+use crate::patch::SoundPatch;
+use crate::sound::{ParamChange, Sound};
+
+/// A change of a single parameter, as recorded in the journal of an
+/// [`EditableSound`].
+#[derive(Clone, Copy)]
+pub struct Edit {
+    pub param: &'static Param,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// The state of an [`EditableSound`] at some point, to compare against
+/// later with [`EditableSound::diff_since`].
+#[derive(Clone)]
+pub struct Checkpoint(Arc<Sound>);
+
+impl Checkpoint {
+    /// Returns the sound as it was when the checkpoint was taken.
+    pub fn sound(&self) -> &Sound {
+        &self.0
+    }
+}
+
+/// A sound with a history of edits that can be undone and redone. Each call
+/// to an editing method is one step of the history. Only parameters are
+/// tracked; the name and the custom harmonic amplitudes are not edited
+/// through this type.
+#[derive(Clone)]
+pub struct EditableSound {
+    sound: Arc<Sound>,
+    /// Steps that can be undone, the last one first.
+    done: Vec<Vec<Edit>>,
+    /// Steps that can be redone, the last one first.
+    undone: Vec<Vec<Edit>>,
+}
+
+impl EditableSound {
+    pub fn new(sound: Sound) -> Self {
+        Self {
+            sound: Arc::new(sound),
+            done: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn sound(&self) -> &Sound {
+        &self.sound
+    }
+
+    /// Returns the current sound without copying it, for example to render
+    /// it on another thread. Later edits do not affect the snapshot.
+    pub fn snapshot(&self) -> Arc<Sound> {
+        self.sound.clone()
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.sound.clone())
+    }
+
+    /// Returns the parameters that changed since the checkpoint was taken,
+    /// as in [`Sound::diff`]. Parameters that were changed and then changed
+    /// back are not included.
+    pub fn diff_since(&self, checkpoint: &Checkpoint) -> Vec<ParamChange> {
+        checkpoint.0.diff(&self.sound)
+    }
+
+    /// Sets the parameter with the given key in the `jfxr` format, as in
+    /// [`Param::set`]. The value is not clamped. Returns whether the sound
+    /// changed; setting a parameter to its current value is not recorded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no parameter with the given key.
+    pub fn set(&mut self, key: &str, value: Value) -> bool {
+        let param = Sound::param(key).unwrap_or_else(|| panic!("unknown parameter {key:?}"));
+        self.edit(std::iter::once((param, value)))
+    }
+
+    /// Applies the patch as a single step.
+    pub fn apply(&mut self, patch: &SoundPatch) -> bool {
+        self.edit(patch.iter())
+    }
+
+    /// Sets every parameter to its value in `other` as a single step, for
+    /// example after randomizing or mutating a copy of the sound.
+    pub fn assign(&mut self, other: &Sound) -> bool {
+        self.edit(Sound::params().iter().map(|param| (param, param.get(other))))
+    }
+
+    fn edit(&mut self, values: impl Iterator<Item = (&'static Param, Value)>) -> bool {
+        let mut edited = Sound::clone(&self.sound);
+        let mut step = Vec::new();
+        for (param, value) in values {
+            let old = param.get(&edited);
+            param.set(&mut edited, value);
+            let new = param.get(&edited);
+            if new != old {
+                step.push(Edit { param, old, new });
+            }
+        }
+        if step.is_empty() {
+            return false;
+        }
+        self.sound = Arc::new(edited);
+        self.done.push(step);
+        self.undone.clear();
+        true
+    }
+
+    /// Reverts the last step. Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(step) = self.done.pop() else {
+            return false;
+        };
+        let sound = Arc::make_mut(&mut self.sound);
+        for edit in step.iter().rev() {
+            edit.param.set(sound, edit.old);
+        }
+        self.undone.push(step);
+        true
+    }
+
+    /// Applies the last undone step again. Returns false if there is nothing
+    /// to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(step) = self.undone.pop() else {
+            return false;
+        };
+        let sound = Arc::make_mut(&mut self.sound);
+        for edit in &step {
+            edit.param.set(sound, edit.new);
+        }
+        self.done.push(step);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Returns the edits that can be undone, in the order in which they were
+    /// made.
+    pub fn journal(&self) -> impl Iterator<Item = &Edit> {
+        self.done.iter().flatten()
+    }
+}
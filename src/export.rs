@@ -0,0 +1,192 @@
+//! Rendered samples as source code, to embed sounds in a binary without a
+//! file system or an audio decoder.
+
+/// Size of each sample in the generated array.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BitDepth {
+    /// Signed 8-bit samples, `int8_t` or `i8`.
+    Eight,
+    /// Signed 16-bit samples, `int16_t` or `i16`.
+    #[default]
+    Sixteen,
+}
+
+impl BitDepth {
+    /// Returns the number of bits per sample.
+    pub fn bits(self) -> u32 {
+        match self {
+            BitDepth::Eight => 8,
+            BitDepth::Sixteen => 16,
+        }
+    }
+    fn max_value(self) -> f64 {
+        match self {
+            BitDepth::Eight => i8::MAX as f64,
+            BitDepth::Sixteen => i16::MAX as f64,
+        }
+    }
+    fn c_type(self) -> &'static str {
+        match self {
+            BitDepth::Eight => "int8_t",
+            BitDepth::Sixteen => "int16_t",
+        }
+    }
+    fn rust_type(self) -> &'static str {
+        match self {
+            BitDepth::Eight => "i8",
+            BitDepth::Sixteen => "i16",
+        }
+    }
+}
+
+/// Settings of [`to_c_array`] and [`to_rust_static`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// Name of the sound, from which the symbols are derived. It is turned
+    /// into a valid identifier with [`sanitize_identifier`].
+    pub name: String,
+    pub bit_depth: BitDepth,
+    /// Maximum length of the lines that hold the samples, including the
+    /// indentation. Each line holds at least one sample.
+    pub line_width: usize,
+}
+
+impl ExportOptions {
+    /// Creates options for a sound with the given name, with 16-bit samples
+    /// and lines of up to 100 characters.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            bit_depth: BitDepth::default(),
+            line_width: 100,
+        }
+    }
+}
+
+/// Turns a name into a valid C and Rust identifier, by replacing every
+/// character other than an ASCII letter, digit or underscore with an
+/// underscore, and prefixing an underscore if it starts with a digit. An
+/// empty name becomes `sound`.
+///
+/// ```rust
+/// use jfxr::export::sanitize_identifier;
+///
+/// assert_eq!(sanitize_identifier("Coin"), "Coin");
+/// assert_eq!(sanitize_identifier("laser shot-2"), "laser_shot_2");
+/// assert_eq!(sanitize_identifier("8bit"), "_8bit");
+/// assert_eq!(sanitize_identifier("Café"), "Caf_");
+/// assert_eq!(sanitize_identifier(""), "sound");
+/// ```
+pub fn sanitize_identifier(name: &str) -> String {
+    let identifier: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    match identifier.chars().next() {
+        None => "sound".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("_{identifier}"),
+        Some(_) => identifier,
+    }
+}
+
+/// Returns a C source file that defines the samples as a constant array
+/// named `<name>_pcm`, and their number as `<name>_pcm_len`, where the name
+/// is lowercased. Samples are clamped to the range -1 to 1 and rounded, like
+/// in [`crate::wav::write_wav`]. Since C does not allow empty arrays, an
+/// empty sound is stored as a single zero with a length of 0.
+///
+/// ```rust
+/// use jfxr::export::{BitDepth, ExportOptions};
+///
+/// let c = jfxr::export::to_c_array(&[0.0, 0.5, -1.0], &ExportOptions::new("Coin"));
+/// assert_eq!(c, "\
+/// ##include <stdint.h>
+///
+/// const int16_t coin_pcm[] = {
+///     0, 16384, -32767,
+/// };
+/// const unsigned coin_pcm_len = 3;
+/// ");
+///
+/// let options = ExportOptions { bit_depth: BitDepth::Eight, ..ExportOptions::new("") };
+/// let c = jfxr::export::to_c_array(&[], &options);
+/// assert!(c.contains("const int8_t sound_pcm[] = {\n    0,\n};\nconst unsigned sound_pcm_len = 0;\n"));
+/// ```
+pub fn to_c_array(samples: &[f64], options: &ExportOptions) -> String {
+    let symbol = format!("{}_pcm", sanitize_identifier(&options.name).to_lowercase());
+    let values = if samples.is_empty() { vec!["0".to_string()] } else { quantize(samples, options.bit_depth) };
+    format!(
+        "#include <stdint.h>\n\nconst {} {symbol}[] = {{\n{}}};\nconst unsigned {symbol}_len = {};\n",
+        options.bit_depth.c_type(),
+        wrap(&values, options.line_width),
+        samples.len(),
+    )
+}
+
+/// Returns Rust source code that defines the samples as a static array
+/// named `<NAME>_PCM`, where the name is uppercased. Samples are clamped and
+/// rounded like in [`to_c_array`].
+///
+/// ```rust
+/// use jfxr::export::{BitDepth, ExportOptions};
+///
+/// let options = ExportOptions { line_width: 20, ..ExportOptions::new("laser shot") };
+/// let rust = jfxr::export::to_rust_static(&[0.0, 0.25, 0.5, 0.75, 1.0], &options);
+/// assert_eq!(rust, "\
+/// pub static LASER_SHOT_PCM: [i16; 5] = [
+///     0, 8192, 16384,
+///     24575, 32767,
+/// ];
+/// ");
+/// // The generated code compiles as it is.
+/// pub static LASER_SHOT_PCM: [i16; 5] = [
+///     0, 8192, 16384,
+///     24575, 32767,
+/// ];
+/// assert_eq!(LASER_SHOT_PCM.len(), 5);
+///
+/// let options = ExportOptions { bit_depth: BitDepth::Eight, ..ExportOptions::new("2") };
+/// let rust = jfxr::export::to_rust_static(&[], &options);
+/// assert_eq!(rust, "pub static _2_PCM: [i8; 0] = [\n];\n");
+/// pub static _2_PCM: [i8; 0] = [
+/// ];
+/// assert!(_2_PCM.is_empty());
+/// ```
+pub fn to_rust_static(samples: &[f64], options: &ExportOptions) -> String {
+    format!(
+        "pub static {}_PCM: [{}; {}] = [\n{}];\n",
+        sanitize_identifier(&options.name).to_uppercase(),
+        options.bit_depth.rust_type(),
+        samples.len(),
+        wrap(&quantize(samples, options.bit_depth), options.line_width),
+    )
+}
+
+fn quantize(samples: &[f64], bit_depth: BitDepth) -> Vec<String> {
+    samples
+        .iter()
+        .map(|sample| ((sample.clamp(-1.0, 1.0) * bit_depth.max_value()).round() as i32).to_string())
+        .collect()
+}
+
+/// Formats the values as indented lines of comma-separated values, each
+/// ending with a comma and a newline.
+fn wrap(values: &[String], line_width: usize) -> String {
+    let mut out = String::new();
+    let mut line = String::new();
+    for value in values {
+        if !line.is_empty() && line.len() + 1 + value.len() + 1 > line_width {
+            out.push_str(&line);
+            out.push('\n');
+            line.clear();
+        }
+        line.push_str(if line.is_empty() { "    " } else { " " });
+        line.push_str(value);
+        line.push(',');
+    }
+    if !line.is_empty() {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
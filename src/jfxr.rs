@@ -1,3 +1,6 @@
+use crate::parameter::{Param, ParamKind, Value};
+use crate::patch::{Conflict, SoundDelta, SoundPatch};
+use crate::presets::{DistributionTable, ParamDistribution};
 use crate::sound::Sound;
 
 /// Error encountered while parsing a `jfxr` sound.
@@ -15,16 +18,68 @@ pub enum JfxrFormatError {
     /// A field had an invalid value or wrong type.
     InvalidField(&'static str),
 
-    /// The file was encoded with a newer version of `jfxr`.
-    UnsupportedVersion,
+    /// The file was encoded with a newer version of `jfxr`, which writes the
+    /// given format version.
+    UnsupportedVersion { version: u32 },
+
+    /// A field did not correspond to any parameter.
+    UnknownField(String),
+
+    /// The input is longer than allowed by [`Limits::max_input_len`].
+    InputTooLong { len: usize, max_len: usize },
+
+    /// Arrays and objects are nested deeper than allowed by
+    /// [`Limits::max_nesting`].
+    TooDeeplyNested { max_nesting: usize },
+
+    /// A number field is too large to be represented.
+    NonFiniteNumber(&'static str),
+}
+
+impl std::fmt::Display for JfxrFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(error) => write!(f, "invalid JSON: {error}"),
+            Self::NotAnObject => write!(f, "expected a JSON object"),
+            Self::MissingField(field) => write!(f, "missing field \"{field}\""),
+            Self::InvalidField(field) => write!(f, "invalid value for field \"{field}\""),
+            Self::UnsupportedVersion { version } => write!(f, "file has format version {version}, but only versions up to {VERSION} are supported"),
+            Self::UnknownField(field) => write!(f, "unknown field \"{field}\""),
+            Self::InputTooLong { len, max_len } => write!(f, "input is {len} bytes long, which is more than the limit of {max_len}"),
+            Self::TooDeeplyNested { max_nesting } => write!(f, "input is nested more than {max_nesting} levels deep"),
+            Self::NonFiniteNumber(field) => write!(f, "field \"{field}\" is not a finite number"),
+        }
+    }
 }
 
+impl std::error::Error for JfxrFormatError {}
+
 impl From<json::Error> for JfxrFormatError {
     fn from(value: json::Error) -> Self {
         Self::Json(value)
     }
 }
 
+/// Reading of numbers without the rounding errors of
+/// [`json::JsonValue::as_f64`], which can read `0.21000000000000002` as
+/// `0.21`. The decimal digits are kept by the parser and converted with the
+/// correctly rounded parser of the standard library instead, so that every
+/// number written by [`write_jfxr`] reads back as the same value. The NaN
+/// that [`json::JsonValue`] stores for NaN and infinite numbers is not a
+/// number.
+trait AsExactF64 {
+    fn as_exact_f64(&self) -> Option<f64>;
+}
+
+impl AsExactF64 for json::JsonValue {
+    fn as_exact_f64(&self) -> Option<f64> {
+        let number = self.as_number().filter(|number| !number.is_nan())?;
+        let (positive, mantissa, exponent) = number.as_parts();
+        let value: f64 = format!("{mantissa}e{exponent}").parse().ok()?;
+        Some(if positive { value } else { -value })
+    }
+}
+
 /// This is the version written out to sound files. We maintain backwards
 /// compatibility with files written by older versions where possible, but
 /// refuse to read files written by newer versions. Only bump the version
@@ -32,10 +87,228 @@ impl From<json::Error> for JfxrFormatError {
 /// files written by this version.
 pub const VERSION: u32 = 1;
 
+/// Returns the format version that [`write_jfxr`] writes, which is
+/// [`VERSION`].
+pub fn format_version() -> u32 {
+    VERSION
+}
+
+/// Returns whether files with the given format version can be read, which
+/// holds for the current version and all older ones.
+///
+/// ```rust
+/// assert!(jfxr::supports_version(jfxr::format_version()));
+/// assert!(jfxr::supports_version(0));
+/// assert!(!jfxr::supports_version(jfxr::format_version() + 1));
+///
+/// let newer = jfxr::write_jfxr(jfxr::Sound::default()).replace("\"_version\":1", "\"_version\":2");
+/// assert_eq!(jfxr::read_jfxr(&newer).err(), Some(jfxr::jfxr::JfxrFormatError::UnsupportedVersion { version: 2 }));
+/// ```
+pub fn supports_version(version: u32) -> bool {
+    version <= VERSION
+}
+
+/// Returns the format version of a `jfxr` file, reading only as far as its
+/// `_version` field, which files start with, to check whether many files
+/// can be read without parsing them. The rest of the file is not checked,
+/// so [`read_jfxr`] can still fail on a file whose version is supported.
+///
+/// ```rust
+/// use jfxr::jfxr::{peek_version, JfxrFormatError};
+///
+/// let data = jfxr::write_jfxr(jfxr::Sound::default());
+/// assert_eq!(peek_version(&data), Ok(jfxr::format_version()));
+/// // Only the start of the file is read.
+/// assert_eq!(peek_version(&format!("{}garbage", &data[..14])), Ok(1));
+/// assert_eq!(peek_version(r#" { "_name": "a \"b\"", "_locked": [["}"]], "_version" : 3 }"#), Ok(3));
+///
+/// assert_eq!(peek_version(r#"{"_name": "a"}"#), Err(JfxrFormatError::MissingField("_version")));
+/// assert_eq!(peek_version(r#"{"_version": "1"}"#), Err(JfxrFormatError::InvalidField("_version")));
+/// assert_eq!(peek_version("[1]"), Err(JfxrFormatError::NotAnObject));
+/// assert!(matches!(peek_version(r#"{"_name": "#), Err(JfxrFormatError::Json(_))));
+/// ```
+pub fn peek_version(jfxr: &str) -> Result<u32, JfxrFormatError> {
+    let mut scanner = Scanner { json: jfxr, pos: 0 };
+    scanner.skip_whitespace();
+    if !scanner.eat(b'{') {
+        // Not an object, or not even JSON.
+        json::parse(jfxr)?;
+        return Err(JfxrFormatError::NotAnObject);
+    }
+    scanner.skip_whitespace();
+    if scanner.eat(b'}') {
+        return Err(JfxrFormatError::MissingField("_version"));
+    }
+    loop {
+        let key = scanner.string()?;
+        scanner.skip_whitespace();
+        scanner.expect(b':')?;
+        scanner.skip_whitespace();
+        let start = scanner.pos;
+        scanner.skip_value()?;
+        let is_version = match key.contains('\\') {
+            false => key == "\"_version\"",
+            true => json::parse(key)?.as_str() == Some("_version"),
+        };
+        if is_version {
+            return json::parse(&jfxr[start..scanner.pos])?.as_u32().ok_or(JfxrFormatError::InvalidField("_version"));
+        }
+        scanner.skip_whitespace();
+        if scanner.eat(b'}') {
+            return Err(JfxrFormatError::MissingField("_version"));
+        }
+        scanner.expect(b',')?;
+        scanner.skip_whitespace();
+    }
+}
+
+/// Reads through JSON without parsing it, for [`peek_version`].
+struct Scanner<'a> {
+    json: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.json.as_bytes().get(self.pos).copied()
+    }
+    /// Moves past the character at the current position.
+    fn advance(&mut self) {
+        self.pos += self.json[self.pos..].chars().next().map_or(0, char::len_utf8);
+    }
+    fn eat(&mut self, byte: u8) -> bool {
+        let matches = self.peek() == Some(byte);
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+    fn expect(&mut self, byte: u8) -> Result<(), JfxrFormatError> {
+        match self.eat(byte) {
+            true => Ok(()),
+            false => Err(self.unexpected()),
+        }
+    }
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+    /// Returns the error for the byte at the current position, like the
+    /// `json` crate.
+    fn unexpected(&self) -> JfxrFormatError {
+        let before = &self.json[..self.pos];
+        match self.json[self.pos..].chars().next() {
+            None => JfxrFormatError::Json(json::Error::UnexpectedEndOfJson),
+            Some(ch) => JfxrFormatError::Json(json::Error::UnexpectedCharacter {
+                ch,
+                line: before.matches('\n').count() + 1,
+                column: before.rsplit('\n').next().unwrap_or("").chars().count() + 1,
+            }),
+        }
+    }
+    /// Skips a string and returns it, including the quotes.
+    fn string(&mut self) -> Result<&'a str, JfxrFormatError> {
+        let start = self.pos;
+        self.expect(b'"')?;
+        loop {
+            match self.peek() {
+                None => return Err(self.unexpected()),
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    self.pos += 1;
+                    self.advance();
+                }
+                Some(_) => self.advance(),
+            }
+        }
+        self.pos += 1;
+        Ok(&self.json[start..self.pos])
+    }
+    /// Skips a value, only checking that brackets are balanced.
+    fn skip_value(&mut self) -> Result<(), JfxrFormatError> {
+        let mut depth = 0usize;
+        loop {
+            match self.peek() {
+                None => return Err(self.unexpected()),
+                Some(b'"') => {
+                    self.string()?;
+                }
+                Some(b'[' | b'{') => {
+                    depth += 1;
+                    self.pos += 1;
+                }
+                Some(b']' | b'}') if depth > 0 => {
+                    depth -= 1;
+                    self.pos += 1;
+                }
+                Some(b',' | b']' | b'}' | b' ' | b'\t' | b'\n' | b'\r') if depth == 0 => return Ok(()),
+                Some(_) => self.advance(),
+            }
+            if depth == 0 && matches!(self.json.as_bytes()[self.pos - 1], b'"' | b']' | b'}') {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Largest magnitude of a number in a `jfxr` file, far outside the range of
+/// every parameter. Parameters are not clamped to their range on reading,
+/// but larger values are rejected, so that the durations and sample counts
+/// computed from them stay finite.
+pub const MAX_MAGNITUDE: f64 = 1e6;
+
 /// Parses a string as a `jfxr` file and outputs the parsed [`Sound`], if
-/// successful.
+/// successful. Numbers that are too large to be represented are rejected
+/// with [`JfxrFormatError::NonFiniteNumber`], and numbers larger than
+/// [`MAX_MAGNITUDE`] with [`JfxrFormatError::InvalidField`].
+///
+/// ```rust
+/// use jfxr::jfxr::{read_jfxr, JfxrFormatError};
+///
+/// let data = jfxr::write_jfxr(jfxr::Sound::example(jfxr::sound::Example::Coin));
+/// for field in ["attack", "sustain", "frequency", "sampleRate"] {
+///     let from = format!("\"{field}\":");
+///     let huge = data.replace(&from, &format!("\"{field}\":1e308,\"_\":"));
+///     assert_eq!(read_jfxr(&huge).err(), Some(JfxrFormatError::InvalidField(field)));
+///     let infinite = data.replace(&from, &format!("\"{field}\":-1e999,\"_\":"));
+///     assert_eq!(read_jfxr(&infinite).err(), Some(JfxrFormatError::NonFiniteNumber(field)));
+/// }
+/// ```
 pub fn read_jfxr(jfxr: &str) -> Result<Sound, JfxrFormatError> {
-    let json = match json::parse(jfxr)? {
+    #[cfg(feature = "tracing")]
+    let _entered = tracing::debug_span!("parse", len = jfxr.len()).entered();
+    from_json_value(&json::parse(jfxr)?)
+}
+
+/// Reads a [`Sound`] from the parsed JSON of a `jfxr` file, such as one
+/// that is embedded in a larger document, with the same checks as
+/// [`read_jfxr`].
+///
+/// ```rust
+/// use jfxr::jfxr::{from_json_value, to_json_value};
+///
+/// let coin = jfxr::Sound::example(jfxr::sound::Example::Coin);
+/// let mut project = json::object! { "sounds": [] };
+/// project["sounds"].push(to_json_value(&coin)).unwrap();
+///
+/// let text = project.dump();
+/// let project = json::parse(&text).unwrap();
+/// let read = from_json_value(&project["sounds"][0]).unwrap();
+/// assert!(read.diff(&coin).is_empty());
+/// assert_eq!(from_json_value(&project).err(), Some(jfxr::jfxr::JfxrFormatError::MissingField("_version")));
+///
+/// // Unlike JSON text, parsed JSON can hold NaN and infinite numbers.
+/// for field in ["attack", "decay", "frequency", "harmonicsCustomAmplitudes"] {
+///     for value in [f64::NAN, f64::INFINITY, 1e308] {
+///         let mut sound = to_json_value(&coin);
+///         sound[field] = if field == "harmonicsCustomAmplitudes" { json::array![0.5, value] } else { value.into() };
+///         assert_eq!(from_json_value(&sound).err(), Some(jfxr::jfxr::JfxrFormatError::InvalidField(field)));
+///     }
+/// }
+/// ```
+pub fn from_json_value(value: &json::JsonValue) -> Result<Sound, JfxrFormatError> {
+    let json = match value {
         json::JsonValue::Object(o) => o,
         _ => return Err(JfxrFormatError::NotAnObject),
     };
@@ -52,32 +325,77 @@ pub fn read_jfxr(jfxr: &str) -> Result<Sound, JfxrFormatError> {
             crate::parameter::$ty(read_field!($name, $get))
         };
     }
+    macro_rules! read_param_or_default {
+        ($ty:ident, $name:literal, $get:ident) => {
+            match json.get($name) {
+                Some(value) => crate::parameter::$ty(value.$get().ok_or(JfxrFormatError::InvalidField($name))?),
+                None => crate::parameter::$ty::default(),
+            }
+        };
+    }
     let version = read_field!("_version", as_u32);
-    if version > VERSION {
-        return Err(JfxrFormatError::UnsupportedVersion);
+    if !supports_version(version) {
+        return Err(JfxrFormatError::UnsupportedVersion { version });
     }
     let name = read_field!("_name", as_str).to_string();
-    // TODO: _locked field
-    Ok(Sound {
+    let locked = match json.get("_locked") {
+        None => Vec::new(),
+        Some(json::JsonValue::Array(keys)) => keys
+            .iter()
+            .map(|key| key.as_str().map(str::to_string).ok_or(JfxrFormatError::InvalidField("_locked")))
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(JfxrFormatError::InvalidField("_locked")),
+    };
+    let sound = Sound {
         name,
+        locked,
 
-        sample_rate: read_param!(SampleRate, "sampleRate", as_f64),
-        attack: read_param!(Attack, "attack", as_f64),
-        sustain: read_param!(Sustain, "sustain", as_f64),
-        sustain_punch: read_param!(SustainPunch, "sustainPunch", as_f64),
-        decay: read_param!(Decay, "decay", as_f64),
-        tremolo_depth: read_param!(TremoloDepth, "tremoloDepth", as_f64),
-        tremolo_frequency: read_param!(TremoloFrequency, "tremoloFrequency", as_f64),
-        frequency: read_param!(Frequency, "frequency", as_f64),
-        frequency_sweep: read_param!(FrequencySweep, "frequencySweep", as_f64),
-        frequency_delta_sweep: read_param!(FrequencyDeltaSweep, "frequencyDeltaSweep", as_f64),
-        repeat_frequency: read_param!(RepeatFrequency, "repeatFrequency", as_f64),
-        frequency_jump1_onset: read_param!(FrequencyJump1Onset, "frequencyJump1Onset", as_f64),
-        frequency_jump1_amount: read_param!(FrequencyJump1Amount, "frequencyJump1Amount", as_f64),
-        frequency_jump2_onset: read_param!(FrequencyJump2Onset, "frequencyJump2Onset", as_f64),
-        frequency_jump2_amount: read_param!(FrequencyJump2Amount, "frequencyJump2Amount", as_f64),
+        sample_rate: read_param!(SampleRate, "sampleRate", as_exact_f64),
+        attack: read_param!(Attack, "attack", as_exact_f64),
+        sustain: read_param!(Sustain, "sustain", as_exact_f64),
+        sustain_punch: read_param!(SustainPunch, "sustainPunch", as_exact_f64),
+        decay: read_param!(Decay, "decay", as_exact_f64),
+        tremolo_depth: read_param!(TremoloDepth, "tremoloDepth", as_exact_f64),
+        tremolo_frequency: read_param!(TremoloFrequency, "tremoloFrequency", as_exact_f64),
+        frequency: read_param!(Frequency, "frequency", as_exact_f64),
+        frequency_sweep: read_param!(FrequencySweep, "frequencySweep", as_exact_f64),
+        frequency_delta_sweep: read_param!(FrequencyDeltaSweep, "frequencyDeltaSweep", as_exact_f64),
+        frequency_sweep_semitones: read_param_or_default!(FrequencySweepSemitones, "frequencySweepSemitones", as_exact_f64),
+        frequency_delta_sweep_semitones: read_param_or_default!(FrequencyDeltaSweepSemitones, "frequencyDeltaSweepSemitones", as_exact_f64),
+        repeat_frequency: read_param!(RepeatFrequency, "repeatFrequency", as_exact_f64),
+        phase_reset: read_param_or_default!(PhaseReset, "phaseReset", as_bool),
+        repeat_decay: read_param_or_default!(RepeatDecay, "repeatDecay", as_exact_f64),
+        repeat_jitter: read_param_or_default!(RepeatJitter, "repeatJitter", as_exact_f64),
+        frequency_jump1_onset: read_param!(FrequencyJump1Onset, "frequencyJump1Onset", as_exact_f64),
+        frequency_jump1_amount: read_param!(FrequencyJump1Amount, "frequencyJump1Amount", as_exact_f64),
+        frequency_jump2_onset: read_param!(FrequencyJump2Onset, "frequencyJump2Onset", as_exact_f64),
+        frequency_jump2_amount: read_param!(FrequencyJump2Amount, "frequencyJump2Amount", as_exact_f64),
         harmonics: read_param!(Harmonics, "harmonics", as_i32),
-        harmonics_falloff: read_param!(HarmonicsFalloff, "harmonicsFalloff", as_f64),
+        harmonics_falloff: read_param!(HarmonicsFalloff, "harmonicsFalloff", as_exact_f64),
+        harmonics_profile: match json.get("harmonicsProfile") {
+            None => crate::parameter::HarmonicsProfile::default(),
+            Some(value) => match value.as_str() {
+                Some("geometric") => crate::parameter::HarmonicsProfile::Geometric,
+                Some("oddonly") => crate::parameter::HarmonicsProfile::OddOnly,
+                Some("oneovern") => crate::parameter::HarmonicsProfile::OneOverN,
+                Some("oneovernsquared") => crate::parameter::HarmonicsProfile::OneOverNSquared,
+                Some("custom") => crate::parameter::HarmonicsProfile::Custom,
+                _ => return Err(JfxrFormatError::InvalidField("harmonicsProfile")),
+            },
+        },
+        harmonics_custom_amplitudes: match json.get("harmonicsCustomAmplitudes") {
+            None => Vec::new(),
+            Some(value) => read_amplitudes(value).ok_or(JfxrFormatError::InvalidField("harmonicsCustomAmplitudes"))?,
+        },
+        harmonics_gain: match json.get("harmonicsGain") {
+            None => crate::parameter::HarmonicsGain::default(),
+            Some(value) => match value.as_str() {
+                Some("sum") => crate::parameter::HarmonicsGain::Sum,
+                Some("rms") => crate::parameter::HarmonicsGain::Rms,
+                _ => return Err(JfxrFormatError::InvalidField("harmonicsGain")),
+            },
+        },
+        inharmonicity: read_param_or_default!(Inharmonicity, "inharmonicity", as_exact_f64),
         waveform: match read_field!("waveform", as_str) {
             "sine" => crate::parameter::Waveform::Sine,
             "triangle" => crate::parameter::Waveform::Triangle,
@@ -89,77 +407,824 @@ pub fn read_jfxr(jfxr: &str) -> Result<Sound, JfxrFormatError> {
             "whitenoise" => crate::parameter::Waveform::Whitenoise,
             "pinknoise" => crate::parameter::Waveform::Pinknoise,
             "brownnoise" => crate::parameter::Waveform::Brownnoise,
+            "bluenoise" => crate::parameter::Waveform::Bluenoise,
+            "velvetnoise" => crate::parameter::Waveform::Velvetnoise,
             _ => return Err(JfxrFormatError::InvalidField("waveform")),
         },
-        interpolate_noise: read_param!(InterpolateNoise, "interpolateNoise", as_bool),
-        vibrato_depth: read_param!(VibratoDepth, "vibratoDepth", as_f64),
-        vibrato_frequency: read_param!(VibratoFrequency, "vibratoFrequency", as_f64),
-        square_duty: read_param!(SquareDuty, "squareDuty", as_f64),
-        square_duty_sweep: read_param!(SquareDutySweep, "squareDutySweep", as_f64),
-        flanger_offset: read_param!(FlangerOffset, "flangerOffset", as_f64),
-        flanger_offset_sweep: read_param!(FlangerOffsetSweep, "flangerOffsetSweep", as_f64),
+        start_phase: read_param_or_default!(StartPhase, "startPhase", as_exact_f64),
+        noise_interpolation: match json.get("noiseInterpolation") {
+            Some(value) => match value.as_str() {
+                Some("none") => crate::parameter::NoiseInterpolation::None,
+                Some("linear") => crate::parameter::NoiseInterpolation::Linear,
+                Some("cubic") => crate::parameter::NoiseInterpolation::Cubic,
+                _ => return Err(JfxrFormatError::InvalidField("noiseInterpolation")),
+            },
+            // Files written by the web tool only have the boolean flag.
+            None => match read_field!("interpolateNoise", as_bool) {
+                true => crate::parameter::NoiseInterpolation::Linear,
+                false => crate::parameter::NoiseInterpolation::None,
+            },
+        },
+        // The flag is read into the noise interpolation above.
+        #[allow(deprecated)]
+        interpolate_noise: Default::default(),
+        brown_noise_roughness: read_param_or_default!(BrownNoiseRoughness, "brownNoiseRoughness", as_exact_f64),
+        vibrato_depth: read_param!(VibratoDepth, "vibratoDepth", as_exact_f64),
+        vibrato_frequency: read_param!(VibratoFrequency, "vibratoFrequency", as_exact_f64),
+        square_duty: read_param!(SquareDuty, "squareDuty", as_exact_f64),
+        square_duty_sweep: read_param!(SquareDutySweep, "squareDutySweep", as_exact_f64),
+        tangent_clip: read_param_or_default!(TangentClip, "tangentClip", as_exact_f64),
+        whistle_overtone_ratio: read_param_or_default!(WhistleOvertoneRatio, "whistleOvertoneRatio", as_i32),
+        whistle_overtone_mix: read_param_or_default!(WhistleOvertoneMix, "whistleOvertoneMix", as_exact_f64),
+        breaker_phase_offset: read_param_or_default!(BreakerPhaseOffset, "breakerPhaseOffset", as_exact_f64),
+        flanger_offset: read_param!(FlangerOffset, "flangerOffset", as_exact_f64),
+        flanger_offset_sweep: read_param!(FlangerOffsetSweep, "flangerOffsetSweep", as_exact_f64),
+        flanger_depth: read_param_or_default!(FlangerDepth, "flangerDepth", as_exact_f64),
+        flanger_rate: read_param_or_default!(FlangerRate, "flangerRate", as_exact_f64),
+        flanger_feedback: read_param_or_default!(FlangerFeedback, "flangerFeedback", as_exact_f64),
+        chorus_voices: read_param_or_default!(ChorusVoices, "chorusVoices", as_i32),
+        chorus_depth: read_param_or_default!(ChorusDepth, "chorusDepth", as_exact_f64),
+        chorus_rate: read_param_or_default!(ChorusRate, "chorusRate", as_exact_f64),
         bit_crush: read_param!(BitCrush, "bitCrush", as_i32),
         bit_crush_sweep: read_param!(BitCrushSweep, "bitCrushSweep", as_i32),
-        low_pass_cutoff: read_param!(LowPassCutoff, "lowPassCutoff", as_f64),
-        low_pass_cutoff_sweep: read_param!(LowPassCutoffSweep, "lowPassCutoffSweep", as_f64),
-        high_pass_cutoff: read_param!(HighPassCutoff, "highPassCutoff", as_f64),
-        high_pass_cutoff_sweep: read_param!(HighPassCutoffSweep, "highPassCutoffSweep", as_f64),
-        compression: read_param!(Compression, "compression", as_f64),
+        low_pass_cutoff: read_param!(LowPassCutoff, "lowPassCutoff", as_exact_f64),
+        low_pass_cutoff_sweep: read_param!(LowPassCutoffSweep, "lowPassCutoffSweep", as_exact_f64),
+        high_pass_cutoff: read_param!(HighPassCutoff, "highPassCutoff", as_exact_f64),
+        high_pass_cutoff_sweep: read_param!(HighPassCutoffSweep, "highPassCutoffSweep", as_exact_f64),
+        reverb_amount: read_param_or_default!(ReverbAmount, "reverbAmount", as_exact_f64),
+        reverb_decay: read_param_or_default!(ReverbDecay, "reverbDecay", as_exact_f64),
+        reverb_tail: read_param_or_default!(ReverbTail, "reverbTail", as_bool),
+        compression: read_param!(Compression, "compression", as_exact_f64),
         normalization: read_param!(Normalization, "normalization", as_bool),
-        amplification: read_param!(Amplification, "amplification", as_f64),
-    })
+        leveling_mode: match json.get("levelingMode") {
+            None => crate::parameter::LevelingMode::default(),
+            Some(value) => match value.as_str() {
+                Some("peak") => crate::parameter::LevelingMode::Peak,
+                Some("rms") => crate::parameter::LevelingMode::Rms,
+                Some("lufs") => crate::parameter::LevelingMode::Lufs,
+                _ => return Err(JfxrFormatError::InvalidField("levelingMode")),
+            },
+        },
+        leveling_target: read_param_or_default!(LevelingTarget, "levelingTarget", as_exact_f64),
+        leveling_max_gain: read_param_or_default!(LevelingMaxGain, "levelingMaxGain", as_exact_f64),
+        amplification: read_param!(Amplification, "amplification", as_exact_f64),
+        stereo_width: read_param_or_default!(StereoWidth, "stereoWidth", as_exact_f64),
+        pan: read_param_or_default!(Pan, "pan", as_exact_f64),
+        pan_sweep: read_param_or_default!(PanSweep, "panSweep", as_exact_f64),
+        auto_pan_depth: read_param_or_default!(AutoPanDepth, "autoPanDepth", as_exact_f64),
+        auto_pan_frequency: read_param_or_default!(AutoPanFrequency, "autoPanFrequency", as_exact_f64),
+    };
+    check_magnitudes(&sound)?;
+    Ok(sound)
+}
+
+/// Rejects parameters that are not finite or larger than [`MAX_MAGNITUDE`].
+fn check_magnitudes(sound: &Sound) -> Result<(), JfxrFormatError> {
+    let check = |key, value: f64| match value {
+        value if !value.is_finite() => Err(JfxrFormatError::NonFiniteNumber(key)),
+        value if value.abs() > MAX_MAGNITUDE => Err(JfxrFormatError::InvalidField(key)),
+        _ => Ok(()),
+    };
+    for param in Sound::params() {
+        check(param.key, param.get(sound).as_f64())?;
+    }
+    for amplitude in &sound.harmonics_custom_amplitudes {
+        check("harmonicsCustomAmplitudes", *amplitude)?;
+    }
+    Ok(())
+}
+
+/// Limits on the input of [`read_jfxr_untrusted`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum length of the input, in bytes.
+    pub max_input_len: usize,
+    /// Maximum depth to which arrays and objects may be nested.
+    pub max_nesting: usize,
+}
+
+impl Default for Limits {
+    /// Returns limits that allow every file written by `jfxr`: 64 KiB of
+    /// input, nested 4 levels deep.
+    fn default() -> Self {
+        Self {
+            max_input_len: 64 * 1024,
+            max_nesting: 4,
+        }
+    }
+}
+
+/// Parses a `jfxr` file from an untrusted source, such as a user upload. On
+/// top of the checks of [`read_jfxr`], the size and nesting of the input are
+/// limited before it is parsed, and every parameter is clamped to its range
+/// with [`Sound::clamp_all`]. The result can be rendered with
+/// [`crate::try_generate`] or [`crate::Synth::try_new`], which enforce limits
+/// on the length of the sound.
+///
+/// ```rust
+/// use jfxr::jfxr::{read_jfxr_untrusted, JfxrFormatError, Limits};
+///
+/// let limits = Limits::default();
+/// let data = jfxr::write_jfxr(jfxr::Sound::example(jfxr::sound::Example::Coin));
+/// let sound = read_jfxr_untrusted(&data, &limits).unwrap();
+/// assert!(jfxr::try_generate(&sound).is_ok());
+///
+/// let huge = data.replace("\"frequency\":", "\"frequency\":1e999,\"_\":");
+/// assert!(matches!(read_jfxr_untrusted(&huge, &limits), Err(JfxrFormatError::NonFiniteNumber("frequency"))));
+/// let high = data.replace("\"frequency\":", "\"frequency\":1e5,\"_\":");
+/// assert_eq!(read_jfxr_untrusted(&high, &limits).unwrap().frequency.0, 10000.0);
+///
+/// let deep = format!("{}{}", "[".repeat(1000), "]".repeat(1000));
+/// assert!(matches!(read_jfxr_untrusted(&deep, &limits), Err(JfxrFormatError::TooDeeplyNested { max_nesting: 4 })));
+/// let long = " ".repeat(limits.max_input_len + 1);
+/// assert!(matches!(read_jfxr_untrusted(&long, &limits), Err(JfxrFormatError::InputTooLong { .. })));
+/// ```
+pub fn read_jfxr_untrusted(jfxr: &str, limits: &Limits) -> Result<Sound, JfxrFormatError> {
+    if jfxr.len() > limits.max_input_len {
+        return Err(JfxrFormatError::InputTooLong {
+            len: jfxr.len(),
+            max_len: limits.max_input_len,
+        });
+    }
+    if nesting_depth(jfxr) > limits.max_nesting {
+        return Err(JfxrFormatError::TooDeeplyNested { max_nesting: limits.max_nesting });
+    }
+    let mut sound = read_jfxr(jfxr)?;
+    sound.clamp_all();
+    Ok(sound)
+}
+
+/// Returns the maximum depth to which arrays and objects are nested in the
+/// given JSON, without parsing it. Brackets inside strings are skipped.
+fn nesting_depth(json: &str) -> usize {
+    let mut depth = 0usize;
+    let mut max_depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for byte in json.bytes() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    max_depth
 }
 
 /// Encodes a [`Sound`] to the `jfxr` format.
+///
+/// The output is formatted like `JSON.stringify` in the web tool: integral
+/// numbers have no fractional part and every other number is written with
+/// the fewest digits that read back as the same value. Parameters that the
+/// web tool does not have are only written when they differ from their
+/// defaults. Together, this means that a file saved by the web tool is
+/// written back byte for byte when it is read and written without changes,
+/// and that writing a sound again gives the same file, so unchanged sounds
+/// can be cached by the hash of their file.
+///
+/// ```rust
+/// let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/web-tool");
+/// let mut files = 0;
+/// for entry in std::fs::read_dir(dir).unwrap() {
+///     let path = entry.unwrap().path();
+///     let original = std::fs::read_to_string(&path).unwrap();
+///     let sound = jfxr::read_jfxr(&original).unwrap();
+///     assert_eq!(jfxr::write_jfxr(sound), original, "{}", path.display());
+///     files += 1;
+/// }
+/// assert!(files > 0);
+///
+/// // Sounds with any parameters survive a round trip exactly.
+/// for seed in 0..100 {
+///     let mut sound = jfxr::Sound::default();
+///     sound.mutate(&mut jfxr::Random::new(seed), 1.0);
+///     let written = jfxr::write_jfxr(sound.clone());
+///     let read = jfxr::read_jfxr(&written).unwrap();
+///     assert!(read.diff(&sound).is_empty(), "{written}");
+///     assert_eq!(jfxr::write_jfxr(read), written);
+/// }
+/// ```
 pub fn write_jfxr(sound: Sound) -> String {
-    let mut json = json::object::Object::new();
-    json.insert("_version", VERSION.into());
-    json.insert("_name", sound.name.into());
-    json.insert("_locked", json::JsonValue::new_array());
-    json.insert("sampleRate", sound.sample_rate.0.into());
-    json.insert("attack", sound.attack.0.into());
-    json.insert("sustain", sound.sustain.0.into());
-    json.insert("sustainPunch", sound.sustain_punch.0.into());
-    json.insert("decay", sound.decay.0.into());
-    json.insert("tremoloDepth", sound.tremolo_depth.0.into());
-    json.insert("tremoloFrequency", sound.tremolo_frequency.0.into());
-    json.insert("frequency", sound.frequency.0.into());
-    json.insert("frequencySweep", sound.frequency_sweep.0.into());
-    json.insert("frequencyDeltaSweep", sound.frequency_delta_sweep.0.into());
-    json.insert("repeatFrequency", sound.repeat_frequency.0.into());
-    json.insert("frequencyJump1Onset", sound.frequency_jump1_onset.0.into());
-    json.insert("frequencyJump1Amount", sound.frequency_jump1_amount.0.into());
-    json.insert("frequencyJump2Onset", sound.frequency_jump2_onset.0.into());
-    json.insert("frequencyJump2Amount", sound.frequency_jump2_amount.0.into());
-    json.insert("harmonics", sound.harmonics.0.into());
-    json.insert("harmonicsFalloff", sound.harmonics_falloff.0.into());
-    json.insert("waveform", match sound.waveform {
-        crate::parameter::Waveform::Sine => "sine",
-        crate::parameter::Waveform::Triangle => "triangle",
-        crate::parameter::Waveform::Sawtooth => "sawtooth",
-        crate::parameter::Waveform::Square => "square",
-        crate::parameter::Waveform::Tangent => "tangent",
-        crate::parameter::Waveform::Whistle => "whistle",
-        crate::parameter::Waveform::Breaker => "breaker",
-        crate::parameter::Waveform::Whitenoise => "whitenoise",
-        crate::parameter::Waveform::Pinknoise => "pinknoise",
-        crate::parameter::Waveform::Brownnoise => "brownnoise",
-    }.into());
-    json.insert("interpolateNoise", sound.interpolate_noise.0.into());
-    json.insert("vibratoDepth", sound.vibrato_depth.0.into());
-    json.insert("vibratoFrequency", sound.vibrato_frequency.0.into());
-    json.insert("squareDuty", sound.square_duty.0.into());
-    json.insert("squareDutySweep", sound.square_duty_sweep.0.into());
-    json.insert("flangerOffset", sound.flanger_offset.0.into());
-    json.insert("flangerOffsetSweep", sound.flanger_offset_sweep.0.into());
-    json.insert("bitCrush", sound.bit_crush.0.into());
-    json.insert("bitCrushSweep", sound.bit_crush_sweep.0.into());
-    json.insert("lowPassCutoff", sound.low_pass_cutoff.0.into());
-    json.insert("lowPassCutoffSweep", sound.low_pass_cutoff_sweep.0.into());
-    json.insert("highPassCutoff", sound.high_pass_cutoff.0.into());
-    json.insert("highPassCutoffSweep", sound.high_pass_cutoff_sweep.0.into());
-    json.insert("compression", sound.compression.0.into());
-    json.insert("normalization", sound.normalization.0.into());
-    json.insert("amplification", sound.amplification.0.into());
-    json.dump()
+    write_jfxr_with(sound, &WriteOptions::default())
+}
+
+/// Options of [`write_jfxr_with`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Number of decimals to which numbers are rounded before they are
+    /// written, or `None` to write every number exactly, as [`write_jfxr`]
+    /// does.
+    pub max_decimals: Option<u32>,
+}
+
+/// Encodes a [`Sound`] to the `jfxr` format, like [`write_jfxr`], with the
+/// given options.
+///
+/// Numbers are written the same way whatever the locale. By default, they
+/// are written with the fewest digits that read back as the same value,
+/// which for the result of arithmetic can be many. With
+/// [`WriteOptions::max_decimals`], each number is first rounded to that many
+/// decimals, correctly: the exact binary value is rounded, with ties to
+/// even. The rounded number is then written with the fewest digits, so
+/// trailing zeros are dropped. Rounding is lossy, but writing a sound read
+/// back from rounded output gives the same file.
+///
+/// ```rust
+/// use jfxr::jfxr::{write_jfxr_with, WriteOptions};
+///
+/// let rounded = WriteOptions { max_decimals: Some(6) };
+/// let mut sound = jfxr::Sound::default();
+/// for value in [0.1 + 0.2, 1e-17, -1e-7, 2.0f64.powi(-20), 999999.9999999999, -123456.78901234567, 0.0000125, 1.0 / 3.0] {
+///     sound.frequency_sweep.0 = value;
+///     let exact = write_jfxr_with(sound.clone(), &WriteOptions::default());
+///     assert_eq!(exact, jfxr::write_jfxr(sound.clone()));
+///     let read = jfxr::read_jfxr(&exact).unwrap();
+///     assert_eq!(read.frequency_sweep.0.to_bits(), value.to_bits(), "{value}");
+///
+///     let written = write_jfxr_with(sound.clone(), &rounded);
+///     let read = jfxr::read_jfxr(&written).unwrap();
+///     let expected: f64 = format!("{value:.6}").parse().unwrap();
+///     assert_eq!(read.frequency_sweep.0, expected, "{value}");
+///     assert_eq!(write_jfxr_with(read, &rounded), written);
+/// }
+///
+/// sound.frequency_sweep.0 = 0.1 + 0.2;
+/// assert!(jfxr::write_jfxr(sound.clone()).contains(r#""frequencySweep":0.30000000000000004,"#));
+/// assert!(write_jfxr_with(sound.clone(), &rounded).contains(r#""frequencySweep":0.3,"#));
+/// sound.frequency_sweep.0 = 1e-17;
+/// assert!(jfxr::write_jfxr(sound.clone()).contains(r#""frequencySweep":1e-17,"#));
+/// assert!(write_jfxr_with(sound.clone(), &rounded).contains(r#""frequencySweep":0,"#));
+/// // Ties are rounded to even.
+/// sound.frequency_sweep.0 = 0.125;
+/// let written = write_jfxr_with(sound, &WriteOptions { max_decimals: Some(2) });
+/// assert!(written.contains(r#""frequencySweep":0.12,"#));
+/// ```
+pub fn write_jfxr_with(sound: Sound, options: &WriteOptions) -> String {
+    let mut out = String::new();
+    write_json_value(&mut out, &to_json_value(&sound), options);
+    out
+}
+
+/// Encodes a [`Sound`] as the JSON of a `jfxr` file, with the keys in the
+/// order in which [`write_jfxr`] writes them, for embedding in a larger
+/// document. See [`from_json_value`].
+pub fn to_json_value(sound: &Sound) -> json::JsonValue {
+    use crate::parameter::*;
+    let mut json = json::JsonValue::new_object();
+    // Writes a parameter that the web tool does not have, unless it is at
+    // its default.
+    macro_rules! extension {
+        ($key:literal, $value:expr, $default:expr) => {
+            if $value != $default {
+                json[$key] = $value.into();
+            }
+        };
+    }
+    json["_version"] = VERSION.into();
+    json["_name"] = sound.name.as_str().into();
+    json["_locked"] = sound.locked.clone().into();
+    json["sampleRate"] = sound.sample_rate.0.into();
+    json["attack"] = sound.attack.0.into();
+    json["sustain"] = sound.sustain.0.into();
+    json["sustainPunch"] = sound.sustain_punch.0.into();
+    json["decay"] = sound.decay.0.into();
+    json["tremoloDepth"] = sound.tremolo_depth.0.into();
+    json["tremoloFrequency"] = sound.tremolo_frequency.0.into();
+    json["frequency"] = sound.frequency.0.into();
+    json["frequencySweep"] = sound.frequency_sweep.0.into();
+    json["frequencyDeltaSweep"] = sound.frequency_delta_sweep.0.into();
+    extension!("frequencySweepSemitones", sound.frequency_sweep_semitones.0, FrequencySweepSemitones::DEFAULT_VALUE);
+    extension!("frequencyDeltaSweepSemitones", sound.frequency_delta_sweep_semitones.0, FrequencyDeltaSweepSemitones::DEFAULT_VALUE);
+    json["repeatFrequency"] = sound.repeat_frequency.0.into();
+    extension!("phaseReset", sound.phase_reset.0, PhaseReset::DEFAULT_VALUE);
+    extension!("repeatDecay", sound.repeat_decay.0, RepeatDecay::DEFAULT_VALUE);
+    extension!("repeatJitter", sound.repeat_jitter.0, RepeatJitter::DEFAULT_VALUE);
+    json["frequencyJump1Onset"] = sound.frequency_jump1_onset.0.into();
+    json["frequencyJump1Amount"] = sound.frequency_jump1_amount.0.into();
+    json["frequencyJump2Onset"] = sound.frequency_jump2_onset.0.into();
+    json["frequencyJump2Amount"] = sound.frequency_jump2_amount.0.into();
+    json["harmonics"] = sound.harmonics.0.into();
+    json["harmonicsFalloff"] = sound.harmonics_falloff.0.into();
+    extension!("harmonicsProfile", sound.harmonics_profile.name(), HarmonicsProfile::DEFAULT_VALUE.name());
+    if !sound.harmonics_custom_amplitudes.is_empty() {
+        json["harmonicsCustomAmplitudes"] = sound.harmonics_custom_amplitudes.clone().into();
+    }
+    extension!("harmonicsGain", sound.harmonics_gain.name(), HarmonicsGain::DEFAULT_VALUE.name());
+    extension!("inharmonicity", sound.inharmonicity.0, Inharmonicity::DEFAULT_VALUE);
+    json["waveform"] = sound.waveform.name().into();
+    extension!("startPhase", sound.start_phase.0, StartPhase::DEFAULT_VALUE);
+    let noise_interpolation = sound.effective_noise_interpolation();
+    json["interpolateNoise"] = (noise_interpolation != NoiseInterpolation::None).into();
+    // The flag above is enough for the interpolations of the web tool.
+    if noise_interpolation == NoiseInterpolation::Cubic {
+        json["noiseInterpolation"] = noise_interpolation.name().into();
+    }
+    extension!("brownNoiseRoughness", sound.brown_noise_roughness.0, BrownNoiseRoughness::DEFAULT_VALUE);
+    json["vibratoDepth"] = sound.vibrato_depth.0.into();
+    json["vibratoFrequency"] = sound.vibrato_frequency.0.into();
+    json["squareDuty"] = sound.square_duty.0.into();
+    json["squareDutySweep"] = sound.square_duty_sweep.0.into();
+    extension!("tangentClip", sound.tangent_clip.0, TangentClip::DEFAULT_VALUE);
+    extension!("whistleOvertoneRatio", sound.whistle_overtone_ratio.0, WhistleOvertoneRatio::DEFAULT_VALUE);
+    extension!("whistleOvertoneMix", sound.whistle_overtone_mix.0, WhistleOvertoneMix::DEFAULT_VALUE);
+    extension!("breakerPhaseOffset", sound.breaker_phase_offset.0, BreakerPhaseOffset::DEFAULT_VALUE);
+    json["flangerOffset"] = sound.flanger_offset.0.into();
+    json["flangerOffsetSweep"] = sound.flanger_offset_sweep.0.into();
+    extension!("flangerDepth", sound.flanger_depth.0, FlangerDepth::DEFAULT_VALUE);
+    extension!("flangerRate", sound.flanger_rate.0, FlangerRate::DEFAULT_VALUE);
+    extension!("flangerFeedback", sound.flanger_feedback.0, FlangerFeedback::DEFAULT_VALUE);
+    extension!("chorusVoices", sound.chorus_voices.0, ChorusVoices::DEFAULT_VALUE);
+    extension!("chorusDepth", sound.chorus_depth.0, ChorusDepth::DEFAULT_VALUE);
+    extension!("chorusRate", sound.chorus_rate.0, ChorusRate::DEFAULT_VALUE);
+    json["bitCrush"] = sound.bit_crush.0.into();
+    json["bitCrushSweep"] = sound.bit_crush_sweep.0.into();
+    json["lowPassCutoff"] = sound.low_pass_cutoff.0.into();
+    json["lowPassCutoffSweep"] = sound.low_pass_cutoff_sweep.0.into();
+    json["highPassCutoff"] = sound.high_pass_cutoff.0.into();
+    json["highPassCutoffSweep"] = sound.high_pass_cutoff_sweep.0.into();
+    extension!("reverbAmount", sound.reverb_amount.0, ReverbAmount::DEFAULT_VALUE);
+    extension!("reverbDecay", sound.reverb_decay.0, ReverbDecay::DEFAULT_VALUE);
+    extension!("reverbTail", sound.reverb_tail.0, ReverbTail::DEFAULT_VALUE);
+    json["compression"] = sound.compression.0.into();
+    json["normalization"] = sound.normalization.0.into();
+    extension!("levelingMode", sound.leveling_mode.name(), LevelingMode::DEFAULT_VALUE.name());
+    extension!("levelingTarget", sound.leveling_target.0, LevelingTarget::DEFAULT_VALUE);
+    extension!("levelingMaxGain", sound.leveling_max_gain.0, LevelingMaxGain::DEFAULT_VALUE);
+    json["amplification"] = sound.amplification.0.into();
+    extension!("stereoWidth", sound.stereo_width.0, StereoWidth::DEFAULT_VALUE);
+    extension!("pan", sound.pan.0, Pan::DEFAULT_VALUE);
+    extension!("panSweep", sound.pan_sweep.0, PanSweep::DEFAULT_VALUE);
+    extension!("autoPanDepth", sound.auto_pan_depth.0, AutoPanDepth::DEFAULT_VALUE);
+    extension!("autoPanFrequency", sound.auto_pan_frequency.0, AutoPanFrequency::DEFAULT_VALUE);
+    json
+}
+
+/// Encodes a [`Sound`] as a [`serde_json::Value`], like [`to_json_value`].
+/// Unless `serde_json` preserves the order of keys, they are sorted. The
+/// `serde` feature enables the `float_roundtrip` feature of `serde_json`,
+/// without which numbers parsed from text may differ in the last digit.
+///
+#[cfg_attr(feature = "serde", doc = "```rust")]
+#[cfg_attr(not(feature = "serde"), doc = "```rust,ignore")]
+/// let coin = jfxr::Sound::example(jfxr::sound::Example::Coin);
+/// let value = jfxr::jfxr::to_serde_json_value(&coin);
+/// assert_eq!(value["frequency"], serde_json::json!(coin.frequency.0));
+/// let read = jfxr::jfxr::from_serde_json_value(&value).unwrap();
+/// assert!(read.diff(&coin).is_empty());
+///
+/// // Every number survives a round trip through text exactly.
+/// for seed in 0..20 {
+///     let mut sound = coin.clone();
+///     sound.mutate(&mut jfxr::Random::new(seed), 1.0);
+///     let text = serde_json::to_string(&jfxr::jfxr::to_serde_json_value(&sound)).unwrap();
+///     let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+///     assert!(jfxr::jfxr::from_serde_json_value(&value).unwrap().diff(&sound).is_empty(), "{text}");
+/// }
+/// ```
+#[cfg(feature = "serde")]
+pub fn to_serde_json_value(sound: &Sound) -> serde_json::Value {
+    json_to_serde(&to_json_value(sound))
+}
+
+/// Reads a [`Sound`] from a [`serde_json::Value`], like
+/// [`from_json_value`].
+#[cfg(feature = "serde")]
+pub fn from_serde_json_value(value: &serde_json::Value) -> Result<Sound, JfxrFormatError> {
+    from_json_value(&serde_to_json(value))
+}
+
+#[cfg(feature = "serde")]
+fn json_to_serde(value: &json::JsonValue) -> serde_json::Value {
+    match value {
+        json::JsonValue::Null => serde_json::Value::Null,
+        json::JsonValue::Short(_) | json::JsonValue::String(_) => value.as_str().unwrap_or_default().into(),
+        json::JsonValue::Number(_) => match value.as_i64() {
+            Some(integer) => integer.into(),
+            // Not finite numbers become null, as in `JSON.stringify`.
+            None => value.as_exact_f64().and_then(serde_json::Number::from_f64).map_or(serde_json::Value::Null, serde_json::Value::Number),
+        },
+        json::JsonValue::Boolean(value) => (*value).into(),
+        json::JsonValue::Object(object) => object.iter().map(|(key, value)| (key.to_string(), json_to_serde(value))).collect(),
+        json::JsonValue::Array(values) => values.iter().map(json_to_serde).collect(),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn serde_to_json(value: &serde_json::Value) -> json::JsonValue {
+    match value {
+        serde_json::Value::Null => json::JsonValue::Null,
+        serde_json::Value::String(value) => value.as_str().into(),
+        serde_json::Value::Number(number) => match (number.as_i64(), number.as_f64()) {
+            (Some(integer), _) => integer.into(),
+            (None, Some(value)) => value.into(),
+            (None, None) => json::JsonValue::Null,
+        },
+        serde_json::Value::Bool(value) => (*value).into(),
+        serde_json::Value::Object(object) => {
+            let mut json = json::JsonValue::new_object();
+            for (key, value) in object {
+                json[key.as_str()] = serde_to_json(value);
+            }
+            json
+        }
+        serde_json::Value::Array(values) => json::JsonValue::Array(values.iter().map(serde_to_json).collect()),
+    }
+}
+
+/// Writes a JSON value in the format of `JSON.stringify`, with the keys of
+/// objects in their order of insertion.
+fn write_json_value(out: &mut String, value: &json::JsonValue, options: &WriteOptions) {
+    match value {
+        json::JsonValue::Null => out.push_str("null"),
+        json::JsonValue::Short(_) | json::JsonValue::String(_) => write_json_string(out, value.as_str().unwrap_or_default()),
+        json::JsonValue::Number(number) if number.is_nan() => out.push_str("null"),
+        json::JsonValue::Number(_) => {
+            let number = value.as_exact_f64().unwrap_or(f64::NAN);
+            write_json_number(out, options.max_decimals.map_or(number, |decimals| round_decimals(number, decimals)));
+        }
+        json::JsonValue::Boolean(value) => out.push_str(if *value { "true" } else { "false" }),
+        json::JsonValue::Object(object) => {
+            out.push('{');
+            for (i, (key, value)) in object.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json_string(out, key);
+                out.push(':');
+                write_json_value(out, value, options);
+            }
+            out.push('}');
+        }
+        json::JsonValue::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json_value(out, value, options);
+            }
+            out.push(']');
+        }
+    }
+}
+
+/// Rounds a number to the given number of decimals. The standard library
+/// formats the exact binary value, rounding ties to even, and parsing gives
+/// the nearest number to the result. No number has more than 1074 decimals.
+fn round_decimals(value: f64, decimals: u32) -> f64 {
+    if !value.is_finite() {
+        return value;
+    }
+    let decimals = decimals.min(1074) as usize;
+    format!("{value:.decimals$}").parse().unwrap_or(value)
+}
+
+/// Writes a number like JavaScript's `Number.prototype.toString`: with the
+/// shortest digits that identify the value, in decimal notation from 1e-6
+/// up to but excluding 1e21, and in exponent notation otherwise. Like in `JSON.stringify`,
+/// negative zero is written as `0` and values that are not finite as `null`.
+fn write_json_number(out: &mut String, value: f64) {
+    if !value.is_finite() {
+        out.push_str("null");
+        return;
+    }
+    if value == 0.0 {
+        out.push('0');
+        return;
+    }
+    if value < 0.0 {
+        out.push('-');
+    }
+    // Rust also writes the shortest digits, as `d.ddde-x`.
+    let formatted = format!("{:e}", value.abs());
+    let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+    let digits = mantissa.replace('.', "");
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    // Position of the decimal point relative to the digits.
+    let point = exponent + 1;
+    let len = digits.len() as i32;
+    if len <= point && point <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat_n('0', (point - len) as usize));
+    } else if 0 < point && point <= 21 {
+        out.push_str(&digits[..point as usize]);
+        out.push('.');
+        out.push_str(&digits[point as usize..]);
+    } else if -6 < point && point <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat_n('0', -point as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if len > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        out.push_str(if exponent < 0 { "e-" } else { "e+" });
+        out.push_str(&exponent.unsigned_abs().to_string());
+    }
+}
+
+/// Writes a string literal like `JSON.stringify`, which only escapes quotes,
+/// backslashes and control characters.
+fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Parses a [`SoundPatch`] from a JSON object, which contains only the
+/// overridden parameters, with the same keys and values as in the `jfxr`
+/// format.
+///
+/// ```rust
+/// let patch = jfxr::patch::SoundPatch::new()
+///     .with("attack", 0.01)
+///     .with("bitCrush", 8)
+///     .with("normalization", true)
+///     .with("waveform", jfxr::parameter::Value::Enum(3));
+/// let json = jfxr::jfxr::write_patch(&patch);
+/// assert_eq!(json, r#"{"attack":0.01,"bitCrush":8,"normalization":true,"waveform":"square"}"#);
+/// let read = jfxr::jfxr::read_patch(&json).unwrap();
+/// assert_eq!(jfxr::jfxr::write_patch(&read), json);
+/// ```
+pub fn read_patch(json: &str) -> Result<SoundPatch, JfxrFormatError> {
+    let json = match json::parse(json)? {
+        json::JsonValue::Object(o) => o,
+        _ => return Err(JfxrFormatError::NotAnObject),
+    };
+    let mut patch = SoundPatch::new();
+    for (key, value) in json.iter() {
+        let param = Sound::param(key).ok_or_else(|| JfxrFormatError::UnknownField(key.to_string()))?;
+        patch.set(param, read_value(param, value).ok_or(JfxrFormatError::InvalidField(param.key))?);
+    }
+    Ok(patch)
+}
+
+/// Encodes a [`SoundPatch`] as a JSON object. See [`read_patch`].
+pub fn write_patch(patch: &SoundPatch) -> String {
+    let mut json = json::JsonValue::new_object();
+    for (param, value) in patch.iter() {
+        json[param.key] = value_to_json(param, value);
+    }
+    let mut out = String::new();
+    write_json_value(&mut out, &json, &WriteOptions::default());
+    out
+}
+
+/// Parses a [`SoundDelta`] from a JSON object, which maps the keys of the
+/// changed parameters in the `jfxr` format to an array of their old and new
+/// values, as in the `jfxr` format. A change of the custom harmonic
+/// amplitudes has the key `harmonicsCustomAmplitudes` and an array of the
+/// old and the new array.
+///
+/// ```rust
+/// let before = jfxr::Sound::example(jfxr::sound::Example::Coin);
+/// let mut after = before.clone();
+/// after.frequency.0 = 1200.5;
+/// after.waveform = jfxr::parameter::Waveform::Sawtooth;
+/// let delta = jfxr::patch::SoundDelta::from_diff(&before, &after);
+/// let json = jfxr::jfxr::write_delta(&delta);
+/// assert_eq!(json, format!(r#"{{"frequency":[{},1200.5],"waveform":["square","sawtooth"]}}"#, before.frequency.0));
+/// let read = jfxr::jfxr::read_delta(&json).unwrap();
+/// assert_eq!(jfxr::jfxr::write_delta(&read), json);
+///
+/// assert!(jfxr::jfxr::read_delta(r#"{"frequency":[500]}"#).is_err());
+/// assert!(jfxr::jfxr::read_delta(r#"{"waveform":["sine","chirp"]}"#).is_err());
+///
+/// let mut custom = before.clone();
+/// custom.harmonics.0 = 2;
+/// custom.harmonics_profile = jfxr::parameter::HarmonicsProfile::Custom;
+/// custom.harmonics_custom_amplitudes = vec![1.0, 0.0, 0.5];
+/// let json = jfxr::Sound::make_patch_json(&before, &custom);
+/// assert!(json.ends_with(r#""harmonicsCustomAmplitudes":[[],[1,0,0.5]]}"#), "{json}");
+/// let mut sound = before.clone();
+/// sound.apply_patch_json(&json).unwrap();
+/// assert_eq!(sound.harmonics_custom_amplitudes, [1.0, 0.0, 0.5]);
+/// assert!(sound.diff(&custom).is_empty());
+/// assert!(jfxr::jfxr::read_delta(r#"{"harmonicsCustomAmplitudes":[[],1]}"#).is_err());
+/// ```
+pub fn read_delta(json: &str) -> Result<SoundDelta, JfxrFormatError> {
+    let json = match json::parse(json)? {
+        json::JsonValue::Object(o) => o,
+        _ => return Err(JfxrFormatError::NotAnObject),
+    };
+    let mut delta = SoundDelta::new();
+    for (key, value) in json.iter() {
+        if key == "harmonicsCustomAmplitudes" {
+            let (old, new) = match value {
+                json::JsonValue::Array(values) if values.len() == 2 => (read_amplitudes(&values[0]), read_amplitudes(&values[1])),
+                _ => (None, None),
+            };
+            match (old, new) {
+                (Some(old), Some(new)) => delta.set_harmonics_custom_amplitudes(old, new),
+                _ => return Err(JfxrFormatError::InvalidField("harmonicsCustomAmplitudes")),
+            }
+            continue;
+        }
+        let param = Sound::param(key).ok_or_else(|| JfxrFormatError::UnknownField(key.to_string()))?;
+        let (old, new) = match value {
+            json::JsonValue::Array(values) if values.len() == 2 => (read_value(param, &values[0]), read_value(param, &values[1])),
+            _ => (None, None),
+        };
+        match (old, new) {
+            (Some(old), Some(new)) => delta.set(param, old, new),
+            _ => return Err(JfxrFormatError::InvalidField(param.key)),
+        }
+    }
+    Ok(delta)
+}
+
+/// Encodes a [`SoundDelta`] as a JSON object. See [`read_delta`].
+pub fn write_delta(delta: &SoundDelta) -> String {
+    let mut json = json::JsonValue::new_object();
+    for (param, old, new) in delta.iter() {
+        json[param.key] = json::JsonValue::Array(vec![value_to_json(param, old), value_to_json(param, new)]);
+    }
+    if let Some((old, new)) = delta.harmonics_custom_amplitudes() {
+        json["harmonicsCustomAmplitudes"] = json::JsonValue::Array(vec![old.into(), new.into()]);
+    }
+    let mut out = String::new();
+    write_json_value(&mut out, &json, &WriteOptions::default());
+    out
+}
+
+/// Error returned by [`Sound::apply_patch_json`].
+#[derive(Debug, PartialEq)]
+pub enum PatchError {
+    /// The patch could not be parsed.
+    Format(JfxrFormatError),
+    /// Parameters of the sound were changed since the patch was made.
+    Conflicts(Vec<Conflict>),
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Format(error) => write!(f, "{error}"),
+            Self::Conflicts(conflicts) => {
+                write!(f, "conflicting changes:")?;
+                for conflict in conflicts {
+                    write!(f, "\n{conflict}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+impl From<JfxrFormatError> for PatchError {
+    fn from(value: JfxrFormatError) -> Self {
+        Self::Format(value)
+    }
+}
+
+/// Reads a value of the given parameter, as written by [`value_to_json`].
+/// Reads an array of custom harmonic amplitudes.
+fn read_amplitudes(value: &json::JsonValue) -> Option<Vec<f64>> {
+    match value {
+        json::JsonValue::Array(values) => values.iter().map(|value| value.as_exact_f64()).collect(),
+        _ => None,
+    }
+}
+
+fn read_value(param: &Param, value: &json::JsonValue) -> Option<Value> {
+    match param.kind {
+        ParamKind::Float { .. } => value.as_exact_f64().map(Value::Float),
+        ParamKind::Integer { .. } => value.as_i32().map(Value::Integer),
+        ParamKind::Boolean => value.as_bool().map(Value::Boolean),
+        ParamKind::Enum { names } => value.as_str().and_then(|name| names.iter().position(|n| *n == name)).map(Value::Enum),
+    }
+}
+
+/// Encodes a value of the given parameter as in the `jfxr` format, with
+/// enums by name.
+fn value_to_json(param: &Param, value: Value) -> json::JsonValue {
+    match (param.kind, value) {
+        (ParamKind::Enum { names }, value) => names.get(value.as_f64() as usize).copied().unwrap_or("").into(),
+        (_, Value::Float(value)) => value.into(),
+        (_, Value::Integer(value)) => value.into(),
+        (_, Value::Boolean(value)) => value.into(),
+        (_, Value::Enum(index)) => index.into(),
+    }
+}
+
+/// Parses a [`DistributionTable`] from a JSON object, which maps the keys of
+/// the parameters in the `jfxr` format to their distributions, in the order
+/// in which they are drawn. Each distribution is an object with a single
+/// field: `uniform` or `logUniform` with an array of the two bounds,
+/// `choice` with an array of distributions, or `constant` with a value as in
+/// the `jfxr` format.
+///
+/// ```rust
+/// use jfxr::sound::Example;
+///
+/// let json = r#"{"waveform":{"choice":[{"constant":"sine"},{"constant":"square"}]},"frequency":{"logUniform":[100,2000]},"harmonics":{"uniform":[0,3]}}"#;
+/// let table = jfxr::jfxr::read_distributions(json).unwrap();
+/// assert_eq!(table.len(), 3);
+/// assert_eq!(jfxr::jfxr::write_distributions(&table), json);
+///
+/// // The built-in tables can be stored and edited as house presets.
+/// for example in Example::ALL {
+///     let json = jfxr::jfxr::write_distributions(&jfxr::presets::distributions(*example));
+///     let table = jfxr::jfxr::read_distributions(&json).unwrap();
+///     assert_eq!(jfxr::jfxr::write_distributions(&table), json);
+///     for seed in 0..20 {
+///         let sound = jfxr::Sound::random_from(&table, &mut jfxr::Random::new(seed));
+///         let preset = jfxr::presets::random(*example, &mut jfxr::Random::new(seed));
+///         assert!(sound.diff(&preset).is_empty(), "{} {seed}", example.name());
+///     }
+/// }
+///
+/// assert!(jfxr::jfxr::read_distributions(r#"{"frequency":{"logUniform":[0,2000]}}"#).is_err());
+/// assert!(jfxr::jfxr::read_distributions(r#"{"waveform":{"choice":[]}}"#).is_err());
+/// assert!(jfxr::jfxr::read_distributions(r#"{"waveform":{"constant":"chirp"}}"#).is_err());
+/// ```
+pub fn read_distributions(json: &str) -> Result<DistributionTable, JfxrFormatError> {
+    let json = match json::parse(json)? {
+        json::JsonValue::Object(o) => o,
+        _ => return Err(JfxrFormatError::NotAnObject),
+    };
+    let mut table = DistributionTable::new();
+    for (key, value) in json.iter() {
+        let param = Sound::param(key).ok_or_else(|| JfxrFormatError::UnknownField(key.to_string()))?;
+        table.set(param, read_distribution(param, value).ok_or(JfxrFormatError::InvalidField(param.key))?);
+    }
+    Ok(table)
+}
+
+fn read_distribution(param: &Param, json: &json::JsonValue) -> Option<ParamDistribution> {
+    let (kind, value) = match json {
+        json::JsonValue::Object(o) if o.len() == 1 => o.iter().next()?,
+        _ => return None,
+    };
+    let bounds = || match value {
+        json::JsonValue::Array(bounds) if bounds.len() == 2 => {
+            let bounds = (bounds[0].as_exact_f64()?, bounds[1].as_exact_f64()?);
+            (bounds.0.is_finite() && bounds.1.is_finite()).then_some(bounds)
+        }
+        _ => None,
+    };
+    match kind {
+        "uniform" => bounds().map(|(from, to)| ParamDistribution::Uniform(from, to)),
+        "logUniform" => bounds().filter(|(from, to)| *from > 0.0 && *to > 0.0).map(|(from, to)| ParamDistribution::LogUniform(from, to)),
+        "choice" => match value {
+            json::JsonValue::Array(choices) if !choices.is_empty() => {
+                choices.iter().map(|choice| read_distribution(param, choice)).collect::<Option<_>>().map(ParamDistribution::Choice)
+            }
+            _ => None,
+        },
+        "constant" => read_value(param, value).map(ParamDistribution::Constant),
+        _ => None,
+    }
+}
+
+/// Encodes a [`DistributionTable`] as a JSON object. See
+/// [`read_distributions`].
+pub fn write_distributions(table: &DistributionTable) -> String {
+    let mut json = json::JsonValue::new_object();
+    for (param, distribution) in table.iter() {
+        json[param.key] = distribution_to_json(param, distribution);
+    }
+    let mut out = String::new();
+    write_json_value(&mut out, &json, &WriteOptions::default());
+    out
+}
+
+fn distribution_to_json(param: &Param, distribution: &ParamDistribution) -> json::JsonValue {
+    let mut json = json::JsonValue::new_object();
+    match distribution {
+        ParamDistribution::Uniform(from, to) => json["uniform"] = json::array![*from, *to],
+        ParamDistribution::LogUniform(from, to) => json["logUniform"] = json::array![*from, *to],
+        ParamDistribution::Choice(choices) => json["choice"] = json::JsonValue::Array(choices.iter().map(|choice| distribution_to_json(param, choice)).collect()),
+        ParamDistribution::Constant(value) => json["constant"] = value_to_json(param, *value),
+    }
+    json
 }
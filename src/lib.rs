@@ -7,41 +7,168 @@
 //! tool. When the `json` feature is enabled, `.jfxr` files can be parsed and
 //! encoded into instances of [`Sound`]:
 //!
-//! ```rust
-//! let sound_data = std::fs::read_to_string("example.jfxr").unwrap();
-//! let mut sound = jfxr::read_jfxr(sound_data).unwrap();
+#![cfg_attr(feature = "json", doc = "```rust")]
+#![cfg_attr(not(feature = "json"), doc = "```rust,ignore")]
+//! // The contents of a `.jfxr` file, which would usually be read from disk.
+//! let sound_data = jfxr::write_jfxr(jfxr::Sound::example(jfxr::sound::Example::Coin));
+//!
+//! let mut sound = jfxr::read_jfxr(&sound_data).unwrap();
 //! sound.frequency.0 = 200.0;
 //! let new_sound_data = jfxr::write_jfxr(sound);
-//! std::fs::write("new_example.jfxr", new_sound_data).unwrap();
+//! assert_eq!(jfxr::read_jfxr(&new_sound_data).unwrap().frequency.0, 200.0);
 //! ```
 //!
+//! To embed sounds in a larger JSON document, [`jfxr::to_json_value`] and
+//! [`jfxr::from_json_value`] convert them without going through a string,
+//! and the `serde` feature adds equivalents for `serde_json`.
+//!
 //! # Generating samples
 //!
 //! Sound samples in the form of a [`Vec<f64>`] can be generated from a
 //! [`Sound`]:
 //!
 //! ```rust
-//! let samples = jfxr::generate(sound);
+//! let sound = jfxr::Sound::example(jfxr::sound::Example::Laser);
+//! let samples = jfxr::generate(&sound);
+//! assert_eq!(samples.len(), sound.num_samples());
 //! ```
 //!
 //! By default, [`generate`] generates single-channel samples at a 44100 Hz
 //! sample rate, and the entire sound is generated in a single synchronous
 //! call. For more control, create an instance of [`Synth`] with a reference
-//! to a [`Sound`]. Output settings can be adjusted on the [`Synth`] instance,
-//! and the generation can be split across multiple calls to
-//! [`Synth::generate_block`].
+//! to a [`Sound`], optionally with [`OutputSettings`] such as the sample rate
+//! and the number of channels. Further settings can be adjusted on the
+//! [`Synth`] instance, and the generation can be split across multiple calls
+//! to [`Synth::generate_block`]:
+//!
+//! ```rust
+//! let sound = jfxr::Sound::example(jfxr::sound::Example::Laser);
+//! let mut synth = jfxr::Synth::new(&sound);
+//! synth.set_preview(true);
+//! while !synth.generate_block() {
+//!     // Other work can be done between blocks, such as drawing a frame.
+//! }
+//! let samples = synth.generate();
+//! assert_eq!(samples.len(), sound.num_samples());
+//! ```
+//!
+//! # Output stability
+//!
+//! Rendering is deterministic: the same [`Sound`], rendered with the same
+//! [`OutputSettings`] and the same other [`Synth`] settings, produces the
+//! same samples, bit for bit, regardless of the block size, the thread or
+//! the number of times it is rendered. Across platforms, the functions of
+//! the platform's math library, such as `sin` and `powf`, may differ in the
+//! last bits. Enable the `deterministic` feature to use a pure Rust math
+//! library instead, which makes renders, and the processing of
+//! [`convert`] and [`Sequence`], bit-exact on every platform.
+//!
+//! Within a minor version, the output never changes: patch releases may fix
+//! bugs and add features, but not change a single sample of any render,
+//! unless the render panicked or produced non-finite samples before. Changes
+//! that alter the output of existing sounds, such as fixes to a synth stage
+//! or new defaults, are made in minor releases (which, while the version is
+//! 0.x, are also the breaking releases), and are listed in the release
+//! notes. New parameters whose defaults leave the sound unchanged can be
+//! added in patch releases. The hashes of the renders of the example sounds
+//! and of the output of [`convert`] are checked against
+//! `jfxr::testing::EXAMPLE_HASHES` and `jfxr::testing::CONVERT_HASHES`,
+//! available with the `test-util` feature, to catch accidental changes.
+//!
+//! # Tracing
+//!
+//! With the `tracing` feature, parsing and rendering emit spans at the debug
+//! level through the [`tracing`](https://docs.rs/tracing) crate: `parse`
+//! around [`read_jfxr`], `render` once per render, recording the peak, the
+//! number of clipped samples and the duration, `normalize` with the measured
+//! peak and the gain, and a `stage` span with the duration of each stage.
+//! Each render also logs a summary event. Without the feature, none of this
+//! is compiled in. The command line tool logs to standard error, filtered by
+//! `RUST_LOG`, so `RUST_LOG=jfxr=debug` shows a summary of each render.
+//!
+#![cfg_attr(all(feature = "tracing", feature = "json"), doc = "```rust")]
+#![cfg_attr(not(all(feature = "tracing", feature = "json")), doc = "```rust,ignore")]
+//! use std::sync::{Arc, Mutex};
+//! use tracing::span::{Attributes, Id, Record};
+//!
+//! // A subscriber that collects the names of all spans.
+//! struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+//! impl tracing::Subscriber for SpanNames {
+//!     fn enabled(&self, _: &tracing::Metadata<'_>) -> bool { true }
+//!     fn new_span(&self, span: &Attributes<'_>) -> Id {
+//!         let mut names = self.0.lock().unwrap();
+//!         names.push(span.metadata().name());
+//!         Id::from_u64(names.len() as u64)
+//!     }
+//!     fn record(&self, _: &Id, _: &Record<'_>) {}
+//!     fn record_follows_from(&self, _: &Id, _: &Id) {}
+//!     fn event(&self, _: &tracing::Event<'_>) {}
+//!     fn enter(&self, _: &Id) {}
+//!     fn exit(&self, _: &Id) {}
+//! }
+//!
+//! let names = Arc::new(Mutex::new(Vec::new()));
+//! tracing::subscriber::with_default(SpanNames(names.clone()), || {
+//!     let data = jfxr::write_jfxr(jfxr::Sound::example(jfxr::sound::Example::Coin));
+//!     let sound = jfxr::read_jfxr(&data).unwrap();
+//!     jfxr::generate(&sound);
+//!     let mut stream = jfxr::Synth::new(&sound).stream();
+//!     while stream.next_block().is_some() {}
+//! });
+//! let names = names.lock().unwrap();
+//! let count = |name| names.iter().filter(|span| **span == name).count();
+//! assert_eq!(count("parse"), 1);
+//! assert_eq!(count("render"), 2);
+//! assert_eq!(count("normalize"), 2);
+//! // One for each of the 11 stages.
+//! assert_eq!(count("stage"), 2 * 11);
+//! ```
 
+pub mod analysis;
+pub mod bake;
+#[cfg(feature = "bench")]
+pub mod bench;
+mod capabilities;
+pub mod convert;
+#[cfg(feature = "edit")]
+pub mod edit;
+#[cfg(feature = "dasp")]
+pub mod dasp;
+pub mod export;
 #[cfg(feature = "json")]
 pub mod jfxr;
+#[cfg(feature = "json")]
+pub mod library;
+mod math;
+mod precision;
 pub mod oscillator;
 pub mod parameter;
+pub mod patch;
+pub mod pool;
+pub mod presets;
+pub mod sequence;
+#[cfg(all(feature = "json", feature = "wav"))]
+pub mod snapshot;
 pub mod sound;
 pub mod synth;
+#[cfg(feature = "test-util")]
+pub mod testing;
+#[cfg(feature = "egui")]
+pub mod ui;
+#[cfg(feature = "watch")]
+pub mod watch;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "wav")]
+pub mod wav;
 
+pub use capabilities::{capabilities, Capabilities};
 #[cfg(feature = "json")]
-pub use jfxr::{read_jfxr, write_jfxr};
+pub use jfxr::{format_version, peek_version, read_jfxr, supports_version, write_jfxr};
+pub use oscillator::Random;
+pub use sequence::Sequence;
 pub use sound::Sound;
-pub use synth::Synth;
+pub use synth::{AutomationTarget, Compatibility, Curve, OutputSettings, PullSource, RenderReport, Samples, Stage, StageOrderError, StaticSynth, Stream, Synth, SynthError};
 
 /// Generates the given [`Sound`] sound into samples. The output vector
 /// contains single-channel samples at a 44100 Hz sample rate, and the entire
@@ -49,6 +176,27 @@ pub use synth::Synth;
 /// an instance of [`Synth`] with a reference to a [`Sound`]. Output settings
 /// can be adjusted on the [`Synth`] instance, and the generation can be split
 /// across multiple calls to [`Synth::generate_block`].
+///
+/// A sound that cannot be generated, such as one with a duration of zero,
+/// renders to no samples. Use [`try_generate`] to handle this as an error
+/// instead.
+///
+/// # Panics
+///
+/// Panics if the sound exceeds the default safety limits of [`Synth`].
 pub fn generate(sound: &Sound) -> Vec<f64> {
     Synth::new(sound).generate()
 }
+
+/// Generates the given [`Sound`] sound into samples, like [`generate`], but
+/// returns an error if the sound cannot be generated, or if it exceeds the
+/// default safety limits of [`Synth`]. Use this for sounds from untrusted
+/// sources.
+///
+/// ```rust
+/// let sound = jfxr::Sound::default();
+/// assert_eq!(jfxr::try_generate(&sound), Err(jfxr::SynthError::ZeroDuration));
+/// ```
+pub fn try_generate(sound: &Sound) -> Result<Vec<f64>, SynthError> {
+    Synth::try_new(sound)?.try_generate()
+}
@@ -0,0 +1,354 @@
+//! Collections of `.jfxr` files in a directory, such as the sounds of a
+//! game, which are parsed and rendered on first use. Requires the `json`
+//! feature.
+//!
+//! Opening a [`Library`] only lists the directory, so it is fast even for
+//! thousands of files. Each sound is parsed the first time it is accessed,
+//! and kept along with its render. A file that cannot be read or parsed
+//! records its error and leaves the other sounds alone:
+//!
+//! ```rust
+//! use jfxr::library::{Library, LibraryError};
+//!
+//! let dir = std::env::temp_dir().join(format!("jfxr-library-doctest-{}", std::process::id()));
+//! std::fs::create_dir_all(&dir).unwrap();
+//! let mut coin = jfxr::Sound::example(jfxr::sound::Example::Coin);
+//! coin.name = "Pickup".to_string();
+//! std::fs::write(dir.join("coin.jfxr"), jfxr::write_jfxr(coin)).unwrap();
+//! std::fs::write(dir.join("broken.jfxr"), "{").unwrap();
+//! std::fs::write(dir.join("notes.txt"), "not a sound").unwrap();
+//!
+//! let mut library = Library::open(&dir).unwrap();
+//! assert_eq!(library.stems().collect::<Vec<_>>(), ["broken", "coin"]);
+//! assert!(matches!(library.get("broken"), Some(Err(LibraryError::Format(_)))));
+//! assert_eq!(library.get("coin").unwrap().unwrap().name, "Pickup");
+//! assert!(library.get("missing").is_none());
+//! assert!(library.find_by_name("Pickup").is_some());
+//! assert_eq!(library.failed().map(|(stem, _)| stem).collect::<Vec<_>>(), ["broken"]);
+//!
+//! let samples = library.render("coin").unwrap().unwrap();
+//! assert_eq!(*samples, jfxr::generate(library.get("coin").unwrap().unwrap()));
+//!
+//! // After fixing the file, reloading it parses it again.
+//! std::fs::write(dir.join("broken.jfxr"), jfxr::write_jfxr(jfxr::Sound::example(jfxr::sound::Example::Blip))).unwrap();
+//! library.reload(dir.join("broken.jfxr"));
+//! assert!(library.get("broken").unwrap().is_ok());
+//! assert_eq!(library.failed().count(), 0);
+//!
+//! // A sound that cannot be rendered is also reported once it has been
+//! // rendered. The path may be spelled differently from the directory.
+//! let silent = jfxr::Sound {
+//!     attack: jfxr::parameter::Attack(0.0),
+//!     sustain: jfxr::parameter::Sustain(0.0),
+//!     decay: jfxr::parameter::Decay(0.0),
+//!     ..jfxr::Sound::default()
+//! };
+//! std::fs::create_dir_all(dir.join("sub")).unwrap();
+//! std::fs::write(dir.join("silent.jfxr"), jfxr::write_jfxr(silent)).unwrap();
+//! library.reload(dir.join("sub").join("..").join("silent.jfxr"));
+//! assert!(library.get("silent").unwrap().is_ok());
+//! assert_eq!(library.failed().count(), 0);
+//! assert!(matches!(library.render("silent"), Some(Err(LibraryError::Synth(_)))));
+//! assert_eq!(library.failed().map(|(stem, _)| stem).collect::<Vec<_>>(), ["silent"]);
+//! std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+//!
+//! With the `watch` feature, [`Library::reload`] can be called with the
+//! input of each [`crate::watch::WatchEvent`] to keep the library up to date.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::jfxr::JfxrFormatError;
+use crate::sound::Sound;
+
+/// Version of the format written by [`Library::save_index`]. Indexes with
+/// other versions are ignored.
+const INDEX_VERSION: u32 = 1;
+
+/// Error encountered while reading a library or one of its sounds.
+#[derive(Debug)]
+pub enum LibraryError {
+    /// A file or directory could not be read or written.
+    Io(std::io::Error),
+    /// A file is not a valid `jfxr` sound.
+    Format(JfxrFormatError),
+    /// A sound cannot be generated.
+    Synth(crate::SynthError),
+}
+
+impl std::fmt::Display for LibraryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Format(error) => write!(f, "{error}"),
+            Self::Synth(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for LibraryError {}
+
+/// A file of the library, and what is known about it so far.
+struct Entry {
+    path: PathBuf,
+    /// Size and modification time of the file when it was listed, which
+    /// tell whether the name in an index is up to date.
+    stamp: Option<(u64, SystemTime)>,
+    /// Name of the sound, from the file or from an index.
+    name: Option<String>,
+    sound: Option<Result<Sound, LibraryError>>,
+    samples: Option<Result<Arc<Vec<f64>>, LibraryError>>,
+}
+
+impl Entry {
+    fn new(path: PathBuf) -> Self {
+        let stamp = std::fs::metadata(&path).ok().and_then(|metadata| Some((metadata.len(), metadata.modified().ok()?)));
+        Self {
+            path,
+            stamp,
+            name: None,
+            sound: None,
+            samples: None,
+        }
+    }
+
+    fn sound(&mut self) -> Result<&Sound, &LibraryError> {
+        let path = &self.path;
+        let sound = self.sound.get_or_insert_with(|| {
+            let data = std::fs::read_to_string(path).map_err(LibraryError::Io)?;
+            crate::read_jfxr(&data).map_err(LibraryError::Format)
+        });
+        if let Ok(sound) = sound {
+            self.name = Some(sound.name.clone());
+        }
+        sound.as_ref()
+    }
+}
+
+/// The `.jfxr` files in a directory, by the name of the file without the
+/// extension. Subdirectories are not included.
+pub struct Library {
+    dir: PathBuf,
+    entries: BTreeMap<String, Entry>,
+}
+
+impl Library {
+    /// Lists the `.jfxr` files in the directory, without reading them.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, LibraryError> {
+        let dir = dir.into();
+        let mut entries = BTreeMap::new();
+        for entry in std::fs::read_dir(&dir).map_err(LibraryError::Io)? {
+            let path = entry.map_err(LibraryError::Io)?.path();
+            if let Some(stem) = sound_file_stem(&path) {
+                entries.insert(stem, Entry::new(path));
+            }
+        }
+        Ok(Self { dir, entries })
+    }
+
+    /// Lists the `.jfxr` files in the directory like [`Self::open`], and
+    /// takes the names of the sounds from an index written by
+    /// [`Self::save_index`], so that [`Self::find_by_name`] does not need
+    /// to parse every file. Files that changed since the index was written
+    /// are parsed as usual. An index that is missing or cannot be read is
+    /// ignored.
+    ///
+    /// ```rust
+    /// use jfxr::library::Library;
+    ///
+    /// let dir = std::env::temp_dir().join(format!("jfxr-library-index-doctest-{}", std::process::id()));
+    /// std::fs::create_dir_all(&dir).unwrap();
+    /// for example in jfxr::sound::Example::ALL {
+    ///     let path = dir.join(example.name().to_lowercase()).with_extension("jfxr");
+    ///     std::fs::write(path, jfxr::write_jfxr(jfxr::Sound::example(*example))).unwrap();
+    /// }
+    /// let index = dir.join("index.json");
+    /// Library::open(&dir).unwrap().save_index(&index).unwrap();
+    ///
+    /// // The names come from the index, and no sound is parsed until it is
+    /// // accessed.
+    /// let mut library = Library::open_with_index(&dir, &index).unwrap();
+    /// assert_eq!(library.parsed_count(), 0);
+    /// assert_eq!(library.names().count(), jfxr::sound::Example::ALL.len());
+    /// assert!(library.find_by_name("Laser").is_some());
+    /// assert_eq!(library.parsed_count(), 1);
+    ///
+    /// // A file that changed since is parsed again.
+    /// let mut laser = jfxr::Sound::example(jfxr::sound::Example::Laser);
+    /// laser.name = "Zap".to_string();
+    /// std::fs::write(dir.join("laser.jfxr"), " ".repeat(100) + &jfxr::write_jfxr(laser)).unwrap();
+    /// let mut library = Library::open_with_index(&dir, &index).unwrap();
+    /// assert!(library.find_by_name("Laser").is_none());
+    /// assert!(library.find_by_name("Zap").is_some());
+    /// std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn open_with_index(dir: impl Into<PathBuf>, index: impl AsRef<Path>) -> Result<Self, LibraryError> {
+        let mut library = Self::open(dir)?;
+        let Some(index) = std::fs::read_to_string(index).ok().and_then(|data| json::parse(&data).ok()) else {
+            return Ok(library);
+        };
+        if index["version"].as_u32() != Some(INDEX_VERSION) {
+            return Ok(library);
+        }
+        for file in index["files"].members() {
+            let (Some(stem), Some(name), Some(size), Some(secs), Some(nanos)) = (
+                file["stem"].as_str(),
+                file["name"].as_str(),
+                file["size"].as_u64(),
+                file["modifiedSecs"].as_u64(),
+                file["modifiedNanos"].as_u32(),
+            ) else {
+                continue;
+            };
+            if let Some(entry) = library.entries.get_mut(stem) {
+                if entry.stamp == Some((size, SystemTime::UNIX_EPOCH + Duration::new(secs, nanos))) {
+                    entry.name = Some(name.to_string());
+                }
+            }
+        }
+        Ok(library)
+    }
+
+    /// Writes the name, size and modification time of each sound to an
+    /// index for [`Self::open_with_index`]. Sounds whose names are not known
+    /// yet are parsed first; files that cannot be parsed are left out.
+    pub fn save_index(&mut self, index: impl AsRef<Path>) -> Result<(), LibraryError> {
+        let mut files = json::JsonValue::new_array();
+        for (stem, entry) in &mut self.entries {
+            if entry.name.is_none() {
+                let _ = entry.sound();
+            }
+            let (Some(name), Some((size, modified))) = (&entry.name, entry.stamp) else {
+                continue;
+            };
+            let modified = modified.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+            files
+                .push(json::object! {
+                    "stem": stem.as_str(),
+                    "name": name.as_str(),
+                    "size": size,
+                    "modifiedSecs": modified.as_secs(),
+                    "modifiedNanos": modified.subsec_nanos(),
+                })
+                .unwrap();
+        }
+        let index_json = json::object! { "version": INDEX_VERSION, "files": files };
+        std::fs::write(index, index_json.dump()).map_err(LibraryError::Io)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the number of files in the library, including those that
+    /// cannot be parsed.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the names of the files without the extension, in order.
+    pub fn stems(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Returns the names of the sounds that are known without parsing, from
+    /// an index or from an earlier access.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.values().filter_map(|entry| entry.name.as_deref())
+    }
+
+    /// Returns the number of files that have been parsed so far, whether
+    /// successfully or not.
+    pub fn parsed_count(&self) -> usize {
+        self.entries.values().filter(|entry| entry.sound.is_some()).count()
+    }
+
+    /// Returns the sound in the file with the given name, without the
+    /// extension, parsing it if this is the first access. Returns `None` if
+    /// there is no such file, and the error if it cannot be read or parsed.
+    pub fn get(&mut self, stem: &str) -> Option<Result<&Sound, &LibraryError>> {
+        Some(self.entries.get_mut(stem)?.sound())
+    }
+
+    /// Returns the first sound, in the order of the files, whose name is
+    /// the given name. Files whose names are not known yet are parsed until
+    /// one is found.
+    pub fn find_by_name(&mut self, name: &str) -> Option<&Sound> {
+        for entry in self.entries.values_mut() {
+            if entry.name.is_none() {
+                let _ = entry.sound();
+            }
+            if entry.name.as_deref() == Some(name) {
+                return entry.sound().ok();
+            }
+        }
+        None
+    }
+
+    /// Returns the render of the sound in the file with the given name,
+    /// rendering it with [`crate::try_generate`] if this is the first
+    /// access.
+    pub fn render(&mut self, stem: &str) -> Option<Result<Arc<Vec<f64>>, &LibraryError>> {
+        let entry = self.entries.get_mut(stem)?;
+        if entry.samples.is_none() {
+            let samples = match entry.sound() {
+                Ok(sound) => crate::try_generate(sound).map(Arc::new).map_err(LibraryError::Synth),
+                Err(_) => return Some(Err(entry.sound.as_ref()?.as_ref().err()?)),
+            };
+            entry.samples = Some(samples);
+        }
+        Some(entry.samples.as_ref()?.as_ref().map(Arc::clone))
+    }
+
+    /// Returns the files that have been accessed and could not be read,
+    /// parsed or rendered, with their errors.
+    pub fn failed(&self) -> impl Iterator<Item = (&str, &LibraryError)> {
+        self.entries.iter().filter_map(|(stem, entry)| {
+            let error = match entry.sound.as_ref()? {
+                Err(error) => error,
+                Ok(_) => entry.samples.as_ref()?.as_ref().err()?,
+            };
+            Some((stem.as_str(), error))
+        })
+    }
+
+    /// Forgets everything about the file at the given path, so that it is
+    /// parsed again on the next access. Files that were added to the
+    /// directory are added to the library, and files that were removed are
+    /// removed. Paths outside of the directory are ignored. The path and
+    /// the directory are compared after resolving symbolic links and `..`,
+    /// so the path does not have to be spelled like the directory was.
+    pub fn reload(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let Some(stem) = sound_file_stem(path) else {
+            return;
+        };
+        // The file itself may have been removed, so only its directory is
+        // resolved.
+        let canonical = |dir: &Path| dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        if path.parent().map(canonical) != Some(canonical(&self.dir)) {
+            return;
+        }
+        if path.is_file() {
+            self.entries.insert(stem, Entry::new(path.to_path_buf()));
+        } else {
+            self.entries.remove(&stem);
+        }
+    }
+}
+
+/// Returns the name without the extension if the path has the `.jfxr`
+/// extension.
+fn sound_file_stem(path: &Path) -> Option<String> {
+    if path.extension()? != "jfxr" {
+        return None;
+    }
+    Some(path.file_stem()?.to_string_lossy().into_owned())
+}
@@ -0,0 +1,35 @@
+//! Transcendental functions used to render sounds. The standard library
+//! calls the math library of the platform, whose results may differ in the
+//! last bits from one platform to the next. With the `deterministic`
+//! feature, the pure Rust implementations of the `libm` crate are used
+//! instead, which give the same results everywhere.
+
+macro_rules! math {
+    ($($name:ident($($arg:ident),*) => $std:expr;)*) => {
+        $(
+            #[cfg(feature = "deterministic")]
+            #[inline]
+            pub fn $name($($arg: f64),*) -> f64 {
+                libm::$name($($arg),*)
+            }
+
+            #[cfg(not(feature = "deterministic"))]
+            #[inline]
+            pub fn $name($($arg: f64),*) -> f64 {
+                $std
+            }
+        )*
+    };
+}
+
+math! {
+    sin(x) => x.sin();
+    cos(x) => x.cos();
+    tan(x) => x.tan();
+    exp(x) => x.exp();
+    exp2(x) => x.exp2();
+    log(x) => x.ln();
+    log10(x) => x.log10();
+    pow(x, y) => x.powf(y);
+    hypot(x, y) => x.hypot(y);
+}
@@ -4,7 +4,11 @@ fn lerp(a: f64, b: f64, f: f64) -> f64 {
     (1.0 - f) * a + f * b
 }
 
-struct Random {
+/// The xorshift random number generator used by the `jfxr` tool, which is
+/// used to generate noise and random sounds. The same seed always produces
+/// the same sequence of numbers.
+#[derive(Clone)]
+pub struct Random {
     x: u32,
     y: u32,
     z: u32,
@@ -34,39 +38,151 @@ impl Random {
         self.w.wrapping_add(0x80000000)
     }
 
+    /// Returns a number between `min` and `max`, inclusive.
     pub fn uniform(&mut self, min: f64, max: f64) -> f64 {
         min + (max - min) * self.uint32() as f64 / 0xffffffffu64 as f64
     }
+
+    /// Returns an integer between `min`, inclusive, and `max`, which is
+    /// exclusive except with a very small probability.
+    pub fn int(&mut self, min: i32, max: i32) -> i32 {
+        self.uniform(min as f64, max as f64).floor() as i32
+    }
+
+    /// Returns `true` with the given probability.
+    pub fn boolean(&mut self, true_probability: f64) -> bool {
+        self.uniform(0.0, 1.0) < true_probability
+    }
+
+    /// Returns a random element of the given non-empty slice.
+    pub fn from_slice<T: Copy>(&mut self, slice: &[T]) -> T {
+        let index = self.int(0, slice.len() as i32) as usize;
+        slice[index.min(slice.len() - 1)]
+    }
 }
 
-/*
-  
-  Random.prototype.uniform = function(min, max) {
-    if (min === undefined && max === undefined) {
-      min = 0;
-      max = 1;
-    } else if (max === undefined) {
-      max = min;
-      min = 0;
-    }
-    return min + (max - min) * this.uint32() / 0xffffffff;
-  };
-  
-  Random.prototype.int = function(min, max) {
-    return Math.floor(this.uniform(min, max));
-  };
-  
-  Random.prototype.boolean = function(trueProbability) {
-    return this.uniform() < trueProbability;
-  };
-  
-  Random.prototype.fromArray = function(array) {
-    return array[this.int(array.length)];
-  };
-*/
+/// Seed used for the random number generator of noise oscillators.
+pub const NOISE_SEED: u32 = 0x3cf78ba3;
 
 pub trait Oscillator {
     fn get_sample(&mut self, sound: &super::sound::Sound, phase: f64, time: f64) -> f64;
+
+    /// Returns a copy of the state that the oscillator carries from one
+    /// sample to the next, such as the state of its random number
+    /// generator, or `None` if its output only depends on the phase and the
+    /// time.
+    fn state(&self) -> Option<Box<dyn std::any::Any + Send>> {
+        None
+    }
+
+    /// Restores a state returned by [`Self::state`] of an oscillator of the
+    /// same type. Other states are ignored.
+    fn restore_state(&mut self, _state: &dyn std::any::Any) {}
+}
+
+/// Implements [`Oscillator::state`] and [`Oscillator::restore_state`] for an
+/// oscillator whose whole state is a clone of itself.
+macro_rules! cloned_state {
+    () => {
+        fn state(&self) -> Option<Box<dyn std::any::Any + Send>> {
+            Some(Box::new(self.clone()))
+        }
+
+        fn restore_state(&mut self, state: &dyn std::any::Any) {
+            if let Some(state) = state.downcast_ref::<Self>() {
+                *self = state.clone();
+            }
+        }
+    };
+}
+
+/// Constructs the oscillator for the given waveform.
+pub fn for_waveform(waveform: super::parameter::Waveform, sound: &super::sound::Sound) -> Box<dyn Oscillator> {
+    create(waveform, sound, NOISE_SEED, super::synth::Compatibility::default())
+}
+
+/// Constructs the oscillator for the given waveform. Noise oscillators use
+/// the given seed for their random number generator; other oscillators
+/// ignore it.
+pub fn for_waveform_with_seed(waveform: super::parameter::Waveform, sound: &super::sound::Sound, seed: u32) -> Box<dyn Oscillator> {
+    create(waveform, sound, seed, super::synth::Compatibility::default())
+}
+
+/// Constructs the oscillator for the given waveform, like
+/// [`for_waveform_with_seed`], with the behavior of the given compatibility
+/// mode.
+pub(crate) fn create(waveform: super::parameter::Waveform, sound: &super::sound::Sound, seed: u32, compatibility: super::synth::Compatibility) -> Box<dyn Oscillator + Send> {
+    let improved = compatibility == super::synth::Compatibility::Improved;
+    // Noise oscillators produce their first random value at the end of the
+    // first half cycle. When improved, they are primed with random values
+    // up front instead, so that noise does not rise from silence.
+    match waveform {
+        super::parameter::Waveform::Sine => Box::new(SineOscillator::new(sound)),
+        super::parameter::Waveform::Triangle => Box::new(TriangleOscillator::new(sound)),
+        super::parameter::Waveform::Sawtooth => Box::new(SawtoothOscillator::new(sound)),
+        super::parameter::Waveform::Square => {
+            let mut osc = SquareOscillator::new(sound);
+            osc.clamp_duty = improved;
+            Box::new(osc)
+        }
+        super::parameter::Waveform::Tangent => Box::new(TangentOscillator::new(sound)),
+        super::parameter::Waveform::Whistle => Box::new(WhistleOscillator::new(sound)),
+        super::parameter::Waveform::Breaker => Box::new(BreakerOscillator::new(sound)),
+        super::parameter::Waveform::Whitenoise => {
+            let mut osc = WhiteNoiseOscillator::with_seed(sound, seed);
+            if improved {
+                osc.prime(sound);
+            }
+            Box::new(osc)
+        }
+        super::parameter::Waveform::Pinknoise => {
+            let mut osc = PinkNoiseOscillator::with_seed(sound, seed);
+            if improved {
+                osc.prime(sound);
+            }
+            Box::new(osc)
+        }
+        super::parameter::Waveform::Brownnoise => {
+            let mut osc = BrownNoiseOscillator::with_seed(sound, seed);
+            osc.reflect = improved;
+            if improved {
+                osc.prime(sound);
+            }
+            Box::new(osc)
+        }
+        super::parameter::Waveform::Bluenoise => {
+            let mut osc = BlueNoiseOscillator::with_seed(sound, seed);
+            if improved {
+                osc.prime(sound);
+            }
+            Box::new(osc)
+        }
+        super::parameter::Waveform::Velvetnoise => {
+            let mut osc = VelvetNoiseOscillator::with_seed(sound, seed);
+            if improved {
+                osc.prime();
+            }
+            Box::new(osc)
+        }
+    }
+}
+
+/// Renders a single cycle of the given waveform, sampled at `n_samples`
+/// evenly spaced phases starting at zero, at time zero of the sound. This
+/// is useful for drawing waveform icons, or for inspecting an oscillator in
+/// isolation. Noise waveforms only produce a new random value twice per
+/// cycle, so their output is not very representative.
+///
+/// ```rust
+/// let sound = jfxr::Sound::default();
+/// let cycle = jfxr::oscillator::render_cycle(jfxr::parameter::Waveform::Sawtooth, &sound, 4);
+/// assert_eq!(cycle, vec![0.0, 0.5, -1.0, -0.5]);
+/// ```
+pub fn render_cycle(waveform: super::parameter::Waveform, sound: &super::sound::Sound, n_samples: usize) -> Vec<f64> {
+    let mut oscillator = for_waveform(waveform, sound);
+    (0..n_samples)
+        .map(|i| oscillator.get_sample(sound, i as f64 / n_samples as f64, 0.0))
+        .collect()
 }
 
 pub struct SineOscillator;
@@ -79,7 +195,7 @@ impl SineOscillator {
 
 impl Oscillator for SineOscillator {
     fn get_sample(&mut self, _sound: &super::sound::Sound, phase: f64, _time: f64) -> f64 {
-        (2.0 * PI * phase).sin()
+        crate::math::sin(2.0 * PI * phase)
     }
 }
 
@@ -114,21 +230,135 @@ impl Oscillator for SawtoothOscillator {
     }
 }
 
-pub struct SquareOscillator;
+/// Square (pulse) wave oscillator. The duty cycle is taken from
+/// [`super::sound::Sound::square_duty_at`] and compared against the phase
+/// passed in, so when harmonics are enabled, each harmonic is a pulse wave of
+/// the same duty cycle at a multiple of the base frequency. This matches the
+/// upstream implementation, where every harmonic receives
+/// `frac(phase * (harmonicIndex + 1))` and the same `squareDutyAt(time)`.
+///
+/// A duty cycle of 0% or 100%, including one reached by the square duty
+/// sweep, makes the wave constant, which is silent but shifts the whole
+/// sound to -1 or 1. With [`super::synth::Compatibility::Improved`], the
+/// duty cycle of every sample is kept between [`Self::MIN_DUTY`] and
+/// [`Self::MAX_DUTY`] instead, so the wave becomes a train of narrow pulses.
+/// At high frequencies, pulses can still be shorter than a sample and be
+/// missed.
+///
+/// ```rust
+/// use jfxr::parameter::{Attack, BitCrush, Frequency, Normalization, SquareDuty, SquareDutySweep, Sustain, Waveform};
+/// use jfxr::synth::Compatibility;
+///
+/// let render = |duty, sweep, compatibility| {
+///     let sound = jfxr::Sound {
+///         waveform: Waveform::Square,
+///         frequency: Frequency(100.0),
+///         square_duty: SquareDuty(duty),
+///         square_duty_sweep: SquareDutySweep(sweep),
+///         attack: Attack(0.0),
+///         sustain: Sustain(0.2),
+///         bit_crush: BitCrush(0),
+///         normalization: Normalization(false),
+///         ..Default::default()
+///     };
+///     let mut synth = jfxr::Synth::new(&sound);
+///     synth.set_compatibility(compatibility);
+///     synth.generate()
+/// };
+/// // Every cycle of 100 Hz is 441 samples long, and has both levels.
+/// let has_pulses = |samples: &[f64]| samples.chunks(441).all(|cycle| cycle.contains(&1.0) && cycle.contains(&-1.0));
+/// for (duty, sweep) in [(0.0, 0.0), (100.0, 0.0), (50.0, -100.0), (50.0, 100.0), (0.0, 100.0), (100.0, -100.0)] {
+///     assert!(has_pulses(&render(duty, sweep, Compatibility::Improved)), "{duty} {sweep}");
+/// }
+/// // Like upstream, the exact mode plays a constant level.
+/// assert!(render(0.0, 0.0, Compatibility::Exact).iter().all(|sample| *sample == -1.0));
+/// assert!(render(100.0, 0.0, Compatibility::Exact).iter().all(|sample| *sample == 1.0));
+/// let swept = render(50.0, -100.0, Compatibility::Exact);
+/// assert!(swept[swept.len() / 2..].iter().all(|sample| *sample == -1.0));
+/// ```
+///
+/// With harmonics, every harmonic has its own duty cycle, as in the web
+/// tool. `tests/web_tool.rs` compares `tests/web-tool/square-harmonics.jfxr`,
+/// which has three harmonics and a duty sweep, with its export from the web
+/// tool.
+pub struct SquareOscillator {
+    duty: Option<super::synth::Curve>,
+    /// Whether the duty cycle is kept between [`Self::MIN_DUTY`] and
+    /// [`Self::MAX_DUTY`].
+    pub clamp_duty: bool,
+    /// Seed of the repeat jitter of the duty cycle, which the synth derives
+    /// from its variation.
+    pub(crate) jitter_seed: u32,
+}
 
 impl SquareOscillator {
+    /// Smallest duty cycle, as a fraction, when the duty cycle is clamped.
+    pub const MIN_DUTY: f64 = 0.01;
+    /// Largest duty cycle, as a fraction, when the duty cycle is clamped.
+    pub const MAX_DUTY: f64 = 0.99;
+
     pub fn new(_sound: &super::sound::Sound) -> Self {
-        Self
+        Self {
+            duty: None,
+            clamp_duty: false,
+            jitter_seed: NOISE_SEED,
+        }
+    }
+
+    /// Creates a square oscillator which takes its duty cycle, in percent,
+    /// from the given curve rather than from the sound.
+    pub fn with_duty_curve(duty: super::synth::Curve) -> Self {
+        Self {
+            duty: Some(duty),
+            clamp_duty: false,
+            jitter_seed: NOISE_SEED,
+        }
     }
 }
 
 impl Oscillator for SquareOscillator {
     fn get_sample(&mut self, sound: &super::sound::Sound, phase: f64, time: f64) -> f64 {
-        if phase < sound.square_duty_at(time) { return 1.0; }
+        use super::parameter::FloatParameter;
+        let duty = match &self.duty {
+            Some(curve) => curve(time).clamp(super::parameter::SquareDuty::MIN_VALUE, super::parameter::SquareDuty::MAX_VALUE) / 100.0,
+            None => sound.square_duty_at_with_seed(time, self.jitter_seed),
+        };
+        let duty = if self.clamp_duty { duty.clamp(Self::MIN_DUTY, Self::MAX_DUTY) } else { duty };
+        if phase < duty { return 1.0; }
         -1.0
     }
 }
 
+/// Tangent wave oscillator, which drives a tangent into a clipping level
+/// set by [`super::parameter::TangentClip`]. The drive rises as the clipping
+/// level falls, so that a low level clips most of the cycle, for a harsh,
+/// square-like tone, while a high level leaves narrow, saw-like spikes. The
+/// output is scaled to the same peak level whatever the clipping level, and
+/// at the default level it is the wave of upstream.
+///
+/// ```rust
+/// use jfxr::parameter::{FloatParameter, TangentClip, Waveform};
+///
+/// let cycle = |clip| {
+///     let sound = jfxr::Sound { tangent_clip: TangentClip(clip), ..Default::default() };
+///     jfxr::oscillator::render_cycle(Waveform::Tangent, &sound, 1000)
+/// };
+/// // Upstream clips the tangent, scaled by 0.3, at 2.
+/// let upstream: Vec<f64> = (0..1000)
+///     .map(|i| (0.3 * (std::f64::consts::PI * i as f64 / 1000.0).tan()).clamp(-2.0, 2.0))
+///     .collect();
+/// let default = cycle(TangentClip::DEFAULT_VALUE);
+/// assert!(default.iter().zip(&upstream).all(|(a, b)| (a - b).abs() < 1e-12));
+///
+/// // Fraction of the cycle spent at the peak level.
+/// let clipped = |clip| cycle(clip).iter().filter(|sample| sample.abs() >= 2.0 - 1e-9).count() as f64 / 1000.0;
+/// let (harsh, default, mild) = (clipped(0.5), clipped(2.0), clipped(5.0));
+/// assert!(harsh > 0.5 && harsh > 2.0 * default, "{harsh} {default}");
+/// assert!(mild < 0.1 && mild < default / 2.0, "{mild} {default}");
+/// for clip in [0.5, 2.0, 5.0] {
+///     assert_eq!(jfxr::analysis::peak(&cycle(clip)), 2.0);
+/// }
+/// ```
 pub struct TangentOscillator;
 
 impl TangentOscillator {
@@ -138,9 +368,14 @@ impl TangentOscillator {
 }
 
 impl Oscillator for TangentOscillator {
-    fn get_sample(&mut self, _sound: &super::sound::Sound, phase: f64, _time: f64) -> f64 {
-        // Arbitrary cutoff value to make normalization behave.
-        (0.3 * (PI * phase).tan()).clamp(-2.0, 2.0)
+    fn get_sample(&mut self, sound: &super::sound::Sound, phase: f64, _time: f64) -> f64 {
+        // Cutoff value to make normalization behave. The drive is scaled
+        // against the cutoff, and the output is scaled so that the peak
+        // level stays the same regardless of the cutoff. At the default
+        // cutoff of 2, both factors are exactly 1.
+        let clip = sound.tangent_clip.0;
+        let scale = 2.0 / clip;
+        (0.3 * scale * crate::math::tan(PI * phase)).clamp(-clip, clip) * scale
     }
 }
 
@@ -153,43 +388,109 @@ impl WhistleOscillator {
 }
 
 impl Oscillator for WhistleOscillator {
-    fn get_sample(&mut self, _sound: &super::sound::Sound, phase: f64, _time: f64) -> f64 {
-        0.75 * (2.0 * PI * phase).sin() + 0.25 * (40.0 * PI * phase).sin()
+    fn get_sample(&mut self, sound: &super::sound::Sound, phase: f64, _time: f64) -> f64 {
+        let mix = sound.whistle_overtone_mix.0 / 100.0;
+        let ratio = sound.whistle_overtone_ratio.0 as f64;
+        (1.0 - mix) * crate::math::sin(2.0 * PI * phase) + mix * crate::math::sin(2.0 * PI * ratio * phase)
     }
 }
 
 pub struct BreakerOscillator;
 
 impl BreakerOscillator {
+    /// Default fraction of a cycle by which the shape is shifted, so that it
+    /// starts at a zero crossing like the other waveforms. This is √0.75, as
+    /// upstream. The offset is set with
+    /// [`super::parameter::BreakerPhaseOffset`].
+    pub const PHASE_OFFSET: f64 = 0.8660254037844386;
+
     pub fn new(_sound: &super::sound::Sound) -> Self {
         Self
     }
 }
 
 impl Oscillator for BreakerOscillator {
-    fn get_sample(&mut self, _sound: &super::sound::Sound, phase: f64, _time: f64) -> f64 {
-        // Make sure to start at a zero crossing.
-        let p = (phase + 0.75f64.sqrt()).fract();
+    fn get_sample(&mut self, sound: &super::sound::Sound, phase: f64, _time: f64) -> f64 {
+        let p = (phase + sound.breaker_phase_offset.0 / 100.0).fract();
         -1.0 + 2.0 * (1.0 - p * p * 2.0).abs()
     }
 }
 
+/// The most recent random values produced by a noise oscillator, used to
+/// interpolate between them.
+#[derive(Clone)]
+struct NoiseHistory {
+    interpolation: super::parameter::NoiseInterpolation,
+    values: [f64; NoiseHistory::LEN],
+}
+
+impl NoiseHistory {
+    /// Number of values kept, which is the number needed by the cubic
+    /// interpolation.
+    const LEN: usize = 4;
+
+    fn new(sound: &super::sound::Sound) -> Self {
+        Self {
+            interpolation: sound.effective_noise_interpolation(),
+            values: [0.0; Self::LEN],
+        }
+    }
+
+    fn current(&self) -> f64 {
+        self.values[3]
+    }
+
+    fn push(&mut self, value: f64) {
+        self.values.rotate_left(1);
+        self.values[3] = value;
+    }
+
+    fn sample(&self, phase: f64) -> f64 {
+        let [p0, p1, p2, p3] = self.values;
+        match self.interpolation {
+            super::parameter::NoiseInterpolation::None => p3,
+            super::parameter::NoiseInterpolation::Linear => lerp(p2, p3, phase),
+            // Catmull-Rom spline between the middle two values. This lags one
+            // value behind the other modes, since it needs a value on either
+            // side of the interpolated segment.
+            super::parameter::NoiseInterpolation::Cubic => 0.5 * (
+                2.0 * p1
+                + (-p0 + p2) * phase
+                + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * phase * phase
+                + (-p0 + 3.0 * p1 - 3.0 * p2 + p3) * phase * phase * phase
+            ),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct WhiteNoiseOscillator {
-    interpolate_noise: bool,
     random: Random,
     prev_phase: f64,
-    prev_random: f64,
-    curr_random: f64,
+    history: NoiseHistory,
 }
 
 impl WhiteNoiseOscillator {
     pub fn new(sound: &super::sound::Sound) -> Self {
+        Self::with_seed(sound, NOISE_SEED)
+    }
+
+    pub fn with_seed(sound: &super::sound::Sound, seed: u32) -> Self {
         Self {
-            interpolate_noise: sound.interpolate_noise.0,
-            random: Random::new(0x3cf78ba3),
+            random: Random::new(seed),
             prev_phase: 0.0,
-            prev_random: 0.0,
-            curr_random: 0.0,
+            history: NoiseHistory::new(sound),
+        }
+    }
+
+    fn next_value(&mut self) -> f64 {
+        self.random.uniform(-1.0, 1.0)
+    }
+
+    fn prime(&mut self, _sound: &super::sound::Sound) {
+        for _ in 0..NoiseHistory::LEN {
+            let value = self.next_value();
+            self.history.push(value);
         }
     }
 }
@@ -199,33 +500,73 @@ impl Oscillator for WhiteNoiseOscillator {
         // Need two samples per phase in order to include the desired frequencies.
         let phase = (phase * 2.0).fract();
         if phase < self.prev_phase {
-            self.prev_random = self.curr_random;
-            self.curr_random = self.random.uniform(-1.0, 1.0);
+            let value = self.next_value();
+            self.history.push(value);
         }
         self.prev_phase = phase;
-        if self.interpolate_noise { return lerp(self.prev_random, self.curr_random, phase); }
-        self.curr_random
+        self.history.sample(phase)
+    }
+
+    cloned_state!();
+}
+
+/// Filter that turns white noise into pink noise, whose power decreases by
+/// 3 dB per octave. This is method pk3 from
+/// http://www.firstpr.com.au/dsp/pink-noise/, due to Paul Kellet.
+#[derive(Clone)]
+struct PinkFilter {
+    b: [f64; 7],
+}
+
+impl PinkFilter {
+    fn new() -> Self {
+        Self { b: [0.0; 7] }
+    }
+
+    fn next(&mut self, white: f64) -> f64 {
+        self.b[0] = 0.99886 * self.b[0] + white * 0.0555179;
+        self.b[1] = 0.99332 * self.b[1] + white * 0.0750759;
+        self.b[2] = 0.96900 * self.b[2] + white * 0.1538520;
+        self.b[3] = 0.86650 * self.b[3] + white * 0.3104856;
+        self.b[4] = 0.55000 * self.b[4] + white * 0.5329522;
+        self.b[5] = -0.7616 * self.b[5] + white * 0.0168980;
+        let value = (self.b[0] + self.b[1] + self.b[2] + self.b[3] + self.b[4] + self.b[5] + self.b[6] + white * 0.5362) / 7.0;
+        self.b[6] = white * 0.115926;
+        value
     }
 }
 
+#[derive(Clone)]
 pub struct PinkNoiseOscillator {
-    interpolate_noise: bool,
     random: Random,
     prev_phase: f64,
-    b: [f64; 7],
-    prev_random: f64,
-    curr_random: f64,
+    filter: PinkFilter,
+    history: NoiseHistory,
 }
 
 impl PinkNoiseOscillator {
     pub fn new(sound: &super::sound::Sound) -> Self {
+        Self::with_seed(sound, NOISE_SEED)
+    }
+
+    pub fn with_seed(sound: &super::sound::Sound, seed: u32) -> Self {
         Self {
-            interpolate_noise: sound.interpolate_noise.0,
-            random: Random::new(0x3cf78ba3),
+            random: Random::new(seed),
             prev_phase: 0.0,
-            b: [0.0; 7],
-            prev_random: 0.0,
-            curr_random: 0.0,
+            filter: PinkFilter::new(),
+            history: NoiseHistory::new(sound),
+        }
+    }
+
+    fn next_value(&mut self) -> f64 {
+        let white = self.random.uniform(-1.0, 1.0);
+        self.filter.next(white)
+    }
+
+    fn prime(&mut self, _sound: &super::sound::Sound) {
+        for _ in 0..NoiseHistory::LEN {
+            let value = self.next_value();
+            self.history.push(value);
         }
     }
 }
@@ -235,57 +576,249 @@ impl Oscillator for PinkNoiseOscillator {
         // Need two samples per phase in order to include the desired frequencies.
         let phase = (phase * 2.0).fract();
         if phase < self.prev_phase {
-            self.prev_random = self.curr_random;
-            // Method pk3 from http://www.firstpr.com.au/dsp/pink-noise/,
-            // due to Paul Kellet.
-            let white = self.random.uniform(-1.0, 1.0);
-            self.b[0] = 0.99886 * self.b[0] + white * 0.0555179;
-            self.b[1] = 0.99332 * self.b[1] + white * 0.0750759;
-            self.b[2] = 0.96900 * self.b[2] + white * 0.1538520;
-            self.b[3] = 0.86650 * self.b[3] + white * 0.3104856;
-            self.b[4] = 0.55000 * self.b[4] + white * 0.5329522;
-            self.b[5] = -0.7616 * self.b[5] + white * 0.0168980;
-            self.curr_random = (self.b[0] + self.b[1] + self.b[2] + self.b[3] + self.b[4] + self.b[5] + self.b[6] + white * 0.5362) / 7.0;
-            self.b[6] = white * 0.115926;
+            let value = self.next_value();
+            self.history.push(value);
         }
         self.prev_phase = phase;
-        if self.interpolate_noise { return lerp(self.prev_random, self.curr_random, phase); }
-        self.curr_random
+        self.history.sample(phase)
     }
-}
-
 
+    cloned_state!();
+}
 
+/// Brown noise, a random walk whose steps are at most
+/// [`super::parameter::BrownNoiseRoughness`] in size. In the exact mode, the
+/// walk is clamped to the range from -1 to 1, and with the default roughness
+/// it is the same walk as upstream, value for value:
+///
+/// ```rust
+/// use jfxr::parameter::{NoiseInterpolation, Waveform};
+///
+/// let sound = jfxr::Sound { noise_interpolation: NoiseInterpolation::None, ..Default::default() };
+/// for seed in [jfxr::oscillator::NOISE_SEED, 1, 2] {
+///     let mut oscillator = jfxr::oscillator::for_waveform_with_seed(Waveform::Brownnoise, &sound, seed);
+///     // A new value is produced whenever the doubled phase wraps around.
+///     let values: Vec<f64> = (0..2 * 4096)
+///         .map(|i| oscillator.get_sample(&sound, if i % 2 == 0 { 0.0 } else { 0.3 }, 0.0))
+///         .skip(2)
+///         .step_by(2)
+///         .collect();
+///
+///     // The walk of upstream, with a step of 0.1.
+///     let mut random = jfxr::Random::new(seed);
+///     let mut value = 0.0f64;
+///     let expected: Vec<f64> = (0..values.len())
+///         .map(|_| {
+///             value = (value + 0.1 * random.uniform(-1.0, 1.0)).clamp(-1.0, 1.0);
+///             value
+///         })
+///         .collect();
+///     assert_eq!(values, expected);
+/// }
+/// ```
+#[derive(Clone)]
 pub struct BrownNoiseOscillator {
-    interpolate_noise: bool,
     random: Random,
     prev_phase: f64,
-    prev_random: f64,
-    curr_random: f64,
+    history: NoiseHistory,
+    /// Whether the random walk is reflected back at the range limits, rather
+    /// than clamped to them. Clamping makes the noise stick to the limits
+    /// for a while, which flattens the peaks of the waveform.
+    reflect: bool,
 }
 
 impl BrownNoiseOscillator {
     pub fn new(sound: &super::sound::Sound) -> Self {
+        Self::with_seed(sound, NOISE_SEED)
+    }
+
+    pub fn with_seed(sound: &super::sound::Sound, seed: u32) -> Self {
         Self {
-            interpolate_noise: sound.interpolate_noise.0,
-            random: Random::new(0x3cf78ba3),
+            random: Random::new(seed),
             prev_phase: 0.0,
-            prev_random: 0.0,
-            curr_random: 0.0,
+            history: NoiseHistory::new(sound),
+            reflect: false,
+        }
+    }
+
+    fn next_value(&mut self, sound: &super::sound::Sound) -> f64 {
+        let mut next = self.history.current() + sound.brown_noise_roughness.0 * self.random.uniform(-1.0, 1.0);
+        if self.reflect {
+            if next > 1.0 { next = 2.0 - next; }
+            if next < -1.0 { next = -2.0 - next; }
+        }
+        next.clamp(-1.0, 1.0)
+    }
+
+    fn prime(&mut self, sound: &super::sound::Sound) {
+        for _ in 0..NoiseHistory::LEN {
+            let value = self.next_value(sound);
+            self.history.push(value);
         }
     }
 }
 
 impl Oscillator for BrownNoiseOscillator {
+    fn get_sample(&mut self, sound: &super::sound::Sound, phase: f64, _time: f64) -> f64 {
+        // Need two samples per phase in order to include the desired frequencies.
+        let phase = (phase * 2.0).fract();
+        if phase < self.prev_phase {
+            let value = self.next_value(sound);
+            self.history.push(value);
+        }
+        self.prev_phase = phase;
+        self.history.sample(phase)
+    }
+
+    cloned_state!();
+}
+
+/// Blue noise, whose power increases by 3 dB per octave. It is created by
+/// differentiating pink noise, which adds 6 dB per octave to the 3 dB per
+/// octave that the pink noise falls off by.
+///
+/// ```rust
+/// use jfxr::parameter::{NoiseInterpolation, Waveform};
+///
+/// // Slope of the spectrum of 4096 random values of the given noise, in dB
+/// // per octave, from the power in the octaves above 1/64 and 1/16 of the
+/// // rate of the random values.
+/// let slope = |waveform| {
+///     let sound = jfxr::Sound { noise_interpolation: NoiseInterpolation::None, ..Default::default() };
+///     let mut oscillator = jfxr::oscillator::for_waveform(waveform, &sound);
+///     // A new value is produced whenever the doubled phase wraps around.
+///     let values: Vec<f64> = (0..2 * 4096)
+///         .map(|i| oscillator.get_sample(&sound, if i % 2 == 0 { 0.0 } else { 0.3 }, 0.0))
+///         .skip(1)
+///         .step_by(2)
+///         .collect();
+///     let n = values.len();
+///     let octave_power = |start: usize| (start..2 * start).map(|k| {
+///         let (re, im) = values.iter().enumerate().fold((0.0, 0.0), |(re, im), (i, value)| {
+///             let angle = 2.0 * std::f64::consts::PI * (k * i % n) as f64 / n as f64;
+///             (re + value * angle.cos(), im - value * angle.sin())
+///         });
+///         re * re + im * im
+///     }).sum::<f64>() / start as f64;
+///     10.0 * (octave_power(n / 16) / octave_power(n / 64)).log10() / 2.0
+/// };
+/// let white = slope(Waveform::Whitenoise);
+/// let blue = slope(Waveform::Bluenoise);
+/// assert!(white.abs() < 1.0, "{white}");
+/// assert!((blue - 3.0).abs() < 1.0, "{blue}");
+/// ```
+#[derive(Clone)]
+pub struct BlueNoiseOscillator {
+    random: Random,
+    prev_phase: f64,
+    filter: PinkFilter,
+    prev_pink: f64,
+    history: NoiseHistory,
+}
+
+impl BlueNoiseOscillator {
+    /// Gain applied to the difference of two pink noise values. Those differ
+    /// by at most 0.53, so this is the largest round number that keeps the
+    /// output between -1 and 1.
+    const GAIN: f64 = 1.8;
+
+    pub fn new(sound: &super::sound::Sound) -> Self {
+        Self::with_seed(sound, NOISE_SEED)
+    }
+
+    pub fn with_seed(sound: &super::sound::Sound, seed: u32) -> Self {
+        Self {
+            random: Random::new(seed),
+            prev_phase: 0.0,
+            filter: PinkFilter::new(),
+            prev_pink: 0.0,
+            history: NoiseHistory::new(sound),
+        }
+    }
+
+    fn next_value(&mut self) -> f64 {
+        let pink = self.filter.next(self.random.uniform(-1.0, 1.0));
+        let value = Self::GAIN * (pink - self.prev_pink);
+        self.prev_pink = pink;
+        value
+    }
+
+    fn prime(&mut self, _sound: &super::sound::Sound) {
+        for _ in 0..NoiseHistory::LEN {
+            let value = self.next_value();
+            self.history.push(value);
+        }
+    }
+}
+
+impl Oscillator for BlueNoiseOscillator {
     fn get_sample(&mut self, _sound: &super::sound::Sound, phase: f64, _time: f64) -> f64 {
         // Need two samples per phase in order to include the desired frequencies.
         let phase = (phase * 2.0).fract();
         if phase < self.prev_phase {
-            self.prev_random = self.curr_random;
-            self.curr_random = (self.curr_random + 0.1 * self.random.uniform(-1.0, 1.0)).clamp(-1.0, 1.0);
+            let value = self.next_value();
+            self.history.push(value);
         }
         self.prev_phase = phase;
-        if self.interpolate_noise { return lerp(self.prev_random, self.curr_random, phase); }
-        self.curr_random
+        self.history.sample(phase)
     }
+
+    cloned_state!();
+}
+
+/// Velvet noise: a sparse sequence of impulses of random sign. Each half
+/// cycle contains exactly one impulse, at a random position. The output is
+/// zero between impulses, so noise interpolation does not apply.
+#[derive(Clone)]
+pub struct VelvetNoiseOscillator {
+    random: Random,
+    prev_phase: f64,
+    impulse_phase: f64,
+    impulse_sign: f64,
+    fired: bool,
+}
+
+impl VelvetNoiseOscillator {
+    pub fn new(sound: &super::sound::Sound) -> Self {
+        Self::with_seed(sound, NOISE_SEED)
+    }
+
+    pub fn with_seed(_sound: &super::sound::Sound, seed: u32) -> Self {
+        Self {
+            random: Random::new(seed),
+            prev_phase: 0.0,
+            impulse_phase: 0.0,
+            impulse_sign: 0.0,
+            fired: true,
+        }
+    }
+
+    fn next_impulse(&mut self) {
+        self.impulse_phase = self.random.uniform(0.0, 1.0);
+        self.impulse_sign = if self.random.uniform(-1.0, 1.0) < 0.0 { -1.0 } else { 1.0 };
+        self.fired = false;
+    }
+
+    fn prime(&mut self) {
+        self.next_impulse();
+    }
+}
+
+impl Oscillator for VelvetNoiseOscillator {
+    fn get_sample(&mut self, _sound: &super::sound::Sound, phase: f64, _time: f64) -> f64 {
+        // Two impulses per phase, to match the density of the other noise
+        // waveforms.
+        let phase = (phase * 2.0).fract();
+        if phase < self.prev_phase {
+            self.next_impulse();
+        }
+        self.prev_phase = phase;
+        if !self.fired && phase >= self.impulse_phase {
+            self.fired = true;
+            return self.impulse_sign;
+        }
+        0.0
+    }
+
+    cloned_state!();
 }
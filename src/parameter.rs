@@ -1,33 +1,817 @@
+//! Parameters of a [`crate::Sound`]. Each parameter is a newtype around its
+//! raw value, with metadata such as the range and the unit in the
+//! implementation of one of the parameter traits.
+//!
+//! # Units
+//!
+//! The raw values are in the units used by the `jfxr` tool, which are not
+//! always the obvious ones: flanger offsets are in milliseconds, and volumes
+//! and other ratios are in percent. To avoid mistakes, parameters can also be
+//! constructed from other units:
+//!
+//! ```rust
+//! use jfxr::parameter::*;
+//! assert_eq!(Attack::from_ms(250.0).0, 0.25);
+//! assert_eq!(Decay::from_duration(std::time::Duration::from_millis(500)).0, 0.5);
+//! assert_eq!(FlangerOffset::from_secs(0.005).0, 5.0);
+//! assert_eq!(FlangerDepth::from_duration(std::time::Duration::from_millis(2)).0, 2.0);
+//! assert_eq!(SustainPunch::from_fraction(0.5).0, 50.0);
+//! assert_eq!(SquareDuty::from_percent(25.0).0, 25.0);
+//! assert_eq!(Frequency::from_khz(1.5).0, 1500.0);
+//! assert_eq!(LowPassCutoff::from_hz(800.0).0, 800.0);
+//! ```
+//!
+//! In debug builds, these constructors check that the converted value is
+//! within the range of the parameter. [`crate::Sound`] also has setters for
+//! the durations, such as [`crate::Sound::set_flanger_offset`], which take a
+//! [`std::time::Duration`] and do the same check.
+//!
+//! # Sweeps
+//!
+//! Swept parameters change linearly, or quadratically for the delta sweeps,
+//! from their base value to the base value plus the sweep. As in the `jfxr`
+//! tool, the pitch parameters restart with every repetition of the sound,
+//! while the effects sweep once over the whole sound:
+//!
+//! | Parameter | Time base |
+//! | --- | --- |
+//! | [`FrequencySweep`], [`FrequencyDeltaSweep`] | Each repetition |
+//! | [`FrequencySweepSemitones`], [`FrequencyDeltaSweepSemitones`] | Each repetition |
+//! | [`FrequencyJump1Onset`], [`FrequencyJump2Onset`] | Each repetition |
+//! | [`SquareDutySweep`] | Each repetition |
+//! | [`FlangerOffsetSweep`] | Whole sound |
+//! | [`BitCrushSweep`] | Whole sound |
+//! | [`LowPassCutoffSweep`], [`HighPassCutoffSweep`] | Whole sound |
+//! | [`PanSweep`] | Whole sound |
+//!
+//! A sound that does not repeat has a single repetition, which is the whole
+//! sound; see [`crate::Sound::effective_repeat_frequency`]. Sweeps over the
+//! whole sound span [`crate::Sound::num_body_samples`], and go on at the
+//! same rate through the tail, except for the pan sweep, which stops at the
+//! end of the sound.
+//!
+//! Halfway through the second of two repetitions, the pitch sweeps are
+//! halfway, and the effect sweeps three quarters of the way:
+//!
+//! ```rust
+//! use jfxr::parameter::*;
+//! use jfxr::{AutomationTarget, Sound, Synth};
+//!
+//! // One second in two repetitions, with no stage other than the one under
+//! // test changing the samples.
+//! let base = Sound {
+//!     attack: Attack(0.0),
+//!     sustain: Sustain(0.9),
+//!     sustain_punch: SustainPunch(0.0),
+//!     decay: Decay(0.1),
+//!     repeat_frequency: RepeatFrequency(2.0),
+//!     bit_crush: BitCrush(0),
+//!     compression: Compression(1.0),
+//!     normalization: Normalization(false),
+//!     ..Default::default()
+//! };
+//! let time = 0.75;
+//! let index = (time * base.sample_rate.0) as usize;
+//! let dry = jfxr::generate(&base);
+//!
+//! // Pitch sweeps: halfway through the repetition.
+//! let sound = Sound { frequency_sweep: FrequencySweep(400.0), ..base.clone() };
+//! assert_eq!(sound.frequency_at(time), base.frequency.0 + 200.0);
+//! let sound = Sound { square_duty: SquareDuty(50.0), square_duty_sweep: SquareDutySweep(-40.0), ..base.clone() };
+//! assert_eq!(sound.square_duty_at(time), 0.3);
+//!
+//! // Flanger: 1 ms plus three quarters of 4 ms, or 176.4 samples, rounded.
+//! let sound = Sound { flanger_offset: FlangerOffset(1.0), flanger_offset_sweep: FlangerOffsetSweep(4.0), ..base.clone() };
+//! assert_eq!(jfxr::generate(&sound)[index], dry[index] + dry[index - 176]);
+//!
+//! // Bit crush: 16 bits minus three quarters of 12, so 7 bits.
+//! let sound = Sound { bit_crush: BitCrush(16), bit_crush_sweep: BitCrushSweep(-12), ..base.clone() };
+//! let level = (0.5 + 0.5 * jfxr::generate(&sound)[index]) * 128.0;
+//! assert_eq!(level, level.round());
+//!
+//! // Filters: the same as the cutoff automated over the whole sound, and
+//! // unlike the cutoff automated over each repetition.
+//! let duration = base.duration();
+//! let automated = |sound: &Sound, target, curve: fn(f64, f64) -> f64| {
+//!     let mut synth = Synth::new(sound);
+//!     synth.automate(target, move |time| curve(time, duration));
+//!     synth.generate()[index]
+//! };
+//! let sound = Sound { low_pass_cutoff: LowPassCutoff(5000.0), low_pass_cutoff_sweep: LowPassCutoffSweep(-4000.0), ..base.clone() };
+//! let swept = jfxr::generate(&sound)[index];
+//! assert!((swept - automated(&sound, AutomationTarget::LowPassCutoff, |t, d| 5000.0 - 4000.0 * t / d)).abs() < 1e-9);
+//! assert!((swept - automated(&sound, AutomationTarget::LowPassCutoff, |t, _| 5000.0 - 4000.0 * (t * 2.0).fract())).abs() > 1e-3);
+//! let sound = Sound { high_pass_cutoff: HighPassCutoff(100.0), high_pass_cutoff_sweep: HighPassCutoffSweep(4000.0), ..base };
+//! let swept = jfxr::generate(&sound)[index];
+//! assert!((swept - automated(&sound, AutomationTarget::HighPassCutoff, |t, d| 100.0 + 4000.0 * t / d)).abs() < 1e-9);
+//! assert!((swept - automated(&sound, AutomationTarget::HighPassCutoff, |t, _| 100.0 + 4000.0 * (t * 2.0).fract())).abs() > 1e-3);
+//! ```
+
 pub trait FloatParameter: Copy + Default {
     const LABEL: &'static str;
     const DESCRIPTION: &'static str = "";
     const UNIT: &'static str = "";
+    /// Value of the parameter in a default sound, which is also returned by
+    /// [`Default::default`].
+    const DEFAULT_VALUE: f64 = 0.0;
     const MIN_VALUE: f64 = 0.0;
     const MAX_VALUE: f64;
     const STEP: f64 = 1.0;
     const LOGARITHMIC: bool = false;
+
+    /// Rounds a value to the nearest multiple of [`Self::STEP`], like the
+    /// sliders of the `jfxr` tool. Steps are taken from zero in linear space,
+    /// also for logarithmic parameters. Values within the range of the
+    /// parameter stay within it; values outside it are not clamped.
+    ///
+    /// ```rust
+    /// use jfxr::parameter::{Attack, FloatParameter};
+    ///
+    /// assert_eq!(Attack::snap(0.304), 0.3);
+    /// assert_eq!(Attack::snap(2.0 / 3.0), 0.67);
+    ///
+    /// #[derive(Clone, Copy, Default)]
+    /// struct Spacing;
+    /// impl FloatParameter for Spacing {
+    ///     const LABEL: &'static str = "Spacing";
+    ///     const MAX_VALUE: f64 = 10.0;
+    ///     const STEP: f64 = 0.3;
+    /// }
+    /// assert_eq!(Spacing::snap(1.0), 0.9);
+    /// assert_eq!(Spacing::snap(2.0), 2.1);
+    /// assert_eq!(Spacing::snap(11.0), 11.0);
+    /// ```
+    fn snap(value: f64) -> f64 {
+        snap_float(value, Self::STEP, Self::MIN_VALUE, Self::MAX_VALUE)
+    }
+
+    /// Returns the reason why this parameter has no effect on the given
+    /// sound, if any. User interfaces can use this to disable the control.
+    fn disabled_reason(_sound: &crate::sound::Sound) -> Option<&'static str> {
+        None
+    }
 }
 
 pub trait IntegerParameter: Copy + Default {
     const LABEL: &'static str;
     const DESCRIPTION: &'static str = "";
     const UNIT: &'static str = "";
+    /// Value of the parameter in a default sound, which is also returned by
+    /// [`Default::default`].
+    const DEFAULT_VALUE: i32 = 0;
     const MIN_VALUE: i32 = 0;
     const MAX_VALUE: i32;
     const STEP: i32 = 1;
+
+    /// Rounds a value to the nearest multiple of [`Self::STEP`]. Values
+    /// within the range of the parameter stay within it.
+    fn snap(value: i32) -> i32 {
+        snap_integer(value, Self::STEP, Self::MIN_VALUE, Self::MAX_VALUE)
+    }
+
+    /// Returns the reason why this parameter has no effect on the given
+    /// sound, if any. User interfaces can use this to disable the control.
+    fn disabled_reason(_sound: &crate::sound::Sound) -> Option<&'static str> {
+        None
+    }
 }
 
 pub trait BooleanParameter: Copy + Default {
     const LABEL: &'static str;
     const DESCRIPTION: &'static str = "";
     const UNIT: &'static str = "";
+    /// Value of the parameter in a default sound, which is also returned by
+    /// [`Default::default`].
+    const DEFAULT_VALUE: bool = false;
+
+    /// Returns the reason why this parameter has no effect on the given
+    /// sound, if any. User interfaces can use this to disable the control.
+    fn disabled_reason(_sound: &crate::sound::Sound) -> Option<&'static str> {
+        None
+    }
 }
 
-pub trait EnumParameter: Copy + Default + 'static {
+pub trait EnumParameter: Copy + Default + PartialEq + 'static {
     const LABEL: &'static str;
     const DESCRIPTION: &'static str = "";
     const UNIT: &'static str = "";
+    /// Value of the parameter in a default sound, which is also returned by
+    /// [`Default::default`].
+    const DEFAULT_VALUE: Self;
     const VALUES: &'static [Self];
+    /// Names of the values, in the same order as [`Self::VALUES`]. These are
+    /// the names used in the `jfxr` format.
+    const NAMES: &'static [&'static str];
+
+    /// Returns the position of this value in [`Self::VALUES`].
+    fn index(self) -> usize {
+        Self::VALUES.iter().position(|value| *value == self).unwrap_or(0)
+    }
+
+    /// Returns the name of this value.
+    fn name(self) -> &'static str {
+        Self::NAMES[self.index()]
+    }
+
+    /// Returns the reason why this parameter has no effect on the given
+    /// sound, if any. User interfaces can use this to disable the control.
+    fn disabled_reason(_sound: &crate::sound::Sound) -> Option<&'static str> {
+        None
+    }
+}
+
+fn snap_float(value: f64, step: f64, min_value: f64, max_value: f64) -> f64 {
+    let mut snapped = (value / step).round() * step;
+    // Round to the decimals of the step, to avoid results like
+    // 0.30000000000000004.
+    if let Some(decimals) = (0..=15).find(|&decimals| {
+        let scaled = step * 10f64.powi(decimals);
+        (scaled - scaled.round()).abs() < 1e-9 * scaled
+    }) {
+        let scale = 10f64.powi(decimals);
+        snapped = (snapped * scale).round() / scale;
+    }
+    snapped.clamp(min_value.min(value), max_value.max(value))
+}
+
+fn snap_integer(value: i32, step: i32, min_value: i32, max_value: i32) -> i32 {
+    let snapped = (value as f64 / step as f64).round() as i32 * step;
+    snapped.clamp(min_value.min(value), max_value.max(value))
+}
+
+/// Value of a parameter of any type, as used by [`Param`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    Float(f64),
+    Integer(i32),
+    Boolean(bool),
+    /// Index into the values of an [`EnumParameter`].
+    Enum(usize),
+}
+
+impl Value {
+    /// Returns the value as a number. Booleans are 0 or 1, and enum values
+    /// are their index.
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Self::Float(value) => value,
+            Self::Integer(value) => value as f64,
+            Self::Boolean(value) => if value { 1.0 } else { 0.0 },
+            Self::Enum(index) => index as f64,
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+/// Type and range of a [`Param`], taken from the corresponding parameter
+/// trait.
+#[derive(Clone, Copy, Debug)]
+pub enum ParamKind {
+    Float {
+        min_value: f64,
+        max_value: f64,
+        step: f64,
+        logarithmic: bool,
+    },
+    Integer {
+        min_value: i32,
+        max_value: i32,
+        step: i32,
+    },
+    Boolean,
+    Enum {
+        names: &'static [&'static str],
+    },
+}
+
+/// Unit of a [`Param`], as given by [`Param::unit_kind`], which tells how
+/// its values convert between the unit in which they are stored and shown,
+/// and the unit that the engine computes with: fractions, seconds and Hz.
+/// Only percentages and milliseconds differ between the two.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Unit {
+    /// No unit, as for counts, booleans and enums. Values are unchanged.
+    None,
+    /// Percent, which converts to a fraction: 50% is 0.5. This covers
+    /// amounts, mixes, positions within a cycle or a repetition, and pan
+    /// positions, which run from -1 to 1.
+    Percent,
+    /// Hertz. Values are unchanged.
+    Hertz,
+    /// Seconds. Values are unchanged.
+    Seconds,
+    /// Milliseconds, which convert to seconds.
+    Milliseconds,
+    /// Semitones, 1/12 of an octave. Values are unchanged.
+    Semitones,
+    /// Bits of resolution. Values are unchanged.
+    Bits,
+    /// Decibels. Values are unchanged rather than converted to gains, as
+    /// they are compared with levels in decibels.
+    Decibels,
+    /// Multiples of a frequency. Values are unchanged.
+    Multiple,
+}
+
+impl Unit {
+    /// Returns the unit with the given symbol, as in [`Param::unit`].
+    pub fn from_symbol(symbol: &str) -> Option<Unit> {
+        Some(match symbol {
+            "" => Unit::None,
+            "%" => Unit::Percent,
+            "Hz" => Unit::Hertz,
+            "s" => Unit::Seconds,
+            "ms" => Unit::Milliseconds,
+            "semitones" => Unit::Semitones,
+            "bits" => Unit::Bits,
+            "dB" => Unit::Decibels,
+            "\u{d7}" => Unit::Multiple,
+            _ => return None,
+        })
+    }
+
+    /// Returns the symbol of the unit, as in [`Param::unit`].
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::None => "",
+            Unit::Percent => "%",
+            Unit::Hertz => "Hz",
+            Unit::Seconds => "s",
+            Unit::Milliseconds => "ms",
+            Unit::Semitones => "semitones",
+            Unit::Bits => "bits",
+            Unit::Decibels => "dB",
+            Unit::Multiple => "\u{d7}",
+        }
+    }
+
+    /// Returns the symbol of the unit of values converted by
+    /// [`Self::to_si`], which is empty for fractions.
+    pub fn si_symbol(self) -> &'static str {
+        match self {
+            Unit::Percent => "",
+            Unit::Milliseconds => "s",
+            unit => unit.symbol(),
+        }
+    }
+
+    /// Returns the factor by which values in this unit are divided to
+    /// convert them, if they are converted at all.
+    fn divisor(self) -> Option<f64> {
+        match self {
+            Unit::Percent => Some(100.0),
+            Unit::Milliseconds => Some(1000.0),
+            _ => None,
+        }
+    }
+
+    /// Converts a value in this unit to a fraction, seconds or Hz, with the
+    /// same arithmetic as the engine, which divides percentages by 100 and
+    /// milliseconds by 1000.
+    pub fn to_si(self, value: f64) -> f64 {
+        match self.divisor() {
+            Some(divisor) => value / divisor,
+            None => value,
+        }
+    }
+
+    /// Converts a value from a fraction, seconds or Hz to this unit, the
+    /// inverse of [`Self::to_si`]. Converting the result back always gives
+    /// `value`. Dividing by 100 or 1000 often maps neighbouring numbers to
+    /// the same result, so of the numbers that convert to `value`, the one
+    /// with the fewest digits is returned. A value with at most 15
+    /// significant digits, as any typed value or step of a parameter,
+    /// therefore comes back unchanged from a round trip; other values may
+    /// come back as a neighbour.
+    pub fn from_si(self, value: f64) -> f64 {
+        let Some(divisor) = self.divisor() else {
+            return value;
+        };
+        let product = value * divisor;
+        if !product.is_finite() {
+            return product;
+        }
+        // The product is rounded twice, so it can be a step or two away
+        // from the values that convert to `value`.
+        let candidates = [product, product.next_down(), product.next_up(), product.next_down().next_down(), product.next_up().next_up()];
+        candidates
+            .into_iter()
+            .filter(|candidate| candidate / divisor == value)
+            .min_by_key(|candidate| candidate.to_string().len())
+            .unwrap_or(product)
+    }
+}
+
+/// Section of the user interface in which a [`Param`] is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParamGroup {
+    Sound,
+    Amplitude,
+    Pitch,
+    Harmonics,
+    Tone,
+    Filter,
+    Output,
+}
+
+impl ParamGroup {
+    /// All groups, in the order in which they are shown.
+    pub const ALL: &'static [Self] = &[
+        Self::Sound,
+        Self::Amplitude,
+        Self::Pitch,
+        Self::Harmonics,
+        Self::Tone,
+        Self::Filter,
+        Self::Output,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Sound => "Sound properties",
+            Self::Amplitude => "Amplitude",
+            Self::Pitch => "Pitch",
+            Self::Harmonics => "Harmonics",
+            Self::Tone => "Tone",
+            Self::Filter => "Filters",
+            Self::Output => "Output",
+        }
+    }
+}
+
+/// Identifier of a [`Param`], named after its parameter type. Code that
+/// refers to particular parameters should use identifiers rather than the
+/// keys of the `jfxr` format or the labels, so that the compiler catches
+/// typos. Identifiers convert to and from both.
+///
+/// There is exactly one identifier for each parameter:
+///
+#[cfg_attr(feature = "json", doc = "```rust")]
+#[cfg_attr(not(feature = "json"), doc = "```rust,ignore")]
+/// use std::collections::BTreeSet;
+/// use jfxr::parameter::{ParamId, ParamKind, Value};
+///
+/// let params = jfxr::Sound::params();
+/// assert_eq!(ParamId::ALL.len(), params.len());
+/// for (id, param) in ParamId::ALL.iter().zip(params) {
+///     assert_eq!(param.id, *id);
+///     assert!(std::ptr::eq(id.param(), param));
+///     assert_eq!(ParamId::from_key(param.key), Some(*id));
+///     assert_eq!(ParamId::from_label(param.label), Some(*id), "{}", param.label);
+///     assert_eq!(id.to_string(), param.key);
+/// }
+/// assert_eq!(ParamId::ALL.iter().collect::<BTreeSet<_>>().len(), params.len());
+/// assert_eq!(ParamId::from_key("frequency_sweep"), None);
+///
+/// // The keys are the fields of the `jfxr` format, once every parameter is
+/// // set to a value that is written out.
+/// let mut sound = jfxr::Sound::default();
+/// for param in params {
+///     let value = match param.kind {
+///         ParamKind::Float { min_value, max_value, .. } => Value::Float(if param.default_value().as_f64() == max_value { min_value } else { max_value }),
+///         ParamKind::Integer { min_value, max_value, .. } => Value::Integer(if param.default_value().as_f64() == max_value as f64 { min_value } else { max_value }),
+///         ParamKind::Boolean => Value::Boolean(param.default_value() == Value::Boolean(false)),
+///         ParamKind::Enum { names } => Value::Enum(if param.default_value() == Value::Enum(names.len() - 1) { 0 } else { names.len() - 1 }),
+///     };
+///     param.set(&mut sound, value);
+/// }
+/// let json = jfxr::jfxr::to_json_value(&sound);
+/// // The flag of the web tool is written next to `noiseInterpolation`.
+/// let fields: BTreeSet<&str> = json.entries().map(|(key, _)| key).filter(|key| !key.starts_with('_') && *key != "interpolateNoise").collect();
+/// let keys: BTreeSet<&str> = ParamId::ALL.iter().map(|id| id.key()).collect();
+/// assert_eq!(fields, keys);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum ParamId {
+    SampleRate,
+    Attack,
+    Sustain,
+    SustainPunch,
+    Decay,
+    TremoloDepth,
+    TremoloFrequency,
+    Frequency,
+    FrequencySweep,
+    FrequencyDeltaSweep,
+    FrequencySweepSemitones,
+    FrequencyDeltaSweepSemitones,
+    RepeatFrequency,
+    PhaseReset,
+    RepeatDecay,
+    RepeatJitter,
+    FrequencyJump1Onset,
+    FrequencyJump1Amount,
+    FrequencyJump2Onset,
+    FrequencyJump2Amount,
+    Harmonics,
+    HarmonicsFalloff,
+    HarmonicsProfile,
+    HarmonicsGain,
+    Inharmonicity,
+    Waveform,
+    StartPhase,
+    NoiseInterpolation,
+    BrownNoiseRoughness,
+    VibratoDepth,
+    VibratoFrequency,
+    SquareDuty,
+    SquareDutySweep,
+    TangentClip,
+    WhistleOvertoneRatio,
+    WhistleOvertoneMix,
+    BreakerPhaseOffset,
+    FlangerOffset,
+    FlangerOffsetSweep,
+    FlangerDepth,
+    FlangerRate,
+    FlangerFeedback,
+    ChorusVoices,
+    ChorusDepth,
+    ChorusRate,
+    BitCrush,
+    BitCrushSweep,
+    LowPassCutoff,
+    LowPassCutoffSweep,
+    HighPassCutoff,
+    HighPassCutoffSweep,
+    ReverbAmount,
+    ReverbDecay,
+    ReverbTail,
+    Compression,
+    Normalization,
+    LevelingMode,
+    LevelingTarget,
+    LevelingMaxGain,
+    Amplification,
+    StereoWidth,
+    Pan,
+    PanSweep,
+    AutoPanDepth,
+    AutoPanFrequency,
+}
+
+impl ParamId {
+    /// All identifiers, in the order of [`crate::Sound::params`].
+    pub const ALL: &'static [Self] = &[
+        Self::SampleRate, Self::Attack, Self::Sustain, Self::SustainPunch, Self::Decay, Self::TremoloDepth,
+        Self::TremoloFrequency, Self::Frequency, Self::FrequencySweep, Self::FrequencyDeltaSweep,
+        Self::FrequencySweepSemitones, Self::FrequencyDeltaSweepSemitones, Self::RepeatFrequency, Self::PhaseReset,
+        Self::RepeatDecay, Self::RepeatJitter, Self::FrequencyJump1Onset, Self::FrequencyJump1Amount,
+        Self::FrequencyJump2Onset, Self::FrequencyJump2Amount, Self::Harmonics, Self::HarmonicsFalloff,
+        Self::HarmonicsProfile, Self::HarmonicsGain, Self::Inharmonicity, Self::Waveform, Self::StartPhase,
+        Self::NoiseInterpolation, Self::BrownNoiseRoughness, Self::VibratoDepth, Self::VibratoFrequency,
+        Self::SquareDuty, Self::SquareDutySweep, Self::TangentClip, Self::WhistleOvertoneRatio,
+        Self::WhistleOvertoneMix, Self::BreakerPhaseOffset, Self::FlangerOffset, Self::FlangerOffsetSweep,
+        Self::FlangerDepth, Self::FlangerRate, Self::FlangerFeedback, Self::ChorusVoices, Self::ChorusDepth, Self::ChorusRate, Self::BitCrush,
+        Self::BitCrushSweep, Self::LowPassCutoff, Self::LowPassCutoffSweep, Self::HighPassCutoff,
+        Self::HighPassCutoffSweep, Self::ReverbAmount, Self::ReverbDecay, Self::ReverbTail, Self::Compression,
+        Self::Normalization, Self::LevelingMode, Self::LevelingTarget, Self::LevelingMaxGain, Self::Amplification,
+        Self::StereoWidth, Self::Pan, Self::PanSweep, Self::AutoPanDepth, Self::AutoPanFrequency,
+    ];
+
+    pub fn param(self) -> &'static Param {
+        &crate::sound::Sound::params()[self as usize]
+    }
+
+    /// Returns the key of the parameter in the `jfxr` format, such as
+    /// `"frequencySweep"`.
+    pub fn key(self) -> &'static str {
+        self.param().key
+    }
+
+    pub fn label(self) -> &'static str {
+        self.param().label
+    }
+
+    /// Returns the identifier of the parameter with the given key in the
+    /// `jfxr` format.
+    pub fn from_key(key: &str) -> Option<Self> {
+        crate::sound::Sound::param(key).map(|param| param.id)
+    }
+
+    /// Returns the identifier of the parameter with the given label, as
+    /// shown to users.
+    pub fn from_label(label: &str) -> Option<Self> {
+        crate::sound::Sound::params().iter().find(|param| param.label == label).map(|param| param.id)
+    }
+}
+
+impl std::fmt::Display for ParamId {
+    /// Writes the key of the parameter in the `jfxr` format.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+/// Description of one parameter of a [`crate::Sound`], which can be used to
+/// inspect and modify sounds without naming each field. The list of all
+/// parameters is returned by [`crate::Sound::params`].
+pub struct Param {
+    pub id: ParamId,
+    /// Key of the parameter in the `jfxr` format.
+    pub key: &'static str,
+    pub label: &'static str,
+    pub description: &'static str,
+    pub unit: &'static str,
+    pub group: ParamGroup,
+    pub kind: ParamKind,
+    pub(crate) disabled_reason: fn(&crate::sound::Sound) -> Option<&'static str>,
+    pub(crate) default_value: fn() -> Value,
+    pub(crate) get: fn(&crate::sound::Sound) -> Value,
+    pub(crate) set: fn(&mut crate::sound::Sound, Value),
+}
+
+impl Param {
+    /// Returns the value of this parameter in a default sound, the
+    /// `DEFAULT_VALUE` of its parameter trait.
+    ///
+    /// ```rust
+    /// let sound = jfxr::Sound::default();
+    /// for param in jfxr::Sound::params() {
+    ///     assert_eq!(param.default_value(), param.get(&sound), "{}", param.key);
+    ///     assert!(param.is_default(&sound), "{}", param.key);
+    /// }
+    ///
+    /// use jfxr::parameter::*;
+    /// assert_eq!(Frequency::default().0, Frequency::DEFAULT_VALUE);
+    /// assert_eq!(BitCrush::default().0, BitCrush::DEFAULT_VALUE);
+    /// assert!(Waveform::default() == Waveform::DEFAULT_VALUE);
+    /// const DUTY: SquareDuty = SquareDuty(SquareDuty::DEFAULT_VALUE);
+    /// assert_eq!(DUTY.0, 50.0);
+    /// ```
+    pub fn default_value(&self) -> Value {
+        (self.default_value)()
+    }
+
+    /// Returns whether this parameter has its default value in the given
+    /// sound.
+    pub fn is_default(&self, sound: &crate::sound::Sound) -> bool {
+        self.get(sound) == self.default_value()
+    }
+
+    /// Returns the value of this parameter in the given sound.
+    pub fn get(&self, sound: &crate::sound::Sound) -> Value {
+        (self.get)(sound)
+    }
+
+    /// Sets the value of this parameter in the given sound. The value is
+    /// converted to the type of the parameter if necessary; enum indices out
+    /// of range select the default value. The value is not clamped.
+    pub fn set(&self, sound: &mut crate::sound::Sound, value: Value) {
+        (self.set)(sound, value)
+    }
+
+    /// Rounds a value to the step of this parameter, as described in
+    /// [`FloatParameter::snap`]. Booleans and enums are returned unchanged.
+    pub fn snap(&self, value: Value) -> Value {
+        match self.kind {
+            ParamKind::Float { min_value, max_value, step, .. } => Value::Float(snap_float(value.as_f64(), step, min_value, max_value)),
+            ParamKind::Integer { min_value, max_value, step } => Value::Integer(snap_integer(value.as_f64().round() as i32, step, min_value, max_value)),
+            ParamKind::Boolean | ParamKind::Enum { .. } => value,
+        }
+    }
+
+    /// Limits a value to the range of this parameter. Floats that are not a
+    /// number become the minimum. Booleans and enums are returned unchanged.
+    pub fn clamp(&self, value: Value) -> Value {
+        match self.kind {
+            ParamKind::Float { min_value, max_value, .. } => {
+                let value = value.as_f64();
+                Value::Float(if value.is_nan() { min_value } else { value.clamp(min_value, max_value) })
+            }
+            ParamKind::Integer { min_value, max_value, .. } => Value::Integer((value.as_f64() as i32).clamp(min_value, max_value)),
+            ParamKind::Boolean | ParamKind::Enum { .. } => value,
+        }
+    }
+
+    /// Sets the value of this parameter in the given sound, after rounding it
+    /// to the step of the parameter.
+    pub fn set_snapped(&self, sound: &mut crate::sound::Sound, value: Value) {
+        self.set(sound, self.snap(value))
+    }
+
+    /// Returns the reason why this parameter has no effect on the given
+    /// sound, if any.
+    pub fn disabled_reason(&self, sound: &crate::sound::Sound) -> Option<&'static str> {
+        (self.disabled_reason)(sound)
+    }
+
+    /// Returns the unit of this parameter, which describes how its values
+    /// convert to the units of the engine.
+    ///
+    /// ```rust
+    /// use jfxr::parameter::{ParamId, Unit, Value};
+    ///
+    /// let punch = ParamId::SustainPunch.param();
+    /// assert_eq!(punch.unit_kind(), Unit::Percent);
+    /// assert_eq!(punch.value_si(Value::Float(50.0)), 0.5);
+    /// assert_eq!(punch.value_from_si(0.07), Value::Float(7.0));
+    /// assert_eq!(ParamId::FlangerOffset.param().value_si(Value::Float(5.0)), 0.005);
+    /// assert_eq!(ParamId::Frequency.param().value_si(Value::Float(440.0)), 440.0);
+    ///
+    /// let mut sound = jfxr::Sound::default();
+    /// ParamId::Amplification.param().set_si(&mut sound, 1.5);
+    /// assert_eq!(sound.amplification.0, 150.0);
+    /// assert_eq!(ParamId::Amplification.param().get_si(&sound), 1.5);
+    ///
+    /// // Every unit is known. Values of percent and millisecond parameters
+    /// // with up to 15 significant digits survive a round trip, and others
+    /// // come back as the nearest number that converts the same way.
+    /// let mut random = jfxr::Random::new(7);
+    /// for param in jfxr::Sound::params() {
+    ///     assert_eq!(Unit::from_symbol(param.unit), Some(param.unit_kind()), "{}", param.key);
+    ///     assert_eq!(param.unit_kind().symbol(), param.unit);
+    ///     let jfxr::parameter::ParamKind::Float { min_value, max_value, step, .. } = param.kind else {
+    ///         continue;
+    ///     };
+    ///     if !matches!(param.unit_kind(), Unit::Percent | Unit::Milliseconds) {
+    ///         continue;
+    ///     }
+    ///     let steps = ((max_value - min_value) / step).round() as usize;
+    ///     let stepped = (0..=steps).map(|i| param.snap(Value::Float(min_value + i as f64 * step)).as_f64());
+    ///     let typed = (0..1000).map(|_| format!("{:.14e}", random.uniform(min_value, max_value)).parse().unwrap());
+    ///     for raw in stepped.chain(typed) {
+    ///         let si = param.value_si(Value::Float(raw));
+    ///         assert_eq!(si, raw / if param.unit_kind() == Unit::Percent { 100.0 } else { 1000.0 });
+    ///         assert_eq!(param.value_from_si(si), Value::Float(raw), "{} {raw}", param.key);
+    ///     }
+    ///     for _ in 0..1000 {
+    ///         let raw = random.uniform(min_value, max_value);
+    ///         let si = param.value_si(Value::Float(raw));
+    ///         let back = param.value_from_si(si).as_f64();
+    ///         assert_eq!(param.value_si(Value::Float(back)), si);
+    ///         assert!(back == raw || back == raw.next_up() || back == raw.next_down(), "{} {raw}", param.key);
+    ///     }
+    /// }
+    /// ```
+    pub fn unit_kind(&self) -> Unit {
+        Unit::from_symbol(self.unit).unwrap_or(Unit::None)
+    }
+
+    /// Converts a value of this parameter to a fraction, seconds or Hz, as
+    /// described in [`Unit::to_si`]. Booleans are 0 or 1, and enums are
+    /// their index.
+    pub fn value_si(&self, value: Value) -> f64 {
+        self.unit_kind().to_si(value.as_f64())
+    }
+
+    /// Converts a value from a fraction, seconds or Hz to a value of this
+    /// parameter, as described in [`Unit::from_si`], rounding it for
+    /// integers and enums. The value is not clamped.
+    pub fn value_from_si(&self, value: f64) -> Value {
+        let value = self.unit_kind().from_si(value);
+        match self.kind {
+            ParamKind::Float { .. } => Value::Float(value),
+            ParamKind::Integer { .. } => Value::Integer(value.round() as i32),
+            ParamKind::Boolean => Value::Boolean(value != 0.0),
+            ParamKind::Enum { .. } => Value::Enum(value.round().max(0.0) as usize),
+        }
+    }
+
+    /// Returns the value of this parameter in the given sound, converted as
+    /// by [`Self::value_si`].
+    pub fn get_si(&self, sound: &crate::sound::Sound) -> f64 {
+        self.value_si(self.get(sound))
+    }
+
+    /// Sets the value of this parameter in the given sound from a fraction,
+    /// seconds or Hz, converted as by [`Self::value_from_si`].
+    pub fn set_si(&self, sound: &mut crate::sound::Sound, value: f64) {
+        self.set(sound, self.value_from_si(value))
+    }
+
+    /// Formats a value of this parameter for display, without the unit.
+    /// Floats are shown with as many decimals as the step of the parameter
+    /// has.
+    pub fn format_value(&self, value: Value) -> String {
+        match self.kind {
+            ParamKind::Float { step, .. } => {
+                let decimals = (-step.log10().floor()).max(0.0) as usize;
+                format!("{:.*}", decimals, value.as_f64())
+            }
+            ParamKind::Integer { .. } => format!("{}", value.as_f64().round() as i32),
+            ParamKind::Boolean => if value.as_f64() != 0.0 { "on" } else { "off" }.to_string(),
+            ParamKind::Enum { names } => names.get(value.as_f64() as usize).copied().unwrap_or("").to_string(),
+        }
+    }
 }
 
 // Sound properties
@@ -36,12 +820,13 @@ pub trait EnumParameter: Copy + Default + 'static {
 pub struct SampleRate(pub f64);
 impl Default for SampleRate {
     fn default() -> Self {
-        Self(44100.0)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl FloatParameter for SampleRate {
     const LABEL: &'static str = "Sample rate";
     const UNIT: &'static str = "Hz";
+    const DEFAULT_VALUE: f64 = 44100.0;
     const MIN_VALUE: f64 = 44100.0;
     const MAX_VALUE: f64 = 44100.0;
 }
@@ -108,13 +893,14 @@ impl FloatParameter for TremoloDepth {
 pub struct TremoloFrequency(pub f64);
 impl Default for TremoloFrequency {
     fn default() -> Self {
-        Self(10.0)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl FloatParameter for TremoloFrequency {
     const LABEL: &'static str = "Tremolo frequency";
     const DESCRIPTION: &'static str = "Frequency at which the volume oscillates as a sine wave around its base value.";
     const UNIT: &'static str = "Hz";
+    const DEFAULT_VALUE: f64 = 10.0;
     const MIN_VALUE: f64 = 0.0;
     const MAX_VALUE: f64 = 1000.0;
     const STEP: f64 = 1.0;
@@ -127,13 +913,14 @@ impl FloatParameter for TremoloFrequency {
 pub struct Frequency(pub f64);
 impl Default for Frequency {
     fn default() -> Self {
-        Self(500.0)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl FloatParameter for Frequency {
     const LABEL: &'static str = "Frequency";
     const DESCRIPTION: &'static str = "Initial frequency, or pitch, of the sound. This determines how high the sound starts out; higher values result in higher notes.";
     const UNIT: &'static str = "Hz";
+    const DEFAULT_VALUE: f64 = 500.0;
     const MIN_VALUE: f64 = 10.0;
     const MAX_VALUE: f64 = 10000.0;
     const STEP: f64 = 100.0;
@@ -144,7 +931,7 @@ impl FloatParameter for Frequency {
 pub struct FrequencySweep(pub f64);
 impl FloatParameter for FrequencySweep {
     const LABEL: &'static str = "Frequency sweep";
-    const DESCRIPTION: &'static str = "Amount by which the frequency is changed linearly over the duration of the sound.";
+    const DESCRIPTION: &'static str = "Amount by which the frequency is changed linearly over each repetition of the sound, or over the whole sound if it does not repeat.";
     const UNIT: &'static str = "Hz";
     const MIN_VALUE: f64 = -10000.0;
     const MAX_VALUE: f64 = 10000.0;
@@ -156,7 +943,7 @@ impl FloatParameter for FrequencySweep {
 pub struct FrequencyDeltaSweep(pub f64);
 impl FloatParameter for FrequencyDeltaSweep {
     const LABEL: &'static str = "Freq. delta sweep";
-    const DESCRIPTION: &'static str = "Amount by which the frequency is changed quadratically over the duration of the sound.";
+    const DESCRIPTION: &'static str = "Amount by which the frequency is changed quadratically over each repetition of the sound, or over the whole sound if it does not repeat.";
     const UNIT: &'static str = "Hz";
     const MIN_VALUE: f64 = -10000.0;
     const MAX_VALUE: f64 = 10000.0;
@@ -164,6 +951,46 @@ impl FloatParameter for FrequencyDeltaSweep {
     const LOGARITHMIC: bool = true;
 }
 
+/// Sweep of the pitch in semitones. Like [`FrequencySweep`], the sweep
+/// restarts with every repetition of the sound:
+///
+/// ```rust
+/// let mut sound = jfxr::Sound::default();
+/// sound.frequency_sweep_semitones.0 = 12.0;
+/// sound.sustain.0 = 0.5;
+/// let base = sound.frequency.0;
+/// let end = sound.duration();
+/// assert!((sound.frequency_at(end - 1e-9) / base - 2.0).abs() < 1e-6);
+///
+/// // With 4 repetitions, each one sweeps up by an octave from the base.
+/// sound.repeat_frequency.0 = 4.0 / sound.duration();
+/// let repetition = 1.0 / sound.repeat_frequency.0;
+/// assert!((sound.frequency_at(repetition - 1e-9) / base - 2.0).abs() < 1e-6);
+/// assert_eq!(sound.frequency_at(repetition), base);
+/// assert!((sound.frequency_at(1.5 * repetition) / base - 2f64.sqrt()).abs() < 1e-9);
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct FrequencySweepSemitones(pub f64);
+impl FloatParameter for FrequencySweepSemitones {
+    const LABEL: &'static str = "Pitch sweep";
+    const DESCRIPTION: &'static str = "Amount by which the pitch is changed linearly over each repetition of the sound, or over the whole sound if it does not repeat, in semitones; 12 semitones is one octave. This is applied on top of the frequency sweeps in Hz, by multiplying the frequency.";
+    const UNIT: &'static str = "semitones";
+    const MIN_VALUE: f64 = -48.0;
+    const MAX_VALUE: f64 = 48.0;
+    const STEP: f64 = 1.0;
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct FrequencyDeltaSweepSemitones(pub f64);
+impl FloatParameter for FrequencyDeltaSweepSemitones {
+    const LABEL: &'static str = "Pitch delta sweep";
+    const DESCRIPTION: &'static str = "Amount by which the pitch is changed quadratically over each repetition of the sound, or over the whole sound if it does not repeat, in semitones. This is applied on top of the frequency sweeps in Hz, by multiplying the frequency.";
+    const UNIT: &'static str = "semitones";
+    const MIN_VALUE: f64 = -48.0;
+    const MAX_VALUE: f64 = 48.0;
+    const STEP: f64 = 1.0;
+}
+
 #[derive(Clone, Copy, Default)]
 pub struct RepeatFrequency(pub f64);
 impl FloatParameter for RepeatFrequency {
@@ -176,17 +1003,197 @@ impl FloatParameter for RepeatFrequency {
     const LOGARITHMIC: bool = true;
 }
 
+/// Whether the waveform restarts at every repetition.
+///
+/// ```rust
+/// use jfxr::parameter::*;
+///
+/// // A repetition is 44100 / 13 samples, which is not a whole number.
+/// let sound = jfxr::Sound {
+///     waveform: Waveform::Sawtooth,
+///     repeat_frequency: RepeatFrequency(13.0),
+///     phase_reset: PhaseReset(true),
+///     attack: Attack(0.0),
+///     sustain: Sustain(0.5),
+///     bit_crush: BitCrush(0),
+///     normalization: Normalization(false),
+///     ..Default::default()
+/// };
+/// // Indices of the samples from which the first 100 samples repeat.
+/// let restarts = |samples: &[f64]| -> Vec<usize> {
+///     (1..samples.len() - 100).filter(|i| samples[*i..*i + 100] == samples[..100]).collect()
+/// };
+/// // Each repetition starts with the first sample at or after its start.
+/// let expected: Vec<usize> = (1..7).map(|k| (k as f64 * 44100.0 / 13.0).ceil() as usize).collect();
+/// assert_eq!(restarts(&jfxr::generate(&sound)), expected);
+/// assert!(restarts(&jfxr::generate(&jfxr::Sound { phase_reset: PhaseReset(false), ..sound })).is_empty());
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct PhaseReset(pub bool);
+impl BooleanParameter for PhaseReset {
+    const LABEL: &'static str = "Phase reset";
+    const DESCRIPTION: &'static str = "Whether to restart the waveform from the beginning of its cycle at every repetition, rather than continuing where it left off. This gives a sharper, more chip-like retrigger.";
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.repeat_frequency.0 == 0.0 {
+            return Some("Phase reset only applies when the repeat frequency is set");
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct RepeatDecay(pub f64);
+impl FloatParameter for RepeatDecay {
+    const LABEL: &'static str = "Repeat decay";
+    const DESCRIPTION: &'static str = "Amount by which the volume is reduced at every repetition, relative to the previous one. Use this for effects like a bouncing ball, where each repetition is quieter.";
+    const UNIT: &'static str = "%";
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 100.0;
+    const STEP: f64 = 5.0;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.repeat_frequency.0 == 0.0 {
+            return Some("Repeat decay only applies when the repeat frequency is set");
+        }
+        None
+    }
+}
+
+/// Maximum amount by which the frequency and the square duty cycle of each
+/// repetition are scaled up or down, in percent. The amount of each
+/// repetition is derived from its index and the noise seed, so every render
+/// of the sound is the same, while [`crate::Synth::set_variation`] selects
+/// other amounts.
+///
+#[cfg_attr(feature = "json", doc = "```rust")]
+#[cfg_attr(not(feature = "json"), doc = "```rust,ignore")]
+/// use jfxr::parameter::*;
+///
+/// let mut sound = jfxr::Sound::default();
+/// sound.waveform = Waveform::Sine;
+/// sound.frequency.0 = 440.0;
+/// sound.repeat_frequency.0 = 10.0;
+/// sound.attack.0 = 0.0;
+/// sound.sustain.0 = 0.5;
+/// sound.decay.0 = 0.0;
+/// let pitches = |sound: &jfxr::Sound| {
+///     let samples = jfxr::generate(sound);
+///     (0..5)
+///         .map(|repetition| {
+///             let start = repetition * 4410 + 1000;
+///             let pitch = jfxr::analysis::detect_pitch(&samples[start..start + 2048], 44100.0, 1024)[0].1.unwrap();
+///             assert!((pitch - sound.frequency_at(start as f64 / 44100.0)).abs() < 0.5);
+///             pitch
+///         })
+///         .collect::<Vec<_>>()
+/// };
+/// let steady = pitches(&sound);
+/// assert!(steady.iter().all(|pitch| (pitch - 440.0).abs() < 0.5));
+///
+/// sound.repeat_jitter.0 = 10.0;
+/// let jittered = pitches(&sound);
+/// assert!(jittered.iter().all(|pitch| (pitch - 440.0).abs() <= 44.5));
+/// assert!(jittered.windows(2).all(|pair| (pair[0] - pair[1]).abs() > 1.0));
+/// assert_eq!(jfxr::generate(&sound), jfxr::generate(&sound));
+/// let read = jfxr::read_jfxr(&jfxr::write_jfxr(sound.clone())).unwrap();
+/// assert_eq!(read.repeat_jitter.0, 10.0);
+/// let old = jfxr::write_jfxr(jfxr::Sound::default());
+/// assert!(!old.contains("repeatJitter"));
+/// assert_eq!(jfxr::read_jfxr(&old).unwrap().repeat_jitter.0, 0.0);
+///
+/// sound.repeat_frequency.0 = 0.0;
+/// assert!(jfxr::Sound::param("repeatJitter").unwrap().disabled_reason(&sound).is_some());
+/// assert_eq!(sound.frequency_at(0.2), 440.0);
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct RepeatJitter(pub f64);
+impl FloatParameter for RepeatJitter {
+    const LABEL: &'static str = "Repeat jitter";
+    const DESCRIPTION: &'static str = "Amount by which the pitch and the square duty cycle vary randomly from one repetition to the next. A little jitter makes stutters and rattles sound less mechanical.";
+    const UNIT: &'static str = "%";
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 50.0;
+    const STEP: f64 = 1.0;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.repeat_frequency.0 == 0.0 {
+            return Some("Repeat jitter only applies when the repeat frequency is set");
+        }
+        None
+    }
+}
+
+/// Point in each repetition at which the frequency is multiplied by
+/// [`FrequencyJump1Amount`], in percent of the repetition.
+///
+/// A jump applies strictly after its onset, until the end of the
+/// repetition. At an onset of 0%, it applies to every sample but the first
+/// of each repetition, and at 100%, it never applies. The two jumps are
+/// independent: since they multiply the frequency, they have the same
+/// effect in either order, and jumps with equal onsets stack. Elsewhere in
+/// the repetition, the frequency only changes smoothly, with the sweeps and
+/// the vibrato.
+///
+/// ```rust
+/// use jfxr::parameter::*;
+///
+/// // Onsets of both jumps, in percent, and the jump factor before, between
+/// // and after them, at 10%, 50% and 90% of the repetition.
+/// let cases = [
+///     ((33.0, 66.0), [1.0, 1.5, 0.75]),
+///     ((66.0, 33.0), [1.0, 0.5, 0.75]),
+///     ((50.0, 50.0), [1.0, 1.0, 0.75]),
+///     ((0.0, 0.0), [0.75, 0.75, 0.75]),
+///     ((100.0, 100.0), [1.0, 1.0, 1.0]),
+///     ((0.0, 100.0), [1.5, 1.5, 1.5]),
+///     ((100.0, 0.0), [0.5, 0.5, 0.5]),
+///     ((45.0, 55.0), [1.0, 1.5, 0.75]),
+/// ];
+/// for ((onset1, onset2), factors) in cases {
+///     let sound = jfxr::Sound {
+///         sustain: Sustain(1.0),
+///         frequency: Frequency(1000.0),
+///         frequency_sweep: FrequencySweep(500.0),
+///         repeat_frequency: RepeatFrequency(2.0),
+///         frequency_jump1_onset: FrequencyJump1Onset(onset1),
+///         frequency_jump1_amount: FrequencyJump1Amount(50.0),
+///         frequency_jump2_onset: FrequencyJump2Onset(onset2),
+///         frequency_jump2_amount: FrequencyJump2Amount(-50.0),
+///         ..Default::default()
+///     };
+///     let period = 1.0 / sound.effective_repeat_frequency();
+///     for (fraction, factor) in [0.1, 0.5, 0.9].into_iter().zip(factors) {
+///         // In the second repetition, which behaves like the first.
+///         let time = period * (1.0 + fraction);
+///         let expected = (1000.0 + fraction * 500.0) * factor;
+///         assert!((sound.frequency_at(time) - expected).abs() < 1e-6, "{onset1} {onset2} at {fraction}");
+///     }
+///
+///     // Apart from the onsets, the frequency changes by no more than the
+///     // sweep from one sample to the next.
+///     let dt = 1.0 / sound.sample_rate.0;
+///     let max_step = 500.0 * 1.5 * dt / period + 1e-6;
+///     let onsets = [onset1 / 100.0, onset2 / 100.0];
+///     let mut time = period + dt;
+///     while time + dt < 2.0 * period {
+///         let (from, to) = ((time - period) / period, (time + dt - period) / period);
+///         if !onsets.iter().any(|onset| (from..=to).contains(onset)) {
+///             assert!((sound.frequency_at(time + dt) - sound.frequency_at(time)).abs() <= max_step, "{onset1} {onset2} at {from}");
+///         }
+///         time += dt;
+///     }
+/// }
+/// ```
 #[derive(Clone, Copy)]
 pub struct FrequencyJump1Onset(pub f64);
 impl Default for FrequencyJump1Onset {
     fn default() -> Self {
-        Self(33.0)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl FloatParameter for FrequencyJump1Onset {
     const LABEL: &'static str = "Freq. jump 1 onset";
-    const DESCRIPTION: &'static str = "Point in time, as a fraction of the repeat cycle, at which the frequency makes a sudden jump.";
+    const DESCRIPTION: &'static str = "Point in time, as a fraction of the repeat cycle, at which the frequency makes a sudden jump. At 100%, the frequency never jumps.";
     const UNIT: &'static str = "%";
+    const DEFAULT_VALUE: f64 = 33.0;
     const MIN_VALUE: f64 = 0.0;
     const MAX_VALUE: f64 = 100.0;
     const STEP: f64 = 5.0;
@@ -203,17 +1210,21 @@ impl FloatParameter for FrequencyJump1Amount {
     const STEP: f64 = 5.0;
 }
 
+/// Point in each repetition at which the frequency is multiplied by
+/// [`FrequencyJump2Amount`], in percent of the repetition. This works like
+/// [`FrequencyJump1Onset`], in whichever order the onsets are.
 #[derive(Clone, Copy)]
 pub struct FrequencyJump2Onset(pub f64);
 impl Default for FrequencyJump2Onset {
     fn default() -> Self {
-        Self(66.0)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl FloatParameter for FrequencyJump2Onset {
     const LABEL: &'static str = "Freq. jump 2 onset";
-    const DESCRIPTION: &'static str = "Point in time, as a fraction of the repeat cycle, at which the frequency makes a sudden jump.";
+    const DESCRIPTION: &'static str = "Point in time, as a fraction of the repeat cycle, at which the frequency makes a sudden jump. At 100%, the frequency never jumps.";
     const UNIT: &'static str = "%";
+    const DEFAULT_VALUE: f64 = 66.0;
     const MIN_VALUE: f64 = 0.0;
     const MAX_VALUE: f64 = 100.0;
     const STEP: f64 = 5.0;
@@ -232,13 +1243,38 @@ impl FloatParameter for FrequencyJump2Amount {
 
 // Harmonics parameters
 
+/// Number of harmonics added to the base frequency. Each harmonic of most
+/// waveforms is rendered by an oscillator of its own, so the cost grows with
+/// every harmonic. Sine waves with more than the 5 harmonics of upstream, or
+/// any harmonics with [`crate::Compatibility::Improved`], are instead summed
+/// in a single pass, which makes 32 harmonics about as fast as 5. Both ways
+/// agree up to rounding:
+///
+/// ```rust
+/// use jfxr::{Compatibility, Synth};
+///
+/// let mut sound = jfxr::Sound::example(jfxr::sound::Example::Powerup);
+/// sound.waveform = jfxr::parameter::Waveform::Sine;
+/// sound.harmonics_falloff.0 = 0.8;
+/// // Bit crushing would round the difference away.
+/// sound.bit_crush.0 = 0;
+/// for harmonics in 1..=5 {
+///     sound.harmonics.0 = harmonics;
+///     let exact = jfxr::generate(&sound);
+///     let mut synth = Synth::new(&sound);
+///     synth.set_compatibility(Compatibility::Improved);
+///     let additive = synth.generate();
+///     assert_eq!(exact.len(), additive.len());
+///     assert!(exact.iter().zip(&additive).all(|(a, b)| (a - b).abs() < 1e-9), "{harmonics} harmonics");
+/// }
+/// ```
 #[derive(Clone, Copy, Default)]
 pub struct Harmonics(pub i32);
 impl IntegerParameter for Harmonics {
     const LABEL: &'static str = "Harmonics";
-    const DESCRIPTION: &'static str = "Number of harmonics (overtones) to add. Generates the same sound at several multiples of the base frequency (2×, 3×, …), and mixes them with the original sound. Note that this slows down rendering quite a lot, so you may want to leave it at 0 until the last moment.";
+    const DESCRIPTION: &'static str = "Number of harmonics (overtones) to add. Generates the same sound at several multiples of the base frequency (2×, 3×, …), and mixes them with the original sound. Note that this slows down rendering quite a lot for waveforms other than sine, so you may want to leave it at 0 until the last moment.";
     const MIN_VALUE: i32 = 0;
-    const MAX_VALUE: i32 = 5;
+    const MAX_VALUE: i32 = 32;
     const STEP: i32 = 1;
 }
 
@@ -246,22 +1282,284 @@ impl IntegerParameter for Harmonics {
 pub struct HarmonicsFalloff(pub f64);
 impl Default for HarmonicsFalloff {
     fn default() -> Self {
-        Self(6.5)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl FloatParameter for HarmonicsFalloff {
     const LABEL: &'static str = "Harmonics falloff";
     const DESCRIPTION: &'static str = "Volume of each subsequent harmonic, as a fraction of the previous one.";
+    const DEFAULT_VALUE: f64 = 6.5;
     const MIN_VALUE: f64 = 0.0;
     const MAX_VALUE: f64 = 1.0;
     const STEP: f64 = 0.01;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        match sound.harmonics_profile {
+            HarmonicsProfile::Geometric | HarmonicsProfile::OddOnly => None,
+            _ => Some("Harmonics falloff only applies to geometric harmonics profiles"),
+        }
+    }
 }
 
-// Tone parameters
+/// Relative volumes of the harmonics.
+///
+#[cfg_attr(feature = "json", doc = "```rust")]
+#[cfg_attr(not(feature = "json"), doc = "```rust,ignore")]
+/// use jfxr::parameter::*;
+///
+/// // Amplitude of each of the five partials of a 500 Hz sine wave with four
+/// // harmonics, relative to the base frequency, from a discrete Fourier
+/// // transform.
+/// let levels = |profile, amplitudes: &[f64]| {
+///     let sound = jfxr::Sound {
+///         waveform: Waveform::Sine,
+///         frequency: Frequency(500.0),
+///         harmonics: Harmonics(4),
+///         harmonics_falloff: HarmonicsFalloff(0.5),
+///         harmonics_profile: profile,
+///         harmonics_custom_amplitudes: amplitudes.to_vec(),
+///         attack: Attack(0.0),
+///         sustain: Sustain(0.5),
+///         bit_crush: BitCrush(0),
+///         normalization: Normalization(false),
+///         ..Default::default()
+///     };
+///     let samples = jfxr::generate(&sound);
+///     let amplitude = |frequency: f64| {
+///         let (mut re, mut im) = (0.0, 0.0);
+///         for (n, sample) in samples.iter().enumerate() {
+///             let angle = 2.0 * std::f64::consts::PI * frequency * n as f64 / sound.sample_rate.0;
+///             re += sample * angle.cos();
+///             im -= sample * angle.sin();
+///         }
+///         (re * re + im * im).sqrt()
+///     };
+///     let base = amplitude(500.0);
+///     (1..=5).map(|n| amplitude(500.0 * n as f64) / base).collect::<Vec<f64>>()
+/// };
+/// let custom = [1.0, 0.0, 0.5, 0.0, 0.2];
+/// for (profile, expected) in [
+///     (HarmonicsProfile::Geometric, [1.0, 0.5, 0.25, 0.125, 0.0625]),
+///     (HarmonicsProfile::OddOnly, [1.0, 0.0, 0.25, 0.0, 0.0625]),
+///     (HarmonicsProfile::OneOverN, [1.0, 1.0 / 2.0, 1.0 / 3.0, 1.0 / 4.0, 1.0 / 5.0]),
+///     (HarmonicsProfile::OneOverNSquared, [1.0, 1.0 / 4.0, 1.0 / 9.0, 1.0 / 16.0, 1.0 / 25.0]),
+///     (HarmonicsProfile::Custom, custom),
+/// ] {
+///     let levels = levels(profile, &custom);
+///     for (level, expected) in levels.iter().zip(expected) {
+///         assert!((level - expected).abs() < 0.01, "{}: {levels:?}", profile.name());
+///     }
+/// }
+///
+/// // Files of the web tool have no profile, and use its geometric falloff.
+/// let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/web-tool/square-harmonics.jfxr");
+/// let sound = jfxr::read_jfxr(&std::fs::read_to_string(path).unwrap()).unwrap();
+/// assert!(sound.harmonics_profile == HarmonicsProfile::Geometric);
+/// let sound = jfxr::Sound { harmonics_profile: HarmonicsProfile::OddOnly, ..sound };
+/// let read = jfxr::read_jfxr(&jfxr::write_jfxr(sound)).unwrap();
+/// assert!(read.harmonics_profile == HarmonicsProfile::OddOnly);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HarmonicsProfile {
+    Geometric,
+    OddOnly,
+    OneOverN,
+    OneOverNSquared,
+    Custom,
+}
+impl Default for HarmonicsProfile {
+    fn default() -> Self {
+        Self::DEFAULT_VALUE
+    }
+}
+impl EnumParameter for HarmonicsProfile {
+    const LABEL: &'static str = "Harmonics profile";
+    const DESCRIPTION: &'static str = "Relative volumes of the harmonics. Geometric uses the harmonics falloff; odd only does the same but silences the even harmonics, for a hollow, clarinet-like sound; 1/n and 1/n² give sawtooth-like and triangle-like timbres; custom uses a list of volumes given per harmonic.";
+    const DEFAULT_VALUE: Self = Self::Geometric;
+    const VALUES: &'static [Self] = &[
+        Self::Geometric,
+        Self::OddOnly,
+        Self::OneOverN,
+        Self::OneOverNSquared,
+        Self::Custom,
+    ];
+    const NAMES: &'static [&'static str] = &[
+        "geometric",
+        "oddonly",
+        "oneovern",
+        "oneovernsquared",
+        "custom",
+    ];
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.harmonics.0 == 0 {
+            return Some("Harmonics profile only applies when harmonics are enabled");
+        }
+        None
+    }
+}
 
+/// How the volumes of the harmonics are scaled before they are mixed.
+///
+/// ```rust
+/// use jfxr::parameter::HarmonicsGain;
+///
+/// let rms_db = |harmonics, gain| {
+///     let mut sound = jfxr::Sound::default();
+///     sound.sustain.0 = 0.5;
+///     sound.normalization.0 = false;
+///     sound.harmonics.0 = harmonics;
+///     sound.harmonics_falloff.0 = 1.0;
+///     sound.harmonics_gain = gain;
+///     jfxr::analysis::rms_db(&jfxr::generate(&sound))
+/// };
+/// // Six equally loud harmonics, scaled so that their volumes add up to one,
+/// // are much quieter than the base frequency alone.
+/// assert!(rms_db(0, HarmonicsGain::Sum) - rms_db(5, HarmonicsGain::Sum) > 7.0);
+/// // Scaled by their combined power instead, they are as loud.
+/// assert!((rms_db(0, HarmonicsGain::Rms) - rms_db(5, HarmonicsGain::Rms)).abs() < 0.5);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HarmonicsGain {
+    /// Scales the volumes so that they add up to one, like upstream. The
+    /// peak volume stays the same, but the sound gets quieter as harmonics
+    /// are added.
+    Sum,
+    /// Scales the volumes so that their squares add up to one, which keeps
+    /// the loudness the same as without harmonics, since the harmonics of
+    /// tonal waveforms do not reinforce each other on average. Peaks can be
+    /// higher than with [`HarmonicsGain::Sum`].
+    Rms,
+}
+impl Default for HarmonicsGain {
+    fn default() -> Self {
+        Self::DEFAULT_VALUE
+    }
+}
+impl EnumParameter for HarmonicsGain {
+    const LABEL: &'static str = "Harmonics gain";
+    const DESCRIPTION: &'static str = "How the volumes of the harmonics are scaled. Sum keeps the peak volume the same, so adding harmonics makes the sound quieter; RMS keeps the loudness the same.";
+    const DEFAULT_VALUE: Self = Self::Sum;
+    const VALUES: &'static [Self] = &[
+        Self::Sum,
+        Self::Rms,
+    ];
+    const NAMES: &'static [&'static str] = &[
+        "sum",
+        "rms",
+    ];
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.harmonics.0 == 0 {
+            return Some("Harmonics gain only applies when harmonics are enabled");
+        }
+        None
+    }
+}
+
+/// Stretch of the harmonics away from exact multiples of the base frequency.
+///
+/// ```rust
+/// use jfxr::parameter::*;
+///
+/// let sound = jfxr::Sound {
+///     waveform: Waveform::Sine,
+///     frequency: Frequency(500.0),
+///     harmonics: Harmonics(3),
+///     attack: Attack(0.0),
+///     sustain: Sustain(0.5),
+///     bit_crush: BitCrush(0),
+///     normalization: Normalization(false),
+///     ..Default::default()
+/// };
+///
+/// // Without inharmonicity, every harmonic follows the phase of the base
+/// // frequency, multiplied by the number of the harmonic, as upstream does.
+/// let samples = jfxr::generate(&sound);
+/// let mut oscillators: Vec<_> = (0..=3).map(|_| jfxr::oscillator::for_waveform(Waveform::Sine, &sound)).collect();
+/// let total_amp: f64 = (0..=3).map(|k| sound.harmonics_falloff.0.powi(k)).sum();
+/// let mut phase = 0.0f64;
+/// let expected: Vec<f64> = (0..samples.len())
+///     .map(|_| {
+///         phase = (phase + sound.frequency.0 / sound.sample_rate.0).fract();
+///         let mut amp = 1.0 / total_amp;
+///         let mut sample = 0.0;
+///         for (k, oscillator) in oscillators.iter_mut().enumerate() {
+///             sample += amp * oscillator.get_sample(&sound, (phase * (k + 1) as f64).fract(), 0.0);
+///             amp *= sound.harmonics_falloff.0;
+///         }
+///         sample
+///     })
+///     .collect();
+/// assert_eq!(samples, expected);
+///
+/// // Power of the samples at the given frequency, from a discrete Fourier
+/// // transform.
+/// let power = |samples: &[f64], frequency: f64| {
+///     let (mut re, mut im) = (0.0, 0.0);
+///     for (n, sample) in samples.iter().enumerate() {
+///         let angle = 2.0 * std::f64::consts::PI * frequency * n as f64 / sound.sample_rate.0;
+///         re += sample * angle.cos();
+///         im -= sample * angle.sin();
+///     }
+///     re * re + im * im
+/// };
+/// // At 10%, the first overtone moves from 1000 Hz to 2 × 1.1 × 500 Hz.
+/// let stretched = jfxr::generate(&jfxr::Sound { inharmonicity: Inharmonicity(10.0), ..sound.clone() });
+/// assert!(power(&samples, 1000.0) > 100.0 * power(&samples, 1100.0));
+/// assert!(power(&stretched, 1100.0) > 100.0 * power(&stretched, 1000.0));
+///
+/// // Without harmonics, there is nothing to stretch.
+/// assert!(Inharmonicity::disabled_reason(&sound).is_none());
+/// assert!(Inharmonicity::disabled_reason(&jfxr::Sound::default()).is_some());
+/// ```
 #[derive(Clone, Copy, Default)]
+pub struct Inharmonicity(pub f64);
+impl FloatParameter for Inharmonicity {
+    const LABEL: &'static str = "Inharmonicity";
+    const DESCRIPTION: &'static str = "Amount by which harmonics are stretched away from exact multiples of the base frequency, like the overtones of a piano string or a bell. The frequency of the n-th overtone is increased by n² times this percentage.";
+    const UNIT: &'static str = "%";
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 10.0;
+    const STEP: f64 = 0.1;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.harmonics.0 == 0 {
+            return Some("Inharmonicity only applies when harmonics are enabled");
+        }
+        None
+    }
+}
+
+// Tone parameters
+
+fn is_not_square(sound: &crate::sound::Sound) -> Option<&'static str> {
+    match sound.waveform {
+        Waveform::Square => None,
+        _ => Some("Square duty only applies to square waveforms"),
+    }
+}
+
+fn is_not_tangent(sound: &crate::sound::Sound) -> Option<&'static str> {
+    match sound.waveform {
+        Waveform::Tangent => None,
+        _ => Some("Tangent clip only applies to tangent waveforms"),
+    }
+}
+
+fn is_not_whistle(sound: &crate::sound::Sound) -> Option<&'static str> {
+    match sound.waveform {
+        Waveform::Whistle => None,
+        _ => Some("Whistle overtone only applies to whistle waveforms"),
+    }
+}
+
+fn is_not_breaker(sound: &crate::sound::Sound) -> Option<&'static str> {
+    match sound.waveform {
+        Waveform::Breaker => None,
+        _ => Some("Breaker phase offset only applies to breaker waveforms"),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Waveform {
-    #[default] Sine,
+    Sine,
     Triangle,
     Sawtooth,
     Square,
@@ -271,10 +1569,87 @@ pub enum Waveform {
     Whitenoise,
     Pinknoise,
     Brownnoise,
+    Bluenoise,
+    Velvetnoise,
+}
+impl Default for Waveform {
+    fn default() -> Self {
+        Self::DEFAULT_VALUE
+    }
+}
+impl Waveform {
+    /// Returns whether this is one of the noise waveforms.
+    pub fn is_noise(self) -> bool {
+        matches!(self, Self::Whitenoise | Self::Pinknoise | Self::Brownnoise | Self::Bluenoise | Self::Velvetnoise)
+    }
+    /// Returns the fraction of a cycle by which the oscillator shifts the
+    /// shape of the waveform, so that at a phase of 0 it starts at a zero
+    /// crossing. Only the breaker waveform, whose shape does not otherwise
+    /// start at zero, is shifted, by the default [`BreakerPhaseOffset`]; see
+    /// [`crate::oscillator::BreakerOscillator::PHASE_OFFSET`].
+    pub fn phase_offset(self) -> f64 {
+        match self {
+            Self::Breaker => crate::oscillator::BreakerOscillator::PHASE_OFFSET,
+            _ => 0.0,
+        }
+    }
+    /// Returns whether a sound with this waveform and a [`StartPhase`] of 0
+    /// starts at zero when rendered with the given compatibility mode, so
+    /// that it does not click even without attack. Square never does, since
+    /// it starts at its high level like upstream. Noise starts at zero with
+    /// [`crate::Compatibility::Exact`], because it interpolates from zero
+    /// towards its first random value, but not with
+    /// [`crate::Compatibility::Improved`], where it starts at a random value.
+    ///
+    /// ```rust
+    /// use jfxr::parameter::{Attack, EnumParameter, Normalization, Sustain, Waveform};
+    /// use jfxr::synth::Compatibility;
+    ///
+    /// for waveform in Waveform::VALUES {
+    ///     // `render_cycle` uses the default, exact mode.
+    ///     let first = jfxr::oscillator::render_cycle(*waveform, &jfxr::Sound::default(), 4)[0];
+    ///     assert_eq!(first.abs() < 1e-12, waveform.starts_at_zero(Compatibility::Exact), "{}", waveform.name());
+    ///
+    ///     let sound = jfxr::Sound { waveform: *waveform, attack: Attack(0.0), sustain: Sustain(0.1), normalization: Normalization(false), ..Default::default() };
+    ///     let mut synth = jfxr::Synth::new(&sound);
+    ///     synth.set_compatibility(Compatibility::Improved);
+    ///     let first = synth.generate()[0];
+    ///     assert_eq!(waveform.starts_at_zero(Compatibility::Improved), waveform.starts_at_zero(Compatibility::Exact) && !waveform.is_noise());
+    ///     if waveform.is_noise() {
+    ///         assert_ne!(first, 0.0, "{}", waveform.name());
+    ///     }
+    /// }
+    /// ```
+    pub fn starts_at_zero(self, compatibility: crate::synth::Compatibility) -> bool {
+        match self {
+            Self::Square => false,
+            _ if self.is_noise() => compatibility == crate::synth::Compatibility::Exact,
+            _ => true,
+        }
+    }
+    /// Rough cost of generating one sample with this waveform, relative to
+    /// a sine wave. Used by [`crate::Sound::estimated_cost`].
+    pub fn cost_weight(self) -> f64 {
+        match self {
+            Self::Sine => 1.0,
+            Self::Triangle => 0.5,
+            Self::Sawtooth => 0.5,
+            Self::Square => 0.75,
+            Self::Tangent => 1.0,
+            Self::Whistle => 2.0,
+            Self::Breaker => 0.75,
+            Self::Whitenoise => 0.75,
+            Self::Pinknoise => 1.0,
+            Self::Brownnoise => 0.75,
+            Self::Bluenoise => 1.0,
+            Self::Velvetnoise => 0.75,
+        }
+    }
 }
 impl EnumParameter for Waveform {
     const LABEL: &'static str = "Waveform";
     const DESCRIPTION: &'static str = "Shape of the waveform. This is the most important factor in determining the character, or timbre, of the sound.";
+    const DEFAULT_VALUE: Self = Self::Sine;
     const VALUES: &'static [Self] = &[
         Self::Sine,
         Self::Triangle,
@@ -286,27 +1661,191 @@ impl EnumParameter for Waveform {
         Self::Whitenoise,
         Self::Pinknoise,
         Self::Brownnoise,
+        Self::Bluenoise,
+        Self::Velvetnoise,
+    ];
+    const NAMES: &'static [&'static str] = &[
+        "sine",
+        "triangle",
+        "sawtooth",
+        "square",
+        "tangent",
+        "whistle",
+        "breaker",
+        "whitenoise",
+        "pinknoise",
+        "brownnoise",
+        "bluenoise",
+        "velvetnoise",
+    ];
+}
+
+/// Point in the cycle at which the waveform starts.
+///
+#[cfg_attr(feature = "json", doc = "```rust")]
+#[cfg_attr(not(feature = "json"), doc = "```rust,ignore")]
+/// use jfxr::parameter::*;
+///
+/// let sound = jfxr::Sound {
+///     waveform: Waveform::Sine,
+///     attack: Attack(0.0),
+///     sustain: Sustain(0.1),
+///     bit_crush: BitCrush(0),
+///     normalization: Normalization(false),
+///     ..Default::default()
+/// };
+/// // At 0%, the default, a sine wave starts from zero, one step into its
+/// // cycle.
+/// let step = sound.frequency.0 / sound.sample_rate.0;
+/// let samples = jfxr::generate(&sound);
+/// assert!((samples[0] - (2.0 * std::f64::consts::PI * step).sin()).abs() < 1e-12);
+/// // At 25%, it starts at its peak, one step past it.
+/// let samples = jfxr::generate(&jfxr::Sound { start_phase: StartPhase(25.0), ..sound.clone() });
+/// assert!((samples[0] - (2.0 * std::f64::consts::PI * step).cos()).abs() < 1e-12);
+/// assert!(samples[0] > 0.99);
+///
+/// // The start phase is kept in files.
+/// let sound = jfxr::Sound { start_phase: StartPhase(25.0), ..sound };
+/// assert_eq!(jfxr::read_jfxr(&jfxr::write_jfxr(sound)).unwrap().start_phase.0, 25.0);
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct StartPhase(pub f64);
+impl FloatParameter for StartPhase {
+    const LABEL: &'static str = "Start phase";
+    const DESCRIPTION: &'static str = "Point in the cycle of the waveform at which the sound starts. Use this to control the click at the start of sounds without attack; for example, a sine wave starting at 25% starts at its peak. At 0%, every waveform starts at zero, except square, which starts at its high level.";
+    const UNIT: &'static str = "%";
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 100.0;
+    const STEP: f64 = 5.0;
+}
+
+/// How noise waveforms interpolate between their random values. Cubic
+/// interpolation uses a Catmull-Rom spline through the last four values,
+/// which avoids the corners of linear interpolation. These are audible at
+/// low frequencies as high-frequency artifacts:
+///
+/// ```rust
+/// use jfxr::parameter::*;
+///
+/// // Fraction of the power above 2 kHz in the spectrum of 4096 samples of
+/// // 100 Hz brown noise, from a discrete Fourier transform.
+/// let high_fraction = |interpolation| {
+///     let sound = jfxr::Sound {
+///         waveform: Waveform::Brownnoise,
+///         frequency: Frequency(100.0),
+///         noise_interpolation: interpolation,
+///         attack: Attack(0.0),
+///         sustain: Sustain(0.2),
+///         bit_crush: BitCrush(0),
+///         compression: Compression(1.0),
+///         normalization: Normalization(false),
+///         ..Default::default()
+///     };
+///     let samples = &jfxr::generate(&sound)[..4096];
+///     let n = samples.len();
+///     let power = |k: usize| {
+///         let (re, im) = samples.iter().enumerate().fold((0.0, 0.0), |(re, im), (i, sample)| {
+///             let angle = 2.0 * std::f64::consts::PI * (k * i % n) as f64 / n as f64;
+///             (re + sample * angle.cos(), im - sample * angle.sin())
+///         });
+///         re * re + im * im
+///     };
+///     let powers: Vec<f64> = (1..n / 2).map(power).collect();
+///     let cutoff = 2000 * n / sound.sample_rate.0 as usize;
+///     powers[cutoff..].iter().sum::<f64>() / powers.iter().sum::<f64>()
+/// };
+/// let linear = high_fraction(NoiseInterpolation::Linear);
+/// let cubic = high_fraction(NoiseInterpolation::Cubic);
+/// assert!(cubic < 0.8 * linear, "{cubic} {linear}");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NoiseInterpolation {
+    None,
+    Linear,
+    Cubic,
+}
+impl Default for NoiseInterpolation {
+    fn default() -> Self {
+        Self::DEFAULT_VALUE
+    }
+}
+impl EnumParameter for NoiseInterpolation {
+    const LABEL: &'static str = "Noise interpolation";
+    const DESCRIPTION: &'static str = "How to interpolate between individual samples of noise. Linear interpolation results in a smoother sound; cubic interpolation is smoother still, and avoids audible corners at low frequencies.";
+    const DEFAULT_VALUE: Self = Self::Linear;
+    const VALUES: &'static [Self] = &[
+        Self::None,
+        Self::Linear,
+        Self::Cubic,
     ];
+    const NAMES: &'static [&'static str] = &[
+        "none",
+        "linear",
+        "cubic",
+    ];
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        match sound.waveform {
+            Waveform::Whitenoise | Waveform::Pinknoise | Waveform::Brownnoise | Waveform::Bluenoise => None,
+            _ => Some("Noise interpolation only applies to noise waveforms"),
+        }
+    }
 }
 
+/// Legacy boolean form of [`NoiseInterpolation`], kept for code written
+/// before the cubic mode was added. `true` stands for
+/// [`NoiseInterpolation::Linear`] and `false` for
+/// [`NoiseInterpolation::None`].
+#[deprecated(note = "use `NoiseInterpolation` instead")]
 #[derive(Clone, Copy)]
 pub struct InterpolateNoise(pub bool);
+#[allow(deprecated)]
 impl Default for InterpolateNoise {
     fn default() -> Self {
-        Self(true)
+        Self(Self::DEFAULT_VALUE)
     }
 }
+#[allow(deprecated)]
 impl BooleanParameter for InterpolateNoise {
     const LABEL: &'static str = "Interpolate noise";
     const DESCRIPTION: &'static str = "Whether to use linear interpolation between individual samples of noise. This results in a smoother sound.";
-    /*
-    disabledReason: function(sound) {
-      var waveform = sound.waveform.value;
-      if (waveform != 'whitenoise' && waveform != 'pinknoise' && waveform != 'brownnoise') {
-        return 'Noise interpolation only applies to noise waveforms';
-      }
-    },
-    */
+    const DEFAULT_VALUE: bool = true;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        NoiseInterpolation::disabled_reason(sound)
+    }
+}
+#[allow(deprecated)]
+impl From<InterpolateNoise> for NoiseInterpolation {
+    fn from(value: InterpolateNoise) -> Self {
+        if value.0 { Self::Linear } else { Self::None }
+    }
+}
+#[allow(deprecated)]
+impl From<NoiseInterpolation> for InterpolateNoise {
+    fn from(value: NoiseInterpolation) -> Self {
+        Self(value != NoiseInterpolation::None)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct BrownNoiseRoughness(pub f64);
+impl Default for BrownNoiseRoughness {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl FloatParameter for BrownNoiseRoughness {
+    const LABEL: &'static str = "Brown noise roughness";
+    const DESCRIPTION: &'static str = "For brown noise only, the maximum size of each step of the random walk. Higher values result in a rougher, less rumbly sound.";
+    const DEFAULT_VALUE: f64 = 0.1;
+    const MIN_VALUE: f64 = 0.01;
+    const MAX_VALUE: f64 = 0.5;
+    const STEP: f64 = 0.01;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        match sound.waveform {
+            Waveform::Brownnoise => None,
+            _ => Some("Roughness only applies to brown noise waveforms"),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Default)]
@@ -325,13 +1864,14 @@ impl FloatParameter for VibratoDepth {
 pub struct VibratoFrequency(pub f64);
 impl Default for VibratoFrequency {
     fn default() -> Self {
-        Self(10.0)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl FloatParameter for VibratoFrequency {
     const LABEL: &'static str = "Vibrato frequency";
     const DESCRIPTION: &'static str = "Number of times per second to vibrate around the base frequency.";
     const UNIT: &'static str = "Hz";
+    const DEFAULT_VALUE: f64 = 10.0;
     const MIN_VALUE: f64 = 0.0;
     const MAX_VALUE: f64 = 1000.0;
     const STEP: f64 = 1.0;
@@ -342,29 +1882,152 @@ impl FloatParameter for VibratoFrequency {
 pub struct SquareDuty(pub f64);
 impl Default for SquareDuty {
     fn default() -> Self {
-        Self(50.0)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl FloatParameter for SquareDuty {
     const LABEL: &'static str = "Square duty";
-    const DESCRIPTION: &'static str = "For square waves only, the initial fraction of time the square is in the \"on\" state.";
+    const DESCRIPTION: &'static str = "For square waves only, the initial fraction of time the square is in the \"on\" state. At 0% or 100%, the square is constant and silent, unless the improved compatibility mode keeps it between 1% and 99%.";
     const UNIT: &'static str = "%";
+    const DEFAULT_VALUE: f64 = 50.0;
     const MIN_VALUE: f64 = 0.0;
     const MAX_VALUE: f64 = 100.0;
     const STEP: f64 = 5.0;
-    // disabledReason: isNotSquare,
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        is_not_square(sound)
+    }
 }
 
 #[derive(Clone, Copy, Default)]
 pub struct SquareDutySweep(pub f64);
 impl FloatParameter for SquareDutySweep {
     const LABEL: &'static str = "Square duty sweep";
-    const DESCRIPTION: &'static str = "For square waves only, change the square duty linearly by this many percentage points over the course of the sound.";
+    const DESCRIPTION: &'static str = "For square waves only, change the square duty linearly by this many percentage points over each repetition of the sound, or over the whole sound if it does not repeat.";
     const UNIT: &'static str = "%";
     const MIN_VALUE: f64 = -100.0;
     const MAX_VALUE: f64 = 100.0;
     const STEP: f64 = 5.0;
-    // disabledReason: isNotSquare,
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        is_not_square(sound)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct TangentClip(pub f64);
+impl Default for TangentClip {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl FloatParameter for TangentClip {
+    const LABEL: &'static str = "Tangent clip";
+    const DESCRIPTION: &'static str = "For tangent waves only, the level at which the wave is clipped. Lower values give a harsher, more square-like tone; higher values give a sharper, more saw-like tone.";
+    const DEFAULT_VALUE: f64 = 2.0;
+    const MIN_VALUE: f64 = 0.1;
+    const MAX_VALUE: f64 = 5.0;
+    const STEP: f64 = 0.1;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        is_not_tangent(sound)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct WhistleOvertoneRatio(pub i32);
+impl Default for WhistleOvertoneRatio {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl IntegerParameter for WhistleOvertoneRatio {
+    const LABEL: &'static str = "Whistle overtone ratio";
+    const DESCRIPTION: &'static str = "For whistle waves only, the multiple of the base frequency at which the overtone is added.";
+    const UNIT: &'static str = "×";
+    const DEFAULT_VALUE: i32 = 20;
+    const MIN_VALUE: i32 = 2;
+    const MAX_VALUE: i32 = 40;
+    const STEP: i32 = 1;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        is_not_whistle(sound)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct WhistleOvertoneMix(pub f64);
+impl Default for WhistleOvertoneMix {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl FloatParameter for WhistleOvertoneMix {
+    const LABEL: &'static str = "Whistle overtone mix";
+    const DESCRIPTION: &'static str = "For whistle waves only, the volume of the overtone relative to the whole sound.";
+    const UNIT: &'static str = "%";
+    const DEFAULT_VALUE: f64 = 25.0;
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 100.0;
+    const STEP: f64 = 5.0;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        is_not_whistle(sound)
+    }
+}
+
+/// Fraction of a cycle by which the breaker waveform shifts its shape, in
+/// percent. This works like [`StartPhase`], but moves each harmonic by the
+/// same fraction of its own cycle, which changes how the harmonics line up.
+/// The default is the offset of upstream, at which the shape starts at a
+/// zero crossing.
+///
+#[cfg_attr(feature = "json", doc = "```rust")]
+#[cfg_attr(not(feature = "json"), doc = "```rust,ignore")]
+/// use jfxr::parameter::*;
+///
+/// let sound = jfxr::Sound {
+///     waveform: Waveform::Breaker,
+///     attack: Attack(0.0),
+///     sustain: Sustain(0.2),
+///     bit_crush: BitCrush(0),
+///     normalization: Normalization(false),
+///     ..Default::default()
+/// };
+/// assert_eq!(sound.breaker_phase_offset.0 / 100.0, jfxr::oscillator::BreakerOscillator::PHASE_OFFSET);
+///
+/// // Without harmonics, moving the offset back by a quarter of a cycle is
+/// // the same as starting a quarter of a cycle later.
+/// let close = |a: &[f64], b: &[f64]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9);
+/// let shifted = jfxr::Sound {
+///     breaker_phase_offset: BreakerPhaseOffset(sound.breaker_phase_offset.0 - 25.0),
+///     start_phase: StartPhase(25.0),
+///     ..sound.clone()
+/// };
+/// assert!(close(&jfxr::generate(&shifted), &jfxr::generate(&sound)));
+/// // With harmonics, it changes the timbre instead.
+/// let with_harmonics = |sound: &jfxr::Sound| jfxr::generate(&jfxr::Sound { harmonics: Harmonics(2), ..sound.clone() });
+/// assert!(!close(&with_harmonics(&shifted), &with_harmonics(&sound)));
+///
+/// // The offset is kept in files, and only applies to the breaker waveform.
+/// let read = jfxr::read_jfxr(&jfxr::write_jfxr(shifted.clone())).unwrap();
+/// assert_eq!(read.breaker_phase_offset.0, shifted.breaker_phase_offset.0);
+/// assert!(BreakerPhaseOffset::disabled_reason(&sound).is_none());
+/// assert!(BreakerPhaseOffset::disabled_reason(&jfxr::Sound::default()).is_some());
+/// ```
+#[derive(Clone, Copy)]
+pub struct BreakerPhaseOffset(pub f64);
+impl Default for BreakerPhaseOffset {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl FloatParameter for BreakerPhaseOffset {
+    const LABEL: &'static str = "Breaker phase offset";
+    const DESCRIPTION: &'static str = "For breaker waves only, the point in the cycle of each harmonic at which the shape starts. Changing it shifts the harmonics against each other, for a different timbre.";
+    const UNIT: &'static str = "%";
+    const DEFAULT_VALUE: f64 = crate::oscillator::BreakerOscillator::PHASE_OFFSET * 100.0;
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 100.0;
+    const STEP: f64 = 1.0;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        is_not_breaker(sound)
+    }
 }
 
 // Filter parameters
@@ -380,28 +2043,289 @@ impl FloatParameter for FlangerOffset {
     const STEP: f64 = 1.0;
 }
 
+/// Change of the flanger offset over the sound. In the exact mode, the
+/// delay is rounded to whole samples, so a slow sweep steps from one delay
+/// to the next; with [`crate::synth::Compatibility::Improved`], the delay
+/// line is interpolated linearly between samples instead.
+///
+/// ```rust
+/// use jfxr::parameter::*;
+/// use jfxr::synth::Compatibility;
+///
+/// let render = |sound: &jfxr::Sound, compatibility| {
+///     let mut synth = jfxr::Synth::new(sound);
+///     synth.set_compatibility(compatibility);
+///     synth.generate()
+/// };
+/// let dry = jfxr::Sound {
+///     attack: Attack(0.0),
+///     sustain: Sustain(0.5),
+///     bit_crush: BitCrush(0),
+///     normalization: Normalization(false),
+///     ..Default::default()
+/// };
+/// // Mixes in the dry sound delayed by the given number of samples, with
+/// // the delay rounded or interpolated.
+/// let flange = |dry: &[f64], offset_samples: &dyn Fn(usize) -> f64, round: bool| -> Vec<f64> {
+///     let at = |i: usize, delay: usize| if i >= delay { dry[i - delay] } else { 0.0 };
+///     (0..dry.len())
+///         .map(|i| {
+///             let offset = offset_samples(i);
+///             if round {
+///                 return dry[i] + at(i, offset.round() as usize);
+///             }
+///             let whole = offset.floor();
+///             let fraction = offset - whole;
+///             let (a, b) = (at(i, whole as usize), at(i, whole as usize + 1));
+///             dry[i] + a + fraction * (b - a)
+///         })
+///         .collect()
+/// };
+/// let close = |a: &[f64], b: &[f64]| a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-12);
+/// let samples = jfxr::generate(&dry);
+/// let num_samples = samples.len() as f64;
+///
+/// // A slow sweep from 5 to 6 ms.
+/// let sound = jfxr::Sound { flanger_offset: FlangerOffset(5.0), flanger_offset_sweep: FlangerOffsetSweep(1.0), ..dry.clone() };
+/// let offset_samples = |i: usize| (5.0 + i as f64 / num_samples) / 1000.0 * 44100.0;
+/// assert!(close(&render(&sound, Compatibility::Exact), &flange(&samples, &offset_samples, true)));
+/// assert!(close(&render(&sound, Compatibility::Improved), &flange(&samples, &offset_samples, false)));
+///
+/// // An offset below one sample is interpolated with the current sample.
+/// let sound = jfxr::Sound { flanger_offset: FlangerOffset(0.01), ..dry.clone() };
+/// assert!(close(&render(&sound, Compatibility::Exact), &flange(&samples, &|_| 0.441, true)));
+/// assert!(close(&render(&sound, Compatibility::Improved), &flange(&samples, &|_| 0.441, false)));
+///
+/// // A negative sweep stops at no delay.
+/// let sound = jfxr::Sound { flanger_offset: FlangerOffset(5.0), flanger_offset_sweep: FlangerOffsetSweep(-50.0), ..dry.clone() };
+/// let offset_samples = |i: usize| ((5.0 + i as f64 / num_samples * -50.0) / 1000.0 * 44100.0).max(0.0);
+/// assert!(close(&render(&sound, Compatibility::Exact), &flange(&samples, &offset_samples, true)));
+/// assert!(close(&render(&sound, Compatibility::Improved), &flange(&samples, &offset_samples, false)));
+///
+/// // A sweep that reaches the 100 ms limit of the exact mode before the end
+/// // of the sound stops there, while the improved mode keeps going.
+/// let sound = jfxr::Sound {
+///     flanger_offset: FlangerOffset(50.0),
+///     flanger_offset_sweep: FlangerOffsetSweep(50.0),
+///     flanger_depth: FlangerDepth(20.0),
+///     ..dry.clone()
+/// };
+/// let offset_samples = |i: usize| {
+///     let modulation = 20.0 * (0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / 44100.0).cos());
+///     (50.0 + i as f64 / num_samples * 50.0 + modulation) / 1000.0 * 44100.0
+/// };
+/// assert!(offset_samples(samples.len() * 3 / 4) > 4410.0);
+/// let limited = |i: usize| offset_samples(i).min(4409.0);
+/// assert!(close(&render(&sound, Compatibility::Exact), &flange(&samples, &limited, true)));
+/// assert!(close(&render(&sound, Compatibility::Improved), &flange(&samples, &offset_samples, false)));
+/// ```
 #[derive(Clone, Copy, Default)]
 pub struct FlangerOffsetSweep(pub f64);
 impl FloatParameter for FlangerOffsetSweep {
     const LABEL: &'static str = "Flanger offset sweep";
-    const DESCRIPTION: &'static str = "Amount by which the flanger offset changes linearly over the course of the sound.";
+    const DESCRIPTION: &'static str = "Amount by which the flanger offset changes linearly over the whole sound, regardless of repetitions.";
     const UNIT: &'static str = "ms";
     const MIN_VALUE: f64 = -50.0;
     const MAX_VALUE: f64 = 50.0;
     const STEP: f64 = 1.0;
 }
 
+/// Depth of the slow modulation of the flanger offset.
+///
+#[cfg_attr(feature = "json", doc = "```rust")]
+#[cfg_attr(not(feature = "json"), doc = "```rust,ignore")]
+/// use jfxr::parameter::*;
+///
+/// let dry = jfxr::Sound {
+///     attack: Attack(0.0),
+///     sustain: Sustain(0.5),
+///     bit_crush: BitCrush(0),
+///     normalization: Normalization(false),
+///     ..Default::default()
+/// };
+/// let sound = jfxr::Sound { flanger_offset: FlangerOffset(5.0), flanger_offset_sweep: FlangerOffsetSweep(20.0), ..dry.clone() };
+///
+/// // Without depth and feedback, the flanger mixes in the sound delayed by
+/// // the offset and its sweep, rounded to whole samples, as upstream does.
+/// let dry = jfxr::generate(&dry);
+/// let num_samples = dry.len() as f64;
+/// let expected: Vec<f64> = (0..dry.len())
+///     .map(|i| {
+///         let offset = sound.flanger_offset.0 + i as f64 / num_samples * sound.flanger_offset_sweep.0;
+///         let offset_samples = (offset / 1000.0 * sound.sample_rate.0).round() as usize;
+///         dry[i] + if i >= offset_samples { dry[i - offset_samples] } else { 0.0 }
+///     })
+///     .collect();
+/// assert_eq!(jfxr::generate(&sound), expected);
+///
+/// // With depth, the offset swings back and forth, which changes the sound.
+/// let modulated = jfxr::Sound { flanger_depth: FlangerDepth(10.0), ..sound.clone() };
+/// assert!(jfxr::generate(&modulated) != expected);
+///
+/// // Files of the web tool have neither, and render the same as before.
+/// let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/web-tool/square-harmonics.jfxr");
+/// let read = jfxr::read_jfxr(&std::fs::read_to_string(path).unwrap()).unwrap();
+/// assert_eq!((read.flanger_depth.0, read.flanger_rate.0, read.flanger_feedback.0), (0.0, 1.0, 0.0));
+/// let read = jfxr::read_jfxr(&jfxr::write_jfxr(modulated)).unwrap();
+/// assert_eq!(read.flanger_depth.0, 10.0);
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct FlangerDepth(pub f64);
+impl FloatParameter for FlangerDepth {
+    const LABEL: &'static str = "Flanger depth";
+    const DESCRIPTION: &'static str = "Amount by which the flanger offset is modulated by a slow oscillation, for a swooshing effect.";
+    const UNIT: &'static str = "ms";
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 20.0;
+    const STEP: f64 = 0.5;
+}
+
+#[derive(Clone, Copy)]
+pub struct FlangerRate(pub f64);
+impl Default for FlangerRate {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl FloatParameter for FlangerRate {
+    const LABEL: &'static str = "Flanger rate";
+    const DESCRIPTION: &'static str = "Frequency at which the flanger offset is modulated.";
+    const UNIT: &'static str = "Hz";
+    const DEFAULT_VALUE: f64 = 1.0;
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 20.0;
+    const STEP: f64 = 0.1;
+    const LOGARITHMIC: bool = true;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.flanger_depth.0 == 0.0 {
+            return Some("Flanger rate only applies when the flanger depth is set");
+        }
+        None
+    }
+}
+
+/// Amount of the delayed sound that the flanger feeds back into its delay
+/// line. It is limited to 90%, so that the loop always dies down: each trip
+/// around it is quieter than the one before, and the delayed sound is never
+/// more than ten times louder than the dry sound.
+///
+/// ```rust
+/// use jfxr::parameter::*;
+///
+/// for (offset, depth) in [(1.0, 0.0), (5.0, 0.0), (5.0, 20.0), (50.0, 20.0)] {
+///     let sound = jfxr::Sound {
+///         waveform: Waveform::Square,
+///         attack: Attack(0.0),
+///         sustain: Sustain(1.0),
+///         flanger_offset: FlangerOffset(offset),
+///         flanger_depth: FlangerDepth(depth),
+///         flanger_feedback: FlangerFeedback(FlangerFeedback::MAX_VALUE),
+///         bit_crush: BitCrush(0),
+///         normalization: Normalization(false),
+///         ..Default::default()
+///     };
+///     let samples = jfxr::generate(&sound);
+///     let peak = jfxr::analysis::peak(&samples);
+///     assert!(peak.is_finite() && peak <= 11.0, "{offset} {depth}: {peak}");
+///     assert!(samples != jfxr::generate(&jfxr::Sound { flanger_feedback: FlangerFeedback(0.0), ..sound }));
+/// }
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct FlangerFeedback(pub f64);
+impl FloatParameter for FlangerFeedback {
+    const LABEL: &'static str = "Flanger feedback";
+    const DESCRIPTION: &'static str = "Amount of the delayed sound that is fed back into the flanger. Higher values give a more resonant, metallic effect.";
+    const UNIT: &'static str = "%";
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 90.0;
+    const STEP: f64 = 5.0;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.flanger_offset.0 == 0.0 && sound.flanger_offset_sweep.0 == 0.0 && sound.flanger_depth.0 == 0.0 {
+            return Some("Flanger feedback only applies when the flanger is enabled");
+        }
+        None
+    }
+}
+
+/// Number of delayed copies of the sound that the chorus mixes in.
+///
+/// ```rust
+/// let mut sound = jfxr::Sound::example(jfxr::sound::Example::Powerup);
+/// sound.normalization.0 = false;
+/// let dry = jfxr::generate(&sound);
+/// sound.chorus_voices.0 = 3;
+/// let wet = jfxr::generate(&sound);
+/// assert_ne!(dry, wet);
+/// let peak = |samples: &[f64]| samples.iter().fold(0.0f64, |peak, sample| peak.max(sample.abs()));
+/// assert!(peak(&wet) <= peak(&dry) + 1e-9);
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct ChorusVoices(pub i32);
+impl IntegerParameter for ChorusVoices {
+    const LABEL: &'static str = "Chorus voices";
+    const DESCRIPTION: &'static str = "Number of delayed copies of the sound that are mixed in, each with its own slow modulation of the delay, to make the sound richer. 0 disables the chorus.";
+    const MAX_VALUE: i32 = 3;
+}
+
+#[derive(Clone, Copy)]
+pub struct ChorusDepth(pub f64);
+impl Default for ChorusDepth {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl FloatParameter for ChorusDepth {
+    const LABEL: &'static str = "Chorus depth";
+    const DESCRIPTION: &'static str = "Amount by which the delay of each chorus voice is modulated.";
+    const UNIT: &'static str = "ms";
+    const DEFAULT_VALUE: f64 = 3.0;
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 10.0;
+    const STEP: f64 = 0.5;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.chorus_voices.0 == 0 {
+            return Some("Chorus depth only applies when there are chorus voices");
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ChorusRate(pub f64);
+impl Default for ChorusRate {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl FloatParameter for ChorusRate {
+    const LABEL: &'static str = "Chorus rate";
+    const DESCRIPTION: &'static str = "Frequency at which the delay of each chorus voice is modulated.";
+    const UNIT: &'static str = "Hz";
+    const DEFAULT_VALUE: f64 = 1.5;
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 10.0;
+    const STEP: f64 = 0.1;
+    const LOGARITHMIC: bool = true;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.chorus_voices.0 == 0 {
+            return Some("Chorus rate only applies when there are chorus voices");
+        }
+        None
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct BitCrush(pub i32);
 impl Default for BitCrush {
     fn default() -> Self {
-        Self(16)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl IntegerParameter for BitCrush {
     const LABEL: &'static str = "Bit crush";
     const DESCRIPTION: &'static str = "Number of bits per sample. Reduces the number of bits in each sample by this amount, and then increase it again. The result is a lower-fidelity sound effect.";
     const UNIT: &'static str = "bits";
+    const DEFAULT_VALUE: i32 = 16;
     const MIN_VALUE: i32 = 1;
     const MAX_VALUE: i32 = 16;
     const STEP: i32 = 1;
@@ -411,7 +2335,7 @@ impl IntegerParameter for BitCrush {
 pub struct BitCrushSweep(pub i32);
 impl IntegerParameter for BitCrushSweep {
     const LABEL: &'static str = "Bit crush sweep";
-    const DESCRIPTION: &'static str = "Amount by which to change the bit crush value linearly over the course of the sound.";
+    const DESCRIPTION: &'static str = "Amount by which to change the bit crush value linearly over the whole sound, regardless of repetitions.";
     const UNIT: &'static str = "bits";
     const MIN_VALUE: i32 = -16;
     const MAX_VALUE: i32 = 16;
@@ -422,13 +2346,14 @@ impl IntegerParameter for BitCrushSweep {
 pub struct LowPassCutoff(pub f64);
 impl Default for LowPassCutoff {
     fn default() -> Self {
-        Self(22050.0)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl FloatParameter for LowPassCutoff {
     const LABEL: &'static str = "Low-pass cutoff";
     const DESCRIPTION: &'static str = "Threshold above which frequencies should be filtered out, using a simple IIR low-pass filter. Use this to take some \"edge\" off the sound.";
     const UNIT: &'static str = "Hz";
+    const DEFAULT_VALUE: f64 = 22050.0;
     const MIN_VALUE: f64 = 0.0;
     const MAX_VALUE: f64 = 22050.0;
     const STEP: f64 = 100.0;
@@ -439,7 +2364,7 @@ impl FloatParameter for LowPassCutoff {
 pub struct LowPassCutoffSweep(pub f64);
 impl FloatParameter for LowPassCutoffSweep {
     const LABEL: &'static str = "Low-pass sweep";
-    const DESCRIPTION: &'static str = "Amount by which to change the low-pass cutoff frequency over the course of the sound.";
+    const DESCRIPTION: &'static str = "Amount by which to change the low-pass cutoff frequency linearly over the whole sound, regardless of repetitions.";
     const UNIT: &'static str = "Hz";
     const MIN_VALUE: f64 = -22050.0;
     const MAX_VALUE: f64 = 22050.0;
@@ -463,7 +2388,7 @@ impl FloatParameter for HighPassCutoff {
 pub struct HighPassCutoffSweep(pub f64);
 impl FloatParameter for HighPassCutoffSweep {
     const LABEL: &'static str = "High-pass sweep";
-    const DESCRIPTION: &'static str = "Amount by which to change the high-pass cutoff frequency over the course of the sound.";
+    const DESCRIPTION: &'static str = "Amount by which to change the high-pass cutoff frequency linearly over the whole sound, regardless of repetitions.";
     const UNIT: &'static str = "Hz";
     const MIN_VALUE: f64 = -22050.0;
     const MAX_VALUE: f64 = 22050.0;
@@ -474,16 +2399,93 @@ impl FloatParameter for HighPassCutoffSweep {
 
   // Output parameters
 
+#[derive(Clone, Copy, Default)]
+pub struct ReverbAmount(pub f64);
+impl FloatParameter for ReverbAmount {
+    const LABEL: &'static str = "Reverb amount";
+    const DESCRIPTION: &'static str = "Volume of the reverberation, which simulates the echoes of a room, relative to the original sound. 0 disables the reverb.";
+    const UNIT: &'static str = "%";
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 100.0;
+}
+
+/// Time over which the reverb decays by 60 dB. With [`ReverbTail`] enabled,
+/// the sound is extended by this time, and the tail dies out roughly
+/// exponentially:
+///
+/// ```rust
+/// let mut sound = jfxr::Sound::example(jfxr::sound::Example::Hit);
+/// sound.normalization.0 = false;
+/// sound.reverb_amount.0 = 100.0;
+/// sound.reverb_decay.0 = 1.0;
+/// let samples = jfxr::generate(&sound);
+/// assert_eq!(samples.len(), (44100.0 * (sound.duration() + 1.0)).ceil() as usize);
+///
+/// // RMS level in decibels of a 50 ms window, `time` seconds after the end.
+/// let end = sound.num_body_samples();
+/// let level = |time: f64| {
+///     let window = &samples[end + (44100.0 * time) as usize..][..2205];
+///     10.0 * (window.iter().map(|sample| sample * sample).sum::<f64>() / 2205.0).log10()
+/// };
+/// // 60 dB per second: 6 dB for every 100 ms, within a few decibels.
+/// for time in [0.1, 0.2, 0.3, 0.4] {
+///     let drop = level(time) - level(time + 0.1);
+///     assert!((drop - 6.0).abs() < 3.0, "dropped {drop} dB at {time} s");
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct ReverbDecay(pub f64);
+impl Default for ReverbDecay {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl FloatParameter for ReverbDecay {
+    const LABEL: &'static str = "Reverb decay";
+    const DESCRIPTION: &'static str = "Time it takes for the reverberation to die out, by 60 dB. Larger values simulate larger rooms.";
+    const UNIT: &'static str = "s";
+    const DEFAULT_VALUE: f64 = 1.0;
+    const MIN_VALUE: f64 = 0.1;
+    const MAX_VALUE: f64 = 3.0;
+    const STEP: f64 = 0.1;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.reverb_amount.0 == 0.0 {
+            return Some("Reverb decay only applies when the reverb amount is set");
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ReverbTail(pub bool);
+impl Default for ReverbTail {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl BooleanParameter for ReverbTail {
+    const LABEL: &'static str = "Reverb tail";
+    const DESCRIPTION: &'static str = "Whether to extend the sound by the reverb decay time, so that the reverberation is not cut off when the sound ends.";
+    const DEFAULT_VALUE: bool = true;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.reverb_amount.0 == 0.0 {
+            return Some("Reverb tail only applies when the reverb amount is set");
+        }
+        None
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct Compression(pub f64);
 impl Default for Compression {
     fn default() -> Self {
-        Self(1.0)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl FloatParameter for Compression {
     const LABEL: &'static str = "Compression";
     const DESCRIPTION: &'static str = "Power to which sample values should be raised. 1 is the neutral setting. Use a value less than 1 to increase the volume of quiet parts of the sound, higher than 1 to make quiet parts even quieter.";
+    const DEFAULT_VALUE: f64 = 1.0;
     const MIN_VALUE: f64 = 0.0;
     const MAX_VALUE: f64 = 5.0;
     const STEP: f64 = 0.1;
@@ -493,26 +2495,274 @@ impl FloatParameter for Compression {
 pub struct Normalization(pub bool);
 impl Default for Normalization {
     fn default() -> Self {
-        Self(true)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl BooleanParameter for Normalization {
     const LABEL: &'static str = "Normalization";
     const DESCRIPTION: &'static str = "Whether to adjust the volume of the sound so that the peak volume is at 100%.";
+    const DEFAULT_VALUE: bool = true;
+}
+
+/// How normalization chooses the volume of the sound. Leveling by loudness
+/// keeps a set of sounds equally loud, where leveling by peak makes quiet
+/// designs as loud as the others:
+///
+/// ```rust
+/// use jfxr::parameter::LevelingMode;
+/// use jfxr::sound::Example;
+///
+/// let level = |example| {
+///     let mut sound = jfxr::Sound::example(example);
+///     sound.leveling_mode = LevelingMode::Rms;
+///     sound.leveling_target.0 = -20.0;
+///     jfxr::analysis::rms_db(&jfxr::generate(&sound))
+/// };
+/// assert!((level(Example::Coin) - level(Example::Explosion)).abs() < 0.5);
+/// assert!((level(Example::Laser) + 20.0).abs() < 0.5);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LevelingMode {
+    /// Scales the sound so that the peak volume is at 100%.
+    Peak,
+    /// Scales the sound so that its root mean square reaches the target.
+    Rms,
+    /// Scales the sound so that its loudness, measured as in
+    /// [`crate::analysis::LoudnessMeter`], reaches the target.
+    Lufs,
+}
+impl Default for LevelingMode {
+    fn default() -> Self {
+        Self::DEFAULT_VALUE
+    }
+}
+impl EnumParameter for LevelingMode {
+    const LABEL: &'static str = "Leveling";
+    const DESCRIPTION: &'static str = "What normalization adjusts the volume by: the peak volume, or the average loudness, to keep a set of sounds equally loud.";
+    const DEFAULT_VALUE: Self = Self::Peak;
+    const VALUES: &'static [Self] = &[
+        Self::Peak,
+        Self::Rms,
+        Self::Lufs,
+    ];
+    const NAMES: &'static [&'static str] = &[
+        "peak",
+        "rms",
+        "lufs",
+    ];
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if !sound.normalization.0 {
+            return Some("Leveling only applies when normalization is enabled");
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct LevelingTarget(pub f64);
+impl Default for LevelingTarget {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl FloatParameter for LevelingTarget {
+    const LABEL: &'static str = "Leveling target";
+    const DESCRIPTION: &'static str = "Loudness to level the sound to, in dB relative to full scale for RMS leveling, or in LUFS. The sound is never made louder than its peak allows.";
+    const UNIT: &'static str = "dB";
+    const DEFAULT_VALUE: f64 = -18.0;
+    const MIN_VALUE: f64 = -40.0;
+    const MAX_VALUE: f64 = 0.0;
+    const STEP: f64 = 1.0;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if !sound.normalization.0 || sound.leveling_mode == LevelingMode::Peak {
+            return Some("Leveling target only applies when leveling by loudness");
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct LevelingMaxGain(pub f64);
+impl Default for LevelingMaxGain {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl FloatParameter for LevelingMaxGain {
+    const LABEL: &'static str = "Leveling max gain";
+    const DESCRIPTION: &'static str = "Largest amount by which leveling may amplify the sound, so that very quiet sounds are not boosted into noise.";
+    const UNIT: &'static str = "dB";
+    const DEFAULT_VALUE: f64 = 24.0;
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 60.0;
+    const STEP: f64 = 1.0;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if !sound.normalization.0 || sound.leveling_mode == LevelingMode::Peak {
+            return Some("Leveling max gain only applies when leveling by loudness");
+        }
+        None
+    }
 }
 
 #[derive(Clone, Copy)]
 pub struct Amplification(pub f64);
 impl Default for Amplification {
     fn default() -> Self {
-        Self(100.0)
+        Self(Self::DEFAULT_VALUE)
     }
 }
 impl FloatParameter for Amplification {
     const LABEL: &'static str = "Amplification";
     const DESCRIPTION: &'static str = "Percentage to amplify the sound by, after any normalization has occurred. Note that setting this too high can result in clipping.";
     const UNIT: &'static str = "%";
+    const DEFAULT_VALUE: f64 = 100.0;
     const MIN_VALUE: f64 = 0.0;
     const MAX_VALUE: f64 = 500.0;
     const STEP: f64 = 10.0;
 }
+
+/// Width of the stereo image produced by [`crate::Synth::generate_stereo`].
+/// Each channel mixes the sound with a decorrelated render of it, in which
+/// noise and start phases differ; at 0, both channels are the mono sound.
+#[derive(Clone, Copy, Default)]
+pub struct StereoWidth(pub f64);
+impl FloatParameter for StereoWidth {
+    const LABEL: &'static str = "Stereo width";
+    const DESCRIPTION: &'static str = "How different the left and right channels are, when rendering in stereo. 0 plays the same sound on both channels.";
+    const UNIT: &'static str = "%";
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 100.0;
+    const STEP: f64 = 5.0;
+}
+
+/// Position of the sound between the left and right channels of
+/// [`crate::Synth::generate_stereo`] at its start, with a constant-power pan
+/// law that keeps the centre at full volume. See
+/// [`crate::Sound::pan_at`].
+#[derive(Clone, Copy, Default)]
+pub struct Pan(pub f64);
+impl FloatParameter for Pan {
+    const LABEL: &'static str = "Pan";
+    const DESCRIPTION: &'static str = "Position of the sound from left (-100) to right (100) at its start, when rendering in stereo.";
+    const UNIT: &'static str = "%";
+    const MIN_VALUE: f64 = -100.0;
+    const MAX_VALUE: f64 = 100.0;
+    const STEP: f64 = 5.0;
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct PanSweep(pub f64);
+impl FloatParameter for PanSweep {
+    const LABEL: &'static str = "Pan sweep";
+    const DESCRIPTION: &'static str = "Amount by which the position of the sound moves over its duration, when rendering in stereo. A sweep of 200 from a pan of -100 moves it from left to right.";
+    const UNIT: &'static str = "%";
+    const MIN_VALUE: f64 = -200.0;
+    const MAX_VALUE: f64 = 200.0;
+    const STEP: f64 = 5.0;
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct AutoPanDepth(pub f64);
+impl FloatParameter for AutoPanDepth {
+    const LABEL: &'static str = "Auto-pan depth";
+    const DESCRIPTION: &'static str = "Amount by which the position of the sound oscillates as a sine wave around the pan, when rendering in stereo.";
+    const UNIT: &'static str = "%";
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 100.0;
+    const STEP: f64 = 5.0;
+}
+
+#[derive(Clone, Copy)]
+pub struct AutoPanFrequency(pub f64);
+impl Default for AutoPanFrequency {
+    fn default() -> Self {
+        Self(Self::DEFAULT_VALUE)
+    }
+}
+impl FloatParameter for AutoPanFrequency {
+    const LABEL: &'static str = "Auto-pan frequency";
+    const DESCRIPTION: &'static str = "Number of times per second that the position of the sound oscillates.";
+    const UNIT: &'static str = "Hz";
+    const DEFAULT_VALUE: f64 = 2.0;
+    const MIN_VALUE: f64 = 0.0;
+    const MAX_VALUE: f64 = 100.0;
+    const STEP: f64 = 0.5;
+    const LOGARITHMIC: bool = true;
+    fn disabled_reason(sound: &crate::sound::Sound) -> Option<&'static str> {
+        if sound.auto_pan_depth.0 == 0.0 {
+            return Some("Auto-pan frequency only applies when the auto-pan depth is set");
+        }
+        None
+    }
+}
+
+// Unit constructors
+
+macro_rules! float_constructor {
+    ($(#[$meta:meta])* $name:ident($arg:ident: $arg_ty:ty) => $value:expr) => {
+        $(#[$meta])*
+        pub fn $name($arg: $arg_ty) -> Self {
+            let value = $value;
+            debug_assert!(
+                (<Self as FloatParameter>::MIN_VALUE..=<Self as FloatParameter>::MAX_VALUE).contains(&value),
+                "{} {} is out of range", <Self as FloatParameter>::LABEL, value,
+            );
+            Self(value)
+        }
+    };
+}
+
+/// Adds constructors to parameters measured in seconds.
+macro_rules! seconds {
+    ($($ty:ident),*) => {$(
+        impl $ty {
+            float_constructor!(#[doc = "Constructs the parameter from a number of seconds."] from_secs(secs: f64) => secs);
+            float_constructor!(#[doc = "Constructs the parameter from a number of milliseconds."] from_ms(ms: f64) => ms / 1000.0);
+            float_constructor!(#[doc = "Constructs the parameter from a duration."] from_duration(duration: std::time::Duration) => duration.as_secs_f64());
+        }
+    )*};
+}
+
+/// Adds constructors to parameters measured in milliseconds.
+macro_rules! milliseconds {
+    ($($ty:ident),*) => {$(
+        impl $ty {
+            float_constructor!(#[doc = "Constructs the parameter from a number of seconds."] from_secs(secs: f64) => secs * 1000.0);
+            float_constructor!(#[doc = "Constructs the parameter from a number of milliseconds."] from_ms(ms: f64) => ms);
+            float_constructor!(#[doc = "Constructs the parameter from a duration."] from_duration(duration: std::time::Duration) => duration.as_secs_f64() * 1000.0);
+        }
+    )*};
+}
+
+/// Adds constructors to parameters measured in percent.
+macro_rules! percent {
+    ($($ty:ident),*) => {$(
+        impl $ty {
+            float_constructor!(#[doc = "Constructs the parameter from a percentage."] from_percent(percent: f64) => percent);
+            float_constructor!(#[doc = "Constructs the parameter from a fraction, where 1 is 100%."] from_fraction(fraction: f64) => fraction * 100.0);
+        }
+    )*};
+}
+
+/// Adds constructors to parameters measured in Hz.
+macro_rules! hertz {
+    ($($ty:ident),*) => {$(
+        impl $ty {
+            float_constructor!(#[doc = "Constructs the parameter from a frequency in Hz."] from_hz(hz: f64) => hz);
+            float_constructor!(#[doc = "Constructs the parameter from a frequency in kHz."] from_khz(khz: f64) => khz * 1000.0);
+        }
+    )*};
+}
+
+seconds!(Attack, Sustain, Decay, ReverbDecay);
+milliseconds!(FlangerOffset, FlangerOffsetSweep, FlangerDepth, ChorusDepth);
+percent!(
+    SustainPunch, TremoloDepth, RepeatDecay, RepeatJitter, FrequencyJump1Onset, FrequencyJump1Amount, FrequencyJump2Onset,
+    FrequencyJump2Amount, Inharmonicity, StartPhase, SquareDuty, SquareDutySweep, WhistleOvertoneMix,
+    BreakerPhaseOffset, FlangerFeedback, ReverbAmount, Amplification, StereoWidth, Pan, PanSweep, AutoPanDepth
+);
+hertz!(
+    SampleRate, TremoloFrequency, Frequency, FrequencySweep, FrequencyDeltaSweep, RepeatFrequency, VibratoDepth,
+    VibratoFrequency, FlangerRate, ChorusRate, LowPassCutoff, LowPassCutoffSweep, HighPassCutoff, HighPassCutoffSweep,
+    AutoPanFrequency
+);
@@ -0,0 +1,352 @@
+//! Sparse sets of parameter values that can be applied over a sound.
+
+use crate::parameter::{Param, ParamId, Value};
+use crate::sound::Sound;
+
+/// A set of parameter overrides, for example a "house style" that should be
+/// applied to many sounds. Parameters that are not in the patch are left
+/// alone when it is applied.
+///
+/// ```rust
+/// use jfxr::patch::SoundPatch;
+/// let house_style = SoundPatch::new()
+///     .with("attack", 0.01)
+///     .with("normalization", true);
+/// let louder = SoundPatch::new().with("amplification", 150.0);
+/// let patch = house_style.then(&louder);
+///
+/// let mut sound = jfxr::Sound::default();
+/// patch.apply(&mut sound);
+/// assert_eq!(sound.attack.0, 0.01);
+/// assert_eq!(sound.amplification.0, 150.0);
+/// assert_eq!(patch.len(), 3);
+/// ```
+#[derive(Clone, Default)]
+pub struct SoundPatch {
+    values: Vec<(&'static Param, Value)>,
+}
+
+impl SoundPatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a patch with the parameters that differ between `base` and
+    /// `target`, which turns `base` into `target` when applied.
+    pub fn from_diff(base: &Sound, target: &Sound) -> Self {
+        let mut patch = Self::new();
+        for param in Sound::params() {
+            let value = param.get(target);
+            if param.get(base) != value {
+                patch.set(param, value);
+            }
+        }
+        patch
+    }
+
+    /// Adds the parameter with the given key in the `jfxr` format to the
+    /// patch, replacing any previous value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no parameter with the given key.
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        let param = Sound::param(key).unwrap_or_else(|| panic!("unknown parameter {key:?}"));
+        self.set(param, value.into());
+        self
+    }
+
+    /// Adds a parameter to the patch, replacing any previous value.
+    pub fn set(&mut self, param: &'static Param, value: Value) {
+        match self.values.iter_mut().find(|(existing, _)| existing.id == param.id) {
+            Some((_, existing)) => *existing = value,
+            None => self.values.push((param, value)),
+        }
+    }
+
+    /// Returns the value of the parameter with the given key, if it is in
+    /// the patch.
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.values.iter().find(|(param, _)| param.key == key).map(|(_, value)| *value)
+    }
+
+    /// Removes the parameter with the given key from the patch.
+    pub fn remove(&mut self, key: &str) {
+        self.values.retain(|(param, _)| param.key != key);
+    }
+
+    /// Returns the parameters in the patch and their values, in the order in
+    /// which they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static Param, Value)> + '_ {
+        self.values.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns a patch which has the effect of applying this patch, followed
+    /// by `other`.
+    pub fn then(&self, other: &SoundPatch) -> SoundPatch {
+        let mut patch = self.clone();
+        for (param, value) in other.iter() {
+            patch.set(param, value);
+        }
+        patch
+    }
+
+    /// Sets the parameters in the patch on the given sound.
+    pub fn apply(&self, sound: &mut Sound) {
+        for (param, value) in self.iter() {
+            param.set(sound, value);
+        }
+    }
+}
+
+/// The changes between two versions of a sound, which records the old value
+/// of each changed parameter as well as the new one. Unlike a
+/// [`SoundPatch`], applying it detects parameters that were changed in the
+/// meantime, for example when merging the edits of two people to the same
+/// sound. See [`merge`], and [`Sound::apply_patch_json`] for its JSON form.
+///
+/// Besides the parameters, the delta records changes to the custom harmonic
+/// amplitudes, which are compared as a whole.
+#[derive(Clone, Default)]
+pub struct SoundDelta {
+    changes: Vec<(&'static Param, Value, Value)>,
+    harmonics_custom_amplitudes: Option<(Vec<f64>, Vec<f64>)>,
+}
+
+impl SoundDelta {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the changes of the parameters that differ between `before`
+    /// and `after`.
+    pub fn from_diff(before: &Sound, after: &Sound) -> Self {
+        let mut delta = Self::new();
+        for param in Sound::params() {
+            let (old, new) = (param.get(before), param.get(after));
+            if old != new {
+                delta.set(param, old, new);
+            }
+        }
+        if before.harmonics_custom_amplitudes != after.harmonics_custom_amplitudes {
+            delta.set_harmonics_custom_amplitudes(before.harmonics_custom_amplitudes.clone(), after.harmonics_custom_amplitudes.clone());
+        }
+        delta
+    }
+
+    /// Adds a change of a parameter, replacing any previous change of it.
+    pub fn set(&mut self, param: &'static Param, old: Value, new: Value) {
+        match self.changes.iter_mut().find(|(existing, _, _)| existing.id == param.id) {
+            Some(change) => *change = (param, old, new),
+            None => self.changes.push((param, old, new)),
+        }
+    }
+
+    /// Adds a change of the custom harmonic amplitudes, replacing any
+    /// previous change of them.
+    pub fn set_harmonics_custom_amplitudes(&mut self, old: Vec<f64>, new: Vec<f64>) {
+        self.harmonics_custom_amplitudes = Some((old, new));
+    }
+
+    /// Returns the changed parameters with their old and new values, in the
+    /// order in which they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static Param, Value, Value)> + '_ {
+        self.changes.iter().copied()
+    }
+
+    /// Returns the old and new custom harmonic amplitudes, if they changed.
+    pub fn harmonics_custom_amplitudes(&self) -> Option<(&[f64], &[f64])> {
+        self.harmonics_custom_amplitudes.as_ref().map(|(old, new)| (old.as_slice(), new.as_slice()))
+    }
+
+    /// Returns the number of changes, counting the custom harmonic
+    /// amplitudes as one.
+    pub fn len(&self) -> usize {
+        self.changes.len() + usize::from(self.harmonics_custom_amplitudes.is_some())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the parameters of the sound that have neither the old nor the
+    /// new value of their change. A parameter that already has the new
+    /// value, because the same change was made twice, is not a conflict.
+    pub fn conflicts(&self, sound: &Sound) -> Vec<Conflict> {
+        let mut conflicts: Vec<Conflict> = self
+            .iter()
+            .filter_map(|(param, old, new)| {
+                let current = param.get(sound);
+                (current != old && current != new).then_some(Conflict::Param {
+                    id: param.id,
+                    base: old,
+                    ours: current,
+                    theirs: new,
+                })
+            })
+            .collect();
+        if let Some((old, new)) = &self.harmonics_custom_amplitudes {
+            let current = &sound.harmonics_custom_amplitudes;
+            if current != old && current != new {
+                conflicts.push(Conflict::HarmonicsCustomAmplitudes {
+                    base: old.clone(),
+                    ours: current.clone(),
+                    theirs: new.clone(),
+                });
+            }
+        }
+        conflicts
+    }
+
+    /// Sets the new values of the changes on the sound, unless any parameter
+    /// conflicts, in which case the sound is left unchanged and the
+    /// conflicts are returned.
+    pub fn apply(&self, sound: &mut Sound) -> Result<(), Vec<Conflict>> {
+        let conflicts = self.conflicts(sound);
+        if !conflicts.is_empty() {
+            return Err(conflicts);
+        }
+        self.force_apply(sound);
+        Ok(())
+    }
+
+    /// Sets the new values of the changes on the sound, whatever the current
+    /// values are.
+    pub fn force_apply(&self, sound: &mut Sound) {
+        for (param, _, new) in self.iter() {
+            param.set(sound, new);
+        }
+        if let Some((_, new)) = &self.harmonics_custom_amplitudes {
+            sound.harmonics_custom_amplitudes = new.clone();
+        }
+    }
+}
+
+/// A parameter that was changed in two different ways from the same value,
+/// as found by [`SoundDelta::apply`] and [`merge`]. In each variant, `base`
+/// is the value before either change, `ours` the value in our version, or in
+/// the sound that a delta is applied to, and `theirs` the value in their
+/// version, or in the delta.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Conflict {
+    Param { id: ParamId, base: Value, ours: Value, theirs: Value },
+    /// The custom harmonic amplitudes, which are compared as a whole.
+    HarmonicsCustomAmplitudes { base: Vec<f64>, ours: Vec<f64>, theirs: Vec<f64> },
+}
+
+impl Conflict {
+    /// Returns the identifier of the conflicting parameter, or `None` for
+    /// the custom harmonic amplitudes.
+    pub fn id(&self) -> Option<ParamId> {
+        match self {
+            Self::Param { id, .. } => Some(*id),
+            Self::HarmonicsCustomAmplitudes { .. } => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Param { id, base, ours, theirs } => {
+                let param = id.param();
+                write!(
+                    f,
+                    "{}: {} was changed to both {} and {}",
+                    param.label,
+                    param.format_value(*base),
+                    param.format_value(*ours),
+                    param.format_value(*theirs),
+                )?;
+                if !param.unit.is_empty() {
+                    write!(f, " {}", param.unit)?;
+                }
+                Ok(())
+            }
+            Self::HarmonicsCustomAmplitudes { base, ours, theirs } => {
+                write!(f, "Custom harmonic amplitudes: {base:?} was changed to both {ours:?} and {theirs:?}")
+            }
+        }
+    }
+}
+
+/// Merges the changes made to `base` in `ours` and in `theirs`, parameter by
+/// parameter. A parameter changed in only one version takes its value from
+/// that version, and one changed to the same value in both takes that value.
+/// Parameters changed to different values are returned as conflicts, and so
+/// are the custom harmonic amplitudes, which are compared as a whole. The
+/// name and the locked parameters are taken from `theirs` if only `theirs`
+/// changed them, and from `ours` otherwise.
+///
+/// ```rust
+/// use jfxr::parameter::{EnumParameter, ParamId, Value, Waveform};
+/// use jfxr::patch::{merge, Conflict};
+///
+/// let mut base = jfxr::Sound::example(jfxr::sound::Example::Laser);
+/// base.frequency.0 = 1000.0;
+/// let mut ours = base.clone();
+/// ours.frequency.0 = 880.0;
+/// ours.normalization.0 = !base.normalization.0;
+/// let mut theirs = base.clone();
+/// theirs.waveform = Waveform::Whistle;
+/// theirs.attack.0 = 0.02;
+/// // The same change on both sides is not a conflict.
+/// theirs.normalization.0 = !base.normalization.0;
+///
+/// let merged = merge(&base, &ours, &theirs).unwrap();
+/// assert_eq!(merged.frequency.0, 880.0);
+/// assert!(merged.waveform == Waveform::Whistle);
+/// assert_eq!(merged.attack.0, 0.02);
+/// assert_eq!(merged.normalization.0, !base.normalization.0);
+/// assert!(merge(&base, &theirs, &ours).unwrap().diff(&merged).is_empty());
+///
+/// // Different changes to the same parameters conflict. Booleans cannot,
+/// // as they have only one value to change to.
+/// ours.waveform = Waveform::Tangent;
+/// theirs.frequency.0 = 440.0;
+/// let Err(conflicts) = merge(&base, &ours, &theirs) else { panic!() };
+/// let ids: Vec<_> = conflicts.iter().map(|conflict| conflict.id()).collect();
+/// assert_eq!(ids, [Some(ParamId::Frequency), Some(ParamId::Waveform)]);
+/// assert_eq!(conflicts[0].to_string(), "Frequency: 1000 was changed to both 880 and 440 Hz");
+/// assert_eq!(conflicts[1], Conflict::Param {
+///     id: ParamId::Waveform,
+///     base: Value::Enum(base.waveform.index()),
+///     ours: Value::Enum(Waveform::Tangent.index()),
+///     theirs: Value::Enum(Waveform::Whistle.index()),
+/// });
+///
+/// // Custom harmonic amplitudes merge and conflict as a whole.
+/// let mut ours = base.clone();
+/// ours.harmonics_custom_amplitudes = vec![1.0, 0.5];
+/// let mut theirs = base.clone();
+/// theirs.frequency.0 = 440.0;
+/// assert_eq!(merge(&base, &ours, &theirs).unwrap().harmonics_custom_amplitudes, [1.0, 0.5]);
+/// assert_eq!(merge(&base, &theirs, &ours).unwrap().harmonics_custom_amplitudes, [1.0, 0.5]);
+/// theirs.harmonics_custom_amplitudes = vec![1.0, 0.25];
+/// let Err(conflicts) = merge(&base, &ours, &theirs) else { panic!() };
+/// assert_eq!(conflicts, [Conflict::HarmonicsCustomAmplitudes {
+///     base: Vec::new(),
+///     ours: vec![1.0, 0.5],
+///     theirs: vec![1.0, 0.25],
+/// }]);
+/// assert_eq!(conflicts[0].to_string(), "Custom harmonic amplitudes: [] was changed to both [1.0, 0.5] and [1.0, 0.25]");
+/// ```
+pub fn merge(base: &Sound, ours: &Sound, theirs: &Sound) -> Result<Sound, Vec<Conflict>> {
+    let mut merged = ours.clone();
+    SoundDelta::from_diff(base, theirs).apply(&mut merged)?;
+    if ours.name == base.name {
+        merged.name = theirs.name.clone();
+    }
+    if ours.locked == base.locked {
+        merged.locked = theirs.locked.clone();
+    }
+    Ok(merged)
+}
@@ -0,0 +1,265 @@
+//! Mixing of many overlapping sounds at runtime, such as the shots,
+//! footsteps and explosions of a game, with a limit on the number of sounds
+//! that play at once.
+//!
+//! Sounds are rendered ahead of time and shared as `Arc<Vec<f64>>`. A
+//! [`SoundPool`] lives in the audio callback and mixes the playing voices
+//! into each buffer, while [`PoolHandle`]s start new voices from other
+//! threads:
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use jfxr::pool::SoundPool;
+//! use jfxr::sound::Example;
+//!
+//! let coin = Arc::new(jfxr::generate(&jfxr::Sound::example(Example::Coin)));
+//! let mut pool = SoundPool::new(16);
+//! let handle = pool.handle();
+//! let audio_thread = std::thread::spawn(move || {
+//!     let mut buffer = [0.0f32; 2 * 256];
+//!     for _ in 0..100 {
+//!         buffer.fill(0.0);
+//!         pool.mix_into(&mut buffer);
+//!         // ...send the buffer to the audio device...
+//!     }
+//! });
+//! handle.play(&coin, 1.0, 0.0);
+//! audio_thread.join().unwrap();
+//! ```
+
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
+
+/// Which voice stops when a sound is played while all voices are in use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StealPolicy {
+    /// Stops the voice that started first.
+    #[default]
+    Oldest,
+    /// Stops the voice with the lowest gain, or the one that started first
+    /// among those with the lowest gain.
+    Quietest,
+}
+
+/// Request to start a voice, sent from a [`PoolHandle`] to the pool.
+struct Play {
+    samples: Arc<Vec<f64>>,
+    gain: f64,
+    pan: f64,
+}
+
+/// A sound being played by the pool.
+struct Voice {
+    samples: Arc<Vec<f64>>,
+    /// Index of the next sample to be mixed.
+    position: usize,
+    gain: f64,
+    left: f64,
+    right: f64,
+    /// Number of voices started before this one, to find the oldest voice.
+    start: u64,
+}
+
+/// Mixer of overlapping sounds with a maximum number of voices.
+///
+/// The pool does not lock or allocate while mixing: new voices arrive over a
+/// channel, which is drained without blocking at the start of
+/// [`mix_into`](Self::mix_into), and voices are kept in a list allocated
+/// once, in [`new`](Self::new). The samples of a voice are only freed on the
+/// audio thread if the pool held their last reference, so keep a clone of
+/// each `Arc` for as long as the sound may be played.
+///
+/// Samples are played at the rate of the output, so sounds should be
+/// rendered at the sample rate of the audio device.
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use jfxr::pool::{SoundPool, StealPolicy};
+///
+/// let short = Arc::new(vec![0.5; 100]);
+/// let long = Arc::new(vec![0.25; 1000]);
+/// let mut pool = SoundPool::new(2).with_steal_policy(StealPolicy::Oldest);
+/// let mut buffer = [0.0f32; 2 * 64];
+///
+/// // Voices are summed, with a gain and a position from left to right.
+/// pool.play(&short, 1.0, -1.0);
+/// pool.play(&long, 2.0, -1.0);
+/// pool.mix_into(&mut buffer);
+/// assert_eq!(pool.active_voices(), 2);
+/// assert_eq!(buffer[0], 0.5 + 0.5);
+/// assert!(buffer[1].abs() < 1e-6);
+///
+/// // Each voice continues where it left off, and ends with its samples.
+/// buffer.fill(0.0);
+/// pool.mix_into(&mut buffer);
+/// assert_eq!(buffer[2 * 35], 1.0);
+/// assert_eq!(buffer[2 * 36], 0.5);
+/// assert_eq!(pool.active_voices(), 1);
+///
+/// // When all voices are in use, the oldest one is stopped.
+/// pool.play(&short, 1.0, 0.0);
+/// pool.play(&short, 1.0, 0.0);
+/// buffer.fill(0.0);
+/// pool.mix_into(&mut buffer);
+/// assert_eq!(pool.active_voices(), 2);
+/// let center = 0.5 * std::f32::consts::FRAC_1_SQRT_2;
+/// assert!((buffer[0] - 2.0 * center).abs() < 1e-6);
+///
+/// // Simulates an audio callback until every voice has ended.
+/// let mut callbacks = 0;
+/// while pool.active_voices() > 0 {
+///     pool.mix_into(&mut buffer);
+///     callbacks += 1;
+/// }
+/// assert_eq!(callbacks, 1);
+/// ```
+pub struct SoundPool {
+    voices: Vec<Voice>,
+    max_voices: usize,
+    steal_policy: StealPolicy,
+    started: u64,
+    sender: Sender<Play>,
+    receiver: Receiver<Play>,
+}
+
+impl SoundPool {
+    /// Creates a pool that plays up to `max_voices` sounds at once, stopping
+    /// the oldest voice when more are played. With 0 voices, nothing is
+    /// played.
+    pub fn new(max_voices: usize) -> Self {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        Self {
+            voices: Vec::with_capacity(max_voices),
+            max_voices,
+            steal_policy: StealPolicy::default(),
+            started: 0,
+            sender,
+            receiver,
+        }
+    }
+
+    /// Sets which voice stops when a sound is played while all voices are in
+    /// use.
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use jfxr::pool::{SoundPool, StealPolicy};
+    ///
+    /// let samples = Arc::new(vec![1.0; 1000]);
+    /// let mut pool = SoundPool::new(2).with_steal_policy(StealPolicy::Quietest);
+    /// pool.play(&samples, 0.5, -1.0);
+    /// pool.play(&samples, 0.25, -1.0);
+    /// pool.play(&samples, 1.0, -1.0);
+    /// let mut buffer = [0.0f32; 2];
+    /// pool.mix_into(&mut buffer);
+    /// // The voice with a gain of 0.25 was stopped.
+    /// assert_eq!(buffer[0], 1.5);
+    /// ```
+    pub fn with_steal_policy(mut self, steal_policy: StealPolicy) -> Self {
+        self.steal_policy = steal_policy;
+        self
+    }
+
+    /// Returns a handle that plays sounds in this pool, which can be sent to
+    /// other threads.
+    pub fn handle(&self) -> PoolHandle {
+        PoolHandle { sender: self.sender.clone() }
+    }
+
+    /// Plays the samples from the start of the next call to
+    /// [`mix_into`](Self::mix_into). See [`PoolHandle::play`].
+    pub fn play(&self, samples: &Arc<Vec<f64>>, gain: f64, pan: f64) {
+        send(&self.sender, samples, gain, pan);
+    }
+
+    /// Returns the number of voices that are playing, as of the last call to
+    /// [`mix_into`](Self::mix_into).
+    pub fn active_voices(&self) -> usize {
+        self.voices.len()
+    }
+
+    /// Stops every voice, including those played since the last call to
+    /// [`mix_into`](Self::mix_into).
+    pub fn stop_all(&mut self) {
+        while self.receiver.try_recv().is_ok() {}
+        self.voices.clear();
+    }
+
+    /// Adds the active voices to the interleaved stereo frames in `out`,
+    /// which is not cleared first, and advances each voice by the number of
+    /// frames. Voices played since the last call start at the first frame.
+    /// Voices that reach the end of their samples are removed.
+    pub fn mix_into(&mut self, out: &mut [f32]) {
+        while let Ok(play) = self.receiver.try_recv() {
+            self.start(play);
+        }
+        for voice in &mut self.voices {
+            let remaining = &voice.samples[voice.position..];
+            let mut frames = 0;
+            for (frame, sample) in out.chunks_exact_mut(2).zip(remaining) {
+                frame[0] += (sample * voice.left) as f32;
+                frame[1] += (sample * voice.right) as f32;
+                frames += 1;
+            }
+            voice.position += frames;
+        }
+        self.voices.retain(|voice| voice.position < voice.samples.len());
+    }
+
+    fn start(&mut self, play: Play) {
+        if self.max_voices == 0 {
+            return;
+        }
+        if self.voices.len() >= self.max_voices {
+            let stolen = match self.steal_policy {
+                StealPolicy::Oldest => self.voices.iter().enumerate().min_by_key(|(_, voice)| voice.start),
+                StealPolicy::Quietest => self
+                    .voices
+                    .iter()
+                    .enumerate()
+                    .min_by(|(_, a), (_, b)| a.gain.abs().total_cmp(&b.gain.abs()).then(a.start.cmp(&b.start))),
+            };
+            if let Some((index, _)) = stolen {
+                self.voices.swap_remove(index);
+            }
+        }
+        // Constant-power panning, so that a sound is equally loud at every
+        // position.
+        let angle = (play.pan.clamp(-1.0, 1.0) + 1.0) * std::f64::consts::FRAC_PI_4;
+        self.voices.push(Voice {
+            samples: play.samples,
+            position: 0,
+            gain: play.gain,
+            left: play.gain * crate::math::cos(angle),
+            right: play.gain * crate::math::sin(angle),
+            start: self.started,
+        });
+        self.started += 1;
+    }
+}
+
+/// Plays sounds in a [`SoundPool`] from any thread, while the pool itself
+/// is owned by the audio callback.
+#[derive(Clone)]
+pub struct PoolHandle {
+    sender: Sender<Play>,
+}
+
+impl PoolHandle {
+    /// Plays the samples from the start of the next buffer mixed by the pool,
+    /// scaled by the gain and placed by the pan, which goes from -1 for left
+    /// to 1 for right. Does nothing if the pool has been dropped.
+    pub fn play(&self, samples: &Arc<Vec<f64>>, gain: f64, pan: f64) {
+        send(&self.sender, samples, gain, pan);
+    }
+}
+
+fn send(sender: &Sender<Play>, samples: &Arc<Vec<f64>>, gain: f64, pan: f64) {
+    // The pool may have been dropped, in which case there is nothing to play
+    // the sound.
+    let _ = sender.send(Play {
+        samples: samples.clone(),
+        gain,
+        pan,
+    });
+}
@@ -0,0 +1,154 @@
+//! Number types in which the generator and the filters of the synth do
+//! their arithmetic, selected with [`crate::synth::Precision`]. The stages
+//! are generic over [`Real`], so each precision gets its own monomorphized
+//! copy of their hot loops.
+
+use std::ops::{Add, Mul, Sub};
+
+/// Arithmetic of a precision. Signals are samples around zero; phases are
+/// fractions of a cycle, from 0 up to but excluding 1.
+pub(crate) trait Real: Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Send + Sync + 'static {
+    const ZERO: Self;
+    /// Rounds a signal or a coefficient to this precision.
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+    /// Rounds a phase to this precision.
+    fn phase(value: f64) -> Self;
+    /// Advances a phase by the given increment, in cycles, and wraps it
+    /// into a single cycle.
+    fn advance(self, increment: f64) -> Self;
+    /// Returns the phase of the given harmonic, the phase multiplied by
+    /// `n` and wrapped into a single cycle.
+    fn harmonic(self, n: usize) -> Self;
+}
+
+impl Real for f64 {
+    const ZERO: Self = 0.0;
+
+    #[inline]
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    #[inline]
+    fn phase(value: f64) -> Self {
+        value
+    }
+
+    #[inline]
+    fn advance(self, increment: f64) -> Self {
+        (self + increment).fract()
+    }
+
+    #[inline]
+    fn harmonic(self, n: usize) -> Self {
+        (self * n as f64).fract()
+    }
+}
+
+impl Real for f32 {
+    const ZERO: Self = 0.0;
+
+    #[inline]
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    #[inline]
+    fn phase(value: f64) -> Self {
+        value as f32
+    }
+
+    #[inline]
+    fn advance(self, increment: f64) -> Self {
+        (self + increment as f32).fract()
+    }
+
+    #[inline]
+    fn harmonic(self, n: usize) -> Self {
+        (self * n as f32).fract()
+    }
+}
+
+/// Signed 16-bit fixed point, with 15 bits after the point, so from -1 up to
+/// but excluding 1. Arithmetic on signals saturates instead of wrapping
+/// around. Phases wrap around, as in the phase accumulators of fixed-point
+/// synths.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub(crate) struct Q15(i16);
+
+impl Q15 {
+    const ONE: f64 = 32768.0;
+    /// Mask of the bits of a phase, which is never negative.
+    const PHASE_MASK: i32 = 0x7fff;
+}
+
+impl Add for Q15 {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+}
+
+impl Sub for Q15 {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Mul for Q15 {
+    type Output = Self;
+
+    /// Multiplies with rounding to the nearest step. Only -1 times -1 is out
+    /// of range, and saturates.
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        let product = (self.0 as i32 * other.0 as i32 + (1 << 14)) >> 15;
+        Self(product.clamp(i16::MIN as i32, i16::MAX as i32) as i16)
+    }
+}
+
+impl Real for Q15 {
+    const ZERO: Self = Self(0);
+
+    /// The cast saturates, and turns NaN into 0.
+    #[inline]
+    fn from_f64(value: f64) -> Self {
+        Self((value * Self::ONE).round() as i16)
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self.0 as f64 / Self::ONE
+    }
+
+    #[inline]
+    fn phase(value: f64) -> Self {
+        Self(((value.rem_euclid(1.0) * Self::ONE).round() as i32 & Self::PHASE_MASK) as i16)
+    }
+
+    #[inline]
+    fn advance(self, increment: f64) -> Self {
+        Self(((self.0 as i32 + Self::phase(increment).0 as i32) & Self::PHASE_MASK) as i16)
+    }
+
+    #[inline]
+    fn harmonic(self, n: usize) -> Self {
+        Self(((self.0 as i32).wrapping_mul(n as i32) & Self::PHASE_MASK) as i16)
+    }
+}
@@ -0,0 +1,407 @@
+//! Random sounds of a given kind, like the preset buttons of the `jfxr` tool.
+//! Each kind of [`Example`] has a [`DistributionTable`] that draws the
+//! parameters that make up that kind of sound from ranges that keep it
+//! recognizable. The tables are plain data, so they can be changed, or
+//! replaced by house presets.
+//!
+//! The module also provides [`ui_pack`], a fixed set of designed sounds for
+//! user interface feedback.
+
+use crate::parameter::*;
+use crate::sound::Example;
+use crate::{Random, Sound};
+
+/// How the value of one parameter is drawn when generating a random sound
+/// from a [`DistributionTable`].
+///
+/// Numbers are converted to the type of the parameter when they are set:
+/// they are rounded down for integer parameters, select the value with that
+/// index, rounded down, for enum parameters, and are true unless 0 for
+/// booleans. They are not clamped to the range of the parameter, except
+/// that an index past the last value of an enum selects the last value, and
+/// a negative index the first.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamDistribution {
+    /// A number between the two bounds, inclusive, drawn uniformly. The
+    /// bounds may be in either order; swapping them gives the mirrored value
+    /// for the same random numbers.
+    Uniform(f64, f64),
+    /// A number between the two bounds, inclusive, whose logarithm is drawn
+    /// uniformly, so that each octave of a frequency range is equally
+    /// likely. Both bounds must be positive.
+    LogUniform(f64, f64),
+    /// A value drawn from one of the given distributions, each chosen with
+    /// the same probability. There must be at least one.
+    Choice(Vec<ParamDistribution>),
+    /// Always the given value.
+    Constant(Value),
+}
+
+impl ParamDistribution {
+    /// Returns a choice between the given waveforms.
+    pub fn waveforms(waveforms: &[Waveform]) -> Self {
+        Self::Choice(waveforms.iter().map(|waveform| Self::Constant(Value::Enum(waveform.index()))).collect())
+    }
+
+    /// Draws a value.
+    pub fn sample(&self, rng: &mut Random) -> Value {
+        match self {
+            Self::Uniform(from, to) => Value::Float(rng.uniform(*from, *to)),
+            Self::LogUniform(from, to) => {
+                let value = crate::math::exp(rng.uniform(crate::math::log(*from), crate::math::log(*to)));
+                Value::Float(value.clamp(from.min(*to), from.max(*to)))
+            }
+            Self::Choice(choices) => {
+                // The same draw as `Random::from_slice`.
+                let index = (rng.int(0, choices.len() as i32) as usize).min(choices.len() - 1);
+                choices[index].sample(rng)
+            }
+            Self::Constant(value) => *value,
+        }
+    }
+}
+
+/// The distributions from which a random sound is drawn, for each parameter
+/// that does not keep its default value. Values are drawn in the order of
+/// the table, so the same table and seed always give the same sound.
+///
+/// The built-in tables of [`distributions`] can be used as a starting point
+/// for house presets, and stored with `jfxr::jfxr::write_distributions`.
+///
+/// ```rust
+/// use jfxr::parameter::*;
+/// use jfxr::presets::{DistributionTable, ParamDistribution};
+///
+/// let table = DistributionTable::new()
+///     .with("waveform", ParamDistribution::waveforms(&[Waveform::Sine, Waveform::Square]))
+///     .with("frequency", ParamDistribution::LogUniform(100.0, 2000.0))
+///     .with("frequencySweep", ParamDistribution::Uniform(-100.0, -1000.0))
+///     .with("sustain", ParamDistribution::Uniform(0.05, 0.2))
+///     .with("harmonics", ParamDistribution::Uniform(0.0, 3.0))
+///     .with("decay", ParamDistribution::Choice(vec![
+///         ParamDistribution::Constant(Value::Float(0.1)),
+///         ParamDistribution::Uniform(0.2, 0.4),
+///     ]));
+///
+/// for seed in 0..200 {
+///     let sound = jfxr::Sound::random_from(&table, &mut jfxr::Random::new(seed));
+///     assert!(matches!(sound.waveform, Waveform::Sine | Waveform::Square));
+///     assert!((100.0..=2000.0).contains(&sound.frequency.0));
+///     assert!((-1000.0..=-100.0).contains(&sound.frequency_sweep.0));
+///     assert!((0.05..=0.2).contains(&sound.sustain.0));
+///     assert!((0..=3).contains(&sound.harmonics.0));
+///     assert!(sound.decay.0 == 0.1 || (0.2..=0.4).contains(&sound.decay.0));
+///     // Parameters that are not in the table keep their default value.
+///     assert_eq!(sound.attack.0, jfxr::Sound::default().attack.0);
+///
+///     let again = jfxr::Sound::random_from(&table, &mut jfxr::Random::new(seed));
+///     assert!(sound.diff(&again).is_empty());
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct DistributionTable {
+    distributions: Vec<(&'static Param, ParamDistribution)>,
+}
+
+impl DistributionTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the parameter with the given key in the `jfxr` format to the
+    /// table, replacing any previous distribution.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no parameter with the given key.
+    pub fn with(mut self, key: &str, distribution: ParamDistribution) -> Self {
+        let param = Sound::param(key).unwrap_or_else(|| panic!("unknown parameter {key:?}"));
+        self.set(param, distribution);
+        self
+    }
+
+    /// Adds a parameter to the end of the table, or replaces its
+    /// distribution in place if it is already in the table.
+    pub fn set(&mut self, param: &'static Param, distribution: ParamDistribution) {
+        match self.distributions.iter_mut().find(|(existing, _)| existing.id == param.id) {
+            Some((_, existing)) => *existing = distribution,
+            None => self.distributions.push((param, distribution)),
+        }
+    }
+
+    /// Returns the distribution of the parameter with the given key, if it
+    /// is in the table.
+    pub fn get(&self, key: &str) -> Option<&ParamDistribution> {
+        self.distributions.iter().find(|(param, _)| param.key == key).map(|(_, distribution)| distribution)
+    }
+
+    /// Returns the distribution of the parameter with the given key for
+    /// modification, if it is in the table.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut ParamDistribution> {
+        self.distributions.iter_mut().find(|(param, _)| param.key == key).map(|(_, distribution)| distribution)
+    }
+
+    /// Removes the parameter with the given key from the table.
+    pub fn remove(&mut self, key: &str) {
+        self.distributions.retain(|(param, _)| param.key != key);
+    }
+
+    /// Returns the parameters in the table and their distributions, in the
+    /// order in which they are drawn.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static Param, &ParamDistribution)> + '_ {
+        self.distributions.iter().map(|(param, distribution)| (*param, distribution))
+    }
+
+    pub fn len(&self) -> usize {
+        self.distributions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.distributions.is_empty()
+    }
+
+    /// Draws a value for each parameter in the table, in order, and sets it
+    /// on the given sound. Numbers are converted to the type of the
+    /// parameter as described for [`ParamDistribution`].
+    ///
+    /// ```rust
+    /// use jfxr::parameter::*;
+    /// use jfxr::presets::{DistributionTable, ParamDistribution};
+    ///
+    /// let table = |waveform: f64, normalization: f64| {
+    ///     DistributionTable::new()
+    ///         .with("waveform", ParamDistribution::Constant(Value::Float(waveform)))
+    ///         .with("normalization", ParamDistribution::Constant(Value::Float(normalization)))
+    /// };
+    /// let draw = |table: DistributionTable| {
+    ///     let mut sound = jfxr::Sound::default();
+    ///     table.apply(&mut sound, &mut jfxr::Random::new(0));
+    ///     sound
+    /// };
+    /// let sound = draw(table(2.7, 0.0));
+    /// assert!(sound.waveform == Waveform::VALUES[2]);
+    /// assert!(!sound.normalization.0);
+    /// let sound = draw(table(100.0, 0.5));
+    /// assert!(sound.waveform == *Waveform::VALUES.last().unwrap());
+    /// assert!(sound.normalization.0);
+    /// assert!(draw(table(-1.0, -1.0)).waveform == Waveform::VALUES[0]);
+    ///
+    /// // The upper bound of a uniform draw over the indices is included.
+    /// let table = DistributionTable::new().with("waveform", ParamDistribution::Uniform(0.0, Waveform::VALUES.len() as f64));
+    /// for seed in 0..100 {
+    ///     let mut sound = jfxr::Sound::default();
+    ///     table.apply(&mut sound, &mut jfxr::Random::new(seed));
+    ///     assert!(Waveform::VALUES.contains(&sound.waveform));
+    /// }
+    /// ```
+    pub fn apply(&self, sound: &mut Sound, rng: &mut Random) {
+        for (param, distribution) in self.iter() {
+            let value = match (param.kind, distribution.sample(rng)) {
+                (ParamKind::Integer { .. }, Value::Float(value)) => Value::Integer(value.floor() as i32),
+                (ParamKind::Boolean, Value::Float(value)) => Value::Boolean(value != 0.0),
+                (ParamKind::Enum { names }, Value::Float(value)) => Value::Enum((value.floor().max(0.0) as usize).min(names.len() - 1)),
+                (_, value) => value,
+            };
+            param.set(sound, value);
+        }
+    }
+}
+
+/// Returns the distributions from which [`random`] draws a sound of the
+/// given kind.
+///
+/// ```rust
+/// use jfxr::sound::Example;
+///
+/// let table = jfxr::presets::distributions(Example::Laser);
+/// assert!(table.get("frequencySweep").is_some());
+/// let sound = jfxr::Sound::random_from(&table, &mut jfxr::Random::new(7));
+/// let preset = jfxr::presets::random(Example::Laser, &mut jfxr::Random::new(7));
+/// assert!(sound.diff(&preset).is_empty());
+/// ```
+pub fn distributions(example: Example) -> DistributionTable {
+    use ParamDistribution::{Choice, Constant, Uniform};
+    // Downward ranges from zero start at -0.0, so that a draw at the very
+    // start of the range is the same as in earlier versions.
+    //
+    // Parameters that are turned on half of the time.
+    let sometimes = |distribution: ParamDistribution| Choice(vec![distribution, Constant(Value::Float(0.0))]);
+    let table = DistributionTable::new();
+    match example {
+        Example::Coin => table
+            .with("waveform", ParamDistribution::waveforms(&[Waveform::Sine, Waveform::Square, Waveform::Whistle, Waveform::Breaker]))
+            .with("frequency", Uniform(500.0, 3000.0))
+            .with("sustain", Uniform(0.02, 0.1))
+            .with("sustainPunch", Uniform(0.0, 100.0))
+            .with("decay", Uniform(0.05, 0.4))
+            .with("frequencyJump1Onset", Uniform(10.0, 30.0))
+            .with("frequencyJump1Amount", Uniform(10.0, 100.0)),
+        Example::Laser => table
+            .with("waveform", ParamDistribution::waveforms(&[Waveform::Sine, Waveform::Triangle, Waveform::Sawtooth, Waveform::Square, Waveform::Tangent]))
+            .with("frequency", Uniform(500.0, 2000.0))
+            .with("frequencySweep", Uniform(-200.0, -1500.0))
+            .with("frequencyDeltaSweep", Uniform(-0.0, -500.0))
+            .with("sustain", Uniform(0.02, 0.1))
+            .with("decay", Uniform(0.02, 0.3))
+            .with("squareDuty", Uniform(10.0, 50.0))
+            .with("squareDutySweep", Uniform(-20.0, 20.0)),
+        Example::Explosion => table
+            .with("waveform", ParamDistribution::waveforms(&[Waveform::Whitenoise, Waveform::Pinknoise, Waveform::Brownnoise]))
+            .with("frequency", Uniform(200.0, 2000.0))
+            .with("frequencySweep", Uniform(-0.0, -200.0))
+            .with("sustain", Uniform(0.1, 0.4))
+            .with("sustainPunch", Uniform(20.0, 100.0))
+            .with("decay", Uniform(0.3, 1.5))
+            .with("lowPassCutoff", Uniform(2000.0, 10000.0))
+            .with("lowPassCutoffSweep", Uniform(-0.0, -2000.0))
+            .with("compression", Uniform(0.5, 1.0)),
+        Example::Jump => table
+            .with("waveform", ParamDistribution::waveforms(&[Waveform::Sine, Waveform::Triangle, Waveform::Square]))
+            .with("frequency", Uniform(100.0, 500.0))
+            .with("frequencySweep", Uniform(200.0, 1000.0))
+            .with("sustain", Uniform(0.02, 0.1))
+            .with("decay", Uniform(0.1, 0.3))
+            .with("squareDuty", Uniform(10.0, 50.0)),
+        Example::Hit => table
+            .with("waveform", ParamDistribution::waveforms(&[Waveform::Square, Waveform::Sawtooth, Waveform::Whitenoise, Waveform::Pinknoise]))
+            .with("frequency", Uniform(200.0, 2000.0))
+            .with("frequencySweep", Uniform(-200.0, -1000.0))
+            .with("sustain", Uniform(0.0, 0.05))
+            .with("sustainPunch", Uniform(0.0, 50.0))
+            .with("decay", Uniform(0.05, 0.2))
+            .with("lowPassCutoff", Uniform(2000.0, 10000.0)),
+        Example::Powerup => table
+            .with("waveform", ParamDistribution::waveforms(&[Waveform::Sine, Waveform::Triangle, Waveform::Sawtooth, Waveform::Square]))
+            .with("frequency", Uniform(200.0, 1000.0))
+            .with("frequencySweep", Uniform(200.0, 1500.0))
+            .with("repeatFrequency", sometimes(Uniform(3.0, 15.0)))
+            .with("sustain", Uniform(0.1, 0.4))
+            .with("decay", Uniform(0.1, 0.4))
+            .with("vibratoDepth", sometimes(Uniform(0.0, 50.0)))
+            .with("vibratoFrequency", Uniform(5.0, 20.0)),
+        Example::Blip => table
+            .with("waveform", ParamDistribution::waveforms(&[Waveform::Sine, Waveform::Triangle, Waveform::Square, Waveform::Breaker]))
+            .with("frequency", Uniform(200.0, 2000.0))
+            .with("sustain", Uniform(0.02, 0.08))
+            .with("decay", Uniform(0.01, 0.1))
+            .with("squareDuty", Uniform(10.0, 50.0))
+            .with("harmonics", Uniform(0.0, 3.0)),
+    }
+}
+
+/// Generates a random sound of the given kind, from the table returned by
+/// [`distributions`]. The same seed always gives the same sound, on every
+/// platform.
+///
+/// ```rust
+/// use jfxr::sound::Example;
+///
+/// for example in Example::ALL {
+///     let a = jfxr::presets::random(*example, &mut jfxr::Random::new(42));
+///     let b = jfxr::presets::random(*example, &mut jfxr::Random::new(42));
+///     assert!(a.diff(&b).is_empty());
+///     assert_eq!(a.name, example.name());
+///     for seed in 0..20 {
+///         let sound = jfxr::presets::random(*example, &mut jfxr::Random::new(seed));
+///         let samples = jfxr::try_generate(&sound).unwrap();
+///         assert!(jfxr::analysis::peak(&samples) > 0.5, "{} {seed}", example.name());
+///     }
+/// }
+/// ```
+pub fn random(example: Example, rng: &mut Random) -> Sound {
+    Sound {
+        name: example.name().to_string(),
+        ..Sound::random_from(&distributions(example), rng)
+    }
+}
+
+/// Version of the sounds of [`ui_pack`]. It is increased whenever a sound of
+/// the pack changes, which only happens in releases that are allowed to
+/// change the output; see the crate documentation on output stability.
+pub const UI_PACK_VERSION: u32 = 1;
+
+/// Returns a set of short sounds for user interface feedback, with their
+/// names: `hover`, `click`, `confirm`, `error` and `back`. Unlike
+/// [`random`], these are designed sounds that never change within a
+/// [`UI_PACK_VERSION`]; the hashes of their renders are pinned in
+/// `jfxr::testing::UI_PACK_HASHES`, available with the `test-util` feature.
+/// Each sound is a fraction of a second long, so it renders in a few
+/// milliseconds.
+///
+/// ```rust
+/// let pack = jfxr::presets::ui_pack();
+/// let names: Vec<&str> = pack.iter().map(|(name, _)| *name).collect();
+/// assert_eq!(names, ["hover", "click", "confirm", "error", "back"]);
+/// let renders: Vec<Vec<f64>> = pack.iter().map(|(_, sound)| jfxr::generate(sound)).collect();
+/// for ((name, sound), samples) in pack.iter().zip(&renders) {
+///     assert_eq!(sound.name, *name);
+///     assert!(sound.duration() < 0.3, "{name}");
+///     assert!(jfxr::analysis::peak(samples) > 0.1, "{name}");
+/// }
+/// // Every sound is different from the others.
+/// for (i, a) in renders.iter().enumerate() {
+///     assert!(renders[i + 1..].iter().all(|b| a != b));
+/// }
+/// ```
+pub fn ui_pack() -> Vec<(&'static str, Sound)> {
+    let sound = |name: &str| Sound {
+        name: name.to_string(),
+        ..Default::default()
+    };
+    vec![
+        // A soft, high tick.
+        ("hover", Sound {
+            waveform: Waveform::Sine,
+            frequency: Frequency(1800.0),
+            attack: Attack(0.002),
+            sustain: Sustain(0.005),
+            decay: Decay(0.03),
+            amplification: Amplification(40.0),
+            ..sound("hover")
+        }),
+        // A short, muffled square pulse.
+        ("click", Sound {
+            waveform: Waveform::Square,
+            frequency: Frequency(1200.0),
+            sustain: Sustain(0.005),
+            decay: Decay(0.02),
+            low_pass_cutoff: LowPassCutoff(4000.0),
+            amplification: Amplification(60.0),
+            ..sound("click")
+        }),
+        // Two rising notes, a fifth apart.
+        ("confirm", Sound {
+            waveform: Waveform::Triangle,
+            frequency: Frequency(880.0),
+            sustain: Sustain(0.08),
+            decay: Decay(0.12),
+            frequency_jump1_onset: FrequencyJump1Onset(35.0),
+            frequency_jump1_amount: FrequencyJump1Amount(50.0),
+            amplification: Amplification(70.0),
+            ..sound("confirm")
+        }),
+        // A low, buzzing note that drops.
+        ("error", Sound {
+            waveform: Waveform::Square,
+            frequency: Frequency(220.0),
+            square_duty: SquareDuty(40.0),
+            sustain: Sustain(0.12),
+            decay: Decay(0.1),
+            frequency_jump1_onset: FrequencyJump1Onset(50.0),
+            frequency_jump1_amount: FrequencyJump1Amount(-20.0),
+            low_pass_cutoff: LowPassCutoff(3000.0),
+            amplification: Amplification(70.0),
+            ..sound("error")
+        }),
+        // A quick downward sweep.
+        ("back", Sound {
+            waveform: Waveform::Triangle,
+            frequency: Frequency(900.0),
+            frequency_sweep: FrequencySweep(-2000.0),
+            sustain: Sustain(0.03),
+            decay: Decay(0.06),
+            amplification: Amplification(60.0),
+            ..sound("back")
+        }),
+    ]
+}
@@ -0,0 +1,78 @@
+//! Sounds played one after the other, such as a charge-up followed by a shot.
+
+use std::time::Duration;
+
+use crate::{Sound, SynthError};
+
+/// A list of sounds that are rendered and joined end to end, with
+/// [`crate::convert::concat`].
+///
+/// ```rust
+/// use std::time::Duration;
+/// use jfxr::sound::Example;
+/// use jfxr::Sound;
+///
+/// let sequence = jfxr::Sequence::new(vec![Sound::example(Example::Powerup), Sound::example(Example::Laser)])
+///     .with_crossfade(Duration::from_millis(5));
+/// let samples = sequence.render();
+/// let powerup = Sound::example(Example::Powerup).num_samples();
+/// let laser = Sound::example(Example::Laser).num_samples();
+/// assert_eq!(samples.len(), powerup + laser - 221);
+/// ```
+#[derive(Clone, Default)]
+pub struct Sequence {
+    pub sounds: Vec<Sound>,
+    /// Time over which each sound fades into the next.
+    pub crossfade: Duration,
+}
+
+impl Sequence {
+    /// Creates a sequence of the given sounds, without crossfades.
+    pub fn new(sounds: Vec<Sound>) -> Self {
+        Self {
+            sounds,
+            crossfade: Duration::ZERO,
+        }
+    }
+
+    /// Sets the time over which each sound fades into the next.
+    pub fn with_crossfade(mut self, crossfade: Duration) -> Self {
+        self.crossfade = crossfade;
+        self
+    }
+
+    /// Returns the sample rate of the rendered sequence, which is that of the
+    /// first sound. Sounds with other sample rates are resampled to it.
+    pub fn sample_rate(&self) -> f64 {
+        self.sounds.first().map_or(44100.0, |sound| sound.sample_rate.0)
+    }
+
+    /// Renders every sound and joins them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the sounds cannot be generated, like
+    /// [`crate::generate`].
+    pub fn render(&self) -> Vec<f64> {
+        match self.try_render() {
+            Ok(samples) => samples,
+            Err(error) => panic!("cannot generate sound: {error}"),
+        }
+    }
+
+    /// Renders every sound and joins them, or returns the error of the first
+    /// sound that cannot be generated, like [`crate::try_generate`].
+    pub fn try_render(&self) -> Result<Vec<f64>, SynthError> {
+        let sample_rate = self.sample_rate();
+        let parts = self
+            .sounds
+            .iter()
+            .map(|sound| {
+                let samples = crate::try_generate(sound)?;
+                Ok(crate::convert::resample(&samples, sound.sample_rate.0, sample_rate, crate::convert::ResampleQuality::WindowedSinc))
+            })
+            .collect::<Result<Vec<_>, SynthError>>()?;
+        let parts: Vec<&[f64]> = parts.iter().map(Vec::as_slice).collect();
+        Ok(crate::convert::concat(&parts, self.crossfade, sample_rate))
+    }
+}
@@ -0,0 +1,351 @@
+//! Snapshots of the renders of a directory of `.jfxr` files, to detect when
+//! a change to the sounds or an upgrade of this crate changes how they
+//! sound. Requires the `json` and `wav` features.
+//!
+//! [`make`] renders every sound with [`settings`] and writes each render as
+//! a 16-bit WAV file to the snapshot directory, along with a manifest of
+//! their lengths and hashes. [`verify`] renders the sounds again and
+//! compares them with the snapshot:
+//!
+//! ```rust
+//! use jfxr::snapshot::{MismatchKind, Tolerance};
+//! use jfxr::sound::Example;
+//!
+//! let dir = std::env::temp_dir().join(format!("jfxr-snapshot-doctest-{}", std::process::id()));
+//! let snapshot = dir.join("snapshot");
+//! std::fs::create_dir_all(dir.join("ui")).unwrap();
+//! let write = |path: &str, sound| std::fs::write(dir.join(path), jfxr::write_jfxr(sound)).unwrap();
+//! write("laser.jfxr", jfxr::Sound::example(Example::Laser));
+//! write("ui/blip.jfxr", jfxr::Sound::example(Example::Blip));
+//!
+//! let manifest = jfxr::snapshot::make(&dir, &snapshot).unwrap();
+//! let paths: Vec<_> = manifest.files.iter().map(|file| file.path.as_str()).collect();
+//! assert_eq!(paths, ["laser.jfxr", "ui/blip.jfxr"]);
+//! assert!(snapshot.join("ui/blip.wav").is_file());
+//! assert!(jfxr::snapshot::verify(&dir, &snapshot, Tolerance::Exact).unwrap().is_empty());
+//!
+//! // A change in the last bits of the samples does not change them once
+//! // they are rounded to 16 bits.
+//! let mut laser = jfxr::Sound::example(Example::Laser);
+//! laser.amplification.0 = f64::from_bits(laser.amplification.0.to_bits() + 1);
+//! write("laser.jfxr", laser.clone());
+//! assert!(jfxr::snapshot::verify(&dir, &snapshot, Tolerance::Exact).unwrap().is_empty());
+//!
+//! // An audible change is reported, along with added and removed files.
+//! laser.frequency.0 *= 1.01;
+//! write("laser.jfxr", laser);
+//! write("coin.jfxr", jfxr::Sound::example(Example::Coin));
+//! std::fs::remove_file(dir.join("ui/blip.jfxr")).unwrap();
+//! let mismatches = jfxr::snapshot::verify(&dir, &snapshot, Tolerance::default()).unwrap();
+//! assert!(matches!(mismatches[0].kind, MismatchKind::Added));
+//! assert_eq!(mismatches[0].path, "coin.jfxr");
+//! let MismatchKind::Samples { max_abs_diff, .. } = mismatches[1].kind else { panic!() };
+//! assert!(max_abs_diff > 0.1);
+//! assert!(jfxr::snapshot::verify(&dir, &snapshot, Tolerance::MaxAbsDiff(2.0)).unwrap().len() == 2);
+//! assert!(matches!(mismatches[2].kind, MismatchKind::Removed));
+//! assert_eq!(mismatches.len(), 3);
+//! std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+//!
+//! Renders are compared after rounding them to 16 bits, as in the WAV
+//! files, so that differences in the last bits of the samples, such as
+//! those between the math libraries of different platforms without the
+//! `deterministic` feature, do not fail verification. Such a difference
+//! only changes a rounded sample that lies almost exactly halfway between
+//! two 16-bit values, and then by a single step, which the default
+//! [`Tolerance`] allows.
+
+use std::path::{Path, PathBuf};
+
+use crate::library::LibraryError;
+use crate::synth::OutputSettings;
+
+/// Version of the manifest format written by [`make`].
+const MANIFEST_VERSION: u32 = 1;
+
+/// Name of the manifest in the snapshot directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Difference between two consecutive 16-bit sample values, as a fraction
+/// of full scale.
+pub const STEP: f64 = 1.0 / i16::MAX as f64;
+
+/// Returns the settings with which sounds are rendered for snapshots: at
+/// the sample rate of the sound, in mono, normalized if the sound says so.
+/// These do not change when the defaults of [`OutputSettings`] do.
+pub fn settings() -> OutputSettings {
+    OutputSettings {
+        sample_rate: None,
+        channels: crate::synth::Channels::Mono,
+        sample_format: crate::synth::SampleFormat::F64,
+        block_size: crate::synth::DEFAULT_BLOCK_SIZE,
+        normalization_override: None,
+    }
+}
+
+/// How much a render may differ from the snapshot and still pass
+/// [`verify`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tolerance {
+    /// Every sample must round to the same 16-bit value.
+    Exact,
+    /// No sample may differ by more than this, as a fraction of full scale,
+    /// after rounding to 16 bits.
+    MaxAbsDiff(f64),
+}
+
+impl Default for Tolerance {
+    /// Allows a difference of one [`STEP`].
+    fn default() -> Self {
+        Self::MaxAbsDiff(STEP)
+    }
+}
+
+/// Error that prevents [`make`] or [`verify`] from completing.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// A file or directory could not be read or written.
+    Io(PathBuf, std::io::Error),
+    /// The manifest is not valid.
+    Manifest(String),
+    /// A sound could not be read or rendered while making a snapshot. The
+    /// path is relative to the directory of the sounds.
+    Sound(String, LibraryError),
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            Self::Manifest(message) => write!(f, "invalid snapshot manifest: {message}"),
+            Self::Sound(path, error) => write!(f, "{path}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// The renders in a snapshot, as listed in its manifest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Manifest {
+    /// The files, sorted by path.
+    pub files: Vec<FileSnapshot>,
+}
+
+/// The render of a single file in a snapshot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileSnapshot {
+    /// Path of the `.jfxr` file relative to the directory of the sounds,
+    /// with `/` between components on every platform.
+    pub path: String,
+    /// Number of samples.
+    pub len: usize,
+    /// The [`sample_hash`] of the render.
+    pub hash: u64,
+}
+
+/// A difference between the sounds and their snapshot, found by
+/// [`verify`].
+#[derive(Debug)]
+pub struct Mismatch {
+    /// Path of the `.jfxr` file relative to the directory of the sounds.
+    pub path: String,
+    pub kind: MismatchKind,
+}
+
+#[derive(Debug)]
+pub enum MismatchKind {
+    /// The file is not in the snapshot.
+    Added,
+    /// The file is in the snapshot, but no longer exists.
+    Removed,
+    /// The file can no longer be read or rendered.
+    Failed(LibraryError),
+    /// The render has a different number of samples.
+    Length { expected: usize, actual: usize },
+    /// Some samples differ by more than the tolerance.
+    Samples {
+        /// Index of the first sample that differs by more than the
+        /// tolerance.
+        index: usize,
+        /// Largest difference of a sample, as a fraction of full scale.
+        max_abs_diff: f64,
+    },
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = &self.path;
+        match &self.kind {
+            MismatchKind::Added => write!(f, "{path}: not in the snapshot"),
+            MismatchKind::Removed => write!(f, "{path}: in the snapshot, but missing"),
+            MismatchKind::Failed(error) => write!(f, "{path}: {error}"),
+            MismatchKind::Length { expected, actual } => write!(f, "{path}: {actual} samples instead of {expected}"),
+            MismatchKind::Samples { index, max_abs_diff } => write!(f, "{path}: samples differ by up to {max_abs_diff}, starting at sample {index}"),
+        }
+    }
+}
+
+/// Renders the sound with [`settings`] and rounds the samples to 16 bits,
+/// as in [`crate::wav::write_wav`].
+pub fn render(sound: &crate::sound::Sound) -> Result<Vec<i16>, crate::SynthError> {
+    let samples = crate::Synth::try_with_settings(sound, settings())?.try_generate()?;
+    Ok(samples.iter().map(|sample| (sample.clamp(-1.0, 1.0) * i16::MAX as f64).round() as i16).collect())
+}
+
+/// Returns a 64-bit FNV-1a hash of 16-bit samples.
+pub fn sample_hash(samples: &[i16]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in samples.iter().flat_map(|sample| sample.to_le_bytes()) {
+        hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Renders every `.jfxr` file in `sounds_dir` and its subdirectories, and
+/// writes the snapshot to `snapshot_dir`, which is created if it does not
+/// exist: the render of `<path>.jfxr` to `<path>.wav`, and the manifest to
+/// [`MANIFEST_FILE`]. Files of an earlier snapshot that are no longer
+/// needed are left in place. Fails if any sound cannot be read or
+/// rendered.
+pub fn make(sounds_dir: &Path, snapshot_dir: &Path) -> Result<Manifest, SnapshotError> {
+    let mut files = Vec::new();
+    for path in find_sounds(sounds_dir)? {
+        let sound = read_sound(sounds_dir, &path).map_err(|error| SnapshotError::Sound(path.clone(), error))?;
+        let samples = render(&sound).map_err(|error| SnapshotError::Sound(path.clone(), LibraryError::Synth(error)))?;
+        let wav_path = snapshot_dir.join(&path).with_extension("wav");
+        if let Some(parent) = wav_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|error| SnapshotError::Io(parent.to_path_buf(), error))?;
+        }
+        let mut wav = Vec::with_capacity(44 + 2 * samples.len());
+        let wav_samples: Vec<f64> = samples.iter().map(|&sample| sample as f64 / i16::MAX as f64).collect();
+        crate::wav::write_wav(&wav_samples, sound.sample_rate.0.round() as u32, &mut wav).map_err(|error| SnapshotError::Io(wav_path.clone(), error))?;
+        std::fs::write(&wav_path, wav).map_err(|error| SnapshotError::Io(wav_path.clone(), error))?;
+        files.push(FileSnapshot {
+            len: samples.len(),
+            hash: sample_hash(&samples),
+            path,
+        });
+    }
+    let mut manifest_json = json::object! { "version": MANIFEST_VERSION, "files": [] };
+    for file in &files {
+        manifest_json["files"]
+            .push(json::object! {
+                "path": file.path.as_str(),
+                "samples": file.len,
+                "hash": format!("{:016x}", file.hash),
+            })
+            .unwrap();
+    }
+    let manifest_path = snapshot_dir.join(MANIFEST_FILE);
+    std::fs::write(&manifest_path, manifest_json.pretty(2) + "\n").map_err(|error| SnapshotError::Io(manifest_path, error))?;
+    Ok(Manifest { files })
+}
+
+/// Reads the manifest of a snapshot written by [`make`].
+pub fn read_manifest(snapshot_dir: &Path) -> Result<Manifest, SnapshotError> {
+    let manifest_path = snapshot_dir.join(MANIFEST_FILE);
+    let data = std::fs::read_to_string(&manifest_path).map_err(|error| SnapshotError::Io(manifest_path, error))?;
+    let manifest_json = json::parse(&data).map_err(|error| SnapshotError::Manifest(error.to_string()))?;
+    if manifest_json["version"].as_u32() != Some(MANIFEST_VERSION) {
+        return Err(SnapshotError::Manifest(format!("unsupported version {}", manifest_json["version"])));
+    }
+    let mut files = Vec::new();
+    for file in manifest_json["files"].members() {
+        let (Some(path), Some(len), Some(hash)) = (
+            file["path"].as_str(),
+            file["samples"].as_usize(),
+            file["hash"].as_str().and_then(|hash| u64::from_str_radix(hash, 16).ok()),
+        ) else {
+            return Err(SnapshotError::Manifest(format!("invalid file entry {file}")));
+        };
+        files.push(FileSnapshot { path: path.to_string(), len, hash });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(Manifest { files })
+}
+
+/// Renders every `.jfxr` file in `sounds_dir` and its subdirectories again
+/// and compares it with the snapshot in `snapshot_dir`. Returns the
+/// differences, sorted by path, which are empty if the sounds match the
+/// snapshot. Fails if the snapshot cannot be read.
+pub fn verify(sounds_dir: &Path, snapshot_dir: &Path, tolerance: Tolerance) -> Result<Vec<Mismatch>, SnapshotError> {
+    let manifest = read_manifest(snapshot_dir)?;
+    let paths = find_sounds(sounds_dir)?;
+    let mut snapshots = manifest.files.iter().peekable();
+    let mut mismatches = Vec::new();
+    for path in paths {
+        while let Some(removed) = snapshots.next_if(|file| file.path < path) {
+            mismatches.push(Mismatch { path: removed.path.clone(), kind: MismatchKind::Removed });
+        }
+        let kind = match snapshots.next_if(|file| file.path == path) {
+            None => Some(MismatchKind::Added),
+            Some(file) => match render_file(sounds_dir, &path) {
+                Err(error) => Some(MismatchKind::Failed(error)),
+                Ok(samples) if samples.len() != file.len => Some(MismatchKind::Length { expected: file.len, actual: samples.len() }),
+                Ok(samples) if sample_hash(&samples) == file.hash => None,
+                Ok(samples) => compare(&samples, &read_golden(&snapshot_dir.join(&path).with_extension("wav"))?, tolerance),
+            },
+        };
+        if let Some(kind) = kind {
+            mismatches.push(Mismatch { path, kind });
+        }
+    }
+    mismatches.extend(snapshots.map(|removed| Mismatch { path: removed.path.clone(), kind: MismatchKind::Removed }));
+    Ok(mismatches)
+}
+
+/// Compares a render with the samples of the snapshot, which have the same
+/// length.
+fn compare(samples: &[i16], golden: &[i16], tolerance: Tolerance) -> Option<MismatchKind> {
+    let limit = match tolerance {
+        Tolerance::Exact => 0.0,
+        Tolerance::MaxAbsDiff(limit) => limit,
+    };
+    let diffs = samples.iter().zip(golden).map(|(&sample, &golden)| (sample as i32 - golden as i32).abs() as f64 / i16::MAX as f64);
+    let index = diffs.clone().position(|diff| diff > limit)?;
+    let max_abs_diff = diffs.fold(0.0, f64::max);
+    Some(MismatchKind::Samples { index, max_abs_diff })
+}
+
+/// Reads the samples of a WAV file written by [`make`].
+fn read_golden(path: &Path) -> Result<Vec<i16>, SnapshotError> {
+    let data = std::fs::read(path).map_err(|error| SnapshotError::Io(path.to_path_buf(), error))?;
+    if data.len() < 44 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" || &data[36..40] != b"data" {
+        let error = std::io::Error::new(std::io::ErrorKind::InvalidData, "not a WAV file written by a snapshot");
+        return Err(SnapshotError::Io(path.to_path_buf(), error));
+    }
+    Ok(data[44..].chunks_exact(2).map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]])).collect())
+}
+
+fn read_sound(sounds_dir: &Path, path: &str) -> Result<crate::sound::Sound, LibraryError> {
+    let data = std::fs::read_to_string(sounds_dir.join(path)).map_err(LibraryError::Io)?;
+    crate::read_jfxr(&data).map_err(LibraryError::Format)
+}
+
+fn render_file(sounds_dir: &Path, path: &str) -> Result<Vec<i16>, LibraryError> {
+    render(&read_sound(sounds_dir, path)?).map_err(LibraryError::Synth)
+}
+
+/// Returns the paths of the `.jfxr` files in the directory and its
+/// subdirectories, relative to the directory and sorted.
+fn find_sounds(dir: &Path) -> Result<Vec<String>, SnapshotError> {
+    fn visit(dir: &Path, prefix: &str, paths: &mut Vec<String>) -> Result<(), SnapshotError> {
+        let entries = std::fs::read_dir(dir).and_then(|entries| entries.map(|entry| entry.map(|entry| entry.path())).collect::<std::io::Result<Vec<_>>>());
+        for entry in entries.map_err(|error| SnapshotError::Io(dir.to_path_buf(), error))? {
+            let Some(name) = entry.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if entry.is_dir() {
+                visit(&entry, &format!("{prefix}{name}/"), paths)?;
+            } else if entry.extension().is_some_and(|extension| extension == "jfxr") {
+                paths.push(format!("{prefix}{name}"));
+            }
+        }
+        Ok(())
+    }
+    let mut paths = Vec::new();
+    visit(dir, "", &mut paths)?;
+    paths.sort();
+    Ok(paths)
+}
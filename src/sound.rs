@@ -1,6 +1,13 @@
+use crate::parameter::ParamId;
+
 #[derive(Clone, Default)]
 pub struct Sound {
     pub name: String,
+    /// Keys of the parameters which are locked, in the `jfxr` format, as
+    /// read from the file. Locked parameters are not changed by
+    /// [`Sound::random`]. Use [`Sound::is_locked`] and [`Sound::set_locked`]
+    /// to inspect and change them.
+    pub locked: Vec<String>,
 
     pub sample_rate: crate::parameter::SampleRate,
     pub attack: crate::parameter::Attack,
@@ -12,79 +19,1434 @@ pub struct Sound {
     pub frequency: crate::parameter::Frequency,
     pub frequency_sweep: crate::parameter::FrequencySweep,
     pub frequency_delta_sweep: crate::parameter::FrequencyDeltaSweep,
+    pub frequency_sweep_semitones: crate::parameter::FrequencySweepSemitones,
+    pub frequency_delta_sweep_semitones: crate::parameter::FrequencyDeltaSweepSemitones,
     pub repeat_frequency: crate::parameter::RepeatFrequency,
+    pub phase_reset: crate::parameter::PhaseReset,
+    pub repeat_decay: crate::parameter::RepeatDecay,
+    pub repeat_jitter: crate::parameter::RepeatJitter,
     pub frequency_jump1_onset: crate::parameter::FrequencyJump1Onset,
     pub frequency_jump1_amount: crate::parameter::FrequencyJump1Amount,
     pub frequency_jump2_onset: crate::parameter::FrequencyJump2Onset,
     pub frequency_jump2_amount: crate::parameter::FrequencyJump2Amount,
     pub harmonics: crate::parameter::Harmonics,
     pub harmonics_falloff: crate::parameter::HarmonicsFalloff,
+    pub harmonics_profile: crate::parameter::HarmonicsProfile,
+    /// Relative volume of each harmonic, starting with the base frequency,
+    /// used when the harmonics profile is
+    /// [`crate::parameter::HarmonicsProfile::Custom`]. Missing entries are
+    /// treated as zero.
+    pub harmonics_custom_amplitudes: Vec<f64>,
+    pub harmonics_gain: crate::parameter::HarmonicsGain,
+    pub inharmonicity: crate::parameter::Inharmonicity,
     pub waveform: crate::parameter::Waveform,
+    pub start_phase: crate::parameter::StartPhase,
+    pub noise_interpolation: crate::parameter::NoiseInterpolation,
+    /// Legacy alias of [`Self::noise_interpolation`]. When `false`, noise is
+    /// not interpolated, whatever the noise interpolation; see
+    /// [`Sound::effective_noise_interpolation`]. This field is not part of
+    /// the parameter reflection API, which only has the noise interpolation.
+    #[deprecated(note = "use `noise_interpolation` instead")]
+    #[allow(deprecated)]
     pub interpolate_noise: crate::parameter::InterpolateNoise,
+    pub brown_noise_roughness: crate::parameter::BrownNoiseRoughness,
     pub vibrato_depth: crate::parameter::VibratoDepth,
     pub vibrato_frequency: crate::parameter::VibratoFrequency,
     pub square_duty: crate::parameter::SquareDuty,
     pub square_duty_sweep: crate::parameter::SquareDutySweep,
+    pub tangent_clip: crate::parameter::TangentClip,
+    pub whistle_overtone_ratio: crate::parameter::WhistleOvertoneRatio,
+    pub whistle_overtone_mix: crate::parameter::WhistleOvertoneMix,
+    pub breaker_phase_offset: crate::parameter::BreakerPhaseOffset,
     pub flanger_offset: crate::parameter::FlangerOffset,
     pub flanger_offset_sweep: crate::parameter::FlangerOffsetSweep,
+    pub flanger_depth: crate::parameter::FlangerDepth,
+    pub flanger_rate: crate::parameter::FlangerRate,
+    pub flanger_feedback: crate::parameter::FlangerFeedback,
+    pub chorus_voices: crate::parameter::ChorusVoices,
+    pub chorus_depth: crate::parameter::ChorusDepth,
+    pub chorus_rate: crate::parameter::ChorusRate,
     pub bit_crush: crate::parameter::BitCrush,
     pub bit_crush_sweep: crate::parameter::BitCrushSweep,
     pub low_pass_cutoff: crate::parameter::LowPassCutoff,
     pub low_pass_cutoff_sweep: crate::parameter::LowPassCutoffSweep,
     pub high_pass_cutoff: crate::parameter::HighPassCutoff,
     pub high_pass_cutoff_sweep: crate::parameter::HighPassCutoffSweep,
+    pub reverb_amount: crate::parameter::ReverbAmount,
+    pub reverb_decay: crate::parameter::ReverbDecay,
+    pub reverb_tail: crate::parameter::ReverbTail,
     pub compression: crate::parameter::Compression,
     pub normalization: crate::parameter::Normalization,
+    pub leveling_mode: crate::parameter::LevelingMode,
+    pub leveling_target: crate::parameter::LevelingTarget,
+    pub leveling_max_gain: crate::parameter::LevelingMaxGain,
     pub amplification: crate::parameter::Amplification,
+    pub stereo_width: crate::parameter::StereoWidth,
+    pub pan: crate::parameter::Pan,
+    pub pan_sweep: crate::parameter::PanSweep,
+    pub auto_pan_depth: crate::parameter::AutoPanDepth,
+    pub auto_pan_frequency: crate::parameter::AutoPanFrequency,
+}
+
+macro_rules! params {
+    ($($group:ident { $($field:ident: $ty:ident as $kind:ident = $key:literal,)* })*) => {
+        static PARAMS: &[crate::parameter::Param] = &[$($(params!(@param $group, $field, $ty, $kind, $key),)*)*];
+    };
+    (@param $group:ident, $field:ident, $ty:ident, Float, $key:literal) => {
+        crate::parameter::Param {
+            id: crate::parameter::ParamId::$ty,
+            key: $key,
+            label: <crate::parameter::$ty as crate::parameter::FloatParameter>::LABEL,
+            description: <crate::parameter::$ty as crate::parameter::FloatParameter>::DESCRIPTION,
+            unit: <crate::parameter::$ty as crate::parameter::FloatParameter>::UNIT,
+            group: crate::parameter::ParamGroup::$group,
+            kind: crate::parameter::ParamKind::Float {
+                min_value: <crate::parameter::$ty as crate::parameter::FloatParameter>::MIN_VALUE,
+                max_value: <crate::parameter::$ty as crate::parameter::FloatParameter>::MAX_VALUE,
+                step: <crate::parameter::$ty as crate::parameter::FloatParameter>::STEP,
+                logarithmic: <crate::parameter::$ty as crate::parameter::FloatParameter>::LOGARITHMIC,
+            },
+            disabled_reason: <crate::parameter::$ty as crate::parameter::FloatParameter>::disabled_reason,
+            default_value: || crate::parameter::Value::Float(<crate::parameter::$ty as crate::parameter::FloatParameter>::DEFAULT_VALUE),
+            get: |sound| crate::parameter::Value::Float(sound.$field.0),
+            set: |sound, value| sound.$field.0 = value.as_f64(),
+        }
+    };
+    (@param $group:ident, $field:ident, $ty:ident, Integer, $key:literal) => {
+        crate::parameter::Param {
+            id: crate::parameter::ParamId::$ty,
+            key: $key,
+            label: <crate::parameter::$ty as crate::parameter::IntegerParameter>::LABEL,
+            description: <crate::parameter::$ty as crate::parameter::IntegerParameter>::DESCRIPTION,
+            unit: <crate::parameter::$ty as crate::parameter::IntegerParameter>::UNIT,
+            group: crate::parameter::ParamGroup::$group,
+            kind: crate::parameter::ParamKind::Integer {
+                min_value: <crate::parameter::$ty as crate::parameter::IntegerParameter>::MIN_VALUE,
+                max_value: <crate::parameter::$ty as crate::parameter::IntegerParameter>::MAX_VALUE,
+                step: <crate::parameter::$ty as crate::parameter::IntegerParameter>::STEP,
+            },
+            disabled_reason: <crate::parameter::$ty as crate::parameter::IntegerParameter>::disabled_reason,
+            default_value: || crate::parameter::Value::Integer(<crate::parameter::$ty as crate::parameter::IntegerParameter>::DEFAULT_VALUE),
+            get: |sound| crate::parameter::Value::Integer(sound.$field.0),
+            set: |sound, value| sound.$field.0 = value.as_f64().round() as i32,
+        }
+    };
+    (@param $group:ident, $field:ident, $ty:ident, Boolean, $key:literal) => {
+        crate::parameter::Param {
+            id: crate::parameter::ParamId::$ty,
+            key: $key,
+            label: <crate::parameter::$ty as crate::parameter::BooleanParameter>::LABEL,
+            description: <crate::parameter::$ty as crate::parameter::BooleanParameter>::DESCRIPTION,
+            unit: <crate::parameter::$ty as crate::parameter::BooleanParameter>::UNIT,
+            group: crate::parameter::ParamGroup::$group,
+            kind: crate::parameter::ParamKind::Boolean,
+            disabled_reason: <crate::parameter::$ty as crate::parameter::BooleanParameter>::disabled_reason,
+            default_value: || crate::parameter::Value::Boolean(<crate::parameter::$ty as crate::parameter::BooleanParameter>::DEFAULT_VALUE),
+            get: |sound| crate::parameter::Value::Boolean(sound.$field.0),
+            set: |sound, value| sound.$field.0 = value.as_f64() != 0.0,
+        }
+    };
+    (@param $group:ident, $field:ident, $ty:ident, Enum, $key:literal) => {
+        crate::parameter::Param {
+            id: crate::parameter::ParamId::$ty,
+            key: $key,
+            label: <crate::parameter::$ty as crate::parameter::EnumParameter>::LABEL,
+            description: <crate::parameter::$ty as crate::parameter::EnumParameter>::DESCRIPTION,
+            unit: <crate::parameter::$ty as crate::parameter::EnumParameter>::UNIT,
+            group: crate::parameter::ParamGroup::$group,
+            kind: crate::parameter::ParamKind::Enum {
+                names: <crate::parameter::$ty as crate::parameter::EnumParameter>::NAMES,
+            },
+            disabled_reason: <crate::parameter::$ty as crate::parameter::EnumParameter>::disabled_reason,
+            default_value: || crate::parameter::Value::Enum(crate::parameter::EnumParameter::index(<crate::parameter::$ty as crate::parameter::EnumParameter>::DEFAULT_VALUE)),
+            get: |sound| crate::parameter::Value::Enum(crate::parameter::EnumParameter::index(sound.$field)),
+            set: |sound, value| {
+                let values = <crate::parameter::$ty as crate::parameter::EnumParameter>::VALUES;
+                sound.$field = values.get(value.as_f64() as usize).copied().unwrap_or_default();
+            },
+        }
+    };
+}
+
+params! {
+    Sound {
+        sample_rate: SampleRate as Float = "sampleRate",
+    }
+    Amplitude {
+        attack: Attack as Float = "attack",
+        sustain: Sustain as Float = "sustain",
+        sustain_punch: SustainPunch as Float = "sustainPunch",
+        decay: Decay as Float = "decay",
+        tremolo_depth: TremoloDepth as Float = "tremoloDepth",
+        tremolo_frequency: TremoloFrequency as Float = "tremoloFrequency",
+    }
+    Pitch {
+        frequency: Frequency as Float = "frequency",
+        frequency_sweep: FrequencySweep as Float = "frequencySweep",
+        frequency_delta_sweep: FrequencyDeltaSweep as Float = "frequencyDeltaSweep",
+        frequency_sweep_semitones: FrequencySweepSemitones as Float = "frequencySweepSemitones",
+        frequency_delta_sweep_semitones: FrequencyDeltaSweepSemitones as Float = "frequencyDeltaSweepSemitones",
+        repeat_frequency: RepeatFrequency as Float = "repeatFrequency",
+        phase_reset: PhaseReset as Boolean = "phaseReset",
+        repeat_decay: RepeatDecay as Float = "repeatDecay",
+        repeat_jitter: RepeatJitter as Float = "repeatJitter",
+        frequency_jump1_onset: FrequencyJump1Onset as Float = "frequencyJump1Onset",
+        frequency_jump1_amount: FrequencyJump1Amount as Float = "frequencyJump1Amount",
+        frequency_jump2_onset: FrequencyJump2Onset as Float = "frequencyJump2Onset",
+        frequency_jump2_amount: FrequencyJump2Amount as Float = "frequencyJump2Amount",
+    }
+    Harmonics {
+        harmonics: Harmonics as Integer = "harmonics",
+        harmonics_falloff: HarmonicsFalloff as Float = "harmonicsFalloff",
+        harmonics_profile: HarmonicsProfile as Enum = "harmonicsProfile",
+        harmonics_gain: HarmonicsGain as Enum = "harmonicsGain",
+        inharmonicity: Inharmonicity as Float = "inharmonicity",
+    }
+    Tone {
+        waveform: Waveform as Enum = "waveform",
+        start_phase: StartPhase as Float = "startPhase",
+        noise_interpolation: NoiseInterpolation as Enum = "noiseInterpolation",
+        brown_noise_roughness: BrownNoiseRoughness as Float = "brownNoiseRoughness",
+        vibrato_depth: VibratoDepth as Float = "vibratoDepth",
+        vibrato_frequency: VibratoFrequency as Float = "vibratoFrequency",
+        square_duty: SquareDuty as Float = "squareDuty",
+        square_duty_sweep: SquareDutySweep as Float = "squareDutySweep",
+        tangent_clip: TangentClip as Float = "tangentClip",
+        whistle_overtone_ratio: WhistleOvertoneRatio as Integer = "whistleOvertoneRatio",
+        whistle_overtone_mix: WhistleOvertoneMix as Float = "whistleOvertoneMix",
+        breaker_phase_offset: BreakerPhaseOffset as Float = "breakerPhaseOffset",
+    }
+    Filter {
+        flanger_offset: FlangerOffset as Float = "flangerOffset",
+        flanger_offset_sweep: FlangerOffsetSweep as Float = "flangerOffsetSweep",
+        flanger_depth: FlangerDepth as Float = "flangerDepth",
+        flanger_rate: FlangerRate as Float = "flangerRate",
+        flanger_feedback: FlangerFeedback as Float = "flangerFeedback",
+        chorus_voices: ChorusVoices as Integer = "chorusVoices",
+        chorus_depth: ChorusDepth as Float = "chorusDepth",
+        chorus_rate: ChorusRate as Float = "chorusRate",
+        bit_crush: BitCrush as Integer = "bitCrush",
+        bit_crush_sweep: BitCrushSweep as Integer = "bitCrushSweep",
+        low_pass_cutoff: LowPassCutoff as Float = "lowPassCutoff",
+        low_pass_cutoff_sweep: LowPassCutoffSweep as Float = "lowPassCutoffSweep",
+        high_pass_cutoff: HighPassCutoff as Float = "highPassCutoff",
+        high_pass_cutoff_sweep: HighPassCutoffSweep as Float = "highPassCutoffSweep",
+        reverb_amount: ReverbAmount as Float = "reverbAmount",
+        reverb_decay: ReverbDecay as Float = "reverbDecay",
+        reverb_tail: ReverbTail as Boolean = "reverbTail",
+    }
+    Output {
+        compression: Compression as Float = "compression",
+        normalization: Normalization as Boolean = "normalization",
+        leveling_mode: LevelingMode as Enum = "levelingMode",
+        leveling_target: LevelingTarget as Float = "levelingTarget",
+        leveling_max_gain: LevelingMaxGain as Float = "levelingMaxGain",
+        amplification: Amplification as Float = "amplification",
+        stereo_width: StereoWidth as Float = "stereoWidth",
+        pan: Pan as Float = "pan",
+        pan_sweep: PanSweep as Float = "panSweep",
+        auto_pan_depth: AutoPanDepth as Float = "autoPanDepth",
+        auto_pan_frequency: AutoPanFrequency as Float = "autoPanFrequency",
+    }
+}
+
+/// Constraints for [`Sound::random`].
+#[derive(Clone)]
+pub struct RandomizeOptions {
+    /// Sound to start from. Its locked parameters, as well as its name and
+    /// custom harmonic amplitudes, are kept.
+    pub template: Sound,
+    /// Waveform to use instead of a random one.
+    pub waveform: Option<crate::parameter::Waveform>,
+    /// Maximum duration of the sound, in seconds.
+    pub max_duration: f64,
+    /// Whether noise waveforms may be chosen.
+    pub allow_noise: bool,
+}
+
+impl Default for RandomizeOptions {
+    fn default() -> Self {
+        Self {
+            template: Sound::default(),
+            waveform: None,
+            max_duration: 1.0,
+            allow_noise: true,
+        }
+    }
+}
+
+/// Built-in example sounds, returned by [`Sound::example`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Example {
+    Coin,
+    Laser,
+    Explosion,
+    Jump,
+    Hit,
+    Powerup,
+    Blip,
+}
+
+impl Example {
+    /// All example sounds.
+    pub const ALL: &'static [Example] = &[
+        Example::Coin,
+        Example::Laser,
+        Example::Explosion,
+        Example::Jump,
+        Example::Hit,
+        Example::Powerup,
+        Example::Blip,
+    ];
+
+    /// Returns the name of the example, which is also the name of its sound.
+    pub fn name(self) -> &'static str {
+        match self {
+            Example::Coin => "Coin",
+            Example::Laser => "Laser",
+            Example::Explosion => "Explosion",
+            Example::Jump => "Jump",
+            Example::Hit => "Hit",
+            Example::Powerup => "Powerup",
+            Example::Blip => "Blip",
+        }
+    }
+
+    /// Returns the example with the given name, ignoring case.
+    ///
+    /// ```rust
+    /// use jfxr::sound::Example;
+    ///
+    /// assert_eq!(Example::from_name("explosion"), Some(Example::Explosion));
+    /// assert_eq!(Example::from_name("Boom"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Example> {
+        Self::ALL.iter().copied().find(|example| example.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Parameters that are left alone by [`Sound::random`], because random
+/// values would only make the sound unusable.
+const NOT_RANDOMIZED: &[ParamId] = &[ParamId::SampleRate, ParamId::HarmonicsGain, ParamId::Normalization, ParamId::Amplification];
+
+/// Parameters that [`Sound::random`] always randomizes. The others are only
+/// randomized some of the time, so that not every effect is applied at once.
+const ALWAYS_RANDOMIZED: &[ParamId] = &[ParamId::Attack, ParamId::Sustain, ParamId::Decay, ParamId::Frequency, ParamId::Waveform];
+
+/// Upper limits used by [`Sound::random`] and [`Sound::mutate`] for integer
+/// parameters whose full range makes sounds slow to render.
+const RANDOMIZED_MAX: &[(ParamId, i32)] = &[(ParamId::Harmonics, 5)];
+
+fn randomized_max(id: ParamId, max_value: i32) -> i32 {
+    RANDOMIZED_MAX.iter().find(|(max_id, _)| *max_id == id).map_or(max_value, |(_, max)| *max)
+}
+
+/// A parameter that differs between two sounds, as returned by
+/// [`Sound::diff`]. The values are formatted for display, without the unit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParamChange {
+    /// The parameter, or `None` for the custom harmonic amplitudes, which
+    /// are not a parameter.
+    pub id: Option<crate::parameter::ParamId>,
+    pub label: &'static str,
+    pub unit: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+impl std::fmt::Display for ParamChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} \u{2192} {}", self.label, self.old, self.new)?;
+        if !self.unit.is_empty() {
+            write!(f, " {}", self.unit)?;
+        }
+        Ok(())
+    }
+}
+
+/// A combination of parameters that makes a sound silent, or that has no
+/// effect, as found by [`Sound::diagnose`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// The parameters involved, starting with the one most likely to need
+    /// changing.
+    pub params: Vec<ParamId>,
+    pub message: String,
+    pub suggestion: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.message, self.suggestion)
+    }
 }
 
 impl Sound {
+    /// Returns the volume of each harmonic, starting with the base frequency,
+    /// relative to the other harmonics. The values are not normalized.
+    ///
+    /// A negative number of harmonics, which is out of range, plays the base
+    /// frequency alone:
+    ///
+    /// ```rust
+    /// use jfxr::parameter::Harmonics;
+    ///
+    /// let sound = jfxr::Sound { harmonics: Harmonics(-2), ..jfxr::Sound::example(jfxr::sound::Example::Blip) };
+    /// assert_eq!(sound.harmonic_levels(), [1.0]);
+    /// let base = jfxr::Sound { harmonics: Harmonics(0), ..sound.clone() };
+    /// assert_eq!(jfxr::generate(&sound), jfxr::generate(&base));
+    /// ```
+    pub fn harmonic_levels(&self) -> Vec<f64> {
+        let falloff = self.harmonics_falloff.0;
+        let mut amp = 1.0;
+        (0..=self.harmonics.0.max(0) as usize)
+            .map(|harmonic_index| {
+                let n = (harmonic_index + 1) as f64;
+                let level = match self.harmonics_profile {
+                    crate::parameter::HarmonicsProfile::Geometric => amp,
+                    crate::parameter::HarmonicsProfile::OddOnly if harmonic_index % 2 == 0 => amp,
+                    crate::parameter::HarmonicsProfile::OddOnly => 0.0,
+                    crate::parameter::HarmonicsProfile::OneOverN => 1.0 / n,
+                    crate::parameter::HarmonicsProfile::OneOverNSquared => 1.0 / (n * n),
+                    crate::parameter::HarmonicsProfile::Custom => self.harmonics_custom_amplitudes.get(harmonic_index).copied().unwrap_or(0.0),
+                };
+                amp *= falloff;
+                level
+            })
+            .collect()
+    }
+    /// Returns all parameters of a sound, in the order of the fields of
+    /// [`Sound`]. The name and the custom harmonic amplitudes are not
+    /// parameters.
+    pub fn params() -> &'static [crate::parameter::Param] {
+        PARAMS
+    }
+    /// Returns all parameters of a sound, organized by the section of the
+    /// user interface in which they are shown.
+    ///
+    /// ```rust
+    /// let groups = jfxr::Sound::param_groups();
+    /// for param in jfxr::Sound::params() {
+    ///     assert!(!param.label.is_empty());
+    ///     let count = groups.iter().filter(|(_, params)| params.iter().any(|p| p.key == param.key)).count();
+    ///     assert_eq!(count, 1, "{}", param.key);
+    /// }
+    /// ```
+    pub fn param_groups() -> Vec<(crate::parameter::ParamGroup, Vec<&'static crate::parameter::Param>)> {
+        crate::parameter::ParamGroup::ALL
+            .iter()
+            .map(|group| (*group, PARAMS.iter().filter(|param| param.group == *group).collect()))
+            .collect()
+    }
+    /// Returns the parameter with the given key in the `jfxr` format, such as
+    /// `"frequencySweep"`.
+    pub fn param(key: &str) -> Option<&'static crate::parameter::Param> {
+        PARAMS.iter().find(|param| param.key == key)
+    }
+    /// Returns whether the given parameter is locked.
+    pub fn is_locked(&self, id: ParamId) -> bool {
+        self.locked.iter().any(|key| key == id.key())
+    }
+    /// Locks or unlocks the given parameter.
+    ///
+    /// ```rust
+    /// use jfxr::parameter::ParamId;
+    ///
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.set_locked(ParamId::FrequencySweep, true);
+    /// sound.set_locked(ParamId::FrequencySweep, true);
+    /// assert_eq!(sound.locked, ["frequencySweep"]);
+    /// assert!(sound.is_locked(ParamId::FrequencySweep));
+    /// sound.set_locked(ParamId::FrequencySweep, false);
+    /// assert!(sound.locked.is_empty());
+    /// ```
+    pub fn set_locked(&mut self, id: ParamId, locked: bool) {
+        if !locked {
+            self.locked.retain(|key| key != id.key());
+        } else if !self.is_locked(id) {
+            self.locked.push(id.key().to_string());
+        }
+    }
+    /// Returns the parameters that differ between this sound and `other`,
+    /// with the values of this sound as the old values. The custom harmonic
+    /// amplitudes are compared too, and listed last.
+    ///
+    /// ```rust
+    /// let old = jfxr::Sound::default();
+    /// let mut new = old.clone();
+    /// new.frequency.0 = 720.0;
+    /// new.waveform = jfxr::parameter::Waveform::Sawtooth;
+    /// assert_eq!(old.format_diff(&new), "Frequency: 500 \u{2192} 720 Hz\nWaveform: sine \u{2192} sawtooth");
+    ///
+    /// let mut custom = old.clone();
+    /// custom.harmonics_custom_amplitudes = vec![1.0, 0.5];
+    /// assert_eq!(old.diff(&custom)[0].id, None);
+    /// assert_eq!(old.format_diff(&custom), "Custom harmonic amplitudes: [] \u{2192} [1, 0.5]");
+    /// ```
+    pub fn diff(&self, other: &Sound) -> Vec<ParamChange> {
+        let amplitudes = (self.harmonics_custom_amplitudes != other.harmonics_custom_amplitudes).then(|| {
+            let format = |amplitudes: &[f64]| {
+                format!("[{}]", amplitudes.iter().map(f64::to_string).collect::<Vec<_>>().join(", "))
+            };
+            ParamChange {
+                id: None,
+                label: "Custom harmonic amplitudes",
+                unit: "",
+                old: format(&self.harmonics_custom_amplitudes),
+                new: format(&other.harmonics_custom_amplitudes),
+            }
+        });
+        PARAMS
+            .iter()
+            .filter_map(|param| {
+                let (old, new) = (param.get(self), param.get(other));
+                if old == new {
+                    return None;
+                }
+                let (mut old, mut new) = (param.format_value(old), param.format_value(new));
+                if old == new {
+                    // The difference is smaller than the step of the
+                    // parameter; show the full values instead.
+                    (old, new) = (param.get(self).as_f64().to_string(), param.get(other).as_f64().to_string());
+                }
+                Some(ParamChange {
+                    id: Some(param.id),
+                    label: param.label,
+                    unit: param.unit,
+                    old,
+                    new,
+                })
+            })
+            .chain(amplitudes)
+            .collect()
+    }
+    /// Formats the result of [`Self::diff`] as one line per changed
+    /// parameter.
+    pub fn format_diff(&self, other: &Sound) -> String {
+        self.diff(other)
+            .iter()
+            .map(ParamChange::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    /// Checks the sound for combinations of parameters that make it silent,
+    /// or that make a parameter ineffective, without rendering it. The
+    /// checks only look at the parameters; a sound that passes them may
+    /// still be silent, and [`crate::Synth::render_report`] points at the
+    /// diagnostics of renders that turn out so.
+    ///
+    /// Each rule, starting from a plain sine wave, which passes them all:
+    ///
+    /// ```rust
+    /// use jfxr::parameter::{HarmonicsProfile, ParamId, Sustain, Waveform};
+    ///
+    /// let culprits = |sound: &jfxr::Sound| sound.diagnose().iter().map(|diagnostic| diagnostic.params.clone()).collect::<Vec<_>>();
+    /// let silent = |sound: &jfxr::Sound| jfxr::analysis::peak(&sound.render()) < 1e-3;
+    /// let default = jfxr::Sound { sustain: Sustain(0.2), ..jfxr::Sound::default() };
+    /// assert!(default.diagnose().is_empty());
+    ///
+    /// let sound = jfxr::Sound { amplification: jfxr::parameter::Amplification(0.0), ..default.clone() };
+    /// assert_eq!(culprits(&sound), [vec![ParamId::Amplification]]);
+    /// assert!(silent(&sound));
+    ///
+    /// let mut sound = default.clone();
+    /// (sound.attack.0, sound.sustain.0, sound.decay.0) = (0.0, 0.0, 0.0);
+    /// assert_eq!(culprits(&sound), [vec![ParamId::Sustain, ParamId::Attack, ParamId::Decay]]);
+    /// assert!(jfxr::try_generate(&sound).is_err());
+    /// assert!(sound.render().is_empty());
+    ///
+    /// let mut sound = default.clone();
+    /// (sound.sustain.0, sound.decay.0, sound.sustain_punch.0) = (0.0, 0.2, 50.0);
+    /// assert_eq!(culprits(&sound), [vec![ParamId::Sustain, ParamId::SustainPunch]]);
+    /// assert_eq!(sound.render(), jfxr::Sound { sustain_punch: Default::default(), ..sound.clone() }.render());
+    ///
+    /// let mut sound = default.clone();
+    /// (sound.tremolo_depth.0, sound.tremolo_frequency.0) = (100.0, 0.0);
+    /// assert_eq!(culprits(&sound), [vec![ParamId::TremoloFrequency, ParamId::TremoloDepth]]);
+    /// assert!(silent(&sound));
+    ///
+    /// // A sweep that opens the filter up again is fine.
+    /// let mut sound = default.clone();
+    /// sound.low_pass_cutoff.0 = 0.0;
+    /// assert_eq!(culprits(&sound), [vec![ParamId::LowPassCutoff, ParamId::LowPassCutoffSweep]]);
+    /// assert!(silent(&sound));
+    /// sound.low_pass_cutoff_sweep.0 = 5000.0;
+    /// assert!(sound.diagnose().is_empty());
+    ///
+    /// let mut sound = default.clone();
+    /// (sound.waveform, sound.high_pass_cutoff.0) = (Waveform::Whitenoise, 22050.0);
+    /// assert_eq!(culprits(&sound), [vec![ParamId::HighPassCutoff, ParamId::HighPassCutoffSweep]]);
+    /// assert!(silent(&sound));
+    /// // A sine wave, with its third harmonic at 1500 Hz, has nothing above
+    /// // a lower cutoff either.
+    /// (sound.waveform, sound.harmonics.0, sound.high_pass_cutoff.0) = (Waveform::Sine, 2, 2000.0);
+    /// assert_eq!(culprits(&sound), [vec![ParamId::HighPassCutoff, ParamId::HighPassCutoffSweep, ParamId::Frequency]]);
+    /// sound.harmonics.0 = 4;
+    /// assert!(sound.diagnose().is_empty());
+    ///
+    /// let mut sound = default.clone();
+    /// (sound.harmonics.0, sound.harmonics_profile) = (3, HarmonicsProfile::Custom);
+    /// sound.harmonics_custom_amplitudes = vec![0.0; 4];
+    /// assert_eq!(culprits(&sound), [vec![ParamId::HarmonicsProfile, ParamId::Harmonics]]);
+    /// assert!(silent(&sound));
+    /// ```
+    pub fn diagnose(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut push = |params: &[ParamId], message: String, suggestion: &str| {
+            diagnostics.push(Diagnostic {
+                params: params.to_vec(),
+                message,
+                suggestion: suggestion.to_string(),
+            });
+        };
+        if self.amplification.0 <= 0.0 {
+            push(&[ParamId::Amplification], "the amplification is 0%, which silences the output".to_string(), "raise the amplification");
+        }
+        if self.duration() <= 0.0 {
+            push(
+                &[ParamId::Sustain, ParamId::Attack, ParamId::Decay],
+                "the attack, sustain and decay are all 0 s, so there is nothing to render".to_string(),
+                "lengthen the sustain",
+            );
+        } else if self.sustain.0 <= 0.0 && self.sustain_punch.0 != 0.0 {
+            push(
+                &[ParamId::Sustain, ParamId::SustainPunch],
+                "the sustain punch has no effect, because the sustain is 0 s".to_string(),
+                "lengthen the sustain, or set the sustain punch to 0%",
+            );
+        }
+        if self.tremolo_depth.0 >= 100.0 && self.tremolo_frequency.0 <= 0.0 {
+            push(
+                &[ParamId::TremoloFrequency, ParamId::TremoloDepth],
+                "a tremolo of 100% at 0 Hz keeps the volume at 0".to_string(),
+                "raise the tremolo frequency, or lower the tremolo depth",
+            );
+        }
+        if self.harmonic_levels().iter().all(|level| *level == 0.0) {
+            push(
+                &[ParamId::HarmonicsProfile, ParamId::Harmonics],
+                "every harmonic has a custom amplitude of 0".to_string(),
+                "set the custom amplitudes, or pick another harmonics profile",
+            );
+        }
+        if self.low_pass_cutoff.0.max(self.low_pass_cutoff.0 + self.low_pass_cutoff_sweep.0) <= 0.0 {
+            push(
+                &[ParamId::LowPassCutoff, ParamId::LowPassCutoffSweep],
+                "the low-pass cutoff stays at 0 Hz, which removes the whole sound".to_string(),
+                "raise the low-pass cutoff",
+            );
+        }
+        // Like the filter, which clamps the cutoff to the Nyquist frequency.
+        let high_pass_cutoff = self.high_pass_cutoff.0.min(self.high_pass_cutoff.0 + self.high_pass_cutoff_sweep.0);
+        let nyquist = self.sample_rate.0 / 2.0;
+        if high_pass_cutoff >= nyquist {
+            push(
+                &[ParamId::HighPassCutoff, ParamId::HighPassCutoffSweep],
+                format!("the high-pass cutoff stays at {nyquist} Hz, half the sample rate, which removes the whole sound"),
+                "lower the high-pass cutoff",
+            );
+        } else if self.waveform == crate::parameter::Waveform::Sine && self.inharmonicity.0 == 0.0 {
+            // Other waveforms have overtones up to the Nyquist frequency. The
+            // highest frequency is estimated from evenly spaced times, which
+            // can miss the very top of a fast vibrato.
+            let fundamental = self.frequencies_between(0.0, self.duration(), 1024).into_iter().fold(0.0, f64::max);
+            let partials = self.harmonic_levels().iter().rposition(|level| *level != 0.0).map_or(1, |index| index + 1);
+            let highest = fundamental * partials as f64;
+            if high_pass_cutoff > highest {
+                push(
+                    &[ParamId::HighPassCutoff, ParamId::HighPassCutoffSweep, ParamId::Frequency],
+                    format!("the high-pass cutoff stays above {highest:.0} Hz, the highest frequency of the sound, which weakens all of it"),
+                    "lower the high-pass cutoff, or raise the frequency",
+                );
+            }
+        }
+        diagnostics
+    }
+    /// Returns the changes from `before` to `after` as a JSON patch, with
+    /// the old and the new value of each changed parameter, as written by
+    /// [`crate::jfxr::write_delta`]. Only the changed parameters are in the
+    /// patch, so patches of edits to different parameters can be applied to
+    /// the same sound with [`Self::apply_patch_json`] in any order.
+    #[cfg(feature = "json")]
+    pub fn make_patch_json(before: &Sound, after: &Sound) -> String {
+        crate::jfxr::write_delta(&crate::patch::SoundDelta::from_diff(before, after))
+    }
+    /// Applies a JSON patch made with [`Self::make_patch_json`]. If any
+    /// parameter of this sound has neither the old nor the new value in the
+    /// patch, because it was changed in the meantime, the sound is left
+    /// unchanged and the conflicts are returned; use
+    /// [`Self::force_patch_json`] to overwrite them. See
+    /// [`crate::patch::SoundDelta::apply`].
+    ///
+    /// ```rust
+    /// use jfxr::jfxr::PatchError;
+    /// use jfxr::parameter::ParamId;
+    ///
+    /// let base = jfxr::Sound::example(jfxr::sound::Example::Jump);
+    /// let mut edited = base.clone();
+    /// edited.sustain.0 = 0.3;
+    /// edited.phase_reset.0 = !base.phase_reset.0;
+    /// let patch = jfxr::Sound::make_patch_json(&base, &edited);
+    /// assert_eq!(patch, format!(r#"{{"sustain":[{},0.3],"phaseReset":[{},{}]}}"#, base.sustain.0, base.phase_reset.0, !base.phase_reset.0));
+    ///
+    /// // Someone else changed other parameters in the meantime.
+    /// let mut sound = base.clone();
+    /// sound.decay.0 = 0.4;
+    /// sound.apply_patch_json(&patch).unwrap();
+    /// assert_eq!((sound.sustain.0, sound.decay.0), (0.3, 0.4));
+    /// // Applying it again changes nothing.
+    /// sound.apply_patch_json(&patch).unwrap();
+    ///
+    /// // Someone else changed the sustain too.
+    /// let mut sound = base.clone();
+    /// sound.sustain.0 = 0.5;
+    /// let Err(PatchError::Conflicts(conflicts)) = sound.apply_patch_json(&patch) else { panic!() };
+    /// assert_eq!(conflicts[0].id(), Some(ParamId::Sustain));
+    /// assert_eq!(sound.phase_reset.0, base.phase_reset.0);
+    /// sound.force_patch_json(&patch).unwrap();
+    /// assert!(sound.diff(&edited).is_empty());
+    ///
+    /// assert!(matches!(sound.apply_patch_json("{"), Err(PatchError::Format(_))));
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn apply_patch_json(&mut self, json: &str) -> Result<(), crate::jfxr::PatchError> {
+        crate::jfxr::read_delta(json)?.apply(self).map_err(crate::jfxr::PatchError::Conflicts)
+    }
+    /// Applies a JSON patch made with [`Self::make_patch_json`], setting the
+    /// new values whatever the current values are.
+    #[cfg(feature = "json")]
+    pub fn force_patch_json(&mut self, json: &str) -> Result<(), crate::jfxr::JfxrFormatError> {
+        crate::jfxr::read_delta(json)?.force_apply(self);
+        Ok(())
+    }
+    /// Returns one of the built-in example sounds. These are constructed in
+    /// code, so they are available without the `json` feature, and are meant
+    /// for demos, tests and benchmarks.
+    ///
+    /// ```rust
+    /// use jfxr::sound::Example;
+    ///
+    /// let renders: Vec<Vec<f64>> = Example::ALL
+    ///     .iter()
+    ///     .map(|example| jfxr::generate(&jfxr::Sound::example(*example)))
+    ///     .collect();
+    /// for (example, samples) in Example::ALL.iter().zip(&renders) {
+    ///     assert!(samples.iter().all(|sample| sample.is_finite()), "{example:?}");
+    ///     assert!(samples.iter().any(|sample| sample.abs() > 0.1), "{example:?}");
+    /// }
+    /// for (i, a) in renders.iter().enumerate() {
+    ///     for b in &renders[i + 1..] {
+    ///         assert_ne!(a, b);
+    ///     }
+    /// }
+    /// ```
+    pub fn example(example: Example) -> Sound {
+        use crate::parameter::*;
+        let sound = Sound {
+            name: example.name().to_string(),
+            harmonics_falloff: HarmonicsFalloff(0.5),
+            ..Default::default()
+        };
+        match example {
+            Example::Coin => Sound {
+                waveform: Waveform::Square,
+                frequency: Frequency(1000.0),
+                sustain: Sustain(0.05),
+                sustain_punch: SustainPunch(50.0),
+                decay: Decay(0.25),
+                frequency_jump1_onset: FrequencyJump1Onset(15.0),
+                frequency_jump1_amount: FrequencyJump1Amount(33.0),
+                ..sound
+            },
+            Example::Laser => Sound {
+                waveform: Waveform::Square,
+                frequency: Frequency(1500.0),
+                frequency_sweep: FrequencySweep(-1200.0),
+                sustain: Sustain(0.1),
+                decay: Decay(0.15),
+                square_duty: SquareDuty(25.0),
+                square_duty_sweep: SquareDutySweep(20.0),
+                ..sound
+            },
+            Example::Explosion => Sound {
+                waveform: Waveform::Brownnoise,
+                frequency: Frequency(1000.0),
+                sustain: Sustain(0.3),
+                sustain_punch: SustainPunch(60.0),
+                decay: Decay(0.8),
+                low_pass_cutoff: LowPassCutoff(4000.0),
+                low_pass_cutoff_sweep: LowPassCutoffSweep(-3500.0),
+                ..sound
+            },
+            Example::Jump => Sound {
+                waveform: Waveform::Square,
+                frequency: Frequency(300.0),
+                frequency_sweep: FrequencySweep(600.0),
+                sustain: Sustain(0.1),
+                decay: Decay(0.15),
+                ..sound
+            },
+            Example::Hit => Sound {
+                waveform: Waveform::Whitenoise,
+                frequency: Frequency(1500.0),
+                frequency_sweep: FrequencySweep(-1000.0),
+                sustain: Sustain(0.02),
+                decay: Decay(0.15),
+                low_pass_cutoff: LowPassCutoff(6000.0),
+                ..sound
+            },
+            Example::Powerup => Sound {
+                waveform: Waveform::Triangle,
+                frequency: Frequency(400.0),
+                frequency_sweep: FrequencySweep(800.0),
+                repeat_frequency: RepeatFrequency(8.0),
+                sustain: Sustain(0.4),
+                decay: Decay(0.2),
+                harmonics: Harmonics(2),
+                ..sound
+            },
+            Example::Blip => Sound {
+                waveform: Waveform::Sine,
+                frequency: Frequency(880.0),
+                sustain: Sustain(0.04),
+                decay: Decay(0.05),
+                ..sound
+            },
+        }
+    }
+    /// Generates a random sound, like the "Random" button of the `jfxr` tool.
+    /// Each parameter is drawn from its range: uniformly, or log-uniformly
+    /// for logarithmic parameters. Apart from the envelope, frequency and
+    /// waveform, each parameter keeps its template value two thirds of the
+    /// time. Harmonics are drawn from 0 to 5, the range of the `jfxr` tool,
+    /// rather than up to their maximum.
+    ///
+    /// ```rust
+    /// let options = jfxr::sound::RandomizeOptions::default();
+    /// let a = jfxr::Sound::random(&mut jfxr::Random::new(42), &options);
+    /// let b = jfxr::Sound::random(&mut jfxr::Random::new(42), &options);
+    /// assert!(a.diff(&b).is_empty());
+    ///
+    /// for seed in 0..100 {
+    ///     let sound = jfxr::Sound::random(&mut jfxr::Random::new(seed), &options);
+    ///     assert!(sound.duration() <= options.max_duration + 1e-9);
+    ///     // Parameters that were not randomized keep the template value.
+    ///     for param in jfxr::Sound::params().iter().filter(|param| param.get(&sound) != param.get(&options.template)) {
+    ///         let value = param.get(&sound).as_f64();
+    ///         match param.kind {
+    ///             jfxr::parameter::ParamKind::Float { min_value, max_value, .. } => assert!(value >= min_value && value <= max_value, "{}", param.key),
+    ///             jfxr::parameter::ParamKind::Integer { min_value, max_value, .. } => assert!(value >= min_value as f64 && value <= max_value as f64, "{}", param.key),
+    ///             _ => {}
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn random(rng: &mut crate::oscillator::Random, options: &RandomizeOptions) -> Sound {
+        use crate::parameter::{EnumParameter, ParamKind, Value, Waveform};
+        let mut sound = options.template.clone();
+        let is_locked = |id| options.template.is_locked(id);
+        let mut changes = Vec::new();
+        for param in PARAMS {
+            if NOT_RANDOMIZED.contains(&param.id) || is_locked(param.id) {
+                continue;
+            }
+            if !ALWAYS_RANDOMIZED.contains(&param.id) && !rng.boolean(1.0 / 3.0) {
+                continue;
+            }
+            let value = match param.kind {
+                ParamKind::Float { min_value, max_value, step, logarithmic } => {
+                    if logarithmic {
+                        // Negative ranges are mirrored, with a random sign.
+                        let sign = if min_value < 0.0 && rng.boolean(0.5) { -1.0 } else { 1.0 };
+                        let max_magnitude = if sign < 0.0 { -min_value } else { max_value };
+                        let min_magnitude = if min_value < 0.0 { step } else { min_value.max(step) };
+                        Value::Float(sign * crate::math::exp(rng.uniform(crate::math::log(min_magnitude), crate::math::log(max_magnitude))).clamp(min_magnitude, max_magnitude))
+                    } else {
+                        Value::Float(rng.uniform(min_value, max_value))
+                    }
+                }
+                ParamKind::Integer { min_value, max_value, .. } => {
+                    let max_value = randomized_max(param.id, max_value);
+                    Value::Integer(rng.int(min_value, max_value + 1).min(max_value))
+                }
+                ParamKind::Boolean => Value::Boolean(rng.boolean(0.5)),
+                ParamKind::Enum { names } => Value::Enum(rng.int(0, names.len() as i32).min(names.len() as i32 - 1) as usize),
+            };
+            changes.push((param, value));
+        }
+        for (param, value) in changes {
+            param.set(&mut sound, value);
+        }
+
+        if !is_locked(ParamId::Waveform) {
+            let waveforms: Vec<Waveform> = Waveform::VALUES
+                .iter()
+                .copied()
+                .filter(|waveform| options.allow_noise || !waveform.is_noise())
+                .collect();
+            sound.waveform = options.waveform.unwrap_or_else(|| rng.from_slice(&waveforms));
+        }
+
+        // Shorten the unlocked parts of the envelope to fit the duration.
+        let duration = sound.duration();
+        if duration > options.max_duration {
+            let locked_duration: f64 = [(ParamId::Attack, sound.attack.0), (ParamId::Sustain, sound.sustain.0), (ParamId::Decay, sound.decay.0)]
+                .iter()
+                .filter(|(id, _)| is_locked(*id))
+                .map(|(_, value)| value)
+                .sum();
+            let factor = ((options.max_duration - locked_duration) / (duration - locked_duration)).max(0.0);
+            if !is_locked(ParamId::Attack) { sound.attack.0 *= factor; }
+            if !is_locked(ParamId::Sustain) { sound.sustain.0 *= factor; }
+            if !is_locked(ParamId::Decay) { sound.decay.0 *= factor; }
+        }
+        sound
+    }
+    /// Generates a random sound from the given distributions, starting from
+    /// the default sound. See [`crate::presets::DistributionTable`].
+    pub fn random_from(table: &crate::presets::DistributionTable, rng: &mut crate::oscillator::Random) -> Sound {
+        let mut sound = Sound::default();
+        table.apply(&mut sound, rng);
+        sound
+    }
+    /// Combines two sounds into a new one, taking each parameter from either
+    /// parent at random. Parameters that are locked in `a` are always taken
+    /// from `a`, as are the name and the locked parameters themselves. The
+    /// custom harmonic amplitudes go together with the harmonics profile.
+    ///
+    /// ```rust
+    /// let options = jfxr::sound::RandomizeOptions::default();
+    /// let a = jfxr::Sound::random(&mut jfxr::Random::new(1), &options);
+    /// let b = jfxr::Sound::random(&mut jfxr::Random::new(2), &options);
+    /// let child = jfxr::Sound::crossover(&a, &b, &mut jfxr::Random::new(3));
+    /// for param in jfxr::Sound::params() {
+    ///     let value = param.get(&child);
+    ///     assert!(value == param.get(&a) || value == param.get(&b));
+    /// }
+    /// let again = jfxr::Sound::crossover(&a, &b, &mut jfxr::Random::new(3));
+    /// assert!(child.diff(&again).is_empty());
+    /// ```
+    pub fn crossover(a: &Sound, b: &Sound, rng: &mut crate::oscillator::Random) -> Sound {
+        let mut sound = a.clone();
+        for param in PARAMS {
+            if a.is_locked(param.id) {
+                continue;
+            }
+            if rng.boolean(0.5) {
+                param.set(&mut sound, param.get(b));
+                if param.id == ParamId::HarmonicsProfile {
+                    sound.harmonics_custom_amplitudes = b.harmonics_custom_amplitudes.clone();
+                }
+            }
+        }
+        sound
+    }
+    /// Changes each unlocked numeric parameter by a random amount of up to
+    /// `amount` times its range, like the "Mutate" button of the `jfxr` tool.
+    /// The results are clamped to the range of each parameter. Booleans and
+    /// enums are left alone. As with [`Sound::random`], harmonics are not
+    /// raised above 5, which keeps sounds quick to render.
+    pub fn mutate(&mut self, rng: &mut crate::oscillator::Random, amount: f64) {
+        use crate::parameter::{ParamKind, Value};
+        for param in PARAMS {
+            if NOT_RANDOMIZED.contains(&param.id) || self.is_locked(param.id) {
+                continue;
+            }
+            let value = param.get(self).as_f64();
+            let value = match param.kind {
+                ParamKind::Float { min_value, max_value, .. } => {
+                    Value::Float((value + rng.uniform(-amount, amount) * (max_value - min_value)).clamp(min_value, max_value))
+                }
+                ParamKind::Integer { min_value, max_value, .. } => {
+                    // Values beyond the randomized range may stay there, but
+                    // are not pushed further.
+                    let randomized_max = randomized_max(param.id, max_value);
+                    let delta = rng.uniform(-amount, amount) * (randomized_max - min_value) as f64;
+                    let max_value = if value > randomized_max as f64 { value as i32 } else { randomized_max };
+                    Value::Integer(((value + delta).round() as i32).clamp(min_value, max_value))
+                }
+                ParamKind::Boolean | ParamKind::Enum { .. } => continue,
+            };
+            param.set(self, value);
+        }
+    }
+    /// Rounds every parameter to its step, so that it matches the
+    /// granularity of the sliders in the `jfxr` tool.
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.frequency.0 = 1234.5;
+    /// sound.decay.0 = 0.123;
+    /// sound.bit_crush_sweep.0 = -7;
+    /// sound.square_duty_sweep.0 = -12.6;
+    /// sound.snap_all();
+    /// assert_eq!(sound.frequency.0, 1200.0);
+    /// assert_eq!(sound.decay.0, 0.12);
+    /// assert_eq!(sound.bit_crush_sweep.0, -7);
+    /// assert_eq!(sound.square_duty_sweep.0, -15.0);
+    /// ```
+    pub fn snap_all(&mut self) {
+        for param in PARAMS {
+            param.set_snapped(self, param.get(self));
+        }
+    }
+    /// Limits every parameter to its range, as described in
+    /// [`crate::parameter::Param::clamp`].
+    ///
+    /// ```rust
+    /// use jfxr::parameter::FloatParameter;
+    ///
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.frequency.0 = 1e9;
+    /// sound.decay.0 = f64::NAN;
+    /// sound.harmonics.0 = -3;
+    /// sound.clamp_all();
+    /// assert_eq!(sound.frequency.0, jfxr::parameter::Frequency::MAX_VALUE);
+    /// assert_eq!(sound.decay.0, jfxr::parameter::Decay::MIN_VALUE);
+    /// assert_eq!(sound.harmonics.0, 0);
+    /// ```
+    pub fn clamp_all(&mut self) {
+        for param in PARAMS {
+            param.set(self, param.clamp(param.get(self)));
+        }
+    }
+    /// Renders the sound with a fresh [`crate::Synth`], like
+    /// [`crate::generate`]. Since every render has its own synth, the same
+    /// sound can be rendered from several threads at once.
+    ///
+    /// ```rust
+    /// let sound = jfxr::Sound::example(jfxr::sound::Example::Explosion);
+    /// let render = std::thread::scope(|scope| scope.spawn(|| sound.render()).join().unwrap());
+    /// assert_eq!(render, jfxr::generate(&sound));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sound cannot be generated, like [`crate::generate`].
+    pub fn render(&self) -> Vec<f64> {
+        crate::generate(self)
+    }
     pub fn duration(&self) -> f64 {
         self.attack.0 + self.sustain.0 + self.decay.0
     }
+    /// Returns the time that is rendered after the sound has ended, so that
+    /// effects such as the reverb can ring out, in seconds. This is the
+    /// longest tail of any stage of the synth.
+    pub fn tail_duration(&self) -> f64 {
+        super::synth::extra_tail_seconds(self)
+    }
+    /// Returns the position of the sound between the left (-1) and right
+    /// (1) channels of a stereo render at the given time, in seconds. The
+    /// pan sweep moves it over the duration of the sound, after which it
+    /// stays put, and the auto-pan oscillates around it.
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::example(jfxr::sound::Example::Laser);
+    /// assert_eq!(sound.pan_at(0.1), 0.0);
+    /// sound.pan.0 = -100.0;
+    /// sound.pan_sweep.0 = 200.0;
+    /// assert_eq!(sound.pan_at(0.0), -1.0);
+    /// assert_eq!(sound.pan_at(sound.duration() / 2.0), 0.0);
+    /// assert_eq!(sound.pan_at(sound.duration() + 1.0), 1.0);
+    /// ```
+    pub fn pan_at(&self, time: f64) -> f64 {
+        let progress = if self.duration() > 0.0 { (time / self.duration()).min(1.0) } else { 1.0 };
+        let auto_pan = self.auto_pan_depth.0 / 100.0 * crate::math::sin(2.0 * std::f64::consts::PI * self.auto_pan_frequency.0 * time);
+        ((self.pan.0 + progress * self.pan_sweep.0) / 100.0 + auto_pan).clamp(-1.0, 1.0)
+    }
+    /// Returns the number of samples in the rendered sound, including the
+    /// tail. Any sound with a positive duration has at least one sample,
+    /// however short it is.
+    ///
+    /// Sounds of a few samples, shorter than the flanger delay and than a
+    /// single block, render like any other: every sample is finite, and
+    /// normalization is applied once, whatever the block size. With
+    /// [`crate::Compatibility::Improved`], noise is audible from the first
+    /// sample, rather than rising from silence over the first half cycle;
+    /// only velvet noise, being sparse, may still be silent for a while.
+    ///
+    /// ```rust
+    /// use jfxr::parameter::{EnumParameter, Waveform};
+    /// use jfxr::{Compatibility, Synth};
+    ///
+    /// for len in [1, 2, 64] {
+    ///     for waveform in Waveform::VALUES {
+    ///         let mut sound = jfxr::Sound::default();
+    ///         sound.waveform = *waveform;
+    ///         sound.sustain.0 = len as f64 / sound.sample_rate.0;
+    ///         sound.flanger_offset.0 = 20.0;
+    ///         sound.low_pass_cutoff.0 = 200.0;
+    ///         assert_eq!(sound.num_samples(), len);
+    ///         let render = |block_size| {
+    ///             let mut synth = Synth::new(&sound);
+    ///             synth.set_compatibility(Compatibility::Improved);
+    ///             synth.set_block_size(block_size);
+    ///             synth.generate()
+    ///         };
+    ///         let samples = render(1);
+    ///         assert_eq!(samples, render(4096));
+    ///         assert_eq!(samples.len(), len);
+    ///         assert!(samples.iter().all(|sample| sample.is_finite()));
+    ///         // Normalized to full scale, and not scaled a second time.
+    ///         let peak = jfxr::analysis::peak(&samples);
+    ///         if *waveform != Waveform::Velvetnoise {
+    ///             assert!((peak - 1.0).abs() < 1e-12, "{} {len}: {peak}", waveform.name());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn num_samples(&self) -> usize {
+        1.max((self.sample_rate.0 * (self.duration() + self.tail_duration())).ceil() as usize)
+    }
+    /// Returns the number of samples before the tail. Sweeps of effect
+    /// parameters span these samples.
+    pub fn num_body_samples(&self) -> usize {
+        1.max((self.sample_rate.0 * self.duration()).ceil() as usize)
+    }
+    /// Returns a rough estimate of the relative cost of rendering this sound,
+    /// computed as the number of samples, times the number of oscillators
+    /// used for the harmonics, times a weight for the waveform. The value has
+    /// no unit; it is only useful for comparing sounds with each other, for
+    /// example to decide whether to use [`crate::Synth::set_preview`].
+    pub fn estimated_cost(&self) -> f64 {
+        self.num_samples() as f64 * (self.harmonics.0 + 1) as f64 * self.waveform.cost_weight()
+    }
+    /// Returns the noise interpolation that is used when rendering, which is
+    /// [`Self::noise_interpolation`], unless it is turned off through the
+    /// deprecated [`Self::interpolate_noise`].
+    ///
+    /// ```rust
+    /// # #![allow(deprecated)]
+    /// use jfxr::parameter::{InterpolateNoise, NoiseInterpolation};
+    ///
+    /// let mut sound = jfxr::Sound { noise_interpolation: NoiseInterpolation::Cubic, ..Default::default() };
+    /// assert!(sound.effective_noise_interpolation() == NoiseInterpolation::Cubic);
+    /// sound.interpolate_noise = InterpolateNoise(false);
+    /// assert!(sound.effective_noise_interpolation() == NoiseInterpolation::None);
+    /// ```
+    pub fn effective_noise_interpolation(&self) -> crate::parameter::NoiseInterpolation {
+        #[allow(deprecated)]
+        match self.interpolate_noise.0 {
+            true => self.noise_interpolation,
+            false => crate::parameter::NoiseInterpolation::None,
+        }
+    }
     pub fn effective_repeat_frequency(&self) -> f64 {
         self.repeat_frequency.0.max(1.0 / self.duration())
     }
+    /// Returns the index of the repetition that the given time falls into.
+    pub fn repetition_at(&self, time: f64) -> f64 {
+        (time * self.effective_repeat_frequency()).floor()
+    }
+    /// Returns the frequency of the sound at the given time, in Hz, from the
+    /// base frequency, its sweeps and jumps, and the vibrato. This is part of
+    /// the model API; see [`Self::frequencies_at`].
     pub fn frequency_at(&self, time: f64) -> f64 {
-        let repeat_frequency = self.effective_repeat_frequency();
-        let fraction_in_repetition = (time * repeat_frequency).fract();
-        let mut freq = self.frequency.0
-            + fraction_in_repetition * self.frequency_sweep.0
-            + fraction_in_repetition * fraction_in_repetition * self.frequency_delta_sweep.0;
-        if fraction_in_repetition > self.frequency_jump1_onset.0 / 100.0 {
-            freq *= 1.0 + self.frequency_jump1_amount.0 / 100.0;
+        Model::new(self).frequency_at(time)
+    }
+    /// Returns the factor by which the semitone sweeps multiply the
+    /// frequency at the given fraction of a repetition.
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.frequency_sweep_semitones.0 = 12.0;
+    /// assert_eq!(sound.frequency.0 * sound.pitch_sweep_factor(1.0), 2.0 * sound.frequency.0);
+    /// ```
+    pub fn pitch_sweep_factor(&self, fraction_in_repetition: f64) -> f64 {
+        let semitones = fraction_in_repetition * self.frequency_sweep_semitones.0
+            + fraction_in_repetition * fraction_in_repetition * self.frequency_delta_sweep_semitones.0;
+        crate::math::exp2(semitones / 12.0)
+    }
+    /// Returns the duty cycle of square waves at the given time, as a
+    /// fraction of a cycle. This is part of the model API; see
+    /// [`Self::frequencies_at`].
+    pub fn square_duty_at(&self, time: f64) -> f64 {
+        Model::new(self).square_duty_at(time)
+    }
+    /// Returns [`Self::square_duty_at`] with the repeat jitter drawn from the
+    /// given seed, like [`Self::frequencies_at_with_seed`].
+    pub(crate) fn square_duty_at_with_seed(&self, time: f64, seed: u32) -> f64 {
+        Model::with_seed(self, seed).square_duty_at(time)
+    }
+    /// Returns the volume of the sound at the given time, from the envelope,
+    /// tremolo and repeat decay. This is part of the model API; see
+    /// [`Self::frequencies_at`].
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.repeat_frequency.0 = 10.0;
+    /// sound.repeat_decay.0 = 30.0;
+    /// sound.attack.0 = 0.0;
+    /// sound.sustain.0 = 0.3;
+    /// sound.decay.0 = 0.0;
+    /// sound.frequency.0 = 1000.0;
+    /// let samples = jfxr::generate(&sound);
+    /// let peak = |repetition: usize| samples[repetition * 4410..(repetition + 1) * 4410]
+    ///     .iter()
+    ///     .fold(0.0f64, |peak, sample| peak.max(sample.abs()));
+    /// // Allow for the 16-bit quantization of the default bit crush.
+    /// assert!((peak(2) - 0.7 * 0.7 * peak(0)).abs() < 1e-4);
+    /// ```
+    pub fn amplitude_at(&self, time: f64) -> f64 {
+        Model::new(self).amplitude_at(time)
+    }
+    /// Evaluates [`Self::frequency_at`] at each of the given times, into
+    /// `out`, which must have the same length.
+    ///
+    /// Together with [`Self::amplitudes_at`], [`Self::square_duties_at`],
+    /// the `_between` variants and the scalar methods they vectorize, this
+    /// forms the model API: it evaluates the parameter curves of the sound
+    /// without rendering it, for example to draw them. The synth uses the
+    /// same code to render the sound, so the model and the output always
+    /// agree, apart from the repeat jitter of variations other than 0 (see
+    /// [`crate::Synth::set_variation`]), and the model falls under the same output stability policy as
+    /// rendering. The vectorized variants give bit-identical results to the
+    /// scalar methods, and are faster, because the work that does not depend
+    /// on the time is done once.
+    ///
+    /// ```rust
+    /// use jfxr::sound::Example;
+    ///
+    /// for example in Example::ALL {
+    ///     let mut sound = jfxr::Sound::example(*example);
+    ///     sound.vibrato_depth.0 = 10.0;
+    ///     sound.tremolo_depth.0 = 50.0;
+    ///     sound.repeat_decay.0 = 20.0;
+    ///     sound.repeat_jitter.0 = 10.0;
+    ///     sound.frequency_jump1_amount.0 = 30.0;
+    ///     let times: Vec<f64> = (0..sound.num_samples()).map(|i| i as f64 / 44100.0).collect();
+    ///     let mut out = vec![0.0; times.len()];
+    ///     let bits = |values: &[f64]| values.iter().map(|value| value.to_bits()).collect::<Vec<_>>();
+    ///     sound.frequencies_at(&times, &mut out);
+    ///     assert_eq!(bits(&out), bits(&times.iter().map(|time| sound.frequency_at(*time)).collect::<Vec<_>>()));
+    ///     sound.amplitudes_at(&times, &mut out);
+    ///     assert_eq!(bits(&out), bits(&times.iter().map(|time| sound.amplitude_at(*time)).collect::<Vec<_>>()));
+    ///     sound.square_duties_at(&times, &mut out);
+    ///     assert_eq!(bits(&out), bits(&times.iter().map(|time| sound.square_duty_at(*time)).collect::<Vec<_>>()));
+    /// }
+    /// ```
+    pub fn frequencies_at(&self, times: &[f64], out: &mut [f64]) {
+        self.frequencies_at_with_seed(times, out, crate::oscillator::NOISE_SEED);
+    }
+    /// Evaluates [`Self::frequencies_at`] with the repeat jitter drawn from
+    /// the given seed, which the synth derives from its variation.
+    pub(crate) fn frequencies_at_with_seed(&self, times: &[f64], out: &mut [f64], seed: u32) {
+        assert_eq!(times.len(), out.len(), "output length differs from the number of times");
+        let model = Model::with_seed(self, seed);
+        for (out, time) in out.iter_mut().zip(times) {
+            *out = model.frequency_at(*time);
         }
-        if fraction_in_repetition > self.frequency_jump2_onset.0 / 100.0 {
-            freq *= 1.0 + self.frequency_jump2_amount.0 / 100.0;
+    }
+    /// Evaluates [`Self::amplitude_at`] at each of the given times, into
+    /// `out`, which must have the same length. See [`Self::frequencies_at`].
+    pub fn amplitudes_at(&self, times: &[f64], out: &mut [f64]) {
+        assert_eq!(times.len(), out.len(), "output length differs from the number of times");
+        let model = Model::new(self);
+        for (out, time) in out.iter_mut().zip(times) {
+            *out = model.amplitude_at(*time);
         }
-        if self.vibrato_depth.0 != 0.0 {
-            freq += 1.0 - self.vibrato_depth.0 * (0.5 - 0.5 * (2.0 * std::f64::consts::PI * time * self.vibrato_frequency.0).sin());
+    }
+    /// Evaluates [`Self::square_duty_at`] at each of the given times, into
+    /// `out`, which must have the same length. See [`Self::frequencies_at`].
+    pub fn square_duties_at(&self, times: &[f64], out: &mut [f64]) {
+        assert_eq!(times.len(), out.len(), "output length differs from the number of times");
+        let model = Model::new(self);
+        for (out, time) in out.iter_mut().zip(times) {
+            *out = model.square_duty_at(*time);
+        }
+    }
+    /// Returns [`Self::frequency_at`] at `n` evenly spaced times, starting at
+    /// `t0` and ending just before `t1`. See [`Self::frequencies_at`].
+    ///
+    /// ```rust
+    /// let sound = jfxr::Sound::example(jfxr::sound::Example::Powerup);
+    /// let curve = sound.frequencies_between(0.0, sound.duration(), 200);
+    /// assert_eq!(curve.len(), 200);
+    /// assert_eq!(curve[0], sound.frequency_at(0.0));
+    /// ```
+    pub fn frequencies_between(&self, t0: f64, t1: f64, n: usize) -> Vec<f64> {
+        let model = Model::new(self);
+        evenly_spaced(t0, t1, n).map(|time| model.frequency_at(time)).collect()
+    }
+    /// Returns [`Self::amplitude_at`] at `n` evenly spaced times, starting at
+    /// `t0` and ending just before `t1`. See [`Self::frequencies_at`].
+    pub fn amplitudes_between(&self, t0: f64, t1: f64, n: usize) -> Vec<f64> {
+        let model = Model::new(self);
+        evenly_spaced(t0, t1, n).map(|time| model.amplitude_at(time)).collect()
+    }
+    /// Returns [`Self::square_duty_at`] at `n` evenly spaced times, starting
+    /// at `t0` and ending just before `t1`. See [`Self::frequencies_at`].
+    pub fn square_duties_between(&self, t0: f64, t1: f64, n: usize) -> Vec<f64> {
+        let model = Model::new(self);
+        evenly_spaced(t0, t1, n).map(|time| model.square_duty_at(time)).collect()
+    }
+}
+
+/// Adds setters which take a [`std::time::Duration`], converted with the
+/// `from_duration` constructor of the parameter, which checks the range in
+/// debug builds.
+macro_rules! duration_setters {
+    ($($(#[$meta:meta])* $name:ident: $field:ident: $ty:ident),* $(,)?) => {
+        impl Sound {$(
+            #[doc = concat!("Sets [`Self::", stringify!($field), "`] from a duration.")]
+            $(#[$meta])*
+            pub fn $name(&mut self, duration: std::time::Duration) -> &mut Self {
+                self.$field = crate::parameter::$ty::from_duration(duration);
+                self
+            }
+        )*}
+    };
+}
+
+duration_setters!(
+    /// The setters can be chained:
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.set_attack(Duration::from_millis(50)).set_sustain(Duration::from_millis(200)).set_decay(Duration::from_secs(1));
+    /// assert_eq!((sound.attack.0, sound.sustain.0, sound.decay.0), (0.05, 0.2, 1.0));
+    /// ```
+    set_attack: attack: Attack,
+    set_sustain: sustain: Sustain,
+    set_decay: decay: Decay,
+    /// The flanger offset is in milliseconds:
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.set_flanger_offset(std::time::Duration::from_millis(5));
+    /// assert_eq!(sound.flanger_offset.0, 5.0);
+    /// ```
+    set_flanger_offset: flanger_offset: FlangerOffset,
+    /// The flanger depth is in milliseconds:
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.set_flanger_depth(std::time::Duration::from_micros(2500));
+    /// assert_eq!(sound.flanger_depth.0, 2.5);
+    /// ```
+    set_flanger_depth: flanger_depth: FlangerDepth,
+);
+
+fn evenly_spaced(t0: f64, t1: f64, n: usize) -> impl Iterator<Item = f64> {
+    (0..n).map(move |i| t0 + (t1 - t0) * i as f64 / n as f64)
+}
+
+/// Returns a number between -1 and 1 for the repetition that the given
+/// position, in repetitions, falls into, hashed from the repetition index
+/// and the seed. Each curve passes its own `stream`, so that the curves vary
+/// independently.
+fn repetition_noise(position: f64, seed: u32, stream: u32) -> f64 {
+    // The finalizer of MurmurHash3, which spreads consecutive indices over
+    // the whole range.
+    let mut hash = (position.floor() as u32).wrapping_mul(0x9e3779b9) ^ seed.wrapping_add(stream);
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85ebca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2ae35);
+    hash ^= hash >> 16;
+    hash as f64 / u32::MAX as f64 * 2.0 - 1.0
+}
+
+/// The parameter curves of a sound, with everything that does not depend on
+/// the time computed once. The arithmetic is kept exactly as it was when it
+/// was done per sample, so that the output does not change.
+struct Model<'a> {
+    sound: &'a Sound,
+    repeat_frequency: f64,
+    pitch_sweep: bool,
+    jump1_onset: f64,
+    jump1_factor: f64,
+    jump2_onset: f64,
+    jump2_factor: f64,
+    attack_sustain: f64,
+    attack_sustain_decay: f64,
+    sustain_punch: f64,
+    repeat_decay: f64,
+    repeat_jitter: f64,
+    /// Seed of the repeat jitter.
+    seed: u32,
+    tremolo_depth: f64,
+}
+
+impl<'a> Model<'a> {
+    fn new(sound: &'a Sound) -> Self {
+        Self::with_seed(sound, crate::oscillator::NOISE_SEED)
+    }
+
+    fn with_seed(sound: &'a Sound, seed: u32) -> Self {
+        Self {
+            sound,
+            repeat_frequency: sound.effective_repeat_frequency(),
+            pitch_sweep: sound.frequency_sweep_semitones.0 != 0.0 || sound.frequency_delta_sweep_semitones.0 != 0.0,
+            jump1_onset: sound.frequency_jump1_onset.0 / 100.0,
+            jump1_factor: 1.0 + sound.frequency_jump1_amount.0 / 100.0,
+            jump2_onset: sound.frequency_jump2_onset.0 / 100.0,
+            jump2_factor: 1.0 + sound.frequency_jump2_amount.0 / 100.0,
+            attack_sustain: sound.attack.0 + sound.sustain.0,
+            attack_sustain_decay: sound.attack.0 + sound.sustain.0 + sound.decay.0,
+            sustain_punch: sound.sustain_punch.0 / 100.0,
+            repeat_decay: 1.0 - sound.repeat_decay.0 / 100.0,
+            repeat_jitter: if sound.repeat_frequency.0 == 0.0 { 0.0 } else { sound.repeat_jitter.0 / 100.0 },
+            seed,
+            tremolo_depth: sound.tremolo_depth.0 / 100.0,
+        }
+    }
+
+    fn frequency_at(&self, time: f64) -> f64 {
+        let sound = self.sound;
+        let fraction_in_repetition = (time * self.repeat_frequency).fract();
+        let mut freq = sound.frequency.0
+            + fraction_in_repetition * sound.frequency_sweep.0
+            + fraction_in_repetition * fraction_in_repetition * sound.frequency_delta_sweep.0;
+        if self.pitch_sweep {
+            freq *= sound.pitch_sweep_factor(fraction_in_repetition);
+        }
+        // The factors commute, so the order of the onsets does not matter,
+        // and an onset of 100% never fires since the fraction stays below 1.
+        if fraction_in_repetition > self.jump1_onset {
+            freq *= self.jump1_factor;
+        }
+        if fraction_in_repetition > self.jump2_onset {
+            freq *= self.jump2_factor;
+        }
+        if self.repeat_jitter != 0.0 {
+            freq *= 1.0 + self.repeat_jitter * repetition_noise(time * self.repeat_frequency, self.seed, 0);
+        }
+        if sound.vibrato_depth.0 != 0.0 {
+            freq += 1.0 - sound.vibrato_depth.0 * (0.5 - 0.5 * crate::math::sin(2.0 * std::f64::consts::PI * time * sound.vibrato_frequency.0));
         }
         freq.max(0.0)
     }
-    pub fn square_duty_at(&self, time: f64) -> f64 {
-        let repeat_frequency = self.effective_repeat_frequency();
-        let fraction_in_repetition = (time * repeat_frequency).fract();
-        (self.square_duty.0 + fraction_in_repetition * self.square_duty_sweep.0) / 100.0
+
+    fn square_duty_at(&self, time: f64) -> f64 {
+        let fraction_in_repetition = (time * self.repeat_frequency).fract();
+        let duty = (self.sound.square_duty.0 + fraction_in_repetition * self.sound.square_duty_sweep.0) / 100.0;
+        if self.repeat_jitter != 0.0 {
+            return duty * (1.0 + self.repeat_jitter * repetition_noise(time * self.repeat_frequency, self.seed, 1));
+        }
+        duty
     }
-    pub fn amplitude_at(&self, time: f64) -> f64 {
-        let attack = self.attack.0;
-        let sustain = self.sustain.0;
-        let sustain_punch = self.sustain_punch.0;
-        let decay = self.decay.0;
-        let tremolo_depth = self.tremolo_depth.0;
+
+    fn amplitude_at(&self, time: f64) -> f64 {
+        let sound = self.sound;
+        let attack = sound.attack.0;
         let mut amp;
         if time < attack {
             amp = time / attack;
-        } else if time < attack + sustain {
-            amp = 1.0 + sustain_punch / 100.0 * (1.0 - (time - attack) / sustain);
-        } else if time < attack + sustain + decay {
-            amp = 1.0 - (time - attack - sustain) / decay;
-        } else { // This can happen due to roundoff error because the sample count is an integer.
+        } else if time < self.attack_sustain {
+            amp = 1.0 + self.sustain_punch * (1.0 - (time - attack) / sound.sustain.0);
+        } else if time < self.attack_sustain_decay {
+            amp = 1.0 - (time - attack - sound.sustain.0) / sound.decay.0;
+        } else { // This can happen in the tail, or due to roundoff error because the sample count is an integer.
             amp = 0.0;
         }
-        if tremolo_depth != 0.0 {
-            amp *= 1.0 - (tremolo_depth / 100.0) * (0.5 + 0.5 * (2.0 * std::f64::consts::PI * time * self.tremolo_frequency.0).cos());
+        if sound.repeat_decay.0 != 0.0 {
+            amp *= crate::math::pow(self.repeat_decay, (time * self.repeat_frequency).floor());
+        }
+        if sound.tremolo_depth.0 != 0.0 {
+            amp *= 1.0 - self.tremolo_depth * (0.5 + 0.5 * crate::math::cos(2.0 * std::f64::consts::PI * time * sound.tremolo_frequency.0));
         }
         amp
     }
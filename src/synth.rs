@@ -1,187 +1,3531 @@
+// The sample loops mirror the structure of the upstream JavaScript
+// implementation, which makes the two easier to compare.
+#![allow(clippy::needless_range_loop)]
+
+use std::any::Any;
+use std::borrow::Cow;
+
+use crate::precision::{Real, Q15};
+
+use super::parameter::FloatParameter;
+
+/// Selects whether the synth reproduces the output of the upstream `jfxr`
+/// implementation exactly, or applies fixes that change the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Output matches the upstream implementation.
+    #[default] Exact,
+    /// Output includes improvements that the upstream implementation lacks:
+    ///
+    /// - Noise oscillators use a different seed for every harmonic, so that
+    ///   harmonics on noise waveforms add texture instead of only volume.
+    /// - Brown noise is reflected back at its range limits instead of being
+    ///   clamped to them, which avoids flat-topped waveforms.
+    /// - The flanger interpolates between samples instead of rounding its
+    ///   delay to a whole number of samples, so that slow sweeps are smooth.
+    /// - The flanger delay is not limited to 100 ms.
+    /// - Compression clamps samples to the range from -1 to 1, and keeps
+    ///   silence silent at a compression of 0. See [`compress_sample`].
+    /// - The square duty cycle stays between 1% and 99%, so that a duty of
+    ///   0% or 100% gives narrow pulses instead of a constant level. See
+    ///   [`super::oscillator::SquareOscillator`].
+    /// - Noise starts with a random value on the first sample, instead of
+    ///   with silence until the end of the first half cycle, so that very
+    ///   short or low-pitched noise is not silent.
+    /// - The harmonics of sine waves are summed in a single pass, which is
+    ///   much faster but differs from upstream in the last bits. Beyond the
+    ///   5 harmonics of upstream, this happens in both modes.
+    ///
+    /// With a different seed for every harmonic, the harmonics of noise are
+    /// independent of each other, so that a render with more harmonics is
+    /// less correlated with the render of the base frequency alone:
+    ///
+    /// ```rust
+    /// use jfxr::parameter::{Attack, BitCrush, Harmonics, HarmonicsFalloff, Normalization, Sustain, Waveform};
+    /// use jfxr::synth::Compatibility;
+    ///
+    /// let correlation = |a: &[f64], b: &[f64]| {
+    ///     let dot = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(a, b)| a * b).sum::<f64>();
+    ///     dot(a, b) / (dot(a, a) * dot(b, b)).sqrt()
+    /// };
+    /// for waveform in [Waveform::Pinknoise, Waveform::Brownnoise] {
+    ///     let render = |harmonics, compatibility| {
+    ///         let sound = jfxr::Sound {
+    ///             waveform,
+    ///             harmonics: Harmonics(harmonics),
+    ///             harmonics_falloff: HarmonicsFalloff(1.0),
+    ///             attack: Attack(0.0),
+    ///             sustain: Sustain(0.5),
+    ///             bit_crush: BitCrush(0),
+    ///             normalization: Normalization(false),
+    ///             ..Default::default()
+    ///         };
+    ///         let mut synth = jfxr::Synth::new(&sound);
+    ///         synth.set_compatibility(compatibility);
+    ///         synth.generate()
+    ///     };
+    ///     let exact = correlation(&render(0, Compatibility::Exact), &render(3, Compatibility::Exact));
+    ///     let improved = correlation(&render(0, Compatibility::Improved), &render(3, Compatibility::Improved));
+    ///     // Four independent harmonics of equal level would give 0.5.
+    ///     assert!(exact > 0.75, "{exact}");
+    ///     assert!(improved < 0.5, "{improved}");
+    /// }
+    /// ```
+    Improved,
+}
+
+/// A stage in the synth pipeline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Stage {
+    Generator,
+    Envelope,
+    Flanger,
+    Chorus,
+    BitCrush,
+    LowPass,
+    HighPass,
+    Compress,
+    Reverb,
+    Normalize,
+    Amplify,
+}
+
+impl Stage {
+    /// The stages in the order in which they run by default, which is the
+    /// order of the web tool.
+    pub const DEFAULT_ORDER: &'static [Stage] = &[
+        Stage::Generator,
+        Stage::Envelope,
+        Stage::Flanger,
+        Stage::Chorus,
+        Stage::BitCrush,
+        Stage::LowPass,
+        Stage::HighPass,
+        Stage::Compress,
+        Stage::Reverb,
+        Stage::Normalize,
+        Stage::Amplify,
+    ];
+}
+
+/// Error returned by [`Synth::set_stage_order`] for an order that is not
+/// allowed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StageOrderError {
+    /// The stage appears more than once.
+    Duplicate(Stage),
+    /// The stage does not appear.
+    Missing(Stage),
+    /// The generator is not the first stage.
+    GeneratorNotFirst,
+    /// Normalization and amplification are not the last stages, in that
+    /// order, which only [`Synth::force_stage_order`] allows.
+    OutputNotLast,
+}
+
+impl std::fmt::Display for StageOrderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Duplicate(stage) => write!(f, "stage {stage:?} appears more than once"),
+            Self::Missing(stage) => write!(f, "stage {stage:?} is missing"),
+            Self::GeneratorNotFirst => write!(f, "the generator must be the first stage"),
+            Self::OutputNotLast => write!(f, "normalization and amplification must be the last stages"),
+        }
+    }
+}
+
+impl std::error::Error for StageOrderError {}
+
+/// A frame of output that [`Synth::set_trim`] can cut: a single sample, or
+/// the samples of both channels of a stereo render.
+trait TrimFrame {
+    /// Returns the level of the louder channel.
+    fn level(&self) -> f64;
+    fn scale(&mut self, gain: f64);
+}
+
+impl TrimFrame for f64 {
+    fn level(&self) -> f64 {
+        self.abs()
+    }
+
+    fn scale(&mut self, gain: f64) {
+        *self *= gain;
+    }
+}
+
+impl TrimFrame for [f64; 2] {
+    fn level(&self) -> f64 {
+        self[0].abs().max(self[1].abs())
+    }
+
+    fn scale(&mut self, gain: f64) {
+        for sample in self {
+            *sample *= gain;
+        }
+    }
+}
+
+/// A curve giving the value of a parameter as a function of time in seconds.
+pub type Curve = std::sync::Arc<dyn Fn(f64) -> f64 + Send + Sync>;
+
+/// Offsets added to the noise seeds of the left and right sides of a stereo
+/// render, so that their noise is uncorrelated with the centre and with each
+/// other.
+const STEREO_SEED_OFFSETS: [u32; 2] = [0x9e3779b9, 0x7f4a7c15];
+
+/// Largest number of harmonics in the upstream implementation. Sine waves
+/// with more harmonics are always summed in a single pass.
+const UPSTREAM_MAX_HARMONICS: i32 = 5;
+
+/// Offset added to the start phase of the right side of a stereo render, and
+/// subtracted from that of the left side, as a fraction of a cycle.
+const STEREO_PHASE_OFFSET: f64 = 0.125;
+
+/// Length of the fade applied where [`Synth::set_trim`] cuts the output, in
+/// seconds.
+const TRIM_FADE: f64 = 0.005;
+
+/// Default limit on the duration of sounds, in seconds. See
+/// [`Synth::set_max_duration`].
+pub const DEFAULT_MAX_DURATION: f64 = 60.0;
+
+/// Default limit on the number of samples in a single buffer, which is
+/// [`DEFAULT_MAX_DURATION`] at the default sample rate. The limit does not
+/// depend on the sample rate of the sound, so a sound with a huge sample rate
+/// cannot raise it. See [`Synth::set_max_samples`].
+pub const DEFAULT_MAX_SAMPLES: usize = (DEFAULT_MAX_DURATION * super::parameter::SampleRate::DEFAULT_VALUE) as usize;
+
+/// Default number of samples generated by each call to
+/// [`Synth::generate_block`]. See [`Synth::set_block_size`].
+pub const DEFAULT_BLOCK_SIZE: usize = 10240;
+
+/// Number of samples generated by [`Synth::render_quantum`], which matches
+/// the render quantum of the Web Audio API.
+pub const RENDER_QUANTUM: usize = 128;
+
+/// Channel layout of the output of [`Synth::generate_interleaved`] and
+/// [`Synth::generate_bytes`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Channels {
+    #[default]
+    Mono,
+    /// Left and right samples, interleaved, as produced by
+    /// [`Synth::generate_stereo`].
+    Stereo,
+}
+
+impl Channels {
+    /// Returns the number of channels.
+    pub fn count(self) -> usize {
+        match self {
+            Self::Mono => 1,
+            Self::Stereo => 2,
+        }
+    }
+}
+
+/// Precision of the arithmetic of the synth, to reproduce the grit of older
+/// tools that computed in single precision or in fixed point. The generator
+/// and the filters do their arithmetic in this precision: the phase
+/// accumulators, the mix of the harmonics, the filter coefficients and the
+/// filter memories are all kept in it, each precision with its own
+/// monomorphized copy of these stages. The oscillators evaluate their
+/// waveforms in double precision from the rounded phase, and their output is
+/// rounded, like a wavetable. The other stages compute in double precision
+/// and round their output, so that every stage receives its input in this
+/// precision. Output is delivered in the format requested, whatever the
+/// precision.
+///
+/// Rounded phases drift slightly out of tune, so the samples of a reduced
+/// precision render are not close to the exact ones one by one, especially
+/// at the edges of square waves, but the level is:
+///
+/// ```rust
+/// use jfxr::synth::Precision;
+///
+/// let sound = jfxr::Sound::example(jfxr::sound::Example::Laser);
+/// let level = |precision| {
+///     let mut synth = jfxr::Synth::new(&sound);
+///     synth.set_precision(precision);
+///     jfxr::analysis::rms(&synth.generate())
+/// };
+/// assert!((level(Precision::F32) / level(Precision::F64) - 1.0).abs() < 1e-6);
+/// assert!((level(Precision::Q15) / level(Precision::F64) - 1.0).abs() < 1e-4);
+/// ```
+///
+/// In fixed point, stages that overshoot full scale saturate rather than
+/// wrap around.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    /// Double precision, which is exact.
+    #[default]
+    F64,
+    /// Single precision.
+    F32,
+    /// Signed 16-bit fixed point, with 15 bits after the point. Samples
+    /// saturate at -1 and just below 1.
+    Q15,
+}
+
+impl Precision {
+    /// Rounds a sample to this precision.
+    pub fn quantize(self, sample: f64) -> f64 {
+        match self {
+            Self::F64 => sample,
+            Self::F32 => f32::from_f64(sample).to_f64(),
+            Self::Q15 => Q15::from_f64(sample).to_f64(),
+        }
+    }
+}
+
+/// How normalization treats the channels of a stereo render. A mono render
+/// has a single channel, so both modes are the same for it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NormalizeMode {
+    /// Both channels are scaled by the same gain, the gain of the louder
+    /// channel, which keeps the balance between them.
+    #[default]
+    PeakLinked,
+    /// Each channel is leveled on its own, so that both reach the target.
+    PeakPerChannel,
+}
+
+/// Encoding of the samples produced by [`Synth::generate_bytes`]. All
+/// formats are little-endian.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SampleFormat {
+    #[default]
+    F64,
+    F32,
+    /// Signed 16-bit integers, with samples clamped to the range -1 to 1 as
+    /// in a WAV file.
+    I16,
+}
+
+impl SampleFormat {
+    /// Returns the size of a single sample, in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            Self::F64 => 8,
+            Self::F32 => 4,
+            Self::I16 => 2,
+        }
+    }
+
+    fn encode(self, sample: f64, bytes: &mut Vec<u8>) {
+        match self {
+            Self::F64 => bytes.extend_from_slice(&sample.to_le_bytes()),
+            Self::F32 => bytes.extend_from_slice(&(sample as f32).to_le_bytes()),
+            Self::I16 => bytes.extend_from_slice(&((sample.clamp(-1.0, 1.0) * i16::MAX as f64).round() as i16).to_le_bytes()),
+        }
+    }
+}
+
+/// Settings that determine the shape of the output of a [`Synth`], given to
+/// [`Synth::with_settings`]. The default settings produce the same output as
+/// [`Synth::new`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OutputSettings {
+    /// Sample rate to render at, instead of the sample rate of the sound.
+    pub sample_rate: Option<f64>,
+    pub channels: Channels,
+    pub sample_format: SampleFormat,
+    /// See [`Synth::set_block_size`].
+    pub block_size: usize,
+    /// Whether to normalize the sound, instead of the normalization setting
+    /// of the sound.
+    pub normalization_override: Option<bool>,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        Self {
+            sample_rate: None,
+            channels: Channels::default(),
+            sample_format: SampleFormat::default(),
+            block_size: DEFAULT_BLOCK_SIZE,
+            normalization_override: None,
+        }
+    }
+}
+
+/// Error returned when a sound cannot be generated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SynthError {
+    /// The sound has a duration that is zero, negative or not a number.
+    ZeroDuration,
+    /// The sample rate is zero, negative, infinite or not a number.
+    InvalidSampleRate,
+    /// The sound is longer than allowed by [`Synth::set_max_samples`].
+    TooManySamples { num_samples: usize, max_samples: usize },
+    /// The flanger delay line would be longer than allowed by
+    /// [`Synth::set_max_samples`].
+    FlangerBufferTooLong { buffer_length: usize, max_samples: usize },
+    /// The block size of the [`OutputSettings`] is zero.
+    ZeroBlockSize,
+    /// The synth was primed with [`Synth::prime_with`], but the given stage,
+    /// which needs the whole sound, runs before the envelope, so the history
+    /// cannot stand in for the samples before the start.
+    UnprimableStage(Stage),
+}
+
+impl std::fmt::Display for SynthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ZeroDuration => write!(f, "sound duration must be positive"),
+            Self::InvalidSampleRate => write!(f, "sample rate must be positive and finite"),
+            Self::TooManySamples { num_samples, max_samples } => {
+                write!(f, "sound has {num_samples} samples, which is more than the limit of {max_samples}")
+            }
+            Self::FlangerBufferTooLong { buffer_length, max_samples } => {
+                write!(f, "flanger needs a delay line of {buffer_length} samples, which is more than the limit of {max_samples}")
+            }
+            Self::ZeroBlockSize => write!(f, "block size must be positive"),
+            Self::UnprimableStage(stage) => write!(f, "cannot prime the synth, since stage {stage:?} needs the whole sound and runs before the envelope"),
+        }
+    }
+}
+
+impl std::error::Error for SynthError {}
+
+type BlockCallback<'a> = Box<dyn FnMut(usize, &[f64]) + Send + 'a>;
+
+/// A parameter that can be automated with [`Synth::automate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AutomationTarget {
+    /// Replaces [`super::sound::Sound::frequency_at`], in Hz.
+    Frequency,
+    /// Replaces the low-pass cutoff and its sweep, in Hz.
+    LowPassCutoff,
+    /// Replaces the high-pass cutoff and its sweep, in Hz.
+    HighPassCutoff,
+    /// Replaces [`super::sound::Sound::square_duty_at`], in percent.
+    SquareDuty,
+    /// Replaces the amplification, in percent.
+    Amplification,
+}
+
+impl AutomationTarget {
+    pub const ALL: &'static [Self] = &[Self::Frequency, Self::LowPassCutoff, Self::HighPassCutoff, Self::SquareDuty, Self::Amplification];
+
+    /// Returns the parameter whose curve is replaced. Its sweep, if any, is
+    /// replaced as well.
+    ///
+    /// ```rust
+    /// use jfxr::parameter::ParamId;
+    /// use jfxr::AutomationTarget;
+    ///
+    /// assert_eq!(AutomationTarget::LowPassCutoff.param(), ParamId::LowPassCutoff);
+    /// assert_eq!(AutomationTarget::for_param(ParamId::SquareDuty), Some(AutomationTarget::SquareDuty));
+    /// assert_eq!(AutomationTarget::for_param(ParamId::SquareDutySweep), None);
+    /// for target in AutomationTarget::ALL {
+    ///     assert_eq!(AutomationTarget::for_param(target.param()), Some(*target));
+    /// }
+    /// ```
+    pub fn param(self) -> super::parameter::ParamId {
+        use super::parameter::ParamId;
+        match self {
+            Self::Frequency => ParamId::Frequency,
+            Self::LowPassCutoff => ParamId::LowPassCutoff,
+            Self::HighPassCutoff => ParamId::HighPassCutoff,
+            Self::SquareDuty => ParamId::SquareDuty,
+            Self::Amplification => ParamId::Amplification,
+        }
+    }
+
+    /// Returns the target that automates the given parameter, if any.
+    pub fn for_param(id: super::parameter::ParamId) -> Option<Self> {
+        Self::ALL.iter().copied().find(|target| target.param() == id)
+    }
+}
+
+/// Measurements of a finished render, as returned by
+/// [`Synth::render_report`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderReport {
+    /// Largest absolute value of the samples.
+    pub peak: f64,
+    /// Number of samples whose absolute value is above 1.
+    pub clipped: usize,
+    /// Clicks found by [`crate::analysis::detect_clicks`] with
+    /// [`crate::analysis::DEFAULT_CLICK_THRESHOLD`].
+    pub clicks: Vec<crate::analysis::ClickReport>,
+    /// Number of samples removed from the start by [`Synth::set_trim`].
+    pub trimmed_start: usize,
+    /// Number of samples removed from the end by [`Synth::set_trim`].
+    pub trimmed_end: usize,
+    /// The results of [`crate::Sound::diagnose`], as likely causes, if the
+    /// peak is below [`crate::analysis::SILENCE_THRESHOLD`]. Empty for
+    /// renders that are not silent.
+    pub silence_causes: Vec<super::sound::Diagnostic>,
+    /// What normalization measured and applied, for each channel. Empty if
+    /// normalization is disabled.
+    pub normalization: Vec<NormalizationLevel>,
+}
+
+/// Level of one channel as measured by normalization, and the gain that
+/// normalization applied to it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NormalizationLevel {
+    /// Largest absolute value of the samples before normalization, as
+    /// measured through the filter of [`Synth::set_normalize_high_pass`]
+    /// if it is enabled.
+    pub peak: f64,
+    /// Factor by which the channel was scaled. With
+    /// [`NormalizeMode::PeakLinked`], this is the same for both channels.
+    pub gain: f64,
+}
+
+impl RenderReport {
+    /// Measures the samples of each channel. Clicks on either channel are
+    /// listed in the order of their indices.
+    fn measure(sound: &super::sound::Sound, channels: &[&[f64]], trimmed: (usize, usize), normalization: Vec<NormalizationLevel>) -> Self {
+        let mut clicks: Vec<_> = channels
+            .iter()
+            .flat_map(|samples| crate::analysis::detect_clicks(samples, crate::analysis::DEFAULT_CLICK_THRESHOLD))
+            .collect();
+        clicks.sort_by_key(|click| click.index);
+        let mut report = Self {
+            peak: channels.iter().map(|samples| crate::analysis::peak(samples)).fold(0.0, f64::max),
+            clipped: channels.iter().map(|samples| samples.iter().filter(|sample| sample.abs() > 1.0).count()).sum(),
+            clicks,
+            trimmed_start: trimmed.0,
+            trimmed_end: trimmed.1,
+            silence_causes: Vec::new(),
+            normalization,
+        };
+        if report.is_silent() {
+            report.silence_causes = sound.diagnose();
+        }
+        report
+    }
+
+    /// Returns whether the peak is below
+    /// [`crate::analysis::SILENCE_THRESHOLD`].
+    pub fn is_silent(&self) -> bool {
+        self.peak < crate::analysis::db_to_gain(crate::analysis::SILENCE_THRESHOLD)
+    }
+}
+
 pub struct Synth<'a> {
-    sound: &'a super::sound::Sound,
+    sound: Cow<'a, super::sound::Sound>,
 
+    /// Samples of the whole sound, allocated when generation starts. This is
+    /// not used when streaming.
     array: Vec<f64>,
+    num_samples: usize,
+    max_samples: usize,
     start_sample: usize,
     block_size: usize,
+    preview: bool,
+    compatibility: Compatibility,
+    precision: Precision,
+    min_flanger_buffer: f64,
+    variation: u64,
+    variation_phase: f64,
+    profile: Option<Vec<std::time::Duration>>,
+    automations: Vec<(AutomationTarget, Curve)>,
+    /// Dry sound of the samples before the start of the render, as set with
+    /// [`Self::prime_with`].
+    history: Vec<f64>,
+    /// Level below which the ends of the output are cut, in dBFS, as set
+    /// with [`Self::set_trim`].
+    trim: Option<f64>,
+    trim_leading: bool,
+    /// Number of samples cut from the start and the end of the last render.
+    trimmed: (usize, usize),
+    on_block: Option<BlockCallback<'a>>,
+    channels: Channels,
+    sample_format: SampleFormat,
+    /// Level measured by normalization, if it is known in advance.
+    normalization: Option<NormalizationLevel>,
+    normalize_mode: NormalizeMode,
+    normalize_high_pass: bool,
+    /// Which decorrelated render of the sound the generator produces: 0 for
+    /// the sound itself, -1 and 1 for the sides of a stereo render.
+    stereo_side: f64,
+    /// Order in which the transformers are created, as set with
+    /// [`Self::set_stage_order`].
+    stage_order: Vec<Stage>,
+    #[cfg(feature = "tracing")]
+    trace: Option<RenderTrace>,
 
     transformers: Vec<Box<dyn Transformer>>,
 }
 
 impl<'a> Synth<'a> {
+    /// Creates a synth for the given sound. This is cheap: no memory is
+    /// allocated for the samples until generation starts.
+    ///
+    /// A synth holds the state of a single render. To render a sound several
+    /// times, possibly concurrently, create a synth for each render, or use
+    /// [`super::sound::Sound::render`].
+    ///
+    /// A sound that cannot be generated at all, because its duration or
+    /// sample rate is invalid, renders to no samples. Use [`Self::try_new`]
+    /// or the `try_` generation methods to handle this as an error instead.
+    ///
+    /// ```rust
+    /// let sound = jfxr::Sound::default();
+    /// assert_eq!(sound.duration(), 0.0);
+    /// assert!(jfxr::Synth::new(&sound).generate().is_empty());
+    /// assert!(jfxr::generate(&sound).is_empty());
+    /// assert!(jfxr::Synth::new(&sound).try_generate().is_err());
+    /// ```
     pub fn new(sound: &'a super::sound::Sound) -> Self {
-        let sample_rate = sound.sample_rate.0;
-        let num_samples = 1.max((sample_rate * sound.duration()).ceil() as usize);
-        let array = vec![0.0f64; num_samples];
+        Self::new_unchecked(Cow::Borrowed(sound))
+    }
+
+    /// Creates a synth for the given sound, after checking that the sound can
+    /// be generated and stays within the default safety limits. Use this for
+    /// sounds from untrusted sources, which could otherwise make the synth
+    /// panic or allocate huge amounts of memory.
+    ///
+    /// ```rust
+    /// use jfxr::SynthError;
+    ///
+    /// let mut sound = jfxr::Sound::default();
+    /// assert_eq!(jfxr::Synth::try_new(&sound).err(), Some(SynthError::ZeroDuration));
+    /// sound.sustain.0 = 1e9;
+    /// assert!(matches!(jfxr::Synth::try_new(&sound), Err(SynthError::TooManySamples { .. })));
+    /// sound.sustain.0 = 0.3;
+    /// sound.flanger_offset.0 = 1e12;
+    /// assert!(matches!(jfxr::Synth::try_new(&sound), Err(SynthError::FlangerBufferTooLong { .. })));
+    /// sound.flanger_offset.0 = 0.0;
+    ///
+    /// // The default limit is a number of samples, so a huge sample rate
+    /// // cannot be used to raise it.
+    /// sound.sample_rate.0 = 1e9;
+    /// assert!(matches!(jfxr::Synth::try_new(&sound), Err(SynthError::TooManySamples { .. })));
+    /// assert_eq!(jfxr::Synth::new(&sound).max_samples(), jfxr::synth::DEFAULT_MAX_SAMPLES);
+    /// sound.sample_rate.0 = f64::NAN;
+    /// assert!(matches!(jfxr::Synth::try_new(&sound), Err(SynthError::InvalidSampleRate)));
+    ///
+    /// // Sounds with parameters within their ranges are always accepted.
+    /// let options = jfxr::sound::RandomizeOptions { max_duration: 15.0, ..Default::default() };
+    /// for seed in 0..100 {
+    ///     let mut sound = jfxr::Sound::random(&mut jfxr::Random::new(seed), &options);
+    ///     sound.flanger_offset.0 = 50.0;
+    ///     sound.flanger_offset_sweep.0 = 50.0;
+    ///     assert!(jfxr::Synth::try_new(&sound).is_ok());
+    /// }
+    /// ```
+    pub fn try_new(sound: &'a super::sound::Sound) -> Result<Self, SynthError> {
+        Self::validate(sound)?;
+        let synth = Self::new_unchecked(Cow::Borrowed(sound));
+        synth.check_limits()?;
+        Ok(synth)
+    }
+
+    /// Creates a synth for the given sound with the given output settings.
+    /// The settings can be read back from the synth, so that the shape of
+    /// the output is known before it is generated:
+    ///
+    /// ```rust
+    /// use jfxr::synth::{Channels, OutputSettings, SampleFormat};
+    ///
+    /// let mut sound = jfxr::Sound::example(jfxr::sound::Example::Powerup);
+    /// let synth = jfxr::Synth::with_settings(&sound, OutputSettings::default());
+    /// assert_eq!((synth.sample_rate(), synth.channels()), (44100.0, Channels::Mono));
+    /// assert_eq!(synth.expected_len(), sound.num_samples());
+    /// assert_eq!(synth.generate(), jfxr::generate(&sound));
+    ///
+    /// sound.reverb_amount.0 = 20.0;
+    /// for sample_rate in [None, Some(8000.0), Some(48000.0)] {
+    ///     for channels in [Channels::Mono, Channels::Stereo] {
+    ///         for sample_format in [SampleFormat::F64, SampleFormat::F32, SampleFormat::I16] {
+    ///             let settings = OutputSettings {
+    ///                 sample_rate,
+    ///                 channels,
+    ///                 sample_format,
+    ///                 block_size: 1000,
+    ///                 normalization_override: Some(false),
+    ///             };
+    ///             let synth = jfxr::Synth::with_settings(&sound, settings);
+    ///             let resolved = OutputSettings { sample_rate: Some(sample_rate.unwrap_or(44100.0)), ..settings };
+    ///             assert_eq!(synth.settings(), resolved);
+    ///             let expected_len = synth.expected_len();
+    ///             assert_eq!(jfxr::Synth::with_settings(&sound, settings).generate_interleaved().len(), expected_len);
+    ///             assert_eq!(synth.generate_bytes().len(), expected_len * sample_format.size());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Like [`Self::new`], a sound that cannot be generated after the
+    /// overrides have been applied renders to no samples. Use
+    /// [`Self::try_with_settings`] to handle this as an error instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block size is zero.
+    pub fn with_settings(sound: &'a super::sound::Sound, settings: OutputSettings) -> Self {
+        match Self::with_settings_unchecked(sound, settings) {
+            Ok(synth) => synth,
+            Err(error) => panic!("cannot generate sound: {error}"),
+        }
+    }
+
+    /// Creates a synth for the given sound with the given output settings,
+    /// like [`Self::with_settings`], after checking the sound like
+    /// [`Self::try_new`]. A block size of zero is an error rather than a
+    /// panic.
+    ///
+    /// ```rust
+    /// use jfxr::synth::OutputSettings;
+    /// use jfxr::SynthError;
+    ///
+    /// let sound = jfxr::Sound::example(jfxr::sound::Example::Coin);
+    /// let settings = OutputSettings { block_size: 0, ..Default::default() };
+    /// assert!(matches!(jfxr::Synth::try_with_settings(&sound, settings), Err(SynthError::ZeroBlockSize)));
+    /// assert!(jfxr::Synth::try_with_settings(&sound, OutputSettings::default()).is_ok());
+    /// ```
+    pub fn try_with_settings(sound: &'a super::sound::Sound, settings: OutputSettings) -> Result<Self, SynthError> {
+        let synth = Self::with_settings_unchecked(sound, settings)?;
+        Self::validate(&synth.sound)?;
+        synth.check_limits()?;
+        Ok(synth)
+    }
+
+    fn with_settings_unchecked(sound: &'a super::sound::Sound, settings: OutputSettings) -> Result<Self, SynthError> {
+        let mut sound = Cow::Borrowed(sound);
+        if let Some(sample_rate) = settings.sample_rate {
+            sound.to_mut().sample_rate.0 = sample_rate;
+        }
+        if let Some(normalization) = settings.normalization_override {
+            sound.to_mut().normalization.0 = normalization;
+        }
+        if settings.block_size == 0 {
+            return Err(SynthError::ZeroBlockSize);
+        }
+        let mut synth = Self::new_unchecked(sound);
+        synth.set_block_size(settings.block_size);
+        synth.channels = settings.channels;
+        synth.sample_format = settings.sample_format;
+        Ok(synth)
+    }
+
+    /// Creates a synth for the given sound that runs the pipeline with static
+    /// dispatch. See [`StaticSynth`].
+    pub fn new_static(sound: &'a super::sound::Sound) -> StaticSynth<'a> {
+        StaticSynth {
+            synth: Self::new(sound),
+            stages: None,
+        }
+    }
+
+    pub(crate) fn new_unchecked(sound: Cow<'a, super::sound::Sound>) -> Self {
         Self {
+            array: Vec::new(),
+            // Sounds that cannot be generated render to no samples.
+            num_samples: if Self::validate(&sound).is_ok() { sound.num_samples() } else { 0 },
+            max_samples: DEFAULT_MAX_SAMPLES,
             sound,
-            array,
             start_sample: 0,
-            block_size: 10240,
-            transformers: vec![
-                Box::new(Generator::new(sound)),
-                Box::new(Envelope::new(sound)),
-                Box::new(Flanger::new(sound)),
-                Box::new(BitCrush::new(sound)),
-                Box::new(LowPass::new(sound)),
-                Box::new(HighPass::new(sound)),
-                Box::new(Compress::new(sound)),
-                Box::new(Normalize::new(sound)),
-                Box::new(Amplify::new(sound)),
-            ],
+            block_size: DEFAULT_BLOCK_SIZE,
+            preview: false,
+            compatibility: Compatibility::default(),
+            precision: Precision::default(),
+            min_flanger_buffer: 0.0,
+            variation: 0,
+            variation_phase: 0.0,
+            profile: None,
+            automations: Vec::new(),
+            history: Vec::new(),
+            trim: None,
+            trim_leading: false,
+            trimmed: (0, 0),
+            on_block: None,
+            channels: Channels::default(),
+            sample_format: SampleFormat::default(),
+            normalization: None,
+            normalize_mode: NormalizeMode::default(),
+            normalize_high_pass: false,
+            #[cfg(feature = "tracing")]
+            trace: None,
+            stereo_side: 0.0,
+            stage_order: Stage::DEFAULT_ORDER.to_vec(),
+            transformers: Vec::new(),
+        }
+    }
+
+    fn validate(sound: &super::sound::Sound) -> Result<(), SynthError> {
+        let sample_rate = sound.sample_rate.0;
+        if !(sample_rate.is_finite() && sample_rate > 0.0) {
+            return Err(SynthError::InvalidSampleRate);
+        }
+        let duration = sound.duration();
+        if duration.is_nan() || duration <= 0.0 {
+            return Err(SynthError::ZeroDuration);
+        }
+        Ok(())
+    }
+
+    /// Sets the maximum number of samples that the synth allocates for a
+    /// single buffer, which limits both the length of the sound and the
+    /// length of the flanger delay line. The default is
+    /// [`DEFAULT_MAX_SAMPLES`], whatever the sample rate of the sound. The limit is checked when generation starts:
+    /// [`Self::try_generate_block`] returns an error if it is exceeded, and
+    /// [`Self::generate_block`] panics. [`Self::try_new`] checks the default
+    /// limit up front.
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    pub fn set_max_samples(&mut self, max_samples: usize) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.max_samples = max_samples;
+    }
+
+    /// Sets the maximum number of samples, as with [`Self::set_max_samples`],
+    /// from a duration in seconds at the sample rate of the sound. Since the
+    /// resulting limit grows with the sample rate, prefer
+    /// [`Self::set_max_samples`] for sounds from untrusted sources.
+    pub fn set_max_duration(&mut self, seconds: f64) {
+        self.set_max_samples((seconds * self.sound.sample_rate.0).ceil() as usize);
+    }
+
+    /// Returns the maximum number of samples. See [`Self::set_max_samples`].
+    pub fn max_samples(&self) -> usize {
+        self.max_samples
+    }
+
+    fn check_limits(&self) -> Result<(), SynthError> {
+        if self.num_samples > self.max_samples {
+            return Err(SynthError::TooManySamples {
+                num_samples: self.num_samples,
+                max_samples: self.max_samples,
+            });
+        }
+        let buffer_length = Flanger::buffer_length(&self.sound, self.min_flanger_buffer);
+        if buffer_length > self.max_samples {
+            return Err(SynthError::FlangerBufferTooLong {
+                buffer_length,
+                max_samples: self.max_samples,
+            });
+        }
+        Ok(())
+    }
+
+    /// Enables or disables preview mode. In preview mode, the sound is
+    /// rendered with at most one harmonic and without noise interpolation,
+    /// which is much faster but does not produce the exact sound. This is
+    /// intended for quick feedback while a parameter is being edited; the
+    /// duration of the output is unaffected. Preview mode is never enabled
+    /// unless requested with this method.
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.sustain.0 = 0.3;
+    /// sound.harmonics.0 = 5;
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// synth.set_preview(true);
+    /// assert_eq!(synth.generate().len(), jfxr::generate(&sound).len());
+    /// ```
+    pub fn set_preview(&mut self, preview: bool) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.preview = preview;
+    }
+
+    /// Returns whether preview mode is enabled. See [`Self::set_preview`].
+    pub fn preview(&self) -> bool {
+        self.preview
+    }
+
+    /// Sets the number of samples generated by each call to
+    /// [`Self::generate_block`], and the size of the blocks of a
+    /// [`Stream`].
+    ///
+    /// The block size never affects the output: every stage carries its
+    /// state over from one block to the next, and stages that need the whole
+    /// sound, such as normalization, hold back the stages after them until
+    /// the last block. The output is bitwise identical for every block size.
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    ///
+    /// ```rust
+    /// use jfxr::parameter::{EnumParameter, Waveform};
+    /// use jfxr::sound::Example;
+    ///
+    /// for example in [Example::Coin, Example::Explosion, Example::Powerup] {
+    ///     for waveform in Waveform::VALUES {
+    ///         let mut sound = jfxr::Sound::example(example);
+    ///         sound.waveform = *waveform;
+    ///         sound.amplification.0 = 70.0;
+    ///         sound.flanger_offset.0 = 5.0;
+    ///         sound.flanger_feedback.0 = 50.0;
+    ///         sound.high_pass_cutoff.0 = 500.0;
+    ///         let expected = jfxr::generate(&sound);
+    ///         for block_size in [1, 3, 1000, sound.num_samples()] {
+    ///             let mut synth = jfxr::Synth::new(&sound);
+    ///             synth.set_block_size(block_size);
+    ///             let samples = synth.generate();
+    ///             assert!(samples.iter().zip(&expected).all(|(a, b)| a.to_bits() == b.to_bits()), "{example:?} {} {block_size}", waveform.name());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn set_block_size(&mut self, block_size: usize) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        assert!(block_size > 0, "block size must be positive");
+        self.block_size = block_size;
+    }
+
+    /// Returns the block size. See [`Self::set_block_size`].
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Selects the compatibility mode. See [`Compatibility`] for the
+    /// differences between the modes.
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    pub fn set_compatibility(&mut self, compatibility: Compatibility) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.compatibility = compatibility;
+    }
+
+    /// Returns the compatibility mode. See [`Self::set_compatibility`].
+    pub fn compatibility(&self) -> Compatibility {
+        self.compatibility
+    }
+
+    /// Selects the precision of the arithmetic of the synth. See
+    /// [`Precision`].
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    pub fn set_precision(&mut self, precision: Precision) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.precision = precision;
+    }
+
+    /// Returns the precision. See [`Self::set_precision`].
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    /// Sets the minimum length, in seconds, of the delay line used by the
+    /// flanger. By default, the delay line is just long enough for the
+    /// largest delay the sound can produce, at the sample rate of the sound.
+    /// A longer delay line does not change the output.
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    ///
+    /// ```rust
+    /// use jfxr::parameter::*;
+    /// use jfxr::synth::Compatibility;
+    ///
+    /// for sample_rate in [11025.0, 22050.0, 44100.0] {
+    ///     // The largest delay of any sound: the largest offset and sweep,
+    ///     // plus the largest depth.
+    ///     let dry = jfxr::Sound {
+    ///         sample_rate: SampleRate(sample_rate),
+    ///         attack: Attack(0.0),
+    ///         sustain: Sustain(0.5),
+    ///         bit_crush: BitCrush(0),
+    ///         normalization: Normalization(false),
+    ///         ..Default::default()
+    ///     };
+    ///     let sound = jfxr::Sound {
+    ///         flanger_offset: FlangerOffset(FlangerOffset::MAX_VALUE),
+    ///         flanger_offset_sweep: FlangerOffsetSweep(FlangerOffsetSweep::MAX_VALUE),
+    ///         flanger_depth: FlangerDepth(FlangerDepth::MAX_VALUE),
+    ///         ..dry.clone()
+    ///     };
+    ///     let render = |min_flanger_buffer| {
+    ///         let mut synth = jfxr::Synth::new(&sound);
+    ///         synth.set_compatibility(Compatibility::Improved);
+    ///         synth.set_min_flanger_buffer(min_flanger_buffer);
+    ///         synth.generate()
+    ///     };
+    ///
+    ///     // Every delayed sample is read from the dry sound, never from a part
+    ///     // of the delay line that has been overwritten.
+    ///     let dry = jfxr::generate(&dry);
+    ///     let num_samples = dry.len() as f64;
+    ///     let at = |i: usize, delay: usize| if i >= delay { dry[i - delay] } else { 0.0 };
+    ///     let expected: Vec<f64> = (0..dry.len())
+    ///         .map(|i| {
+    ///             let time = i as f64 / sample_rate;
+    ///             let modulation = 20.0 * (0.5 - 0.5 * (2.0 * std::f64::consts::PI * time).cos());
+    ///             let offset = (50.0 + i as f64 / num_samples * 50.0 + modulation) / 1000.0 * sample_rate;
+    ///             let whole = offset.floor();
+    ///             let (a, b) = (at(i, whole as usize), at(i, whole as usize + 1));
+    ///             dry[i] + a + (offset - whole) * (b - a)
+    ///         })
+    ///         .collect();
+    ///     let samples = render(0.0);
+    ///     assert!(samples.iter().zip(&expected).all(|(a, b)| (a - b).abs() < 1e-12), "{sample_rate}");
+    ///     assert_eq!(render(1.0), samples);
+    /// }
+    /// ```
+    pub fn set_min_flanger_buffer(&mut self, seconds: f64) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.min_flanger_buffer = seconds;
+    }
+
+    /// Returns the minimum length of the flanger delay line. See
+    /// [`Self::set_min_flanger_buffer`].
+    pub fn min_flanger_buffer(&self) -> f64 {
+        self.min_flanger_buffer
+    }
+
+    /// Selects a variation of the sound. Variations differ only in the
+    /// elements of the sound that are random rather than authored: the seeds
+    /// of noise oscillators, the [repeat
+    /// jitter](super::parameter::RepeatJitter) and, if enabled with
+    /// [`Self::set_variation_phase`], a small offset to the start phase. This
+    /// allows playing the same sound several times without every instance
+    /// sounding exactly alike, while the sound itself is left unchanged. The
+    /// same variation always produces the same output, and variation 0 is
+    /// the canonical render.
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    ///
+    /// ```rust
+    /// let sound = jfxr::Sound::example(jfxr::sound::Example::Explosion);
+    /// let render = |variation| {
+    ///     let mut synth = jfxr::Synth::new(&sound);
+    ///     synth.set_variation(variation);
+    ///     synth.generate()
+    /// };
+    /// assert_eq!(render(0), jfxr::generate(&sound));
+    /// for variation in 1..5 {
+    ///     assert_ne!(render(variation), render(0));
+    ///     assert_eq!(render(variation), render(variation));
+    /// }
+    ///
+    /// // The repeat jitter of a tone changes with the variation too.
+    /// let mut sound = jfxr::Sound::default();
+    /// (sound.waveform, sound.sustain.0) = (jfxr::parameter::Waveform::Square, 0.5);
+    /// (sound.repeat_frequency.0, sound.repeat_jitter.0) = (8.0, 10.0);
+    /// let render = |sound: &jfxr::Sound, variation| {
+    ///     let mut synth = jfxr::Synth::new(sound);
+    ///     synth.set_variation(variation);
+    ///     synth.generate()
+    /// };
+    /// assert_eq!(render(&sound, 0), jfxr::generate(&sound));
+    /// assert_ne!(render(&sound, 1), render(&sound, 0));
+    /// sound.repeat_jitter.0 = 0.0;
+    /// assert_eq!(render(&sound, 1), render(&sound, 0));
+    /// ```
+    pub fn set_variation(&mut self, variation: u64) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.variation = variation;
+    }
+
+    /// Returns the variation. See [`Self::set_variation`].
+    pub fn variation(&self) -> u64 {
+        self.variation
+    }
+
+    /// Sets the largest offset, in percent of a cycle, that
+    /// [`Self::set_variation`] may add to the start phase of the sound. The
+    /// default is zero, so that only noise is affected by the variation. The
+    /// offset of variation 0 is always zero.
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.sustain.0 = 0.3;
+    /// let render = |variation| {
+    ///     let mut synth = jfxr::Synth::new(&sound);
+    ///     synth.set_variation(variation);
+    ///     synth.set_variation_phase(5.0);
+    ///     synth.generate()
+    /// };
+    /// assert_eq!(render(0), jfxr::generate(&sound));
+    /// assert_ne!(render(1), render(0));
+    /// ```
+    pub fn set_variation_phase(&mut self, percent: f64) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.variation_phase = percent;
+    }
+
+    /// Returns the largest start phase offset of variations. See
+    /// [`Self::set_variation_phase`].
+    pub fn variation_phase(&self) -> f64 {
+        self.variation_phase
+    }
+
+    /// Sets how normalization treats the channels of
+    /// [`Self::generate_stereo`]. The default is
+    /// [`NormalizeMode::PeakLinked`].
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    ///
+    /// ```rust
+    /// use jfxr::synth::NormalizeMode;
+    ///
+    /// let mut sound = jfxr::Sound::example(jfxr::sound::Example::Explosion);
+    /// sound.stereo_width.0 = 100.0;
+    /// let peaks = |mode| {
+    ///     let mut synth = jfxr::Synth::new(&sound);
+    ///     synth.set_normalize_mode(mode);
+    ///     let stereo = synth.generate_stereo();
+    ///     let left: Vec<f64> = stereo.iter().map(|[left, _]| *left).collect();
+    ///     let right: Vec<f64> = stereo.iter().map(|[_, right]| *right).collect();
+    ///     [jfxr::analysis::peak(&left), jfxr::analysis::peak(&right)]
+    /// };
+    /// let linked = peaks(NormalizeMode::PeakLinked);
+    /// assert!((linked[0].max(linked[1]) - 1.0).abs() < 1e-12);
+    /// assert!(linked[0].min(linked[1]) < 0.99);
+    /// let per_channel = peaks(NormalizeMode::PeakPerChannel);
+    /// assert!(per_channel.iter().all(|peak| (peak - 1.0).abs() < 1e-12));
+    /// ```
+    pub fn set_normalize_mode(&mut self, mode: NormalizeMode) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.normalize_mode = mode;
+    }
+
+    /// Returns how normalization treats the channels of a stereo render. See
+    /// [`Self::set_normalize_mode`].
+    pub fn normalize_mode(&self) -> NormalizeMode {
+        self.normalize_mode
+    }
+
+    /// Enables or disables a 20 Hz high-pass filter on the signal that
+    /// normalization measures, so that a DC offset, such as the drift of
+    /// brown noise, does not count towards the peak or the RMS. The output
+    /// itself is not filtered, so its peak may exceed the target by the DC
+    /// offset. Loudness in LUFS is measured through its own high-pass
+    /// filter, so it is not affected. The default is disabled.
+    ///
+    /// The measured peak is reported in [`RenderReport::normalization`], and
+    /// when the `tracing` feature is enabled, it is also recorded in the
+    /// `normalize` span.
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    ///
+    /// ```rust
+    /// use jfxr::parameter::{Amplification, BrownNoiseRoughness, Frequency, Sustain, Waveform};
+    ///
+    /// // Brown noise that drifts away from zero.
+    /// let sound = jfxr::Sound {
+    ///     waveform: Waveform::Brownnoise,
+    ///     brown_noise_roughness: BrownNoiseRoughness(0.1),
+    ///     frequency: Frequency(500.0),
+    ///     sustain: Sustain(0.2),
+    ///     amplification: Amplification(25.0),
+    ///     ..Default::default()
+    /// };
+    /// let render = |high_pass| {
+    ///     let mut synth = jfxr::Synth::new(&sound);
+    ///     synth.set_normalize_high_pass(high_pass);
+    ///     while !synth.generate_block() {}
+    ///     (synth.generated().to_vec(), synth.render_report().normalization[0])
+    /// };
+    /// let dc = |samples: &[f64]| samples.iter().sum::<f64>() / samples.len() as f64;
+    /// let ((plain, plain_level), (high_passed, high_passed_level)) = (render(false), render(true));
+    /// assert!(high_passed_level.peak < plain_level.peak);
+    /// assert!(high_passed_level.gain > plain_level.gain);
+    /// assert!(dc(&plain).abs() > 0.05);
+    /// assert_eq!(jfxr::analysis::peak(&plain), 0.25);
+    /// // Without the offset, the measured peak is lower, so the gain is
+    /// // higher; the output has the same shape.
+    /// assert!(jfxr::analysis::peak(&high_passed) > 0.5);
+    /// let ratio = high_passed[100] / plain[100];
+    /// assert!(plain.iter().zip(&high_passed).all(|(a, b)| (a * ratio - b).abs() < 1e-9));
+    /// ```
+    pub fn set_normalize_high_pass(&mut self, high_pass: bool) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.normalize_high_pass = high_pass;
+    }
+
+    /// Returns whether normalization measures the sound through a high-pass
+    /// filter. See [`Self::set_normalize_high_pass`].
+    pub fn normalize_high_pass(&self) -> bool {
+        self.normalize_high_pass
+    }
+
+    /// Returns the offset to add to noise seeds and the offset to add to the
+    /// start phase, as a fraction of a cycle, for the current variation. Both
+    /// are zero for variation 0.
+    fn variation_offsets(&self) -> (u32, f64) {
+        // The finalizer of SplitMix64, which maps 0 to 0 and scatters all
+        // other values.
+        let mut z = self.variation;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        let fraction = (z >> 11) as f64 / (1u64 << 53) as f64;
+        (z as u32, fraction * self.variation_phase / 100.0)
+    }
+
+    /// Starts the render after the given samples of the dry sound, the sound
+    /// as it leaves the envelope, as returned by [`Self::generate_dry`].
+    /// The stages up to the envelope run over the skipped samples to keep
+    /// their state, and the effects after it are primed by running them
+    /// over the history, so that the delay lines, filters and the reverb
+    /// carry on from it rather than starting from silence. The samples
+    /// before the start are left at zero; the rest match a full render
+    /// exactly, as long as the history is the dry sound of this synth, in
+    /// any [stage order](Self::set_stage_order). Only the single-channel
+    /// render with [`Self::generate_block`] and [`Self::generate`] is
+    /// primed.
+    ///
+    /// If a stage that needs the whole sound, such as normalization, is
+    /// [forced](Self::force_stage_order) before the envelope, the history is
+    /// of no use, and [`SynthError::UnprimableStage`] is returned. As the
+    /// stage order and normalization can still change, the first call to
+    /// [`Self::try_generate_block`] checks this again, and
+    /// [`Self::generate_block`] panics.
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::example(jfxr::sound::Example::Laser);
+    /// sound.flanger_offset.0 = 5.0;
+    /// sound.low_pass_cutoff.0 = 3000.0;
+    /// sound.reverb_amount.0 = 50.0;
+    /// let full = jfxr::generate(&sound);
+    /// let dry = jfxr::Synth::new(&sound).generate_dry();
+    /// let half = full.len() / 2;
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// synth.prime_with(&dry[..half]).unwrap();
+    /// assert_eq!(synth.generate()[half..], full[half..]);
+    /// ```
+    pub fn prime_with(&mut self, history: &[f64]) -> Result<(), SynthError> {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.history = history.to_vec();
+        match self.unprimable_stage() {
+            Some(stage) if !history.is_empty() => Err(SynthError::UnprimableStage(stage)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the first stage up to the envelope that needs the whole
+    /// sound, with the current settings.
+    fn unprimable_stage(&self) -> Option<Stage> {
+        let envelope = self.stage_order.iter().position(|stage| *stage == Stage::Envelope).unwrap_or(0);
+        let normalizes = self.sound.normalization.0 && self.normalization.is_none();
+        self.stage_order[..=envelope].iter().copied().find(|stage| *stage == Stage::Normalize && normalizes)
+    }
+
+    /// Primes the stages up to the envelope with the length of the history,
+    /// and the effects after it with the history itself, then continues
+    /// after it.
+    fn prime(&mut self) -> Result<(), SynthError> {
+        let len = self.history.len().min(self.num_samples);
+        if len == 0 {
+            return Ok(());
+        }
+        let envelope = self.transformers.iter().position(|transformer| transformer.stage() == Stage::Envelope).unwrap_or(0);
+        if let Some(transformer) = self.transformers[..=envelope].iter().find(|transformer| transformer.needs_whole_sound(&self.sound)) {
+            return Err(SynthError::UnprimableStage(transformer.stage()));
+        }
+        let mut scratch = vec![0.0; len];
+        let mut history = self.history[..len].to_vec();
+        for (index, transformer) in self.transformers.iter_mut().enumerate() {
+            if index <= envelope {
+                transformer.prime(&self.sound, &mut scratch);
+            } else {
+                transformer.prime(&self.sound, &mut history);
+            }
+            // Later stages only run once the whole sound is known, over all
+            // samples, the skipped ones included.
+            if transformer.needs_whole_sound(&self.sound) {
+                break;
+            }
+        }
+        self.start_sample = len;
+        Ok(())
+    }
+
+    /// Saves the state of a single-channel render after the blocks generated
+    /// so far by [`Self::generate_block`], so that the render can carry on
+    /// from there again with [`Self::restore`]. Returns `None` before the
+    /// first block.
+    ///
+    /// ```rust
+    /// let sound = jfxr::Sound::example(jfxr::sound::Example::Explosion);
+    /// let full = jfxr::generate(&sound);
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// synth.set_block_size(1000);
+    /// assert!(synth.checkpoint().is_none());
+    /// synth.generate_block();
+    /// let checkpoint = synth.checkpoint().unwrap();
+    /// while !synth.generate_block() {}
+    /// synth.restore(&checkpoint);
+    /// assert_eq!(synth.generated().len(), 1000);
+    /// assert_eq!(synth.generate(), full);
+    /// ```
+    pub fn checkpoint(&self) -> Option<Checkpoint> {
+        if self.transformers.is_empty() {
+            return None;
+        }
+        Some(Checkpoint {
+            start_sample: self.start_sample,
+            samples: self.array[..self.start_sample.min(self.array.len())].to_vec(),
+            states: self.transformers.iter().map(|transformer| transformer.state()).collect(),
+        })
+    }
+
+    /// Rewinds the render to a checkpoint made with [`Self::checkpoint`] on
+    /// this synth, whether or not the render has finished since. The next
+    /// call to [`Self::generate_block`] generates the samples after the
+    /// checkpoint again.
+    pub fn restore(&mut self, checkpoint: &Checkpoint) {
+        debug_assert_eq!(checkpoint.states.len(), self.transformers.len(), "checkpoint restored to another synth");
+        for (transformer, state) in self.transformers.iter_mut().zip(&checkpoint.states) {
+            if let Some(state) = state {
+                transformer.restore_state(state.as_ref());
+            }
+        }
+        // Stages that need the whole sound, and trimming, change the samples
+        // before the checkpoint once the render finishes.
+        self.array.clear();
+        self.array.extend_from_slice(&checkpoint.samples);
+        self.array.resize(self.num_samples, 0.0);
+        self.trimmed = (0, 0);
+        self.start_sample = checkpoint.start_sample;
+    }
+
+    /// Generates the sound as it leaves the envelope, with a single channel,
+    /// running the stages up to the envelope in the
+    /// [stage order](Self::set_stage_order). By default, this is the sound
+    /// before any effects. This is the history expected by
+    /// [`Self::prime_with`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Self::generate_block`].
+    pub fn generate_dry(mut self) -> Vec<f64> {
+        if let Err(error) = self.check_limits() {
+            panic!("cannot generate sound: {error}");
+        }
+        self.build_transformers();
+        let mut samples = vec![0.0; self.num_samples];
+        for transformer in self.transformers.iter_mut() {
+            transformer.run(&self.sound, &mut samples, 0);
+            if transformer.needs_whole_sound(&self.sound) {
+                transformer.finish(&self.sound, &mut samples);
+            }
+            if transformer.stage() == Stage::Envelope {
+                break;
+            }
+        }
+        samples
+    }
+
+    /// Makes the synth cut the samples at the end of the output that stay
+    /// below the given level, in dBFS, once the whole sound is generated, or
+    /// disables trimming with `None`. A short fade is applied at the cut, so
+    /// that it does not click. Sounds that never reach the level are cut to
+    /// a single sample. [`Self::output_duration`] and
+    /// [`Self::render_report`] reflect the cut once the render has finished;
+    /// [`super::sound::Sound::duration`] and [`Self::expected_len`] do not.
+    /// A [stereo render](Self::generate_stereo) is cut at the same frames
+    /// on both channels, where the louder channel falls below the level.
+    /// The blocks passed to [`Self::on_block`] are not trimmed.
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.waveform = jfxr::parameter::Waveform::Sine;
+    /// sound.attack.0 = 0.0;
+    /// sound.sustain.0 = 0.1;
+    /// sound.decay.0 = 2.0;
+    /// let full = jfxr::generate(&sound);
+    /// let peak = jfxr::analysis::peak(&full);
+    ///
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// synth.set_trim(Some(-60.0));
+    /// while !synth.generate_block() {}
+    /// // The decay is linear, so it reaches -60 dBFS 1 ms before its end,
+    /// // give or take a cycle of the 500 Hz tone.
+    /// let expected = sound.duration() - 2.0 * 0.001 / peak;
+    /// assert!((synth.output_duration() - expected).abs() < 0.002);
+    /// let report = synth.render_report();
+    /// assert_eq!(report.trimmed_start, 0);
+    /// assert_eq!(report.trimmed_end, full.len() - synth.generated().len());
+    /// let trimmed = synth.generated();
+    /// let fade = 5 * 44100 / 1000;
+    /// assert_eq!(trimmed[..trimmed.len() - fade], full[..trimmed.len() - fade]);
+    /// assert!(trimmed.last().unwrap().abs() < 0.001 / fade as f64 * 2.0);
+    ///
+    /// // With a slow attack, the start can be trimmed as well.
+    /// let slow = jfxr::Sound { attack: jfxr::parameter::Attack(0.5), ..sound.clone() };
+    /// let mut synth = jfxr::Synth::new(&slow);
+    /// synth.set_trim(Some(-20.0));
+    /// synth.set_trim_leading(true);
+    /// while !synth.generate_block() {}
+    /// let report = synth.render_report();
+    /// let expected = 0.5 * 0.1 / jfxr::analysis::peak(&jfxr::generate(&slow));
+    /// assert!((report.trimmed_start as f64 / 44100.0 - expected).abs() < 0.002);
+    /// assert!(report.trimmed_end > 0);
+    ///
+    /// // Stereo renders are trimmed the same way, widened or not.
+    /// for width in [0.0, 50.0] {
+    ///     let wide = jfxr::Sound { stereo_width: jfxr::parameter::StereoWidth(width), ..sound.clone() };
+    ///     let mut synth = jfxr::Synth::new(&wide);
+    ///     synth.set_trim(Some(-60.0));
+    ///     let (stereo, report) = synth.try_generate_stereo_with_report().unwrap();
+    ///     assert!(report.trimmed_end > 0);
+    ///     assert_eq!(stereo.len(), full.len() - report.trimmed_end);
+    ///     assert!(stereo.last().unwrap().iter().all(|sample| sample.abs() < 0.001 / fade as f64 * 2.0));
+    /// }
+    /// ```
+    pub fn set_trim(&mut self, threshold_db: Option<f64>) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.trim = threshold_db;
+    }
+
+    pub fn trim(&self) -> Option<f64> {
+        self.trim
+    }
+
+    /// Makes [`Self::set_trim`] cut the samples at the start of the output
+    /// that stay below the level as well, for sounds with slow attacks.
+    pub fn set_trim_leading(&mut self, trim_leading: bool) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.trim_leading = trim_leading;
+    }
+
+    pub fn trim_leading(&self) -> bool {
+        self.trim_leading
+    }
+
+    /// Cuts the ends of the finished output, as set with [`Self::set_trim`].
+    fn apply_trim(&mut self) {
+        let mut array = std::mem::take(&mut self.array);
+        self.trim_frames(&mut array);
+        self.array = array;
+    }
+
+    /// Cuts the ends of the given finished output, as set with
+    /// [`Self::set_trim`], and records how many frames were cut.
+    fn trim_frames<F: TrimFrame>(&mut self, frames: &mut Vec<F>) {
+        let Some(threshold_db) = self.trim else {
+            return;
+        };
+        let threshold = super::analysis::db_to_gain(threshold_db);
+        let audible = |frame: &F| frame.level() >= threshold;
+        let end = frames.iter().rposition(audible).map_or(1, |index| index + 1);
+        let start = if self.trim_leading { frames[..end].iter().position(audible).unwrap_or(0) } else { 0 };
+        let fade = ((TRIM_FADE * self.sound.sample_rate.0) as usize).min((end - start) / 2);
+        if end < frames.len() {
+            for (i, frame) in frames[end - fade..end].iter_mut().enumerate() {
+                frame.scale((fade - i) as f64 / fade as f64);
+            }
+        }
+        if start > 0 {
+            for (i, frame) in frames[start..start + fade].iter_mut().enumerate() {
+                frame.scale((i + 1) as f64 / fade as f64);
+            }
+        }
+        self.trimmed = (start, frames.len() - end);
+        frames.truncate(end);
+        frames.drain(..start);
+    }
+
+    /// Enables or disables profiling. When enabled, the time spent in each
+    /// stage of the pipeline is recorded, and can be obtained with
+    /// [`Self::profile`]. When disabled, no timing is performed at all.
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    pub fn set_profiling(&mut self, profiling: bool) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.profile = profiling.then(Vec::new);
+    }
+
+    /// Returns the total time spent in each stage of the pipeline so far, in
+    /// pipeline order. This is empty unless profiling was enabled with
+    /// [`Self::set_profiling`].
+    ///
+    /// ```rust
+    /// let sound = jfxr::Sound::example(jfxr::sound::Example::Laser);
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// synth.set_profiling(true);
+    /// let start = std::time::Instant::now();
+    /// while !synth.generate_block() {}
+    /// let elapsed = start.elapsed();
+    ///
+    /// // There is one entry for every stage, in the order in which they ran.
+    /// let profile = synth.profile();
+    /// let stages: Vec<jfxr::Stage> = profile.iter().map(|(stage, _)| *stage).collect();
+    /// assert_eq!(stages, synth.stage_order());
+    /// assert_eq!(stages, jfxr::Stage::DEFAULT_ORDER);
+    /// // The stages take up most of the render, and never more than all of it.
+    /// let total: std::time::Duration = profile.iter().map(|(_, duration)| *duration).sum();
+    /// assert!(total <= elapsed);
+    /// assert!(total > elapsed / 4, "{total:?} of {elapsed:?}");
+    ///
+    /// // Without profiling, nothing is recorded.
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// while !synth.generate_block() {}
+    /// assert!(synth.profile().is_empty());
+    /// ```
+    pub fn profile(&self) -> Vec<(Stage, std::time::Duration)> {
+        match &self.profile {
+            Some(profile) => self.transformers
+                .iter()
+                .zip(profile.iter())
+                .map(|(transformer, duration)| (transformer.stage(), *duration))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Sets the order in which the stages of the pipeline run, for example
+    /// to filter the sound before it is bit crushed, or to compress it
+    /// before the flanger. Every stage must appear once, the generator
+    /// first, and normalization and amplification last, in that order; use
+    /// [`Self::force_stage_order`] to move those too. Only
+    /// [`Stage::DEFAULT_ORDER`] renders like the web tool.
+    ///
+    /// The order is ignored by [`StaticSynth`], whose pipeline is fixed.
+    /// [`Self::generate_dry`] and [`Self::prime_with`] follow the order,
+    /// with the stages up to the envelope making up the dry sound.
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    ///
+    /// ```rust
+    /// use jfxr::synth::StageOrderError;
+    /// use jfxr::Stage;
+    ///
+    /// let mut sound = jfxr::Sound::example(jfxr::sound::Example::Laser);
+    /// sound.bit_crush.0 = 4;
+    /// sound.low_pass_cutoff.0 = 2000.0;
+    /// let default = jfxr::generate(&sound);
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// assert_eq!(synth.stage_order(), Stage::DEFAULT_ORDER);
+    /// synth.set_stage_order(Stage::DEFAULT_ORDER).unwrap();
+    /// assert_eq!(synth.generate(), default);
+    ///
+    /// // Filter before bit crushing.
+    /// let mut order = Stage::DEFAULT_ORDER.to_vec();
+    /// order.swap(4, 5);
+    /// assert_eq!(order[4], Stage::LowPass);
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// synth.set_stage_order(&order).unwrap();
+    /// assert_eq!(synth.stage_order(), order);
+    /// let filtered_first = synth.generate();
+    /// assert_eq!(filtered_first.len(), default.len());
+    /// assert_ne!(filtered_first, default);
+    ///
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// assert_eq!(synth.set_stage_order(&order[1..]), Err(StageOrderError::GeneratorNotFirst));
+    /// assert_eq!(synth.set_stage_order(&order[..10]), Err(StageOrderError::Missing(Stage::Amplify)));
+    /// assert_eq!(synth.set_stage_order(&[&order[..], &[Stage::Reverb]].concat()), Err(StageOrderError::Duplicate(Stage::Reverb)));
+    /// order.swap(8, 9);
+    /// assert_eq!(synth.set_stage_order(&order), Err(StageOrderError::OutputNotLast));
+    /// // A failed call leaves the order unchanged.
+    /// assert_eq!(synth.stage_order(), Stage::DEFAULT_ORDER);
+    /// synth.force_stage_order(&order).unwrap();
+    /// assert_eq!(synth.stage_order()[8], Stage::Normalize);
+    ///
+    /// // Normalize before the reverb, which then adds to the peak.
+    /// let mut reverb = sound.clone();
+    /// reverb.reverb_amount.0 = 50.0;
+    /// let mut synth = jfxr::Synth::new(&reverb);
+    /// synth.force_stage_order(&order).unwrap();
+    /// let reverb_last = synth.generate();
+    /// assert_eq!(reverb_last.len(), reverb.num_samples());
+    /// assert_ne!(reverb_last, jfxr::generate(&reverb));
+    /// assert!(jfxr::analysis::peak(&reverb_last) > reverb.amplification.0 / 100.0);
+    /// ```
+    pub fn set_stage_order(&mut self, order: &[Stage]) -> Result<(), StageOrderError> {
+        if order.len() >= 2 && order[order.len() - 2..] != [Stage::Normalize, Stage::Amplify] {
+            Self::check_stage_order(order)?;
+            return Err(StageOrderError::OutputNotLast);
+        }
+        self.force_stage_order(order)
+    }
+
+    /// Sets the order in which the stages of the pipeline run, like
+    /// [`Self::set_stage_order`], but allows normalization and amplification
+    /// to run before other stages. The generator must still be first.
+    pub fn force_stage_order(&mut self, order: &[Stage]) -> Result<(), StageOrderError> {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        Self::check_stage_order(order)?;
+        self.stage_order = order.to_vec();
+        Ok(())
+    }
+
+    fn check_stage_order(order: &[Stage]) -> Result<(), StageOrderError> {
+        for (i, stage) in order.iter().enumerate() {
+            if order[..i].contains(stage) {
+                return Err(StageOrderError::Duplicate(*stage));
+            }
+        }
+        if order.first() != Some(&Stage::Generator) {
+            return Err(StageOrderError::GeneratorNotFirst);
+        }
+        if let Some(stage) = Stage::DEFAULT_ORDER.iter().find(|stage| !order.contains(stage)) {
+            return Err(StageOrderError::Missing(*stage));
+        }
+        Ok(())
+    }
+
+    pub fn stage_order(&self) -> &[Stage] {
+        &self.stage_order
+    }
+
+    /// Overrides a parameter with a curve, which gives the value of the
+    /// parameter at any point in time during the sound. This replaces the
+    /// value from the sound, including any sweeps or other modulation. The
+    /// values are clamped to the range of the parameter.
+    ///
+    /// This must be called before the first call to
+    /// [`Self::generate_block`].
+    ///
+    /// ```rust
+    /// use jfxr::parameter::{Attack, Frequency, NoiseInterpolation, Normalization, Sustain, Waveform};
+    /// use jfxr::AutomationTarget;
+    ///
+    /// let sound = jfxr::Sound {
+    ///     waveform: Waveform::Whitenoise,
+    ///     frequency: Frequency(10000.0),
+    ///     noise_interpolation: NoiseInterpolation::None,
+    ///     attack: Attack(0.0),
+    ///     sustain: Sustain(1.0),
+    ///     normalization: Normalization(false),
+    ///     ..Default::default()
+    /// };
+    /// let render = |cutoff: fn(f64) -> f64| {
+    ///     let mut synth = jfxr::Synth::new(&sound);
+    ///     synth.automate(AutomationTarget::LowPassCutoff, cutoff);
+    ///     synth.generate()
+    /// };
+    /// // Close the low-pass filter over the course of the sound.
+    /// let samples = render(|time| 8000.0 * (200.0f64 / 8000.0).powf(time));
+    ///
+    /// // Brightness of a part of the sound, from the level of the difference
+    /// // between consecutive samples relative to the level of the samples.
+    /// let brightness = |samples: &[f64]| {
+    ///     let differences: Vec<f64> = samples.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    ///     jfxr::analysis::rms(&differences) / jfxr::analysis::rms(samples)
+    /// };
+    /// let tenth = samples.len() / 10;
+    /// let (start, end) = (brightness(&samples[..tenth]), brightness(&samples[samples.len() - tenth..]));
+    /// assert!(start > 3.0 * end, "{start} {end}");
+    ///
+    /// // The sound alone is as bright at the end as at the start.
+    /// let unfiltered = jfxr::generate(&sound);
+    /// let (start, end) = (brightness(&unfiltered[..tenth]), brightness(&unfiltered[unfiltered.len() - tenth..]));
+    /// assert!((start / end - 1.0).abs() < 0.1, "{start} {end}");
+    ///
+    /// // Values beyond the range of the parameter are clamped to it, so a
+    /// // cutoff above the largest one leaves the filter open.
+    /// assert_eq!(render(|_| 1e9), unfiltered);
+    /// ```
+    pub fn automate(&mut self, target: AutomationTarget, curve: impl Fn(f64) -> f64 + Send + Sync + 'static) {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.automations.retain(|(existing, _)| *existing != target);
+        self.automations.push((target, std::sync::Arc::new(curve)));
+    }
+
+    /// Sets a callback which is invoked after each block has been generated,
+    /// with the index of the first sample in the block and the samples of the
+    /// block. This can be used to display the sound progressively while it
+    /// is being rendered.
+    ///
+    /// The samples passed to the callback are provisional: normalization and
+    /// amplification are only applied to the whole sound once the last block
+    /// has been generated, so the final samples may be scaled differently
+    /// from what the callback has seen. Use the output of [`Self::generate`]
+    /// for the final values.
+    ///
+    /// ```rust
+    /// let sound = jfxr::Sound::example(jfxr::sound::Example::Laser);
+    /// let mut blocks = 0;
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// synth.on_block(|_start, _samples| blocks += 1);
+    /// synth.generate();
+    /// assert_eq!(blocks, sound.num_samples().div_ceil(10240));
+    /// ```
+    ///
+    /// The callback must be `Send`, so that a synth, and a [`PullSource`]
+    /// that owns one, can be moved to another thread.
+    pub fn on_block(&mut self, callback: impl FnMut(usize, &[f64]) + Send + 'a) {
+        self.on_block = Some(Box::new(callback));
+    }
+
+    fn automation(&self, target: AutomationTarget) -> Option<Curve> {
+        self.automations
+            .iter()
+            .find(|(existing, _)| *existing == target)
+            .map(|(_, curve)| curve.clone())
+    }
+
+    fn build_transformers(&mut self) {
+        self.transformers = match self.precision {
+            Precision::F64 => self.boxed_stages::<f64>(),
+            Precision::F32 => self.boxed_stages::<f32>(),
+            Precision::Q15 => self.boxed_stages::<Q15>(),
+        };
+        self.transformers.sort_by_key(|transformer| self.stage_order.iter().position(|stage| *stage == transformer.stage()));
+        if self.precision != Precision::F64 {
+            // The stages that do not compute in the precision round their
+            // output to it.
+            let precision = self.precision;
+            self.transformers = std::mem::take(&mut self.transformers)
+                .into_iter()
+                .map(|inner| match inner.stage() {
+                    Stage::Generator | Stage::LowPass | Stage::HighPass => inner,
+                    _ => Box::new(Quantize { inner, precision }) as Box<dyn Transformer>,
+                })
+                .collect();
+        }
+        // Blocks are at most a render quantum when rendered with
+        // `render_quantum`, and at most the block size otherwise.
+        let max_block_len = self.block_size.max(RENDER_QUANTUM).min(self.num_samples);
+        for transformer in self.transformers.iter_mut() {
+            transformer.reserve(max_block_len);
+        }
+        if let Some(profile) = self.profile.as_mut() {
+            *profile = vec![std::time::Duration::ZERO; self.transformers.len()];
+        }
+    }
+
+    /// Creates the stages of the pipeline, in order, as boxed transformers,
+    /// with the generator and the filters computing in the precision `T`.
+    fn boxed_stages<T: Real>(&mut self) -> Vec<Box<dyn Transformer>> {
+        let (generator, envelope, flanger, chorus, bit_crush, low_pass, high_pass, compress, reverb, normalize, amplify) = self.build_stages::<T>();
+        vec![
+            Box::new(generator),
+            Box::new(envelope),
+            Box::new(flanger),
+            Box::new(chorus),
+            Box::new(bit_crush),
+            Box::new(low_pass),
+            Box::new(high_pass),
+            Box::new(compress),
+            Box::new(reverb),
+            Box::new(normalize),
+            Box::new(amplify),
+        ]
+    }
+
+    /// Creates the stages of the pipeline, in order, for both the boxed
+    /// transformers of this synth and the static chain of [`StaticSynth`].
+    fn build_stages<T: Real>(&mut self) -> Stages<T> {
+        if self.preview {
+            let sound = self.sound.to_mut();
+            sound.harmonics.0 = sound.harmonics.0.min(1);
+            sound.noise_interpolation = super::parameter::NoiseInterpolation::None;
+        }
+        let frequency = self.automation(AutomationTarget::Frequency);
+        let square_duty = self.automation(AutomationTarget::SquareDuty);
+        let low_pass_cutoff = self.automation(AutomationTarget::LowPassCutoff);
+        let high_pass_cutoff = self.automation(AutomationTarget::HighPassCutoff);
+        let amplification = self.automation(AutomationTarget::Amplification);
+        let (mut seed_offset, mut phase_offset) = self.variation_offsets();
+        // The repeat jitter changes with the variation, but not between the
+        // sides of a stereo render, which would detune them.
+        let jitter_seed = super::oscillator::NOISE_SEED.wrapping_add(seed_offset);
+        if self.stereo_side != 0.0 {
+            seed_offset = seed_offset.wrapping_add(if self.stereo_side < 0.0 { STEREO_SEED_OFFSETS[0] } else { STEREO_SEED_OFFSETS[1] });
+            phase_offset += self.stereo_side * STEREO_PHASE_OFFSET;
         }
+        let sound = self.sound.as_ref();
+        (
+            Generator::new(sound, self.compatibility, seed_offset, jitter_seed, phase_offset, frequency, square_duty),
+            Envelope::new(sound),
+            Flanger::new(sound, self.compatibility, self.min_flanger_buffer),
+            Chorus::new(sound),
+            BitCrush::new(sound),
+            LowPass::new(sound, low_pass_cutoff),
+            HighPass::new(sound, high_pass_cutoff),
+            Compress::new(sound, self.compatibility),
+            Reverb::new(sound),
+            Normalize::new(sound, self.normalization, self.normalize_high_pass),
+            Amplify::new(sound, amplification),
+        )
     }
 
     /// Generates a single block of sample data. This function modifies the
     /// internal state of the synth. This function returns `false` while there
     /// are more samples to generate. It returns `true` once all samples have
     /// been generated, at which point the final sample data can be obtained
-    /// with a call to [`Self::generate`]. Further calls to this function have
-    /// no effect.
+    /// with a call to [`Self::generate`] or [`Self::generated`]. Once all
+    /// samples have been generated, this function must not be called again
+    /// until the synth is [reset](Self::reset); debug builds assert this, and
+    /// release builds just return `true`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sound exceeds the limits set with
+    /// [`Self::set_max_samples`]. Use [`Self::try_generate_block`] to handle
+    /// this as an error instead.
     pub fn generate_block(&mut self) -> bool {
-        let num_samples = self.array.len();
+        match self.generate_next_block() {
+            Ok(done) => done,
+            Err(error) => panic!("cannot generate sound: {error}"),
+        }
+    }
+
+    /// Generates a single block of sample data, like [`Self::generate_block`],
+    /// but returns an error instead of panicking if the sound exceeds the
+    /// limits set with [`Self::set_max_samples`], and instead of rendering
+    /// no samples if the sound cannot be generated at all. The limits are
+    /// checked before the first block is generated.
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.sustain.0 = 2.0;
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// synth.set_max_duration(1.0);
+    /// assert!(matches!(synth.try_generate_block(), Err(jfxr::SynthError::TooManySamples { .. })));
+    /// ```
+    pub fn try_generate_block(&mut self) -> Result<bool, SynthError> {
+        Self::validate(&self.sound)?;
+        self.generate_next_block()
+    }
+
+    fn generate_next_block(&mut self) -> Result<bool, SynthError> {
+        let num_samples = self.num_samples;
+
+        debug_assert!(!self.is_finished() || num_samples == 0, "sound generated again without resetting the synth");
+        if self.is_finished() {
+            return Ok(true);
+        }
+
+        if self.transformers.is_empty() {
+            self.check_limits()?;
+            #[cfg(feature = "tracing")]
+            self.start_trace();
+            self.build_transformers();
+            self.array.clear();
+            self.array.resize(num_samples, 0.0);
+            self.trimmed = (0, 0);
+            self.prime()?;
+        }
+
+        let end_sample = (self.start_sample + self.block_size).min(num_samples);
+        {
+            #[cfg(feature = "tracing")]
+            let _entered = self.trace.as_ref().map(|trace| trace.span.enter());
+            run_block(&mut self.transformers, self.profile.as_mut(), &self.sound, &mut self.array, 0, self.start_sample..end_sample, end_sample == num_samples);
+        }
+        if let Some(on_block) = self.on_block.as_mut() {
+            on_block(self.start_sample, &self.array[self.start_sample..end_sample]);
+        }
+        self.start_sample = end_sample;
+        if self.is_finished() {
+            self.apply_trim();
+        }
+
+        #[cfg(feature = "tracing")]
+        if self.is_finished() {
+            // Normalization may only be applied to the whole sound at the
+            // end, so the output is measured then.
+            if let Some(trace) = self.trace.as_mut() {
+                trace.add(&self.array);
+            }
+            self.finish_trace();
+        }
+        Ok(self.start_sample >= num_samples)
+    }
+
+    /// Turns the synth into a [`Stream`], which generates the sound block by
+    /// block without keeping the whole sound in memory. Only one block of
+    /// samples is allocated, so this is suitable for very long sounds.
+    ///
+    /// Normalization needs the peak of the whole sound, so when it is
+    /// enabled, the sound is generated twice: once to find the peak, and once
+    /// more to produce the output. The output then matches [`Self::generate`]
+    /// except for rounding differences in the last bits.
+    ///
+    /// All memory is allocated here, so [`Stream::next_block`] does not
+    /// allocate, and can be called on an audio thread.
+    pub fn stream(mut self) -> Stream<'a> {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.start_streaming();
+        Stream {
+            block: vec![0.0; self.block_size.min(self.num_samples)],
+            synth: self,
+        }
+    }
+
+    /// Prepares the synth to generate the sound one block at a time, without
+    /// keeping the whole sound in memory.
+    fn start_streaming(&mut self) {
+        if let Err(error) = self.check_limits() {
+            panic!("cannot generate sound: {error}");
+        }
+        #[cfg(feature = "tracing")]
+        self.start_trace();
+        if self.sound.normalization.0 {
+            let level = self.measure_level().normalization(&self.sound);
+            #[cfg(feature = "tracing")]
+            if let Some(trace) = &self.trace {
+                tracing::debug_span!(parent: &trace.span, "normalize", peak = level.peak, gain = level.gain).in_scope(|| {});
+            }
+            self.normalization = Some(level);
+        }
+        self.build_transformers();
+    }
+
+    /// Generates the next [`RENDER_QUANTUM`] samples of the sound into `out`,
+    /// as `f32`, which is the format in which the Web Audio API pulls audio
+    /// from an `AudioWorkletProcessor`. Returns `false` once the sound has
+    /// ended, in which case the rest of `out` is filled with silence; further
+    /// calls produce only silence.
+    ///
+    /// The first call prepares the synth, as [`Self::stream`] does, which
+    /// allocates memory and, when normalization is enabled, generates the
+    /// whole sound once to find its peak. Later calls do not allocate, so
+    /// they can be made on an audio thread, as [`Stream::next_block`]. The
+    /// synth must not also be used with [`Self::generate_block`] until it is
+    /// [reset](Self::reset).
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::example(jfxr::sound::Example::Jump);
+    /// sound.normalization.0 = false;
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// let mut out = [0.0f32; jfxr::synth::RENDER_QUANTUM];
+    /// let mut rendered = Vec::new();
+    /// while synth.render_quantum(&mut out) {
+    ///     rendered.extend_from_slice(&out);
+    /// }
+    /// rendered.extend_from_slice(&out);
+    /// let expected: Vec<f32> = jfxr::generate(&sound).iter().map(|sample| *sample as f32).collect();
+    /// assert_eq!(rendered[..expected.len()], expected);
+    /// assert!(rendered[expected.len()..].iter().all(|sample| *sample == 0.0));
+    /// ```
+    pub fn render_quantum(&mut self, out: &mut [f32; RENDER_QUANTUM]) -> bool {
+        if self.transformers.is_empty() && !self.is_finished() {
+            self.start_streaming();
+            self.array.clear();
+            self.array.resize(RENDER_QUANTUM, 0.0);
+        }
+        let start_sample = self.start_sample;
+        let end_sample = (start_sample + RENDER_QUANTUM).min(self.num_samples);
+        let block = &mut self.array[..end_sample - start_sample];
+        if !block.is_empty() {
+            #[cfg(feature = "tracing")]
+            let _entered = self.trace.as_ref().map(|trace| trace.span.enter());
+            run_block(&mut self.transformers, self.profile.as_mut(), &self.sound, block, start_sample, start_sample..end_sample, false);
+            if let Some(on_block) = self.on_block.as_mut() {
+                on_block(start_sample, block);
+            }
+        }
+        for (out, sample) in out.iter_mut().zip(block.iter()) {
+            *out = *sample as f32;
+        }
+        out[block.len()..].fill(0.0);
+        self.start_sample = end_sample;
+        #[cfg(feature = "tracing")]
+        {
+            if let Some(trace) = self.trace.as_mut() {
+                trace.add(&self.array[..end_sample - start_sample]);
+            }
+            if self.is_finished() {
+                self.finish_trace();
+            }
+        }
+        !self.is_finished()
+    }
+
+    /// Starts the `render` span of a new render, if debug spans of this
+    /// crate are enabled. The stages are then timed as if profiling was
+    /// enabled with [`Self::set_profiling`].
+    #[cfg(feature = "tracing")]
+    fn start_trace(&mut self) {
+        self.trace = RenderTrace::new(&self.sound, self.num_samples);
+        if self.trace.is_some() && self.profile.is_none() {
+            self.profile = Some(Vec::new());
+        }
+    }
+
+    /// Records the measurements of the render in its span, with a `stage`
+    /// span for each stage, and logs a summary.
+    #[cfg(feature = "tracing")]
+    fn finish_trace(&mut self) {
+        if let Some(trace) = self.trace.take() {
+            trace.finish(&self.profile());
+        }
+    }
+
+    /// Runs the pipeline up to the normalization stage over the whole sound,
+    /// and returns its level. Leaves the synth ready to start generation
+    /// again from the start.
+    fn measure_level(&mut self) -> Level {
+        self.build_transformers();
+        let mut block = vec![0.0; self.block_size.min(self.num_samples)];
+        let mut level = Level::new(&self.sound, self.normalize_high_pass);
+        let mut start_sample = 0;
+        while start_sample < self.num_samples {
+            let end_sample = (start_sample + self.block_size).min(self.num_samples);
+            let block = &mut block[..end_sample - start_sample];
+            for transformer in self.transformers.iter_mut().take_while(|transformer| transformer.stage() != Stage::Normalize) {
+                transformer.run(&self.sound, block, start_sample);
+            }
+            level.add(block);
+            start_sample = end_sample;
+        }
+        self.transformers.clear();
+        level
+    }
+
+    /// Ensures all sample data is generated, then returns it as a vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Self::generate_block`].
+    pub fn generate(mut self) -> Vec<f64> {
+        while !self.is_finished() {
+            self.generate_block();
+        }
+        self.array
+    }
+
+    /// Ensures all sample data is generated, then returns it as a vector, or
+    /// returns an error under the same conditions as
+    /// [`Self::try_generate_block`].
+    pub fn try_generate(mut self) -> Result<Vec<f64>, SynthError> {
+        Self::validate(&self.sound)?;
+        while !self.is_finished() {
+            self.generate_next_block()?;
+        }
+        Ok(self.array)
+    }
+
+    /// Generates the sound in stereo, as pairs of left and right samples.
+    /// Each channel mixes the sound with a decorrelated render of it, with
+    /// different noise seeds and start phases, by the amount set with the
+    /// stereo width of the sound. The result is then panned, following
+    /// [`super::sound::Sound::pan_at`], with a constant-power pan law under
+    /// which a centred sound keeps its level on both channels. Normalization
+    /// uses the peak of both channels together. At a stereo width of 0,
+    /// without panning, both channels are exactly the output of
+    /// [`Self::generate`].
+    ///
+    /// The sound is rendered three times, in full, so this ignores the block
+    /// size, and the callback set with [`Self::on_block`] is not called.
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::example(jfxr::sound::Example::Explosion);
+    /// let mono = jfxr::generate(&sound);
+    /// let stereo = jfxr::Synth::new(&sound).generate_stereo();
+    /// assert!(stereo.iter().zip(&mono).all(|([left, right], sample)| left == sample && right == sample));
+    ///
+    /// // Correlation between the left and right channels.
+    /// let correlation = |width: f64| {
+    ///     sound.stereo_width.0 = width;
+    ///     let stereo = jfxr::Synth::new(&sound).generate_stereo();
+    ///     let sum = |f: &dyn Fn(&[f64; 2]) -> f64| stereo.iter().map(f).sum::<f64>();
+    ///     sum(&|[l, r]| l * r) / (sum(&|[l, _]| l * l) * sum(&|[_, r]| r * r)).sqrt()
+    /// };
+    /// let correlations: Vec<f64> = [0.0, 25.0, 50.0, 100.0].into_iter().map(correlation).collect();
+    /// assert!((correlations[0] - 1.0).abs() < 1e-9);
+    /// assert!(correlations.windows(2).all(|pair| pair[1] < pair[0]));
+    /// assert!(correlations[3] < 0.5);
+    ///
+    /// // A full sweep moves the sound from the left channel to the right.
+    /// sound.stereo_width.0 = 0.0;
+    /// sound.pan.0 = -100.0;
+    /// sound.pan_sweep.0 = 200.0;
+    /// let stereo = jfxr::Synth::new(&sound).generate_stereo();
+    /// let body = (sound.duration() * sound.sample_rate.0) as usize;
+    /// let rms = |range: std::ops::Range<usize>, channel: usize| {
+    ///     let samples: Vec<f64> = stereo[range].iter().map(|pair| pair[channel]).collect();
+    ///     jfxr::analysis::rms(&samples)
+    /// };
+    /// let quarter = body / 4;
+    /// assert!(rms(0..quarter, 0) > 2.0 * rms(0..quarter, 1));
+    /// assert!(rms(3 * quarter..body, 1) > 2.0 * rms(3 * quarter..body, 0));
+    /// // The envelopes cross over in the middle, where the gains are equal.
+    /// let middle = body / 2 - 500..body / 2 + 500;
+    /// assert!((rms(middle.clone(), 0) / rms(middle, 1) - 1.0).abs() < 0.05);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Self::generate_block`].
+    pub fn generate_stereo(self) -> Vec<[f64; 2]> {
+        // Sounds that cannot be generated render to no samples.
+        if self.num_samples == 0 {
+            return Vec::new();
+        }
+        match self.try_generate_stereo() {
+            Ok(samples) => samples,
+            Err(error) => panic!("cannot generate sound: {error}"),
+        }
+    }
+
+    /// Generates the sound in stereo, like [`Self::generate_stereo`], or
+    /// returns an error under the same conditions as
+    /// [`Self::try_generate_block`].
+    pub fn try_generate_stereo(mut self) -> Result<Vec<[f64; 2]>, SynthError> {
+        Ok(self.render_stereo()?.0)
+    }
+
+    /// Generates the sound in stereo, like [`Self::try_generate_stereo`], and
+    /// measures it like [`Self::render_report`]. The peak, the clipped
+    /// samples and the clicks cover both channels, and normalization is
+    /// reported for each channel.
+    ///
+    /// ```rust
+    /// use jfxr::synth::NormalizeMode;
+    ///
+    /// let mut sound = jfxr::Sound::example(jfxr::sound::Example::Explosion);
+    /// sound.stereo_width.0 = 100.0;
+    /// let report = |sound: &jfxr::Sound, mode| {
+    ///     let mut synth = jfxr::Synth::new(sound);
+    ///     synth.set_normalize_mode(mode);
+    ///     synth.try_generate_stereo_with_report().unwrap()
+    /// };
+    /// let (stereo, linked) = report(&sound, NormalizeMode::PeakLinked);
+    /// let [left, right] = &linked.normalization[..] else { panic!() };
+    /// // Both channels get the gain of the louder one.
+    /// assert_ne!(left.peak, right.peak);
+    /// assert_eq!(left.gain, right.gain);
+    /// assert_eq!(left.gain, 1.0 / left.peak.max(right.peak));
+    /// let left_peak = jfxr::analysis::peak(&stereo.iter().map(|[left, _]| *left).collect::<Vec<_>>());
+    /// assert!((left_peak - left.peak * left.gain).abs() < 1e-12);
+    ///
+    /// let (_, per_channel) = report(&sound, NormalizeMode::PeakPerChannel);
+    /// assert_eq!(per_channel.normalization[0].peak, left.peak);
+    /// assert!(per_channel.normalization.iter().all(|level| level.gain == 1.0 / level.peak));
+    ///
+    /// sound.normalization.0 = false;
+    /// assert!(report(&sound, NormalizeMode::PeakLinked).1.normalization.is_empty());
+    /// ```
+    pub fn try_generate_stereo_with_report(mut self) -> Result<(Vec<[f64; 2]>, RenderReport), SynthError> {
+        let (samples, normalization) = self.render_stereo()?;
+        let [left, right] = [0, 1].map(|channel| samples.iter().map(|pair| pair[channel]).collect::<Vec<_>>());
+        let report = RenderReport::measure(&self.sound, &[&left, &right], self.trimmed, normalization);
+        Ok((samples, report))
+    }
+
+    /// Generates the sound in stereo for [`Self::try_generate_stereo`], and
+    /// returns the samples along with what normalization measured.
+    fn render_stereo(&mut self) -> Result<(Vec<[f64; 2]>, Vec<NormalizationLevel>), SynthError> {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        Self::validate(&self.sound)?;
+        let width = self.sound.stereo_width.0 / 100.0;
+        let panned = self.sound.pan.0 != 0.0 || self.sound.pan_sweep.0 != 0.0 || self.sound.auto_pan_depth.0 != 0.0;
+        if width == 0.0 && !panned {
+            while !self.is_finished() {
+                self.generate_next_block()?;
+            }
+            let samples = std::mem::take(&mut self.array).into_iter().map(|sample| [sample, sample]).collect();
+            return Ok((samples, self.measured_normalization().map_or_else(Vec::new, |level| vec![level; 2])));
+        }
+        self.check_limits()?;
+        let centre = self.generate_unnormalized(0.0);
+        let mut channels = if width == 0.0 {
+            [centre.clone(), centre]
+        } else {
+            let mut channels = [self.generate_unnormalized(-1.0), self.generate_unnormalized(1.0)];
+            for channel in &mut channels {
+                for (sample, centre) in channel.iter_mut().zip(&centre) {
+                    *sample = (1.0 - width) * centre + width * *sample;
+                }
+            }
+            channels
+        };
+        if panned {
+            // Constant-power pan law, scaled so that the centre is at unity
+            // gain on both channels.
+            let [left, right] = &mut channels;
+            for (index, (left, right)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+                let angle = (self.sound.pan_at(index as f64 / self.sound.sample_rate.0) + 1.0) * std::f64::consts::FRAC_PI_4;
+                *left *= crate::math::cos(angle) * std::f64::consts::SQRT_2;
+                *right *= crate::math::sin(angle) * std::f64::consts::SQRT_2;
+            }
+        }
+        let mut levels = channels.each_ref().map(|channel| {
+            let mut level = Level::new(&self.sound, self.normalize_high_pass);
+            level.add(channel);
+            let level = level.normalization(&self.sound);
+            #[cfg(feature = "tracing")]
+            tracing::debug_span!("normalize", peak = level.peak, gain = level.gain).in_scope(|| {});
+            level
+        });
+        if self.normalize_mode == NormalizeMode::PeakLinked {
+            // Level the louder channel, so that neither exceeds the target.
+            let gain = levels[0].gain.min(levels[1].gain);
+            for level in &mut levels {
+                level.gain = gain;
+            }
+        }
+        self.stereo_side = 0.0;
+        for (channel, level) in channels.iter_mut().zip(levels) {
+            self.normalization = Some(level);
+            self.build_transformers();
+            for transformer in self.transformers.iter_mut().skip_while(|transformer| transformer.stage() != Stage::Normalize) {
+                transformer.run(&self.sound, channel, 0);
+            }
+        }
+        let normalization = if self.sound.normalization.0 { levels.to_vec() } else { Vec::new() };
+        let [left, right] = channels;
+        let mut samples = left.into_iter().zip(right).map(|(left, right)| [left, right]).collect();
+        self.trim_frames(&mut samples);
+        Ok((samples, normalization))
+    }
+
+    /// Returns what the normalization stage measured and applied, once it
+    /// is known.
+    fn measured_normalization(&self) -> Option<NormalizationLevel> {
+        self.transformers.iter().find_map(|transformer| transformer.normalization())
+    }
+
+    /// Generates the sound with the channel layout of the synth, with the
+    /// samples of all channels interleaved.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Self::generate_block`].
+    pub fn generate_interleaved(self) -> Vec<f64> {
+        match self.channels {
+            Channels::Mono => self.generate(),
+            Channels::Stereo => self.generate_stereo().into_iter().flatten().collect(),
+        }
+    }
+
+    /// Generates the sound like [`Self::generate_interleaved`], encoded in
+    /// the sample format of the synth.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Self::generate_block`].
+    pub fn generate_bytes(self) -> Vec<u8> {
+        let sample_format = self.sample_format;
+        let samples = self.generate_interleaved();
+        let mut bytes = Vec::with_capacity(samples.len() * sample_format.size());
+        for sample in samples {
+            sample_format.encode(sample, &mut bytes);
+        }
+        bytes
+    }
+
+    /// Runs the pipeline up to the normalization stage over the whole sound,
+    /// for the given side of a stereo render, and returns the samples.
+    fn generate_unnormalized(&mut self, stereo_side: f64) -> Vec<f64> {
+        self.stereo_side = stereo_side;
+        self.build_transformers();
+        let mut samples = vec![0.0; self.num_samples];
+        for transformer in self.transformers.iter_mut().take_while(|transformer| transformer.stage() != Stage::Normalize) {
+            transformer.run(&self.sound, &mut samples, 0);
+        }
+        samples
+    }
+
+    /// Returns the length of the output in seconds. This is the duration of
+    /// the sound, rounded up to whole samples, plus the time that effects
+    /// such as the reverb need to ring out; [`super::sound::Sound::duration`]
+    /// does not include this tail. During the tail, the envelope is silent:
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.sustain.0 = 0.3;
+    /// let output_duration = |sound: &jfxr::Sound| jfxr::Synth::new(sound).output_duration();
+    /// assert_eq!(output_duration(&sound), sound.num_body_samples() as f64 / 44100.0);
+    ///
+    /// sound.reverb_amount.0 = 50.0;
+    /// sound.reverb_decay.0 = 0.5;
+    /// assert!((output_duration(&sound) - (sound.duration() + 0.5)).abs() < 1.0 / 44100.0);
+    /// let tail = sound.num_body_samples()..sound.num_samples();
+    /// assert!(tail.clone().all(|i| sound.amplitude_at(i as f64 / 44100.0) == 0.0));
+    ///
+    /// // The reverb fills the tail, starting audibly and decaying steadily
+    /// // to near silence by the end.
+    /// let samples = jfxr::generate(&sound);
+    /// let peak = |samples: &[f64]| samples.iter().fold(0.0_f64, |peak, sample| peak.max(sample.abs()));
+    /// let peaks: Vec<f64> = samples[tail.clone()].chunks(tail.len() / 10).map(peak).collect();
+    /// assert!(peaks[0] > 0.1, "{peaks:?}");
+    /// assert!(peaks.windows(2).all(|pair| pair[1] < pair[0]), "{peaks:?}");
+    /// assert!(peaks[9] < 0.002, "{peaks:?}");
+    /// ```
+    pub fn output_duration(&self) -> f64 {
+        (self.num_samples - self.trimmed.0 - self.trimmed.1) as f64 / self.sound.sample_rate.0
+    }
+
+    /// Returns the output settings of the synth. The sample rate and
+    /// normalization are always given, even if they were not overridden.
+    pub fn settings(&self) -> OutputSettings {
+        OutputSettings {
+            sample_rate: Some(self.sound.sample_rate.0),
+            channels: self.channels,
+            sample_format: self.sample_format,
+            block_size: self.block_size,
+            normalization_override: Some(self.sound.normalization.0),
+        }
+    }
+
+    /// Returns the sample rate of the output, in Hz.
+    pub fn sample_rate(&self) -> f64 {
+        self.sound.sample_rate.0
+    }
+
+    /// Returns the channel layout of [`Self::generate_interleaved`] and
+    /// [`Self::generate_bytes`].
+    pub fn channels(&self) -> Channels {
+        self.channels
+    }
+
+    /// Returns the encoding of [`Self::generate_bytes`].
+    pub fn sample_format(&self) -> SampleFormat {
+        self.sample_format
+    }
+
+    /// Returns the number of samples, across all channels, that
+    /// [`Self::generate_interleaved`] will produce. [`Self::generate`]
+    /// produces one sample per frame, which is this number divided by the
+    /// number of channels.
+    pub fn expected_len(&self) -> usize {
+        self.num_samples * self.channels.count()
+    }
+
+    /// Returns whether all samples have been generated.
+    pub fn is_finished(&self) -> bool {
+        self.start_sample >= self.num_samples
+    }
+
+    /// Returns the samples generated so far by [`Self::generate_block`].
+    /// Until all samples have been generated, these are provisional, as
+    /// described for [`Self::on_block`].
+    pub fn generated(&self) -> &[f64] {
+        &self.array[..self.start_sample.min(self.array.len())]
+    }
+
+    /// Measures the samples generated so far, which should be done once all
+    /// samples have been generated, as the samples are provisional until
+    /// then. The samples are measured as returned by [`Self::generate`],
+    /// with a single channel; see [`Self::try_generate_stereo_with_report`]
+    /// for stereo. A silent render is reported with the diagnostics of the
+    /// sound, which point at the likely causes.
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::example(jfxr::sound::Example::Coin);
+    /// sound.attack.0 = 0.0;
+    /// sound.waveform = jfxr::parameter::Waveform::Square;
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// while !synth.generate_block() {}
+    /// let report = synth.render_report();
+    /// assert_eq!(report.clicks[0].index, 0);
+    /// assert_eq!(report.clipped, 0);
+    /// assert!(!report.is_silent());
+    /// // Normalization brought the measured peak to full scale.
+    /// let level = report.normalization[0];
+    /// assert_eq!(level.gain, 1.0 / level.peak);
+    /// assert!((report.peak - 1.0).abs() < 1e-12);
+    ///
+    /// let silent = jfxr::Sound { low_pass_cutoff: jfxr::parameter::LowPassCutoff(0.0), ..sound.clone() };
+    /// let mut synth = jfxr::Synth::new(&silent);
+    /// while !synth.generate_block() {}
+    /// let report = synth.render_report();
+    /// assert!(report.is_silent());
+    /// assert_eq!(report.silence_causes, silent.diagnose());
+    /// assert_eq!(report.silence_causes[0].params[0], jfxr::parameter::ParamId::LowPassCutoff);
+    /// ```
+    pub fn render_report(&self) -> RenderReport {
+        RenderReport::measure(&self.sound, &[self.generated()], self.trimmed, self.measured_normalization().into_iter().collect())
+    }
+
+    /// Prepares the synth to generate the sound again from the start, with
+    /// the same settings. The memory for the samples is kept and reused. A
+    /// synth renders one sound at a time, so it must be reset before it is
+    /// used for another render; for concurrent renders, create one synth per
+    /// render, which is cheap.
+    ///
+    /// ```rust
+    /// let sound = jfxr::Sound::example(jfxr::sound::Example::Coin);
+    /// let mut synth = jfxr::Synth::new(&sound);
+    /// while !synth.generate_block() {}
+    /// let first = synth.generated().to_vec();
+    /// synth.reset();
+    /// while !synth.generate_block() {}
+    /// assert_eq!(synth.generated(), first);
+    /// ```
+    pub fn reset(&mut self) {
+        self.transformers.clear();
+        self.start_sample = 0;
+    }
+
+    /// Makes the synth store the samples in the given vector, reusing its
+    /// memory, for example to render many sounds without allocating a new
+    /// buffer for each. The contents of the vector are discarded.
+    ///
+    /// ```rust
+    /// let mut buffer = Vec::new();
+    /// for example in jfxr::sound::Example::ALL {
+    ///     let sound = jfxr::Sound::example(*example);
+    ///     buffer = jfxr::Synth::new(&sound).with_buffer(buffer).generate();
+    ///     assert_eq!(buffer, sound.render());
+    /// }
+    /// ```
+    pub fn with_buffer(mut self, buffer: Vec<f64>) -> Self {
+        debug_assert!(self.transformers.is_empty(), "synth settings changed after generation started");
+        self.array = buffer;
+        self
+    }
+}
+
+/// A synth that runs the stages of the pipeline with static dispatch,
+/// rather than as boxed trait objects, which saves an allocation per stage
+/// and an indirect call per stage and block. The stages do their work per
+/// sample, so renders are only slightly faster, if at all; `cargo bench
+/// --bench dispatch` compares the two. Created by [`Synth::new_static`].
+///
+/// The stages are the same as those of [`Synth`], so the output is
+/// identical. Only the settings that do not change the pipeline are
+/// available; use [`Synth`] for automation, profiling, precision,
+/// priming, trimming and streaming.
+///
+/// ```rust
+/// use jfxr::sound::Example;
+/// use jfxr::{Compatibility, Synth};
+///
+/// let mut sounds: Vec<_> = Example::ALL.iter().map(|example| jfxr::Sound::example(*example)).collect();
+/// let mut effects = jfxr::Sound::example(Example::Laser);
+/// effects.flanger_offset.0 = 5.0;
+/// effects.flanger_offset_sweep.0 = 5.0;
+/// effects.chorus_voices.0 = 3;
+/// effects.bit_crush.0 = 8;
+/// effects.low_pass_cutoff.0 = 3000.0;
+/// effects.high_pass_cutoff.0 = 200.0;
+/// effects.compression.0 = 0.7;
+/// effects.reverb_amount.0 = 50.0;
+/// sounds.push(effects);
+///
+/// for sound in &sounds {
+///     for compatibility in [Compatibility::Exact, Compatibility::Improved] {
+///         for block_size in [1000, jfxr::synth::DEFAULT_BLOCK_SIZE] {
+///             let mut dynamic = Synth::new(sound);
+///             dynamic.set_compatibility(compatibility);
+///             dynamic.set_block_size(block_size);
+///             let mut fixed = Synth::new_static(sound);
+///             fixed.set_compatibility(compatibility);
+///             fixed.set_block_size(block_size);
+///             assert_eq!(fixed.generate(), dynamic.generate(), "{}", sound.name);
+///         }
+///     }
+/// }
+/// ```
+pub struct StaticSynth<'a> {
+    synth: Synth<'a>,
+    stages: Option<Stages>,
+}
+
+impl<'a> StaticSynth<'a> {
+    /// Sets the number of samples generated by each call to
+    /// [`Self::generate_block`], as in [`Synth::set_block_size`].
+    pub fn set_block_size(&mut self, block_size: usize) {
+        debug_assert!(self.stages.is_none(), "synth settings changed after generation started");
+        self.synth.set_block_size(block_size);
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.synth.block_size()
+    }
+
+    /// Sets the compatibility mode, as in [`Synth::set_compatibility`].
+    pub fn set_compatibility(&mut self, compatibility: Compatibility) {
+        debug_assert!(self.stages.is_none(), "synth settings changed after generation started");
+        self.synth.set_compatibility(compatibility);
+    }
+
+    pub fn compatibility(&self) -> Compatibility {
+        self.synth.compatibility()
+    }
+
+    /// Sets the maximum number of samples, as in
+    /// [`Synth::set_max_samples`].
+    pub fn set_max_samples(&mut self, max_samples: usize) {
+        debug_assert!(self.stages.is_none(), "synth settings changed after generation started");
+        self.synth.set_max_samples(max_samples);
+    }
+
+    /// Generates a single block of sample data, as in
+    /// [`Synth::generate_block`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Synth::generate_block`].
+    pub fn generate_block(&mut self) -> bool {
+        match self.generate_next_block() {
+            Ok(done) => done,
+            Err(error) => panic!("cannot generate sound: {error}"),
+        }
+    }
+
+    /// Generates a single block of sample data, or returns an error under
+    /// the same conditions as [`Synth::try_generate_block`].
+    pub fn try_generate_block(&mut self) -> Result<bool, SynthError> {
+        Synth::validate(&self.synth.sound)?;
+        self.generate_next_block()
+    }
+
+    fn generate_next_block(&mut self) -> Result<bool, SynthError> {
+        let synth = &mut self.synth;
+        debug_assert!(!synth.is_finished() || synth.num_samples == 0, "sound generated again without resetting the synth");
+        if synth.is_finished() {
+            return Ok(true);
+        }
+        let stages = match &mut self.stages {
+            Some(stages) => stages,
+            None => {
+                synth.check_limits()?;
+                let mut stages = synth.build_stages();
+                stages.reserve(synth.block_size.min(synth.num_samples));
+                synth.array.clear();
+                synth.array.resize(synth.num_samples, 0.0);
+                self.stages.insert(stages)
+            }
+        };
+        let end_sample = (synth.start_sample + synth.block_size).min(synth.num_samples);
+        stages.run_block(&synth.sound, &mut synth.array, synth.start_sample..end_sample, end_sample == synth.num_samples);
+        synth.start_sample = end_sample;
+        Ok(synth.is_finished())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.synth.is_finished()
+    }
+
+    /// Returns the samples generated so far, as in [`Synth::generated`].
+    pub fn generated(&self) -> &[f64] {
+        self.synth.generated()
+    }
+
+    /// Ensures all sample data is generated, then returns it as a vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Synth::generate_block`].
+    pub fn generate(mut self) -> Vec<f64> {
+        while !self.is_finished() {
+            self.generate_block();
+        }
+        self.synth.array
+    }
+
+    /// Ensures all sample data is generated, then returns it as a vector, or
+    /// returns an error under the same conditions as
+    /// [`Synth::try_generate_block`].
+    pub fn try_generate(mut self) -> Result<Vec<f64>, SynthError> {
+        Synth::validate(&self.synth.sound)?;
+        while !self.is_finished() {
+            self.generate_next_block()?;
+        }
+        Ok(self.synth.array)
+    }
+
+    /// Prepares the synth to generate the sound again from the start, as in
+    /// [`Synth::reset`].
+    pub fn reset(&mut self) {
+        self.stages = None;
+        self.synth.reset();
+    }
+
+    /// Returns the length of the output in seconds, as in
+    /// [`Synth::output_duration`].
+    pub fn output_duration(&self) -> f64 {
+        self.synth.output_duration()
+    }
+}
+
+/// The state of a render partway through, made with [`Synth::checkpoint`].
+pub struct Checkpoint {
+    start_sample: usize,
+    /// Samples generated before the checkpoint.
+    samples: Vec<f64>,
+    /// State of each transformer, in the order of the pipeline.
+    states: Vec<Option<Box<dyn Any + Send>>>,
+}
+
+/// Generates samples from a sound one block at a time, without keeping the
+/// whole sound in memory. Created by [`Synth::stream`].
+///
+/// ```rust
+/// let mut sound = jfxr::Sound::default();
+/// sound.sustain.0 = 0.3;
+/// sound.normalization.0 = false;
+/// let whole = jfxr::generate(&sound);
+/// let mut stream = jfxr::Synth::new(&sound).stream();
+/// let mut streamed = Vec::new();
+/// while let Some(block) = stream.next_block() {
+///     streamed.extend_from_slice(block);
+/// }
+/// assert_eq!(whole, streamed);
+/// ```
+pub struct Stream<'a> {
+    synth: Synth<'a>,
+    block: Vec<f64>,
+}
+
+impl<'a> Stream<'a> {
+    /// Generates the next block of samples, or returns `None` once the whole
+    /// sound has been generated. The block is only valid until the next call.
+    ///
+    /// This does not allocate memory, nor do the other realtime paths built on
+    /// it, so it is safe to call on an audio thread. The exceptions are the
+    /// callbacks set with [`Synth::on_block`] and [`Synth::automate`], which
+    /// must not allocate either, and the summary of the render that is logged
+    /// after the last block when debug spans of this crate are enabled with
+    /// the `tracing` feature. The `test-util` feature provides a counting
+    /// allocator to check this in tests.
+    pub fn next_block(&mut self) -> Option<&[f64]> {
+        let synth = &mut self.synth;
+        if synth.start_sample >= synth.num_samples {
+            return None;
+        }
+
+        let start_sample = synth.start_sample;
+        let end_sample = (start_sample + synth.block_size).min(synth.num_samples);
+        let block = &mut self.block[..end_sample - start_sample];
+        {
+            #[cfg(feature = "tracing")]
+            let _entered = synth.trace.as_ref().map(|trace| trace.span.enter());
+            run_block(&mut synth.transformers, synth.profile.as_mut(), &synth.sound, block, start_sample, start_sample..end_sample, false);
+        }
+        if let Some(on_block) = synth.on_block.as_mut() {
+            on_block(start_sample, block);
+        }
+        synth.start_sample = end_sample;
+        #[cfg(feature = "tracing")]
+        {
+            if let Some(trace) = synth.trace.as_mut() {
+                trace.add(block);
+            }
+            if synth.is_finished() {
+                synth.finish_trace();
+            }
+        }
+        Some(block)
+    }
+
+    /// Returns the number of samples in the whole sound.
+    pub fn num_samples(&self) -> usize {
+        self.synth.num_samples
+    }
+
+    /// Returns the synth that this stream was created from, for example to
+    /// read its [`Synth::profile`].
+    pub fn synth(&self) -> &Synth<'_> {
+        &self.synth
+    }
+
+    /// Turns the stream into an iterator over individual samples.
+    ///
+    /// ```rust
+    /// let mut sound = jfxr::Sound::default();
+    /// sound.sustain.0 = 0.3;
+    /// let samples: Vec<f64> = jfxr::Synth::new(&sound).stream().samples().collect();
+    /// assert_eq!(samples.len(), sound.num_samples());
+    /// ```
+    pub fn samples(self) -> Samples<'a> {
+        Samples {
+            stream: self,
+            position: 0,
+            len: 0,
+        }
+    }
+}
+
+/// Iterator over the samples of a sound, generated block by block. Created
+/// by [`Stream::samples`].
+pub struct Samples<'a> {
+    stream: Stream<'a>,
+    /// Index of the next sample in the current block.
+    position: usize,
+    /// Number of samples in the current block.
+    len: usize,
+}
+
+impl Samples<'_> {
+    /// Returns whether all samples have been returned.
+    pub fn is_exhausted(&self) -> bool {
+        self.position == self.len && self.stream.synth.start_sample >= self.stream.synth.num_samples
+    }
+
+    /// Generates the next block if the current one has been used up. Returns
+    /// whether there are samples left in the current block.
+    fn refill(&mut self) -> bool {
+        if self.position == self.len {
+            match self.stream.next_block() {
+                Some(block) => self.len = block.len(),
+                None => return false,
+            }
+            self.position = 0;
+        }
+        true
+    }
+}
+
+impl Iterator for Samples<'_> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        if !self.refill() {
+            return None;
+        }
+        let sample = self.stream.block[self.position];
+        self.position += 1;
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let synth = &self.stream.synth;
+        let remaining = self.len - self.position + synth.num_samples - synth.start_sample;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Samples<'_> {}
+
+/// Source of samples for audio engines that pull fixed-size chunks of audio,
+/// typically from their mixer thread. Samples are generated lazily as they
+/// are read.
+///
+/// Once created, the source does not allocate, so it is safe to read from on
+/// an audio thread. Everything that needs memory is set up in
+/// [`Self::new`], including the normalization peak: as with
+/// [`Synth::stream`], normalization requires the whole sound to be generated
+/// once in advance, so disable normalization on the sound if that is too
+/// slow.
+///
+/// The source is `Send`, so that it can be created on one thread and read
+/// on the audio thread. A callback set with [`Synth::on_block`] is called
+/// from [`Self::read`], on that thread.
+///
+/// ```rust
+/// let mut sound = jfxr::Sound::default();
+/// sound.sustain.0 = 0.3;
+/// sound.normalization.0 = false;
+/// let mut source = jfxr::PullSource::new(jfxr::Synth::new(&sound));
+/// let mut pulled = Vec::new();
+/// let mut chunk = [0.0f32; 333];
+/// while source.remaining() > 0 {
+///     let read = source.read(&mut chunk);
+///     pulled.extend_from_slice(&chunk[..read]);
+///     assert!(chunk[read..].iter().all(|sample| *sample == 0.0));
+/// }
+/// let expected: Vec<f32> = jfxr::generate(&sound).iter().map(|sample| *sample as f32).collect();
+/// assert_eq!(pulled, expected);
+/// assert_eq!(source.read(&mut chunk), 0);
+///
+/// fn assert_send(_: &impl Send) {}
+/// assert_send(&source);
+/// ```
+pub struct PullSource<'a> {
+    samples: Samples<'a>,
+}
+
+impl<'a> PullSource<'a> {
+    /// Creates a source which generates the sound of the given synth, with
+    /// its settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Synth::stream`].
+    pub fn new(synth: Synth<'a>) -> Self {
+        Self {
+            samples: synth.stream().samples(),
+        }
+    }
+
+    /// Fills `out` with the next samples of the sound, and returns the number
+    /// of samples that were written. If the sound ends before `out` is full,
+    /// the rest of `out` is filled with silence.
+    pub fn read(&mut self, out: &mut [f32]) -> usize {
+        let samples = &mut self.samples;
+        let mut written = 0;
+        while written < out.len() && samples.refill() {
+            let count = (samples.len - samples.position).min(out.len() - written);
+            let block = &samples.stream.block[samples.position..samples.position + count];
+            for (out, sample) in out[written..written + count].iter_mut().zip(block) {
+                *out = *sample as f32;
+            }
+            samples.position += count;
+            written += count;
+        }
+        out[written..].fill(0.0);
+        written
+    }
+
+    /// Returns the number of samples that have not been read yet.
+    pub fn remaining(&self) -> usize {
+        self.samples.len()
+    }
+}
+
+/// Runs all transformers over the given range of samples, where `array`
+/// holds the samples starting at index `offset`. If `finish` is set, `array`
+/// must hold the whole sound, and the range must end at the end of it.
+///
+/// Transformers after one that needs the whole sound are skipped until the
+/// last block, and then run over the whole sound at once, so that every
+/// sample passes through the stages in the same order regardless of the
+/// block size.
+fn run_block(
+    transformers: &mut [Box<dyn Transformer>],
+    profile: Option<&mut Vec<std::time::Duration>>,
+    sound: &super::sound::Sound,
+    array: &mut [f64],
+    offset: usize,
+    samples: std::ops::Range<usize>,
+    finish: bool,
+) {
+    let mut range = samples.start - offset..samples.end - offset;
+    let mut start_sample = samples.start;
+    let mut durations = profile.map(|profile| profile.iter_mut());
+    for transformer in transformers.iter_mut() {
+        let start = durations.is_some().then(std::time::Instant::now);
+        transformer.run(sound, &mut array[range.clone()], start_sample);
+        let needs_whole_sound = transformer.needs_whole_sound(sound);
+        if needs_whole_sound && finish {
+            transformer.finish(sound, array);
+        }
+        if let (Some(start), Some(duration)) = (start, durations.as_mut().and_then(Iterator::next)) {
+            *duration += start.elapsed();
+        }
+        if needs_whole_sound {
+            if !finish {
+                break;
+            }
+            range = 0..array.len();
+            start_sample = offset;
+        }
+    }
+}
+
+/// The stages of the pipeline, in order, as run by [`StaticSynth`], with the
+/// generator and the filters computing in the precision `T`.
+type Stages<T = f64> = (Generator<T>, Envelope, Flanger, Chorus, BitCrush, LowPass<T>, HighPass<T>, Compress, Reverb, Normalize, Amplify);
+
+/// A tuple of transformers that is run like [`run_block`] runs a slice of
+/// boxed ones, over the whole sound, but with static dispatch.
+trait Chain: Send {
+    fn reserve(&mut self, block_len: usize);
+    fn run_block(&mut self, sound: &super::sound::Sound, array: &mut [f64], samples: std::ops::Range<usize>, finish: bool);
+}
+
+macro_rules! chain {
+    ($($ty:ident: $index:tt),*) => {
+        impl<$($ty: Transformer),*> Chain for ($($ty,)*) {
+            fn reserve(&mut self, block_len: usize) {
+                $(self.$index.reserve(block_len);)*
+            }
+
+            fn run_block(&mut self, sound: &super::sound::Sound, array: &mut [f64], mut samples: std::ops::Range<usize>, finish: bool) {
+                $(
+                    if !run_stage(&mut self.$index, sound, array, &mut samples, finish) {
+                        return;
+                    }
+                )*
+            }
+        }
+    };
+}
+
+chain!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10);
+
+/// Runs one transformer of a [`Chain`] over the given samples. Returns
+/// whether the later transformers should run; after a transformer that
+/// needs the whole sound, they run over all of it, once it is finished.
+fn run_stage(transformer: &mut impl Transformer, sound: &super::sound::Sound, array: &mut [f64], samples: &mut std::ops::Range<usize>, finish: bool) -> bool {
+    transformer.run(sound, &mut array[samples.clone()], samples.start);
+    if transformer.needs_whole_sound(sound) {
+        if !finish {
+            return false;
+        }
+        transformer.finish(sound, array);
+        *samples = 0..array.len();
+    }
+    true
+}
+
+/// A stage of the synth pipeline. Every transformer must produce the same
+/// output regardless of how the sound is split into blocks; any state that
+/// depends on earlier samples is carried over from one call to
+/// [`Self::run`] to the next.
+trait Transformer: Send {
+    fn stage(&self) -> Stage;
+    /// Processes one block of samples, the first of which is the sample with
+    /// index `start_sample` in the whole sound.
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize);
+    /// Allocates the buffers needed to process blocks of up to `block_len`
+    /// samples, so that [`Self::run`] does not allocate.
+    fn reserve(&mut self, _block_len: usize) {}
+    /// Brings the state of this stage to where it would be after processing
+    /// `history`, the samples that reach it before the start of the render,
+    /// so that it carries on from them. Leaves the output of this stage in
+    /// `history`, to prime the next stage with.
+    fn prime(&mut self, sound: &super::sound::Sound, history: &mut [f64]) {
+        self.run(sound, history, 0);
+    }
+    /// Returns a copy of the state that this stage carries from one block to
+    /// the next, or `None` if it has none.
+    fn state(&self) -> Option<Box<dyn Any + Send>> {
+        None
+    }
+    /// Restores a state returned by [`Self::state`] of this stage.
+    fn restore_state(&mut self, _state: &dyn Any) {}
+    /// Returns whether this transformer can only produce its output once it
+    /// has seen the whole sound. If so, [`Self::finish`] is called after the
+    /// last block has been passed to [`Self::run`], and the later stages only
+    /// run after that.
+    fn needs_whole_sound(&self, _sound: &super::sound::Sound) -> bool {
+        false
+    }
+    /// Processes the whole sound after the last block has been passed to
+    /// [`Self::run`], if [`Self::needs_whole_sound`] returns `true`. This is
+    /// only called when the whole sound is kept in memory.
+    fn finish(&mut self, _sound: &super::sound::Sound, _array: &mut [f64]) {}
+    /// Returns what this stage measured and applied, if it is an enabled
+    /// normalization stage whose gain is known.
+    fn normalization(&self) -> Option<NormalizationLevel> {
+        None
+    }
+    /// Returns how long this stage keeps producing sound after the envelope
+    /// has ended, in seconds. The output is extended by the longest tail of
+    /// any stage; see [`extra_tail_seconds`].
+    fn extra_tail_seconds(_sound: &super::sound::Sound) -> f64
+    where
+        Self: Sized,
+    {
+        0.0
+    }
+}
+
+/// Returns the longest tail that any stage of the synth adds to the sound, in
+/// seconds. During the tail, the envelope holds the amplitude at zero.
+pub(crate) fn extra_tail_seconds(sound: &super::sound::Sound) -> f64 {
+    [Reverb::extra_tail_seconds(sound)].into_iter().fold(0.0, f64::max)
+}
+
+/// Fills `times` with the time of each sample of the block that starts at
+/// the given sample, in seconds.
+fn block_times(sound: &super::sound::Sound, start_sample: usize, len: usize, times: &mut Vec<f64>) {
+    times.clear();
+    times.extend((start_sample..start_sample + len).map(|i| i as f64 / sound.sample_rate.0));
+}
+
+/// The oscillators of the base frequency and its harmonics, with their
+/// phases. Shared by the [`Generator`] stage and by [`Voice`].
+///
+/// The phases and the mix of the harmonics are computed in the precision
+/// `T`. The oscillators compute their waveforms in double precision, from
+/// the phase in precision `T`, and their output is rounded to `T`, like a
+/// wavetable of reduced precision.
+struct HarmonicStack<T: Real = f64> {
+    oscillators: Vec<Box<dyn super::oscillator::Oscillator + Send>>,
+    /// Amplitude of each harmonic, normalized as selected by
+    /// [`super::parameter::HarmonicsGain`].
+    amplitudes: Vec<T>,
+    /// Phase at the start of the sound and of every repetition, as a fraction
+    /// of a cycle.
+    start_phase: T,
+    phase: T,
+    /// Separate phase accumulators for each harmonic, only used when the
+    /// harmonics are not exact multiples of the base frequency.
+    harmonic_phases: Vec<T>,
+    /// Whether the harmonics of a sine wave are summed in a single pass; see
+    /// [`Self::next_additive_sample`].
+    additive: bool,
+}
+
+impl<T: Real> HarmonicStack<T> {
+    /// Noise oscillators are seeded with [`super::oscillator::NOISE_SEED`]
+    /// plus `seed_offset`, and the repeat jitter of square waves with
+    /// `jitter_seed`.
+    fn new(sound: &super::sound::Sound, compatibility: Compatibility, seed_offset: u32, jitter_seed: u32, phase_offset: f64, square_duty: Option<Curve>) -> Self {
+        let base_seed = super::oscillator::NOISE_SEED.wrapping_add(seed_offset);
+        // A negative number of harmonics plays the base frequency alone.
+        let oscillators = (0..=sound.harmonics.0.max(0))
+            .map(|harmonic_index| {
+                let seed = match compatibility {
+                    Compatibility::Exact => base_seed,
+                    Compatibility::Improved => base_seed.wrapping_add(harmonic_index as u32),
+                };
+                match (sound.waveform, &square_duty) {
+                    (super::parameter::Waveform::Square, Some(curve)) => {
+                        let mut osc = super::oscillator::SquareOscillator::with_duty_curve(curve.clone());
+                        osc.clamp_duty = compatibility == Compatibility::Improved;
+                        Box::new(osc)
+                    }
+                    (super::parameter::Waveform::Square, None) => {
+                        let mut osc = super::oscillator::SquareOscillator::new(sound);
+                        osc.clamp_duty = compatibility == Compatibility::Improved;
+                        osc.jitter_seed = jitter_seed;
+                        Box::new(osc)
+                    }
+                    _ => super::oscillator::create(sound.waveform, sound, seed, compatibility),
+                }
+            })
+            .collect();
+        let levels = sound.harmonic_levels();
+        let total_amp: f64 = levels.iter().sum();
+        let amplitudes = match sound.harmonics_profile {
+            _ if sound.harmonics_gain == super::parameter::HarmonicsGain::Rms => {
+                let magnitude = levels.iter().map(|level| level * level).sum::<f64>().sqrt();
+                if magnitude == 0.0 {
+                    levels
+                } else {
+                    levels.iter().map(|level| level / magnitude).collect()
+                }
+            }
+            // Computed incrementally, the same way as upstream, to produce
+            // identical output.
+            super::parameter::HarmonicsProfile::Geometric => {
+                let mut amp = 1.0 / total_amp;
+                levels
+                    .iter()
+                    .map(|_| {
+                        let harmonic_amp = amp;
+                        amp *= sound.harmonics_falloff.0;
+                        harmonic_amp
+                    })
+                    .collect()
+            }
+            _ if total_amp == 0.0 => levels,
+            _ => levels.iter().map(|level| level / total_amp).collect(),
+        };
+        let start_phase = T::phase(sound.start_phase.0 / 100.0 + phase_offset);
+        let mut stack = Self {
+            oscillators,
+            amplitudes: amplitudes.into_iter().map(T::from_f64).collect(),
+            start_phase,
+            phase: start_phase,
+            harmonic_phases: vec![T::ZERO; sound.harmonics.0.max(0) as usize + 1],
+            // Upstream has at most 5 harmonics, which the exact mode renders
+            // with the oscillators to match it bit for bit.
+            additive: sound.waveform == super::parameter::Waveform::Sine
+                && sound.harmonics.0 > 0
+                && (compatibility == Compatibility::Improved || sound.harmonics.0 > UPSTREAM_MAX_HARMONICS),
+        };
+        stack.reset_phases();
+        stack
+    }
+
+    /// Returns a copy of the phases and of the state of the oscillators.
+    fn state(&self) -> HarmonicState<T> {
+        HarmonicState {
+            phase: self.phase,
+            harmonic_phases: self.harmonic_phases.clone(),
+            oscillators: self.oscillators.iter().map(|oscillator| oscillator.state()).collect(),
+        }
+    }
+
+    fn restore_state(&mut self, state: &HarmonicState<T>) {
+        self.phase = state.phase;
+        self.harmonic_phases.clone_from(&state.harmonic_phases);
+        for (oscillator, state) in self.oscillators.iter_mut().zip(&state.oscillators) {
+            if let Some(state) = state {
+                oscillator.restore_state(state.as_ref());
+            }
+        }
+    }
+
+    /// Sets the phase of the base frequency and of each harmonic to its
+    /// initial value.
+    fn reset_phases(&mut self) {
+        self.phase = self.start_phase;
+        let phase = self.start_phase;
+        for (harmonic_index, harmonic_phase) in self.harmonic_phases.iter_mut().enumerate() {
+            *harmonic_phase = phase.harmonic(harmonic_index + 1);
+        }
+    }
+
+    /// Advances the phases by one sample at the given frequency and returns
+    /// the sum of the harmonics.
+    #[inline]
+    fn next_sample(&mut self, sound: &super::sound::Sound, frequency: f64, sample_rate: f64, time: f64) -> T {
+        let mut sample = T::ZERO;
+        if sound.inharmonicity.0 != 0.0 {
+            // Every harmonic has its own phase, because the harmonic
+            // frequencies are not exact multiples of the base frequency.
+            let inharmonicity = sound.inharmonicity.0 / 100.0;
+            for harmonic_index in 0..self.oscillators.len() {
+                let overtone = harmonic_index as f64;
+                let stretch = (harmonic_index + 1) as f64 * (1.0 + overtone * overtone * inharmonicity);
+                let harmonic_phase = &mut self.harmonic_phases[harmonic_index];
+                *harmonic_phase = harmonic_phase.advance(frequency * stretch / sample_rate);
+                let oscillator = T::from_f64(self.oscillators[harmonic_index].get_sample(sound, harmonic_phase.to_f64(), time));
+                sample = sample + self.amplitudes[harmonic_index] * oscillator;
+            }
+            return sample;
+        }
+        self.phase = self.phase.advance(frequency / sample_rate);
+        if self.additive {
+            return self.next_additive_sample();
+        }
+        for harmonic_index in 0..self.oscillators.len() {
+            let harmonic_phase = self.phase.harmonic(harmonic_index + 1);
+            let oscillator = T::from_f64(self.oscillators[harmonic_index].get_sample(sound, harmonic_phase.to_f64(), time));
+            sample = sample + self.amplitudes[harmonic_index] * oscillator;
+        }
+        sample
+    }
+
+    /// Returns the sum of the harmonics of a sine wave at the current phase,
+    /// computing the sine of each multiple of the phase from the previous
+    /// two with the Chebyshev recurrence, so that only one sine and one
+    /// cosine are evaluated however many harmonics there are. The result
+    /// differs from the sum of the oscillators in the last bits.
+    #[inline]
+    fn next_additive_sample(&self) -> T {
+        let angle = 2.0 * std::f64::consts::PI * self.phase.to_f64();
+        let twice_cos = 2.0 * crate::math::cos(angle);
+        let (mut previous, mut current) = (0.0, crate::math::sin(angle));
+        let mut sample = T::ZERO;
+        for amplitude in &self.amplitudes {
+            sample = sample + *amplitude * T::from_f64(current);
+            (previous, current) = (current, twice_cos * current - previous);
+        }
+        sample
+    }
+}
+
+/// State of a [`HarmonicStack`], as returned by [`HarmonicStack::state`].
+struct HarmonicState<T> {
+    phase: T,
+    harmonic_phases: Vec<T>,
+    oscillators: Vec<Option<Box<dyn Any + Send>>>,
+}
+
+struct Generator<T: Real = f64> {
+    harmonics: HarmonicStack<T>,
+    /// Index of the repetition of the last generated sample, used to detect
+    /// repetition boundaries when phase reset is enabled.
+    repetition: f64,
+    frequency: Option<Curve>,
+    /// Time of each sample of the current block, in seconds.
+    times: Vec<f64>,
+    /// Frequency at each sample of the current block, in Hz.
+    frequencies: Vec<f64>,
+    /// Seed of the repeat jitter of the frequency.
+    jitter_seed: u32,
+}
+
+impl<T: Real> Generator<T> {
+    pub fn new(sound: &super::sound::Sound, compatibility: Compatibility, seed_offset: u32, jitter_seed: u32, phase_offset: f64, frequency: Option<Curve>, square_duty: Option<Curve>) -> Self {
+        Self {
+            harmonics: HarmonicStack::new(sound, compatibility, seed_offset, jitter_seed, phase_offset, square_duty),
+            jitter_seed,
+            repetition: 0.0,
+            frequency,
+            times: Vec::new(),
+            frequencies: Vec::new(),
+        }
+    }
+
+    /// Evaluates the frequency at every sample of the block that starts at
+    /// the given sample, into `self.frequencies`.
+    fn compute_frequencies(&mut self, sound: &super::sound::Sound, start_sample: usize, len: usize) {
+        block_times(sound, start_sample, len, &mut self.times);
+        self.frequencies.resize(len, 0.0);
+        match &self.frequency {
+            Some(curve) => {
+                for (frequency, time) in self.frequencies.iter_mut().zip(&self.times) {
+                    *frequency = curve(*time).clamp(super::parameter::Frequency::MIN_VALUE, super::parameter::Frequency::MAX_VALUE);
+                }
+            }
+            None => sound.frequencies_at_with_seed(&self.times, &mut self.frequencies, self.jitter_seed),
+        }
+    }
+
+    /// Returns whether the phase should be reset before generating the
+    /// sample at the given time, because it starts a new repetition.
+    fn starts_repetition(&mut self, sound: &super::sound::Sound, time: f64) -> bool {
+        if !sound.phase_reset.0 {
+            return false;
+        }
+        let repetition = sound.repetition_at(time);
+        let starts = repetition != self.repetition;
+        self.repetition = repetition;
+        starts
+    }
+}
+
+impl<T: Real> Transformer for Generator<T> {
+    fn stage(&self) -> Stage {
+        Stage::Generator
+    }
 
-        if self.start_sample >= num_samples {
-            return true;
-        }
+    fn reserve(&mut self, block_len: usize) {
+        self.times.reserve(block_len);
+        self.frequencies.reserve(block_len);
+    }
 
-        let end_sample = (self.start_sample + self.block_size).min(num_samples);
-        for transformer in self.transformers.iter_mut() {
-            transformer.run(self.sound, self.array.as_mut_slice(), self.start_sample, end_sample);
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize) {
+        self.compute_frequencies(sound, start_sample, block.len());
+        for (i, sample) in block.iter_mut().enumerate() {
+            let time = self.times[i];
+            if self.starts_repetition(sound, time) {
+                self.harmonics.reset_phases();
+            }
+            *sample = self.harmonics.next_sample(sound, self.frequencies[i], sound.sample_rate.0, time).to_f64();
         }
-        self.start_sample = end_sample;
-
-        self.start_sample >= num_samples
     }
 
-    /// Ensures all sample data is generated, then returns it as a vector.
-    pub fn generate(mut self) -> Vec<f64> {
-        while !self.generate_block() {}
-        self.array
+    fn state(&self) -> Option<Box<dyn Any + Send>> {
+        Some(Box::new((self.harmonics.state(), self.repetition)))
     }
-}
 
-trait Transformer {
-    fn run(&mut self, sound: &super::sound::Sound, array: &mut [f64], start_sample: usize, end_sample: usize);
+    fn restore_state(&mut self, state: &dyn Any) {
+        if let Some((harmonics, repetition)) = state.downcast_ref::<(HarmonicState<T>, f64)>() {
+            self.harmonics.restore_state(harmonics);
+            self.repetition = *repetition;
+        }
+    }
 }
 
-struct Generator {
-    oscillators: Vec<Box<dyn super::oscillator::Oscillator>>,
-    first_harmonic_amp: f64,
-    phase: f64,
+/// The oscillators of a sound on their own, producing one sample at a time
+/// at a frequency set by the caller, for hosts such as modular synths that
+/// apply their own envelopes and effects.
+///
+/// A voice plays the waveform, harmonics, harmonics profile and gain,
+/// inharmonicity and start phase of the sound, with the same oscillators as
+/// [`Synth`]. The time passed to [`next_sample`](Self::next_sample) drives
+/// the parameters of the waveform that change over time, such as the square
+/// duty sweep. Everything else, including the pitch parameters, the
+/// envelope and the filters, is ignored.
+///
+/// Driven at the frequency of a sound without sweeps, a voice reproduces the
+/// output of the generator stage of a render:
+///
+/// ```rust
+/// use jfxr::parameter::{Attack, BitCrush, Harmonics, Inharmonicity, Normalization, Sustain, Waveform};
+///
+/// for (waveform, inharmonicity) in [(Waveform::Sine, 0.0), (Waveform::Sawtooth, 0.0), (Waveform::Whistle, 2.5)] {
+///     // Only the generator stage changes the samples of this sound.
+///     let sound = jfxr::Sound {
+///         waveform,
+///         harmonics: Harmonics(3),
+///         inharmonicity: Inharmonicity(inharmonicity),
+///         attack: Attack(0.0),
+///         sustain: Sustain(0.2),
+///         bit_crush: BitCrush(0),
+///         normalization: Normalization(false),
+///         ..Default::default()
+///     };
+///     let samples = jfxr::generate(&sound);
+///
+///     let mut voice = jfxr::synth::Voice::new(&sound);
+///     voice.set_frequency(sound.frequency.0);
+///     let dt = 1.0 / sound.sample_rate.0;
+///     let voice_samples: Vec<f64> = (0..samples.len()).map(|_| voice.next_sample(dt)).collect();
+///     assert_eq!(voice_samples, samples);
+/// }
+/// ```
+pub struct Voice {
+    sound: super::sound::Sound,
+    harmonics: HarmonicStack,
+    frequency: f64,
+    time: f64,
 }
 
-impl Generator {
+impl Voice {
+    /// Creates a voice with the oscillators of the sound, at the frequency
+    /// of the sound.
     pub fn new(sound: &super::sound::Sound) -> Self {
-        let mut amp = 1.0;
-        let mut total_amp = 0.0;
-        let oscillators = (0..=sound.harmonics.0)
-            .map(|_| {
-                total_amp += amp;
-                amp *= sound.harmonics_falloff.0;
-                let osc: Box<dyn super::oscillator::Oscillator> = match sound.waveform {
-                    super::parameter::Waveform::Sine => Box::new(super::oscillator::SineOscillator::new(sound)),
-                    super::parameter::Waveform::Triangle => Box::new(super::oscillator::TriangleOscillator::new(sound)),
-                    super::parameter::Waveform::Sawtooth => Box::new(super::oscillator::SawtoothOscillator::new(sound)),
-                    super::parameter::Waveform::Square => Box::new(super::oscillator::SquareOscillator::new(sound)),
-                    super::parameter::Waveform::Tangent => Box::new(super::oscillator::TangentOscillator::new(sound)),
-                    super::parameter::Waveform::Whistle => Box::new(super::oscillator::WhistleOscillator::new(sound)),
-                    super::parameter::Waveform::Breaker => Box::new(super::oscillator::BreakerOscillator::new(sound)),
-                    super::parameter::Waveform::Whitenoise => Box::new(super::oscillator::WhiteNoiseOscillator::new(sound)),
-                    super::parameter::Waveform::Pinknoise => Box::new(super::oscillator::PinkNoiseOscillator::new(sound)),
-                    super::parameter::Waveform::Brownnoise => Box::new(super::oscillator::BrownNoiseOscillator::new(sound)),
-                };
-                osc
-            })
-            .collect();
+        Self::with_compatibility(sound, Compatibility::default())
+    }
+
+    /// Creates a voice like [`new`](Self::new), with the oscillators of the
+    /// given compatibility mode.
+    pub fn with_compatibility(sound: &super::sound::Sound, compatibility: Compatibility) -> Self {
         Self {
-            oscillators,
-            first_harmonic_amp: 1.0 / total_amp,
-            phase: 0.0,
+            sound: sound.clone(),
+            harmonics: HarmonicStack::new(sound, compatibility, 0, super::oscillator::NOISE_SEED, 0.0, None),
+            frequency: sound.frequency.0,
+            time: 0.0,
         }
     }
-}
 
-impl Transformer for Generator {
-    fn run(&mut self, sound: &super::sound::Sound, array: &mut [f64], start_sample: usize, end_sample: usize) {
-        let mut phase = self.phase;
-        for i in start_sample..end_sample {
-            let time = i as f64 / sound.sample_rate.0;
-            let current_frequency = sound.frequency_at(time);
-            phase = (phase + current_frequency / sound.sample_rate.0).fract();
-            let mut sample = 0.0;
-            let mut amp = self.first_harmonic_amp;
-            for harmonic_index in 0..=sound.harmonics.0 as usize {
-                let harmonic_phase = (phase * (harmonic_index + 1) as f64).fract();
-                sample += amp * self.oscillators[harmonic_index].get_sample(sound, harmonic_phase, time);
-                amp *= sound.harmonics_falloff.0;
-            }
-            array[i] = sample;
-        }
-        self.phase = phase;
+    /// Sets the frequency of the base harmonic in Hz, from the next sample
+    /// on. The phase continues where it was, so changing the frequency does
+    /// not click.
+    pub fn set_frequency(&mut self, frequency: f64) {
+        self.frequency = frequency;
+    }
+
+    /// Returns the frequency of the base harmonic in Hz.
+    pub fn frequency(&self) -> f64 {
+        self.frequency
+    }
+
+    /// Returns the next sample, `dt` seconds after the previous one.
+    pub fn next_sample(&mut self, dt: f64) -> f64 {
+        let sample = self.harmonics.next_sample(&self.sound, self.frequency, 1.0 / dt, self.time);
+        self.time += dt;
+        sample
+    }
+
+    /// Restarts the waveform at the start phase of the sound and the time
+    /// at zero, such as for a new note. The noise of noise waveforms
+    /// continues, rather than repeating.
+    pub fn reset(&mut self) {
+        self.harmonics.reset_phases();
+        self.time = 0.0;
     }
 }
 
-struct Envelope;
+struct Envelope {
+    times: Vec<f64>,
+    amplitudes: Vec<f64>,
+}
 
 impl Envelope {
     pub fn new(_sound: &super::sound::Sound) -> Self {
-        Self
+        Self {
+            times: Vec::new(),
+            amplitudes: Vec::new(),
+        }
     }
 }
 
 impl Transformer for Envelope {
-    fn run(&mut self, sound: &super::sound::Sound, array: &mut [f64], start_sample: usize, end_sample: usize) {
-        if sound.attack.0 == 0.0 && sound.sustain_punch.0 == 0.0 && sound.decay.0 == 0.0 && sound.tremolo_depth.0 == 0.0 {
+    fn stage(&self) -> Stage {
+        Stage::Envelope
+    }
+
+    fn reserve(&mut self, block_len: usize) {
+        self.times.reserve(block_len);
+        self.amplitudes.reserve(block_len);
+    }
+
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize) {
+        let end_sample = start_sample + block.len();
+        if sound.attack.0 == 0.0 && sound.sustain_punch.0 == 0.0 && sound.decay.0 == 0.0 && sound.tremolo_depth.0 == 0.0 && sound.repeat_decay.0 == 0.0 && sound.tail_duration() == 0.0 {
             return;
         }
+        block_times(sound, start_sample, block.len(), &mut self.times);
+        self.amplitudes.resize(block.len(), 0.0);
+        sound.amplitudes_at(&self.times, &mut self.amplitudes);
         for i in start_sample..end_sample {
-            let time = i as f64 / sound.sample_rate.0;
-            array[i] *= sound.amplitude_at(time);
+            block[i - start_sample] *= self.amplitudes[i - start_sample];
+        }
+    }
+}
+
+/// Circular buffer holding the most recent samples, used by the stages that
+/// mix in delayed copies of the sound.
+#[derive(Clone)]
+struct DelayLine {
+    buffer: Vec<f64>,
+    pos: usize,
+}
+
+impl DelayLine {
+    pub fn new(length: usize) -> Self {
+        Self {
+            buffer: vec![0.; length],
+            pos: 0,
         }
     }
+
+    /// Stores the current sample. It can be read back with an offset of
+    /// zero until [`Self::advance`] is called.
+    fn write(&mut self, sample: f64) {
+        self.buffer[self.pos] = sample;
+    }
+
+    /// Adds to the current sample.
+    fn add(&mut self, sample: f64) {
+        self.buffer[self.pos] += sample;
+    }
+
+    /// Returns the sample from the given number of samples ago.
+    fn at(&self, offset: usize) -> f64 {
+        let length = self.buffer.len();
+        self.buffer[(self.pos + length - offset) % length]
+    }
+
+    /// Returns the sample from the given, possibly fractional, number of
+    /// samples ago, interpolating linearly between the two nearest samples.
+    /// The offset is clamped to `max_offset`.
+    fn interpolate(&self, offset: f64, max_offset: usize) -> f64 {
+        let offset = offset.clamp(0.0, max_offset as f64);
+        let whole = offset.floor() as usize;
+        let next = (whole + 1).min(max_offset);
+        let fraction = offset - whole as f64;
+        let a = self.at(whole);
+        let b = self.at(next);
+        a + fraction * (b - a)
+    }
+
+    /// Moves on to the next sample.
+    fn advance(&mut self) {
+        self.pos = (self.pos + 1) % self.buffer.len();
+    }
 }
 
 struct Flanger {
-    buffer: Option<Vec<f64>>,
-    buffer_pos: usize,
+    delay_line: Option<DelayLine>,
+    max_offset_samples: usize,
+    fractional_delay: bool,
 }
 
 impl Flanger {
-    pub fn new(sound: &super::sound::Sound) -> Self {
-        let mut buffer = None;
-        if sound.flanger_offset.0 != 0.0 || sound.flanger_offset_sweep.0 != 0.0 {
-            // Maximum 100ms offset
-            buffer = Some(vec![0.; (sound.sample_rate.0 * 0.1).ceil() as usize]);
+    /// Returns the length of the delay line needed for the given sound, or
+    /// zero if the flanger is not used.
+    pub fn buffer_length(sound: &super::sound::Sound, min_buffer: f64) -> usize {
+        if sound.flanger_offset.0 == 0.0 && sound.flanger_offset_sweep.0 == 0.0 && sound.flanger_depth.0 == 0.0 {
+            return 0;
+        }
+        let sample_rate = sound.sample_rate.0;
+        let max_offset = sound.flanger_offset.0.max(sound.flanger_offset.0 + sound.flanger_offset_sweep.0).max(0.0)
+            + sound.flanger_depth.0.max(0.0);
+        // Leave room for rounding, and for the neighbouring sample when
+        // interpolating. The cast saturates, so that offsets too large to
+        // allocate fail to allocate rather than wrapping around.
+        ((max_offset / 1000.0 * sample_rate).ceil() as usize).saturating_add(2)
+            .max((min_buffer * sample_rate).ceil() as usize)
+    }
+
+    pub fn new(sound: &super::sound::Sound, compatibility: Compatibility, min_buffer: f64) -> Self {
+        let sample_rate = sound.sample_rate.0;
+        let mut delay_line = None;
+        let mut max_offset_samples = 0;
+        let buffer_length = Self::buffer_length(sound, min_buffer);
+        if buffer_length > 0 {
+            max_offset_samples = buffer_length - 1;
+            if compatibility == Compatibility::Exact {
+                // Maximum 100ms offset
+                max_offset_samples = max_offset_samples.min((sample_rate * 0.1).ceil() as usize - 1);
+            }
+            delay_line = Some(DelayLine::new(buffer_length));
         }
         Self {
-            buffer,
-            buffer_pos: 0,
+            delay_line,
+            max_offset_samples,
+            fractional_delay: compatibility == Compatibility::Improved,
         }
     }
 }
 
 impl Transformer for Flanger {
-    fn run(&mut self, sound: &super::sound::Sound, array: &mut [f64], start_sample: usize, end_sample: usize) {
-        if let Some(buffer) = self.buffer.as_mut() {
-            let num_samples = array.len();
+    fn stage(&self) -> Stage {
+        Stage::Flanger
+    }
+
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize) {
+        let end_sample = start_sample + block.len();
+        if let Some(delay_line) = self.delay_line.as_mut() {
+            let num_samples = sound.num_body_samples();
             let sample_rate = sound.sample_rate.0;
             let flanger_offset = sound.flanger_offset.0;
             let flanger_offset_sweep = sound.flanger_offset_sweep.0;
-
-            let mut buffer_pos = self.buffer_pos;
-            let buffer_length = buffer.len();
+            let flanger_depth = sound.flanger_depth.0;
+            let flanger_rate = sound.flanger_rate.0;
+            // Limited to keep the feedback loop from blowing up.
+            let flanger_feedback = (sound.flanger_feedback.0 / 100.0).clamp(0.0, 0.9);
 
             for i in start_sample..end_sample {
-                buffer[buffer_pos] = array[i];
+                delay_line.write(block[i - start_sample]);
 
-                let mut offset_samples = ((flanger_offset + i as f64 / num_samples as f64 * flanger_offset_sweep) / 1000.0 * sample_rate).round() as usize;
-                offset_samples = offset_samples.clamp(0, buffer_length - 1);
-                array[i] += buffer[(buffer_pos - offset_samples + buffer_length) % buffer_length];
-                buffer_pos = (buffer_pos + 1) % buffer_length;
+                let mut offset = flanger_offset + i as f64 / num_samples as f64 * flanger_offset_sweep;
+                if flanger_depth != 0.0 {
+                    let time = i as f64 / sample_rate;
+                    offset += flanger_depth * (0.5 - 0.5 * crate::math::cos(2.0 * std::f64::consts::PI * time * flanger_rate));
+                }
+                let delayed = if self.fractional_delay {
+                    delay_line.interpolate(offset / 1000.0 * sample_rate, self.max_offset_samples)
+                } else {
+                    // Clamped before the cast, since the sweep can take the
+                    // offset below zero. NaN becomes an offset of 0.
+                    let offset_samples = (offset / 1000.0 * sample_rate).round().clamp(0.0, self.max_offset_samples as f64) as usize;
+                    delay_line.at(offset_samples)
+                };
+                if flanger_feedback != 0.0 {
+                    delay_line.add(flanger_feedback * delayed);
+                }
+                block[i - start_sample] += delayed;
+                delay_line.advance();
+            }
+        }
+    }
+
+    fn state(&self) -> Option<Box<dyn Any + Send>> {
+        self.delay_line.clone().map(|delay_line| Box::new(delay_line) as Box<dyn Any + Send>)
+    }
+
+    fn restore_state(&mut self, state: &dyn Any) {
+        if let (Some(delay_line), Some(state)) = (self.delay_line.as_mut(), state.downcast_ref::<DelayLine>()) {
+            delay_line.clone_from(state);
+        }
+    }
+}
+
+/// Delay of the chorus voices around which their delay is modulated, in
+/// milliseconds.
+const CHORUS_DELAY: f64 = 15.0;
+
+struct Chorus {
+    delay_line: Option<DelayLine>,
+    max_offset_samples: usize,
+}
+
+impl Chorus {
+    pub fn new(sound: &super::sound::Sound) -> Self {
+        if sound.chorus_voices.0 <= 0 {
+            return Self {
+                delay_line: None,
+                max_offset_samples: 0,
+            };
+        }
+        let max_offset = CHORUS_DELAY + sound.chorus_depth.0.max(0.0);
+        let buffer_length = ((max_offset / 1000.0 * sound.sample_rate.0).ceil() as usize).saturating_add(2);
+        Self {
+            delay_line: Some(DelayLine::new(buffer_length)),
+            max_offset_samples: buffer_length - 1,
+        }
+    }
+}
+
+impl Transformer for Chorus {
+    fn stage(&self) -> Stage {
+        Stage::Chorus
+    }
+
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize) {
+        let end_sample = start_sample + block.len();
+        if let Some(delay_line) = self.delay_line.as_mut() {
+            let sample_rate = sound.sample_rate.0;
+            let voices = sound.chorus_voices.0;
+            let chorus_depth = sound.chorus_depth.0;
+            let chorus_rate = sound.chorus_rate.0;
+            // Averaging the dry sound and the voices keeps the level, and
+            // never makes the peak larger.
+            let gain = 1.0 / (voices + 1) as f64;
+
+            for i in start_sample..end_sample {
+                let dry = block[i - start_sample];
+                delay_line.write(dry);
+                let time = i as f64 / sample_rate;
+                let mut sample = dry;
+                for voice in 0..voices {
+                    // Spread the phases of the voices evenly, so that their
+                    // delays are never all the same.
+                    let phase = 2.0 * std::f64::consts::PI * (time * chorus_rate + voice as f64 / voices as f64);
+                    let offset = CHORUS_DELAY + chorus_depth * (0.5 - 0.5 * crate::math::cos(phase));
+                    sample += delay_line.interpolate(offset / 1000.0 * sample_rate, self.max_offset_samples);
+                }
+                block[i - start_sample] = sample * gain;
+                delay_line.advance();
             }
+        }
+    }
 
-            self.buffer_pos = buffer_pos;
+    fn state(&self) -> Option<Box<dyn Any + Send>> {
+        self.delay_line.clone().map(|delay_line| Box::new(delay_line) as Box<dyn Any + Send>)
+    }
+
+    fn restore_state(&mut self, state: &dyn Any) {
+        if let (Some(delay_line), Some(state)) = (self.delay_line.as_mut(), state.downcast_ref::<DelayLine>()) {
+            delay_line.clone_from(state);
         }
     }
 }
@@ -195,8 +3539,13 @@ impl BitCrush {
 }
 
 impl Transformer for BitCrush {
-    fn run(&mut self, sound: &super::sound::Sound, array: &mut [f64], start_sample: usize, end_sample: usize) {
-        let num_samples = array.len();
+    fn stage(&self) -> Stage {
+        Stage::BitCrush
+    }
+
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize) {
+        let end_sample = start_sample + block.len();
+        let num_samples = sound.num_body_samples();
         let bit_crush = sound.bit_crush.0;
         let bit_crush_sweep = sound.bit_crush_sweep.0;
 
@@ -205,36 +3554,47 @@ impl Transformer for BitCrush {
         }
 
         for i in start_sample..end_sample {
-            let mut bits = (bit_crush as f64 + i as f64 / num_samples as f64 * bit_crush_sweep as f64).round() as usize;
-            bits = bits.clamp(1, 16);
-            let steps = f64::powf(2.0, bits as f64);
-            array[i] = -1.0 + 2.0 * ((0.5 + 0.5 * array[i]) * steps).round() / steps;
+            // Clamped as a float, since the sweep can take the number of
+            // bits below zero.
+            let bits = (bit_crush as f64 + i as f64 / num_samples as f64 * bit_crush_sweep as f64).round();
+            let bits = if bits.is_nan() { 1.0 } else { bits.clamp(1.0, 16.0) };
+            let steps = crate::math::pow(2.0, bits);
+            block[i - start_sample] = -1.0 + 2.0 * ((0.5 + 0.5 * block[i - start_sample]) * steps).round() / steps;
         }
     }
 }
 
 
 
-struct LowPass {
-    low_pass_prev: f64,
+/// The low-pass filter, which filters in the precision `T`. The
+/// coefficients are computed in double precision and rounded to `T`.
+struct LowPass<T: Real = f64> {
+    low_pass_prev: T,
+    cutoff: Option<Curve>,
 }
 
-impl LowPass {
-    pub fn new(_sound: &super::sound::Sound) -> Self {
+impl<T: Real> LowPass<T> {
+    pub fn new(_sound: &super::sound::Sound, cutoff: Option<Curve>) -> Self {
         Self {
-            low_pass_prev: 0.0,
+            low_pass_prev: T::ZERO,
+            cutoff,
         }
     }
 }
 
-impl Transformer for LowPass {
-    fn run(&mut self, sound: &super::sound::Sound, array: &mut [f64], start_sample: usize, end_sample: usize) {
-        let num_samples = array.len();
+impl<T: Real> Transformer for LowPass<T> {
+    fn stage(&self) -> Stage {
+        Stage::LowPass
+    }
+
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize) {
+        let end_sample = start_sample + block.len();
+        let num_samples = sound.num_body_samples();
         let low_pass_cutoff = sound.low_pass_cutoff.0;
         let low_pass_cutoff_sweep = sound.low_pass_cutoff_sweep.0;
         let sample_rate = sound.sample_rate.0;
 
-        if low_pass_cutoff >= sample_rate / 2.0 && low_pass_cutoff + low_pass_cutoff_sweep >= sample_rate / 2.0 {
+        if self.cutoff.is_none() && low_pass_cutoff >= sample_rate / 2.0 && low_pass_cutoff + low_pass_cutoff_sweep >= sample_rate / 2.0 {
             return;
         }
 
@@ -242,9 +3602,12 @@ impl Transformer for LowPass {
 
         for i in start_sample..end_sample {
             let fraction = i as f64 / num_samples as f64;
-            let cutoff = (low_pass_cutoff + fraction * low_pass_cutoff_sweep).clamp(0.0, sample_rate / 2.0);
+            let cutoff = match &self.cutoff {
+                Some(curve) => curve(i as f64 / sample_rate).clamp(super::parameter::LowPassCutoff::MIN_VALUE, super::parameter::LowPassCutoff::MAX_VALUE),
+                None => low_pass_cutoff + fraction * low_pass_cutoff_sweep,
+            }.clamp(0.0, sample_rate / 2.0);
             let wc = cutoff / sample_rate * std::f64::consts::PI; // Don't we need a factor 2pi instead of pi?
-            let cos_wc = wc.cos();
+            let cos_wc = crate::math::cos(wc);
             let mut low_pass_alpha;
             if cos_wc <= 0.0 {
                 low_pass_alpha = 1.0;
@@ -253,38 +3616,57 @@ impl Transformer for LowPass {
                 low_pass_alpha = 1.0 / cos_wc - (1.0 / (cos_wc * cos_wc) - 1.0).sqrt();
                 low_pass_alpha = 1.0 - low_pass_alpha; // Probably the internet's definition of alpha is different.
             }
-            let mut sample = array[i];
-            sample = low_pass_alpha * sample + (1.0 - low_pass_alpha) * low_pass_prev;
+            let mut sample = T::from_f64(block[i - start_sample]);
+            sample = T::from_f64(low_pass_alpha) * sample + T::from_f64(1.0 - low_pass_alpha) * low_pass_prev;
             low_pass_prev = sample;
-            array[i] = sample;
+            block[i - start_sample] = sample.to_f64();
         }
 
         self.low_pass_prev = low_pass_prev;
     }
+
+    fn state(&self) -> Option<Box<dyn Any + Send>> {
+        Some(Box::new(self.low_pass_prev))
+    }
+
+    fn restore_state(&mut self, state: &dyn Any) {
+        if let Some(low_pass_prev) = state.downcast_ref::<T>() {
+            self.low_pass_prev = *low_pass_prev;
+        }
+    }
 }
 
-struct HighPass {
-    high_pass_prev_in: f64,
-    high_pass_prev_out: f64,
+/// The high-pass filter, which filters in the precision `T`, like
+/// [`LowPass`].
+struct HighPass<T: Real = f64> {
+    high_pass_prev_in: T,
+    high_pass_prev_out: T,
+    cutoff: Option<Curve>,
 }
 
-impl HighPass {
-    pub fn new(_sound: &super::sound::Sound) -> Self {
+impl<T: Real> HighPass<T> {
+    pub fn new(_sound: &super::sound::Sound, cutoff: Option<Curve>) -> Self {
         Self {
-            high_pass_prev_in: 0.0,
-            high_pass_prev_out: 0.0,
+            high_pass_prev_in: T::ZERO,
+            high_pass_prev_out: T::ZERO,
+            cutoff,
         }
     }
 }
 
-impl Transformer for HighPass {
-    fn run(&mut self, sound: &super::sound::Sound, array: &mut [f64], start_sample: usize, end_sample: usize) {
-        let num_samples = array.len();
+impl<T: Real> Transformer for HighPass<T> {
+    fn stage(&self) -> Stage {
+        Stage::HighPass
+    }
+
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize) {
+        let end_sample = start_sample + block.len();
+        let num_samples = sound.num_body_samples();
         let high_pass_cutoff = sound.high_pass_cutoff.0;
         let high_pass_cutoff_sweep = sound.high_pass_cutoff_sweep.0;
         let sample_rate = sound.sample_rate.0;
 
-        if high_pass_cutoff <= 0.0 && high_pass_cutoff + high_pass_cutoff_sweep <= 0.0 {
+        if self.cutoff.is_none() && high_pass_cutoff <= 0.0 && high_pass_cutoff + high_pass_cutoff_sweep <= 0.0 {
           return;
         }
 
@@ -293,95 +3675,533 @@ impl Transformer for HighPass {
 
         for i in start_sample..end_sample {
             let fraction = i as f64 / num_samples as f64;
-            let cutoff = (high_pass_cutoff + fraction * high_pass_cutoff_sweep).clamp(0.0, sample_rate / 2.0);
+            let cutoff = match &self.cutoff {
+                Some(curve) => curve(i as f64 / sample_rate).clamp(super::parameter::HighPassCutoff::MIN_VALUE, super::parameter::HighPassCutoff::MAX_VALUE),
+                None => high_pass_cutoff + fraction * high_pass_cutoff_sweep,
+            }.clamp(0.0, sample_rate / 2.0);
             let wc = cutoff / sample_rate * std::f64::consts::PI;
             // From somewhere on the internet: a = (1 - sin wc) / cos wc
-            let high_pass_alpha = (1.0 - wc.sin()) / wc.cos();
-            let mut sample = array[i];
+            let high_pass_alpha = T::from_f64((1.0 - crate::math::sin(wc)) / crate::math::cos(wc));
+            let mut sample = T::from_f64(block[i - start_sample]);
             let orig_sample = sample;
             sample = high_pass_alpha * (high_pass_prev_out - high_pass_prev_in + sample);
             high_pass_prev_in = orig_sample;
             high_pass_prev_out = sample;
-            array[i] = sample;
+            block[i - start_sample] = sample.to_f64();
         }
 
         self.high_pass_prev_in = high_pass_prev_in;
         self.high_pass_prev_out = high_pass_prev_out;
     }
+
+    fn state(&self) -> Option<Box<dyn Any + Send>> {
+        Some(Box::new((self.high_pass_prev_in, self.high_pass_prev_out)))
+    }
+
+    fn restore_state(&mut self, state: &dyn Any) {
+        if let Some((prev_in, prev_out)) = state.downcast_ref::<(T, T)>() {
+            self.high_pass_prev_in = *prev_in;
+            self.high_pass_prev_out = *prev_out;
+        }
+    }
 }
 
-struct Compress;
+/// Applies the compression stage to a single sample: raises its absolute
+/// value to the power `compression`, keeping its sign.
+///
+/// With [`Compatibility::Improved`], samples are first clamped to the range
+/// from -1 to 1, so that samples which exceed it, for example because the
+/// flanger added a delayed copy of the signal, are not amplified further by
+/// compressions below 1 or above 1. A compression of 0 then acts as a hard
+/// limiter, which turns every sample into -1, 0 or 1. With
+/// [`Compatibility::Exact`], samples are not clamped, and a sample of 0
+/// becomes 1 at a compression of 0, as it does upstream.
+///
+/// ```rust
+/// use jfxr::Compatibility::{Exact, Improved};
+/// use jfxr::synth::compress_sample;
+///
+/// let samples = [-1.5, -1.0, -0.5, 0.0, 0.5, 1.0, 1.5];
+/// let compress = |compression, compatibility| samples.map(|sample| compress_sample(sample, compression, compatibility));
+/// assert_eq!(compress(0.0, Improved), [-1.0, -1.0, -1.0, 0.0, 1.0, 1.0, 1.0]);
+/// assert_eq!(compress(0.5, Improved), [-1.0, -1.0, -0.5f64.sqrt(), 0.0, 0.5f64.sqrt(), 1.0, 1.0]);
+/// assert_eq!(compress(1.0, Improved), [-1.0, -1.0, -0.5, 0.0, 0.5, 1.0, 1.0]);
+/// assert_eq!(compress(2.0, Improved), [-1.0, -1.0, -0.25, 0.0, 0.25, 1.0, 1.0]);
+///
+/// assert_eq!(compress(0.0, Exact), [-1.0, -1.0, -1.0, 1.0, 1.0, 1.0, 1.0]);
+/// assert_eq!(compress(0.5, Exact), [-1.5f64.sqrt(), -1.0, -0.5f64.sqrt(), 0.0, 0.5f64.sqrt(), 1.0, 1.5f64.sqrt()]);
+/// assert_eq!(compress(1.0, Exact), samples);
+/// assert_eq!(compress(2.0, Exact), [-2.25, -1.0, -0.25, 0.0, 0.25, 1.0, 2.25]);
+/// ```
+///
+/// The compression stage skips a compression of 1 entirely, in both modes,
+/// so that a sound without compression is not clipped:
+///
+/// ```rust
+/// use jfxr::parameter::{BitCrush, Compression, FlangerOffset, Normalization, Sustain};
+///
+/// // The flanger adds a copy of the signal delayed by two cycles, which
+/// // doubles its peak.
+/// let sound = jfxr::Sound {
+///     sustain: Sustain(0.2),
+///     flanger_offset: FlangerOffset(4.0),
+///     bit_crush: BitCrush(0),
+///     compression: Compression(1.0),
+///     normalization: Normalization(false),
+///     ..Default::default()
+/// };
+/// for compatibility in [jfxr::Compatibility::Exact, jfxr::Compatibility::Improved] {
+///     let mut synth = jfxr::Synth::new(&sound);
+///     synth.set_compatibility(compatibility);
+///     assert!(jfxr::analysis::peak(&synth.generate()) > 1.5);
+/// }
+/// ```
+pub fn compress_sample(sample: f64, compression: f64, compatibility: Compatibility) -> f64 {
+    let sample = match compatibility {
+        Compatibility::Exact => sample,
+        Compatibility::Improved => {
+            if sample == 0.0 {
+                return 0.0;
+            }
+            sample.clamp(-1.0, 1.0)
+        }
+    };
+    if sample >= 0.0 {
+        crate::math::pow(sample, compression)
+    } else {
+        -crate::math::pow(-sample, compression)
+    }
+}
+
+struct Compress {
+    compatibility: Compatibility,
+}
 
 impl Compress {
-    pub fn new(_sound: &super::sound::Sound) -> Self {
-        Self
+    pub fn new(_sound: &super::sound::Sound, compatibility: Compatibility) -> Self {
+        Self { compatibility }
     }
 }
 
 impl Transformer for Compress {
-    fn run(&mut self, sound: &super::sound::Sound, array: &mut [f64], start_sample: usize, end_sample: usize) {
+    fn stage(&self) -> Stage {
+        Stage::Compress
+    }
+
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize) {
+        let end_sample = start_sample + block.len();
         let compression = sound.compression.0;
 
         if compression == 1.0 {
             return;
         }
-    
+
         for i in start_sample..end_sample {
-            let mut sample = array[i];
-            if sample >= 0.0 {
-                sample = f64::powf(sample, compression);
-            } else {
-                sample = -f64::powf(-sample, compression);
+            block[i - start_sample] = compress_sample(block[i - start_sample], compression, self.compatibility);
+        }
+    }
+}
+
+/// Delays of the comb filters of the reverb, in samples at 44100 Hz, as in
+/// Freeverb.
+const REVERB_COMB_DELAYS: [usize; 4] = [1116, 1188, 1277, 1356];
+/// Delays of the all-pass filters of the reverb, in samples at 44100 Hz.
+const REVERB_ALLPASS_DELAYS: [usize; 2] = [556, 441];
+/// Feedback of the all-pass filters of the reverb.
+const REVERB_ALLPASS_FEEDBACK: f64 = 0.5;
+
+/// Schroeder reverb: parallel feedback comb filters, whose feedback is chosen
+/// so that they decay by 60 dB over the reverb decay time, followed by
+/// all-pass filters in series to diffuse the echoes.
+struct Reverb {
+    combs: Vec<(DelayLine, f64)>,
+    allpasses: Vec<DelayLine>,
+}
+
+impl Reverb {
+    pub fn new(sound: &super::sound::Sound) -> Self {
+        if sound.reverb_amount.0 == 0.0 {
+            return Self {
+                combs: Vec::new(),
+                allpasses: Vec::new(),
+            };
+        }
+        let scale = sound.sample_rate.0 / 44100.0;
+        let delay_samples = |delay: usize| ((delay as f64 * scale).round() as usize).max(1);
+        let combs = REVERB_COMB_DELAYS
+            .iter()
+            .map(|delay| {
+                let length = delay_samples(*delay);
+                let feedback = crate::math::pow(10.0, -3.0 * length as f64 / (sound.reverb_decay.0 * sound.sample_rate.0));
+                (DelayLine::new(length), feedback)
+            })
+            .collect();
+        let allpasses = REVERB_ALLPASS_DELAYS
+            .iter()
+            .map(|delay| DelayLine::new(delay_samples(*delay)))
+            .collect();
+        Self { combs, allpasses }
+    }
+}
+
+impl Transformer for Reverb {
+    fn stage(&self) -> Stage {
+        Stage::Reverb
+    }
+
+    fn extra_tail_seconds(sound: &super::sound::Sound) -> f64 {
+        if sound.reverb_amount.0 != 0.0 && sound.reverb_tail.0 {
+            sound.reverb_decay.0
+        } else {
+            0.0
+        }
+    }
+
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize) {
+        let end_sample = start_sample + block.len();
+        if self.combs.is_empty() {
+            return;
+        }
+        let wet = sound.reverb_amount.0 / 100.0;
+        let comb_gain = 1.0 / self.combs.len() as f64;
+
+        for i in start_sample..end_sample {
+            let dry = block[i - start_sample];
+            // Each delay line holds exactly one delay, so the oldest sample
+            // is read before the current one overwrites it.
+            let mut reverb = 0.0;
+            for (comb, feedback) in self.combs.iter_mut() {
+                let delayed = comb.at(0);
+                comb.write(dry + *feedback * delayed);
+                comb.advance();
+                reverb += delayed;
             }
-            array[i] = sample;
+            reverb *= comb_gain;
+            for allpass in self.allpasses.iter_mut() {
+                let delayed = allpass.at(0);
+                let input = reverb + REVERB_ALLPASS_FEEDBACK * delayed;
+                allpass.write(input);
+                allpass.advance();
+                reverb = delayed - REVERB_ALLPASS_FEEDBACK * input;
+            }
+            block[i - start_sample] = dry + wet * reverb;
+        }
+    }
+
+    fn state(&self) -> Option<Box<dyn Any + Send>> {
+        if self.combs.is_empty() {
+            return None;
+        }
+        Some(Box::new((self.combs.clone(), self.allpasses.clone())))
+    }
+
+    fn restore_state(&mut self, state: &dyn Any) {
+        if let Some((combs, allpasses)) = state.downcast_ref::<(Vec<(DelayLine, f64)>, Vec<DelayLine>)>() {
+            self.combs.clone_from(combs);
+            self.allpasses.clone_from(allpasses);
         }
     }
 }
 
-struct Normalize {
+/// Measurements of a single render, for the `render` span that is emitted
+/// when the `tracing` feature is enabled.
+#[cfg(feature = "tracing")]
+struct RenderTrace {
+    span: tracing::Span,
+    start: std::time::Instant,
+    peak: f64,
+    /// Number of output samples outside the range from -1 to 1.
+    clipped: usize,
+}
+
+#[cfg(feature = "tracing")]
+impl RenderTrace {
+    fn new(sound: &super::sound::Sound, num_samples: usize) -> Option<Self> {
+        let span = tracing::debug_span!(
+            "render",
+            sound = %sound.name,
+            num_samples,
+            peak = tracing::field::Empty,
+            clipped = tracing::field::Empty,
+            duration_us = tracing::field::Empty,
+        );
+        (!span.is_disabled()).then(|| Self {
+            span,
+            start: std::time::Instant::now(),
+            peak: 0.0,
+            clipped: 0,
+        })
+    }
+
+    fn add(&mut self, block: &[f64]) {
+        for sample in block {
+            self.peak = self.peak.max(sample.abs());
+            self.clipped += (sample.abs() > 1.0) as usize;
+        }
+    }
+
+    fn finish(self, profile: &[(Stage, std::time::Duration)]) {
+        let duration_us = self.start.elapsed().as_micros() as u64;
+        self.span.record("peak", self.peak);
+        self.span.record("clipped", self.clipped);
+        self.span.record("duration_us", duration_us);
+        for (stage, duration) in profile {
+            tracing::debug_span!(parent: &self.span, "stage", stage = ?stage, duration_us = duration.as_micros() as u64).in_scope(|| {});
+        }
+        self.span.in_scope(|| tracing::debug!(peak = self.peak, clipped = self.clipped, duration_us, stages = ?profile, "rendered sound"));
+    }
+}
+
+/// Measurements of the sound before normalization, from which the gain of
+/// normalization is computed according to the leveling mode.
+#[derive(Clone)]
+struct Level {
     max_sample: f64,
+    sum_of_squares: f64,
+    len: usize,
+    meter: Option<super::analysis::LoudnessMeter>,
+    /// Filter that removes the DC offset from the samples before the peak and
+    /// the RMS are measured, if enabled.
+    high_pass: Option<MeasurementHighPass>,
 }
 
-impl Normalize {
-    pub fn new(_sound: &super::sound::Sound) -> Self {
+impl Level {
+    pub fn new(sound: &super::sound::Sound, high_pass: bool) -> Self {
         Self {
             max_sample: 0.0,
+            sum_of_squares: 0.0,
+            len: 0,
+            meter: (sound.leveling_mode == super::parameter::LevelingMode::Lufs).then(|| super::analysis::LoudnessMeter::new(sound.sample_rate.0)),
+            high_pass: high_pass.then(|| MeasurementHighPass::new(sound.sample_rate.0)),
+        }
+    }
+
+    pub fn add(&mut self, block: &[f64]) {
+        let mut max_sample = self.max_sample;
+        for sample in block {
+            let sample = match self.high_pass.as_mut() {
+                Some(high_pass) => high_pass.process(*sample),
+                None => *sample,
+            };
+            max_sample = max_sample.max(sample.abs());
+            self.sum_of_squares += sample * sample;
+        }
+        self.max_sample = max_sample;
+        self.len += block.len();
+        if let Some(meter) = self.meter.as_mut() {
+            meter.push(block);
+        }
+    }
+
+    /// Returns the factor by which normalization scales the sound. When
+    /// leveling by loudness, the gain is limited by the maximum gain, and so
+    /// that the peak does not exceed full scale.
+    pub fn gain(&self, sound: &super::sound::Sound) -> f64 {
+        if self.max_sample == 0.0 {
+            // Silence stays silent, rather than becoming 0 * infinity.
+            return 1.0;
+        }
+        let level = match sound.leveling_mode {
+            super::parameter::LevelingMode::Peak => return 1.0 / self.max_sample,
+            super::parameter::LevelingMode::Rms => super::analysis::gain_to_db((self.sum_of_squares / self.len.max(1) as f64).sqrt()),
+            super::parameter::LevelingMode::Lufs => self.meter.as_ref().map_or(f64::NEG_INFINITY, |meter| meter.integrated_loudness()),
+        };
+        super::analysis::db_to_gain(sound.leveling_target.0 - level)
+            .min(super::analysis::db_to_gain(sound.leveling_max_gain.0))
+            .min(1.0 / self.max_sample)
+    }
+
+    /// Returns the measured peak along with the gain.
+    pub fn normalization(&self, sound: &super::sound::Sound) -> NormalizationLevel {
+        NormalizationLevel {
+            peak: self.max_sample,
+            gain: self.gain(sound),
+        }
+    }
+}
+
+/// First-order high-pass filter at [`MeasurementHighPass::CUTOFF`], as in
+/// the high-pass stage.
+#[derive(Clone)]
+struct MeasurementHighPass {
+    alpha: f64,
+    prev_in: f64,
+    prev_out: f64,
+}
+
+impl MeasurementHighPass {
+    /// Cutoff frequency, in Hz, below the range of hearing.
+    const CUTOFF: f64 = 20.0;
+
+    fn new(sample_rate: f64) -> Self {
+        // Kept well below the Nyquist frequency, where the filter breaks down.
+        let wc = Self::CUTOFF.min(sample_rate / 4.0) / sample_rate * std::f64::consts::PI;
+        Self {
+            alpha: (1.0 - crate::math::sin(wc)) / crate::math::cos(wc),
+            prev_in: 0.0,
+            prev_out: 0.0,
+        }
+    }
+
+    fn process(&mut self, sample: f64) -> f64 {
+        self.prev_out = self.alpha * (self.prev_out - self.prev_in + sample);
+        self.prev_in = sample;
+        self.prev_out
+    }
+}
+
+struct Normalize {
+    level: Level,
+    /// Level of the sound, if it is known in advance, in which case every
+    /// block is normalized as it is generated. Otherwise, this is set once
+    /// the whole sound has been measured.
+    measured: Option<NormalizationLevel>,
+    enabled: bool,
+}
+
+impl Normalize {
+    pub fn new(sound: &super::sound::Sound, measured: Option<NormalizationLevel>, high_pass: bool) -> Self {
+        Self {
+            level: Level::new(sound, high_pass),
+            measured,
+            enabled: sound.normalization.0,
         }
     }
 }
 
 impl Transformer for Normalize {
-    fn run(&mut self, sound: &super::sound::Sound, array: &mut [f64], start_sample: usize, end_sample: usize) {
+    fn stage(&self) -> Stage {
+        Stage::Normalize
+    }
+
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize) {
+        let end_sample = start_sample + block.len();
         if !sound.normalization.0 {
             return;
         }
 
-        let mut max_sample = self.max_sample;
-        for i in start_sample..end_sample {
-            max_sample = max_sample.max(array[i].abs());
+        if let Some(measured) = self.measured {
+            for i in start_sample..end_sample {
+                block[i - start_sample] *= measured.gain;
+            }
+            return;
         }
-        self.max_sample = max_sample;
 
-        let num_samples = array.len();
-        if end_sample == num_samples {
-            let factor = 1.0 / max_sample;
-            for i in 0..end_sample {
-                array[i] *= factor;
-            }
+        self.level.add(block);
+    }
+
+    fn needs_whole_sound(&self, sound: &super::sound::Sound) -> bool {
+        sound.normalization.0 && self.measured.is_none()
+    }
+
+    fn finish(&mut self, sound: &super::sound::Sound, array: &mut [f64]) {
+        let measured = self.level.normalization(sound);
+        #[cfg(feature = "tracing")]
+        let _entered = tracing::debug_span!("normalize", peak = measured.peak, gain = measured.gain).entered();
+        for i in 0..array.len() {
+            array[i] *= measured.gain;
+        }
+        self.measured = Some(measured);
+    }
+
+    fn normalization(&self) -> Option<NormalizationLevel> {
+        self.measured.filter(|_| self.enabled)
+    }
+
+    fn state(&self) -> Option<Box<dyn Any + Send>> {
+        Some(Box::new((self.level.clone(), self.measured)))
+    }
+
+    fn restore_state(&mut self, state: &dyn Any) {
+        if let Some((level, measured)) = state.downcast_ref::<(Level, Option<NormalizationLevel>)>() {
+            self.level = level.clone();
+            self.measured = *measured;
+        }
+    }
+}
+
+/// Rounds the output of a stage that computes in double precision to a
+/// [`Precision`], so that the next stage receives its input in that
+/// precision.
+struct Quantize {
+    inner: Box<dyn Transformer>,
+    precision: Precision,
+}
+
+impl Transformer for Quantize {
+    fn stage(&self) -> Stage {
+        self.inner.stage()
+    }
+
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize) {
+        self.inner.run(sound, block, start_sample);
+        for sample in block.iter_mut() {
+            *sample = self.precision.quantize(*sample);
+        }
+    }
+
+    fn reserve(&mut self, block_len: usize) {
+        self.inner.reserve(block_len);
+    }
+
+    fn prime(&mut self, sound: &super::sound::Sound, history: &mut [f64]) {
+        self.inner.prime(sound, history);
+        for sample in history.iter_mut() {
+            *sample = self.precision.quantize(*sample);
+        }
+    }
+
+    fn state(&self) -> Option<Box<dyn Any + Send>> {
+        self.inner.state()
+    }
+
+    fn restore_state(&mut self, state: &dyn Any) {
+        self.inner.restore_state(state);
+    }
+
+    fn needs_whole_sound(&self, sound: &super::sound::Sound) -> bool {
+        self.inner.needs_whole_sound(sound)
+    }
+
+    fn finish(&mut self, sound: &super::sound::Sound, array: &mut [f64]) {
+        self.inner.finish(sound, array);
+        for sample in array.iter_mut() {
+            *sample = self.precision.quantize(*sample);
         }
     }
+
+    fn normalization(&self) -> Option<NormalizationLevel> {
+        self.inner.normalization()
+    }
 }
 
-struct Amplify;
+struct Amplify {
+    amplification: Option<Curve>,
+}
 
 impl Amplify {
-    pub fn new(_sound: &super::sound::Sound) -> Self {
-        Self
+    pub fn new(_sound: &super::sound::Sound, amplification: Option<Curve>) -> Self {
+        Self {
+            amplification,
+        }
     }
 }
 
 impl Transformer for Amplify {
-    fn run(&mut self, sound: &super::sound::Sound, array: &mut [f64], start_sample: usize, end_sample: usize) {
+    fn stage(&self) -> Stage {
+        Stage::Amplify
+    }
+
+    fn run(&mut self, sound: &super::sound::Sound, block: &mut [f64], start_sample: usize) {
+        let end_sample = start_sample + block.len();
+        if let Some(curve) = &self.amplification {
+            for i in start_sample..end_sample {
+                let time = i as f64 / sound.sample_rate.0;
+                block[i - start_sample] *= curve(time).clamp(super::parameter::Amplification::MIN_VALUE, super::parameter::Amplification::MAX_VALUE) / 100.0;
+            }
+            return;
+        }
+
         let factor = sound.amplification.0 / 100.0;
 
         if factor == 1.0 {
@@ -389,7 +4209,7 @@ impl Transformer for Amplify {
         }
 
         for i in start_sample..end_sample {
-            array[i] *= factor;
+            block[i - start_sample] *= factor;
         }
     }
 }
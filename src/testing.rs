@@ -0,0 +1,350 @@
+//! Assertions for invariants that every sound must satisfy, for use in the
+//! tests of crates that extend or embed the synth. Each assertion panics with
+//! a description of the first violation it finds.
+//!
+//! The tests of this crate check the invariants for every waveform, over
+//! the grid of extreme parameter values of [`extreme_sounds`]:
+//!
+//! ```rust
+//! let sound = jfxr::Sound::example(jfxr::sound::Example::Laser);
+//! let samples = jfxr::generate(&sound);
+//! jfxr::testing::assert_finite(&samples);
+//! jfxr::testing::assert_peak_le(&samples, 1.0);
+//! jfxr::testing::assert_deterministic(&sound);
+//! jfxr::testing::assert_block_size_invariant(&sound);
+//! jfxr::testing::assert_prime_invariant(&sound);
+//! jfxr::testing::assert_checkpoint_invariant(&sound);
+//! ```
+
+use crate::parameter::*;
+use crate::bench::CountingAllocator;
+use crate::{Sound, Synth};
+
+/// Asserts that all samples are finite.
+pub fn assert_finite(samples: &[f64]) {
+    if let Some(index) = samples.iter().position(|sample| !sample.is_finite()) {
+        panic!("sample {index} is not finite: {}", samples[index]);
+    }
+}
+
+/// Asserts that the absolute value of every sample is at most `max`.
+pub fn assert_peak_le(samples: &[f64], max: f64) {
+    if let Some(index) = samples.iter().position(|sample| sample.is_nan() || sample.abs() > max) {
+        panic!("sample {index} exceeds the peak of {max}: {}", samples[index]);
+    }
+}
+
+/// Asserts that rendering the sound twice produces identical samples.
+pub fn assert_deterministic(sound: &Sound) {
+    let first = crate::generate(sound);
+    let second = crate::generate(sound);
+    assert_identical(&first, &second, "second render");
+}
+
+/// Asserts that the samples of the sound do not depend on the block size,
+/// by comparing renders with block sizes of 1, 7, 1024 and the length of
+/// the whole sound, both with [`Synth::generate`] and with
+/// [`Synth::stream`].
+pub fn assert_block_size_invariant(sound: &Sound) {
+    let expected = crate::generate(sound);
+    for block_size in [1, 7, 1024, sound.num_samples()] {
+        let mut synth = Synth::new(sound);
+        synth.set_block_size(block_size);
+        assert_identical(&expected, &synth.generate(), &format!("block size {block_size}"));
+        if !sound.normalization.0 {
+            // With normalization, streamed output may differ in the last bits.
+            let mut synth = Synth::new(sound);
+            synth.set_block_size(block_size);
+            let streamed: Vec<f64> = synth.stream().samples().collect();
+            assert_identical(&expected, &streamed, &format!("streamed with block size {block_size}"));
+        }
+    }
+}
+
+/// Asserts that a render primed with [`Synth::prime_with`] with the first
+/// half of the dry sound matches the second half of a full render.
+pub fn assert_prime_invariant(sound: &Sound) {
+    let expected = crate::generate(sound);
+    let dry = Synth::new(sound).generate_dry();
+    let half = expected.len() / 2;
+    let mut synth = Synth::new(sound);
+    synth.set_block_size(1000);
+    synth.prime_with(&dry[..half]).unwrap();
+    let primed = synth.generate();
+    assert_identical(&expected[half..], &primed[half..], "primed with the first half");
+}
+
+/// Asserts that restoring a checkpoint made partway through a render, with
+/// [`Synth::checkpoint`], renders the rest of the sound again identically,
+/// even after the render has finished.
+pub fn assert_checkpoint_invariant(sound: &Sound) {
+    let expected = crate::generate(sound);
+    let mut synth = Synth::new(sound);
+    synth.set_block_size(expected.len().div_ceil(3).max(1));
+    synth.generate_block();
+    let Some(checkpoint) = synth.checkpoint() else {
+        panic!("no checkpoint after the first block");
+    };
+    while !synth.generate_block() {}
+    synth.restore(&checkpoint);
+    assert_identical(&expected, &synth.generate(), "restored from a checkpoint");
+}
+
+fn assert_identical(expected: &[f64], actual: &[f64], what: &str) {
+    assert_eq!(expected.len(), actual.len(), "{what}: number of samples differs");
+    if let Some(index) = (0..expected.len()).find(|index| expected[*index].to_bits() != actual[*index].to_bits()) {
+        panic!("{what}: sample {index} differs: expected {}, got {}", expected[index], actual[index]);
+    }
+}
+
+/// Asserts that `f` does not allocate, as counted by `allocator`, and
+/// returns its result. Allocations made by other threads in the meantime
+/// are counted too.
+///
+/// This checks that the realtime paths of the synth do not allocate once
+/// they are prepared: the blocks of a [`crate::Stream`], the calls to
+/// [`Synth::render_quantum`] after the first, and the reads of a
+/// [`crate::PullSource`]. The [`CountingAllocator`] of the `bench` module,
+/// which this feature enables, must be registered as the global allocator
+/// of the test binary.
+///
+/// ```rust
+/// use jfxr::bench::CountingAllocator;
+/// use jfxr::testing::assert_allocation_free;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator;
+///
+/// fn main() {
+///     let sound = jfxr::Sound::example(jfxr::sound::Example::Coin);
+///     let mut stream = jfxr::Synth::new(&sound).stream();
+///     while assert_allocation_free(&ALLOCATOR, "stream block", || stream.next_block().is_some()) {}
+/// }
+/// ```
+pub fn assert_allocation_free<R>(allocator: &CountingAllocator, what: &str, f: impl FnOnce() -> R) -> R {
+    let before = allocator.allocations();
+    let result = f();
+    let allocations = allocator.allocations() - before;
+    if allocations > 0 {
+        panic!("{what}: {allocations} allocations");
+    }
+    result
+}
+
+/// Returns a 64-bit FNV-1a hash of the exact bits of the samples, to compare
+/// renders against committed fixtures without storing the samples.
+pub fn sample_hash(samples: &[f64]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in samples.iter().flat_map(|sample| sample.to_bits().to_le_bytes()) {
+        hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// The [`sample_hash`] of the render of each example sound with
+/// [`crate::generate`], with the `deterministic` feature enabled. These only
+/// change in releases that are allowed to change the output; see the crate
+/// documentation on output stability.
+///
+#[cfg_attr(feature = "deterministic", doc = "```rust")]
+#[cfg_attr(not(feature = "deterministic"), doc = "```rust,ignore")]
+/// let (example, hash) = jfxr::testing::EXAMPLE_HASHES[0];
+/// let samples = jfxr::generate(&jfxr::Sound::example(example));
+/// assert_eq!(jfxr::testing::sample_hash(&samples), hash);
+/// ```
+pub const EXAMPLE_HASHES: &[(crate::sound::Example, u64)] = &[
+    (crate::sound::Example::Coin, 0x4dadf8959542a1bd),
+    (crate::sound::Example::Laser, 0xcebba2c39b28c8dc),
+    (crate::sound::Example::Explosion, 0xd29f111157165456),
+    (crate::sound::Example::Jump, 0x1438bbd2eee6bfdc),
+    (crate::sound::Example::Hit, 0x988a2ba34a92e85d),
+    (crate::sound::Example::Powerup, 0x0452d7bd98d5a43b),
+    (crate::sound::Example::Blip, 0x8c2ae62f316befe3),
+];
+
+/// The [`sample_hash`] of the render of each sound of
+/// [`crate::presets::ui_pack`] with [`crate::generate`], with the
+/// `deterministic` feature enabled. These only change together with
+/// [`crate::presets::UI_PACK_VERSION`].
+///
+#[cfg_attr(feature = "deterministic", doc = "```rust")]
+#[cfg_attr(not(feature = "deterministic"), doc = "```rust,ignore")]
+/// let (name, sound) = &jfxr::presets::ui_pack()[0];
+/// assert_eq!(*name, jfxr::testing::UI_PACK_HASHES[0].0);
+/// assert_eq!(jfxr::testing::sample_hash(&jfxr::generate(sound)), jfxr::testing::UI_PACK_HASHES[0].1);
+/// ```
+pub const UI_PACK_HASHES: &[(&str, u64)] = &[
+    ("hover", 0xf69dafefda80782b),
+    ("click", 0x42f10f33f061bf72),
+    ("confirm", 0x1130625d276ebc3e),
+    ("error", 0x7bf2f2bde964810a),
+    ("back", 0xd6bbe4f72b6888b6),
+];
+
+/// Returns the outputs of the functions of [`crate::convert`] that
+/// [`CONVERT_HASHES`] pins, applied to renders of the example sounds.
+pub fn convert_outputs() -> Vec<(&'static str, Vec<f64>)> {
+    use crate::convert::{MorphCurve, MorphLength};
+    use crate::sound::Example;
+
+    let coin = Sound::example(Example::Coin);
+    let laser = Sound::example(Example::Laser);
+    let (coin_samples, laser_samples) = (crate::generate(&coin), crate::generate(&laser));
+    vec![
+        ("concat", crate::convert::concat(&[&coin_samples, &laser_samples], std::time::Duration::from_millis(10), 44100.0)),
+        ("pitch_shift", crate::convert::pitch_shift(&coin_samples, 5.0)),
+        ("morph_render", crate::convert::morph_render(&coin, &laser, MorphCurve::Smooth, MorphLength::Stretch)),
+        ("morph_render_equal_power", crate::convert::morph_render(&coin, &laser, MorphCurve::EqualPower, MorphLength::Pad)),
+    ]
+}
+
+/// The [`sample_hash`] of each of the [`convert_outputs`], with the
+/// `deterministic` feature enabled. Like [`EXAMPLE_HASHES`], these only
+/// change in releases that are allowed to change the output.
+///
+#[cfg_attr(feature = "deterministic", doc = "```rust")]
+#[cfg_attr(not(feature = "deterministic"), doc = "```rust,ignore")]
+/// let (name, samples) = &jfxr::testing::convert_outputs()[0];
+/// assert_eq!(jfxr::testing::CONVERT_HASHES[0], (*name, jfxr::testing::sample_hash(samples)));
+/// ```
+pub const CONVERT_HASHES: &[(&str, u64)] = &[
+    ("concat", 0x0aa59962d0b31b16),
+    ("pitch_shift", 0xc365949458c575c7),
+    ("morph_render", 0x756ac418eb0d8d89),
+    ("morph_render_equal_power", 0x565aebd38380ccf7),
+];
+
+/// The first three samples of a 500 Hz sound without attack or
+/// normalization, for each waveform, rendered with [`crate::generate`] with
+/// the `deterministic` feature enabled. They pin how each waveform starts;
+/// the first sample is one sample into the cycle, which begins at zero for
+/// every waveform except square in the default
+/// [`crate::Compatibility::Exact`] mode, see
+/// [`crate::parameter::Waveform::starts_at_zero`].
+///
+#[cfg_attr(feature = "deterministic", doc = "```rust")]
+#[cfg_attr(not(feature = "deterministic"), doc = "```rust,ignore")]
+/// use jfxr::parameter::{Attack, Normalization, Sustain};
+///
+/// let (waveform, first_samples) = jfxr::testing::FIRST_SAMPLES[0];
+/// let sound = jfxr::Sound {
+///     waveform,
+///     attack: Attack(0.0),
+///     sustain: Sustain(0.1),
+///     normalization: Normalization(false),
+///     ..Default::default()
+/// };
+/// assert_eq!(jfxr::generate(&sound)[..3], first_samples);
+/// ```
+pub const FIRST_SAMPLES: &[(Waveform, [f64; 3])] = &[
+    (Waveform::Sine, [0.0711669921875, 0.141998291015625, 0.21209716796875]),
+    (Waveform::Triangle, [0.04534912109375, 0.0906982421875, 0.13604736328125]),
+    (Waveform::Sawtooth, [0.022674560546875, 0.04534912109375, 0.068023681640625]),
+    (Waveform::Square, [1.0, 1.0, 1.0]),
+    (Waveform::Tangent, [0.01068115234375, 0.021392822265625, 0.03216552734375]),
+    (Waveform::Whistle, [0.30072021484375, 0.178466796875, -0.06732177734375]),
+    (Waveform::Breaker, [0.079071044921875, 0.159149169921875, 0.24029541015625]),
+    (Waveform::Whitenoise, [0.0, 0.0, 0.0]),
+    (Waveform::Pinknoise, [0.0, 0.0, 0.0]),
+    (Waveform::Brownnoise, [0.0, 0.0, 0.0]),
+    (Waveform::Bluenoise, [0.0, 0.0, 0.0]),
+    (Waveform::Velvetnoise, [0.0, 0.0, 0.0]),
+];
+
+/// Returns a set of short sounds that each push a group of parameters to the
+/// ends of their ranges, to be combined with every waveform when checking
+/// invariants.
+pub fn extreme_sounds() -> Vec<Sound> {
+    let base = Sound {
+        sustain: Sustain(0.05),
+        harmonics_falloff: HarmonicsFalloff(0.5),
+        ..Default::default()
+    };
+    vec![
+        base.clone(),
+        Sound {
+            normalization: Normalization(false),
+            ..base.clone()
+        },
+        Sound {
+            attack: Attack(0.02),
+            sustain_punch: SustainPunch(SustainPunch::MAX_VALUE),
+            decay: Decay(0.02),
+            tremolo_depth: TremoloDepth(TremoloDepth::MAX_VALUE),
+            tremolo_frequency: TremoloFrequency(TremoloFrequency::MAX_VALUE),
+            ..base.clone()
+        },
+        Sound {
+            frequency: Frequency(Frequency::MAX_VALUE),
+            frequency_sweep: FrequencySweep(FrequencySweep::MIN_VALUE),
+            frequency_delta_sweep: FrequencyDeltaSweep(FrequencyDeltaSweep::MAX_VALUE),
+            vibrato_depth: VibratoDepth(VibratoDepth::MAX_VALUE),
+            vibrato_frequency: VibratoFrequency(VibratoFrequency::MAX_VALUE),
+            ..base.clone()
+        },
+        Sound {
+            frequency: Frequency(Frequency::MIN_VALUE),
+            repeat_frequency: RepeatFrequency(RepeatFrequency::MAX_VALUE),
+            phase_reset: PhaseReset(true),
+            frequency_jump1_amount: FrequencyJump1Amount(FrequencyJump1Amount::MIN_VALUE),
+            frequency_jump2_amount: FrequencyJump2Amount(FrequencyJump2Amount::MAX_VALUE),
+            ..base.clone()
+        },
+        Sound {
+            harmonics: Harmonics(Harmonics::MAX_VALUE),
+            harmonics_falloff: HarmonicsFalloff(HarmonicsFalloff::MAX_VALUE),
+            inharmonicity: Inharmonicity(Inharmonicity::MAX_VALUE),
+            ..base.clone()
+        },
+        Sound {
+            flanger_offset: FlangerOffset(FlangerOffset::MAX_VALUE),
+            flanger_offset_sweep: FlangerOffsetSweep(FlangerOffsetSweep::MIN_VALUE),
+            flanger_depth: FlangerDepth(FlangerDepth::MAX_VALUE),
+            flanger_rate: FlangerRate(FlangerRate::MAX_VALUE),
+            flanger_feedback: FlangerFeedback(FlangerFeedback::MAX_VALUE),
+            chorus_voices: ChorusVoices(ChorusVoices::MAX_VALUE),
+            chorus_depth: ChorusDepth(ChorusDepth::MAX_VALUE),
+            chorus_rate: ChorusRate(ChorusRate::MAX_VALUE),
+            ..base.clone()
+        },
+        Sound {
+            bit_crush: BitCrush(BitCrush::MIN_VALUE),
+            bit_crush_sweep: BitCrushSweep(BitCrushSweep::MAX_VALUE),
+            low_pass_cutoff: LowPassCutoff(LowPassCutoff::MIN_VALUE),
+            low_pass_cutoff_sweep: LowPassCutoffSweep(LowPassCutoffSweep::MAX_VALUE),
+            high_pass_cutoff: HighPassCutoff(HighPassCutoff::MAX_VALUE),
+            high_pass_cutoff_sweep: HighPassCutoffSweep(HighPassCutoffSweep::MIN_VALUE),
+            compression: Compression(Compression::MIN_VALUE),
+            ..base.clone()
+        },
+        // The sweeps take the bits below 1, the low-pass cutoff from
+        // Nyquist down to 0 and the high-pass cutoff from 0 up to Nyquist.
+        Sound {
+            bit_crush: BitCrush(BitCrush::MIN_VALUE),
+            bit_crush_sweep: BitCrushSweep(BitCrushSweep::MIN_VALUE),
+            low_pass_cutoff: LowPassCutoff(LowPassCutoff::MAX_VALUE),
+            low_pass_cutoff_sweep: LowPassCutoffSweep(LowPassCutoffSweep::MIN_VALUE),
+            high_pass_cutoff: HighPassCutoff(HighPassCutoff::MIN_VALUE),
+            high_pass_cutoff_sweep: HighPassCutoffSweep(HighPassCutoffSweep::MAX_VALUE),
+            ..base.clone()
+        },
+        // The sweep takes the flanger offset below 0.
+        Sound {
+            flanger_offset: FlangerOffset(FlangerOffset::MIN_VALUE),
+            flanger_offset_sweep: FlangerOffsetSweep(FlangerOffsetSweep::MIN_VALUE),
+            flanger_feedback: FlangerFeedback(FlangerFeedback::MAX_VALUE),
+            ..base.clone()
+        },
+        // Noise waveforms stay silent once the frequency drops to 0.
+        Sound {
+            frequency: Frequency(Frequency::MIN_VALUE),
+            frequency_sweep: FrequencySweep(FrequencySweep::MIN_VALUE),
+            ..base.clone()
+        },
+        Sound {
+            reverb_amount: ReverbAmount(ReverbAmount::MAX_VALUE),
+            reverb_decay: ReverbDecay(ReverbDecay::MIN_VALUE),
+            ..base
+        },
+    ]
+}
@@ -0,0 +1,91 @@
+//! Editor widgets for [`egui`](https://docs.rs/egui).
+
+use crate::parameter::{Param, ParamKind, Value};
+use crate::sound::Sound;
+
+/// Shows controls for all parameters of a sound, grouped into sections like
+/// in the `jfxr` tool. Parameters that have no effect on the sound are
+/// disabled, with the reason in their tooltip. Returns whether any parameter
+/// was changed.
+///
+/// The controls are built from [`Sound::param_groups`], so every parameter
+/// of the sound is included.
+pub fn sound_editor(ui: &mut egui::Ui, sound: &mut Sound) -> bool {
+    let mut changed = false;
+    for (group, params) in Sound::param_groups() {
+        egui::CollapsingHeader::new(group.label())
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::Grid::new(group.label()).num_columns(2).show(ui, |ui| {
+                    for param in params {
+                        changed |= param_editor(ui, sound, param);
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+    changed
+}
+
+/// Shows the label and the control for a single parameter. Returns whether
+/// the parameter was changed.
+pub fn param_editor(ui: &mut egui::Ui, sound: &mut Sound, param: &Param) -> bool {
+    let disabled_reason = param.disabled_reason(sound);
+    let suffix = if param.unit.is_empty() { String::new() } else { format!(" {}", param.unit) };
+    ui.label(param.label).on_hover_text(param.description);
+
+    let old_value = param.get(sound);
+    let mut value = old_value;
+    let response = ui.add_enabled_ui(disabled_reason.is_none(), |ui| match param.kind {
+        ParamKind::Float { min_value, max_value, step, logarithmic } => {
+            let mut number = value.as_f64();
+            let response = ui.add(
+                egui::Slider::new(&mut number, min_value..=max_value)
+                    .logarithmic(logarithmic)
+                    .step_by(step)
+                    .suffix(suffix),
+            );
+            value = Value::Float(number);
+            response
+        }
+        ParamKind::Integer { min_value, max_value, step } => {
+            let mut number = value.as_f64().round() as i32;
+            let response = ui.add(
+                egui::Slider::new(&mut number, min_value..=max_value)
+                    .step_by(step as f64)
+                    .suffix(suffix),
+            );
+            value = Value::Integer(number);
+            response
+        }
+        ParamKind::Boolean => {
+            let mut checked = value.as_f64() != 0.0;
+            let response = ui.checkbox(&mut checked, "");
+            value = Value::Boolean(checked);
+            response
+        }
+        ParamKind::Enum { names } => {
+            let mut index = value.as_f64() as usize;
+            let response = egui::ComboBox::from_id_salt(param.key)
+                .selected_text(names.get(index).copied().unwrap_or(""))
+                .show_ui(ui, |ui| {
+                    for (option, name) in names.iter().enumerate() {
+                        ui.selectable_value(&mut index, option, *name);
+                    }
+                })
+                .response;
+            value = Value::Enum(index);
+            response
+        }
+    }).inner;
+    let response = response.on_hover_text(param.description);
+    if let Some(reason) = disabled_reason {
+        response.on_disabled_hover_text(reason);
+    }
+
+    if value != old_value {
+        param.set(sound, value);
+        return true;
+    }
+    false
+}
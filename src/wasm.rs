@@ -0,0 +1,93 @@
+//! Bindings for WebAssembly with [`wasm_bindgen`], to play sounds from a Web
+//! Audio `AudioWorkletProcessor`.
+//!
+//! [`WasmSynth::process`] renders one render quantum of 128 frames into the
+//! output channel of the worklet, with [`Synth::render_quantum`], and
+//! returns whether the sound goes on, which is what `process()` of the
+//! processor returns. Built with `wasm-pack build --target web --features
+//! wasm`, the processor needs no more than this:
+//!
+//! ```js
+//! import { initSync, WasmSynth } from "./pkg/jfxr.js";
+//!
+//! // Created with `new AudioWorkletNode(context, "jfxr", { processorOptions:
+//! // { module, jfxr } })`, where `module` is the compiled WebAssembly.Module
+//! // and `jfxr` the .jfxr file encoded with TextEncoder.
+//! class JfxrProcessor extends AudioWorkletProcessor {
+//!   constructor(options) {
+//!     super();
+//!     const { module, jfxr } = options.processorOptions;
+//!     initSync({ module });
+//!     this.synth = new WasmSynth(jfxr, sampleRate);
+//!   }
+//!
+//!   process(inputs, outputs) {
+//!     const [first, ...rest] = outputs[0];
+//!     const playing = this.synth.process(first);
+//!     rest.forEach((channel) => channel.set(first));
+//!     return playing;
+//!   }
+//! }
+//!
+//! registerProcessor("jfxr", JfxrProcessor);
+//! ```
+
+use std::borrow::Cow;
+
+use wasm_bindgen::prelude::*;
+
+use crate::synth::{Synth, RENDER_QUANTUM};
+
+/// A synth that plays a single sound, one render quantum at a time.
+///
+/// ```rust
+/// let jfxr = jfxr::write_jfxr(jfxr::Sound::example(jfxr::sound::Example::Coin));
+/// let Ok(mut synth) = jfxr::wasm::WasmSynth::new(jfxr.as_bytes(), 48000.0) else {
+///     panic!("cannot read sound");
+/// };
+/// let mut channel = [0.0f32; 128];
+/// let mut quanta = 1;
+/// while synth.process(&mut channel) {
+///     quanta += 1;
+/// }
+/// assert_eq!(quanta, synth.num_samples().div_ceil(128));
+/// ```
+#[wasm_bindgen]
+pub struct WasmSynth {
+    synth: Synth<'static>,
+    quantum: [f32; RENDER_QUANTUM],
+}
+
+#[wasm_bindgen]
+impl WasmSynth {
+    /// Creates a synth for the sound in the given `.jfxr` file, rendered at
+    /// the given sample rate, which is `sampleRate` in a worklet. The file
+    /// is passed as UTF-8 bytes rather than as a string, since the scope of
+    /// a worklet has no `TextDecoder` to pass strings with.
+    #[wasm_bindgen(constructor)]
+    pub fn new(jfxr: &[u8], sample_rate: f64) -> Result<WasmSynth, JsError> {
+        let mut sound = crate::jfxr::read_jfxr(std::str::from_utf8(jfxr)?)?;
+        sound.sample_rate.0 = sample_rate;
+        Synth::try_new(&sound)?;
+        Ok(Self {
+            synth: Synth::new_unchecked(Cow::Owned(sound)),
+            quantum: [0.0; RENDER_QUANTUM],
+        })
+    }
+
+    /// Returns the number of samples of the sound.
+    #[wasm_bindgen(js_name = numSamples)]
+    pub fn num_samples(&self) -> usize {
+        self.synth.expected_len()
+    }
+
+    /// Renders the next render quantum into `out`, the output channel of the
+    /// worklet, and returns `false` once the sound has ended, as
+    /// [`Synth::render_quantum`]. The first call also prepares the synth.
+    pub fn process(&mut self, out: &mut [f32]) -> bool {
+        let playing = self.synth.render_quantum(&mut self.quantum);
+        let len = out.len().min(RENDER_QUANTUM);
+        out[..len].copy_from_slice(&self.quantum[..len]);
+        playing
+    }
+}
@@ -0,0 +1,182 @@
+//! Re-rendering `.jfxr` files to `.wav` files whenever they change on disk,
+//! for quick iteration in a text editor. Requires the `watch` feature.
+
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::Watcher;
+
+/// Settings of [`watch_and_render`].
+#[derive(Clone, Debug)]
+pub struct WatchConfig {
+    /// Directory whose `.jfxr` files are watched. Subdirectories are not
+    /// watched.
+    pub input_dir: PathBuf,
+    /// Directory into which `<name>.wav` is written for each
+    /// `<name>.jfxr`. It is created if it does not exist.
+    pub output_dir: PathBuf,
+    /// Time to wait after a change before rendering, so that a burst of
+    /// writes from a single save is rendered once.
+    pub debounce: Duration,
+}
+
+impl WatchConfig {
+    /// Creates settings for the given directories, with a debounce time of
+    /// 100 ms.
+    pub fn new(input_dir: impl Into<PathBuf>, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            input_dir: input_dir.into(),
+            output_dir: output_dir.into(),
+            debounce: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Error encountered while watching or rendering a file.
+#[derive(Debug)]
+pub enum WatchError {
+    /// A file or directory could not be read or written.
+    Io(std::io::Error),
+    /// A file is not a valid `jfxr` sound.
+    Format(crate::jfxr::JfxrFormatError),
+    /// A sound cannot be generated.
+    Synth(crate::SynthError),
+    /// The directory cannot be watched.
+    Notify(notify::Error),
+}
+
+impl std::fmt::Display for WatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Format(error) => write!(f, "{error}"),
+            Self::Synth(error) => write!(f, "{error}"),
+            Self::Notify(error) => write!(f, "cannot watch directory: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for WatchError {}
+
+/// What happened to a file, as passed to the callback of
+/// [`watch_and_render`].
+pub enum WatchEvent<'a> {
+    /// The sound in `input` was rendered into `output`.
+    Rendered {
+        input: &'a Path,
+        output: &'a Path,
+        sound: &'a crate::Sound,
+        samples: &'a [f64],
+    },
+    /// The sound in `input` could not be rendered. Watching continues.
+    Failed { input: &'a Path, error: &'a WatchError },
+}
+
+/// Renders every `.jfxr` file in the input directory, then watches the
+/// directory and renders each file again whenever it changes, until the
+/// callback returns [`ControlFlow::Break`]. The callback is called after
+/// every file, whether it succeeded or failed, and can, for example, print
+/// errors or play the new samples.
+///
+/// Returns an error if the output directory cannot be created or the input
+/// directory cannot be watched; errors in individual files are passed to the
+/// callback instead.
+///
+/// ```rust
+/// use std::ops::ControlFlow;
+/// use jfxr::watch::{WatchConfig, WatchEvent};
+///
+/// let dir = std::env::temp_dir().join(format!("jfxr-watch-doctest-{}", std::process::id()));
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let coin = jfxr::write_jfxr(jfxr::Sound::example(jfxr::sound::Example::Coin));
+/// std::fs::write(dir.join("coin.jfxr"), &coin).unwrap();
+///
+/// let mut renders = 0;
+/// jfxr::watch::watch_and_render(&WatchConfig::new(&dir, dir.join("out")), |event| {
+///     match event {
+///         WatchEvent::Rendered { output, .. } => {
+///             assert!(output.ends_with("out/coin.wav"));
+///             renders += 1;
+///             if renders == 1 {
+///                 // Saving the file again renders it again.
+///                 std::fs::write(dir.join("coin.jfxr"), &coin).unwrap();
+///             }
+///         }
+///         WatchEvent::Failed { error, .. } => panic!("{error}"),
+///     }
+///     if renders == 2 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+/// })
+/// .unwrap();
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn watch_and_render(config: &WatchConfig, mut callback: impl FnMut(WatchEvent) -> ControlFlow<()>) -> Result<(), WatchError> {
+    std::fs::create_dir_all(&config.output_dir).map_err(WatchError::Io)?;
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(WatchError::Notify)?;
+    watcher.watch(&config.input_dir, notify::RecursiveMode::NonRecursive).map_err(WatchError::Notify)?;
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(&config.input_dir)
+        .map_err(WatchError::Io)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_sound_file(path))
+        .collect();
+    paths.sort();
+    loop {
+        for path in &paths {
+            if render_file(config, path, &mut callback).is_break() {
+                return Ok(());
+            }
+        }
+        paths.clear();
+        // Wait for a change, then collect the changes until none have
+        // arrived for the debounce time.
+        let mut timeout = None;
+        loop {
+            let event = match timeout {
+                None => receiver.recv().map_err(|_| std::sync::mpsc::RecvTimeoutError::Disconnected),
+                Some(timeout) => receiver.recv_timeout(timeout),
+            };
+            match event {
+                Ok(Ok(event)) => {
+                    if matches!(event.kind, notify::EventKind::Create(_) | notify::EventKind::Modify(_)) {
+                        paths.extend(event.paths.into_iter().filter(|path| is_sound_file(path)));
+                    }
+                }
+                Ok(Err(error)) => return Err(WatchError::Notify(error)),
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => break,
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+            if !paths.is_empty() {
+                timeout = Some(config.debounce);
+            }
+        }
+        paths.sort();
+        paths.dedup();
+    }
+}
+
+fn is_sound_file(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "jfxr") && path.is_file()
+}
+
+fn render_file(config: &WatchConfig, input: &Path, callback: &mut impl FnMut(WatchEvent) -> ControlFlow<()>) -> ControlFlow<()> {
+    let output = config.output_dir.join(input.file_stem().unwrap_or_default()).with_extension("wav");
+    let render = || -> Result<(crate::Sound, Vec<f64>), WatchError> {
+        let data = std::fs::read_to_string(input).map_err(WatchError::Io)?;
+        let sound = crate::read_jfxr(&data).map_err(WatchError::Format)?;
+        let samples = crate::try_generate(&sound).map_err(WatchError::Synth)?;
+        let file = std::io::BufWriter::new(std::fs::File::create(&output).map_err(WatchError::Io)?);
+        crate::wav::write_wav(&samples, sound.sample_rate.0 as u32, file).map_err(WatchError::Io)?;
+        Ok((sound, samples))
+    };
+    match render() {
+        Ok((sound, samples)) => callback(WatchEvent::Rendered {
+            input,
+            output: &output,
+            sound: &sound,
+            samples: &samples,
+        }),
+        Err(error) => callback(WatchEvent::Failed { input, error: &error }),
+    }
+}
@@ -0,0 +1,238 @@
+//! Encoding of rendered sounds as WAV files.
+
+use std::io::Write;
+
+/// Size of the RIFF header up to the start of the sample data, in bytes.
+const HEADER_SIZE: u32 = 44;
+
+fn too_large(what: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{what} does not fit in a WAV file"))
+}
+
+/// Returns the size in bytes of the data chunk for the given number of
+/// samples, or an error if the file would be too large for the 32-bit sizes
+/// in the header.
+fn data_size(num_samples: usize, trailer_size: u32) -> std::io::Result<u32> {
+    num_samples
+        .checked_mul(2)
+        .and_then(|size| u32::try_from(size).ok())
+        .filter(|size| size.checked_add(HEADER_SIZE - 8).and_then(|size| size.checked_add(trailer_size)).is_some())
+        .ok_or_else(|| too_large("sound"))
+}
+
+/// Writes the header of a mono, 16-bit PCM WAV file. A data size of
+/// `u32::MAX` means that the size is unknown. `trailer_size` is the size of
+/// the chunks that follow the data chunk.
+fn write_header(writer: &mut impl Write, sample_rate: u32, data_size: u32, trailer_size: u32) -> std::io::Result<()> {
+    let bytes_per_second = sample_rate.checked_mul(2).ok_or_else(|| too_large("sample rate"))?;
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(HEADER_SIZE - 8).saturating_add(data_size).saturating_add(trailer_size).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?; // PCM
+    writer.write_all(&1u16.to_le_bytes())?; // Mono
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&bytes_per_second.to_le_bytes())?;
+    writer.write_all(&2u16.to_le_bytes())?; // Bytes per frame
+    writer.write_all(&16u16.to_le_bytes())?; // Bits per sample
+    writer.write_all(b"data")?;
+    writer.write_all(&data_size.to_le_bytes())?;
+    Ok(())
+}
+
+fn write_samples(writer: &mut impl Write, samples: &[f64]) -> std::io::Result<()> {
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f64).round() as i16;
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Writes samples as a mono, 16-bit PCM WAV file. Samples are clamped to the
+/// range -1 to 1.
+///
+/// The sizes in a WAV header are 32-bit, so a sound of more than about two
+/// billion samples, or a sample rate above `i32::MAX`, results in an error of
+/// kind [`std::io::ErrorKind::InvalidInput`] before anything is written:
+///
+/// ```rust
+/// let mut wav = Vec::new();
+/// let error = jfxr::wav::write_wav(&[0.0; 10], u32::MAX, &mut wav).unwrap_err();
+/// assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+/// assert!(wav.is_empty());
+/// ```
+pub fn write_wav(samples: &[f64], sample_rate: u32, mut writer: impl Write) -> std::io::Result<()> {
+    write_header(&mut writer, sample_rate, data_size(samples.len(), 0)?, 0)?;
+    write_samples(&mut writer, samples)
+}
+
+/// Writes samples as a WAV file, like [`write_wav`], followed by a `cue `
+/// chunk with a cue point at each of the given sample offsets. Cue points
+/// are numbered from 1, in the given order.
+///
+/// ```rust
+/// let mut wav = Vec::new();
+/// jfxr::wav::write_wav_with_cues(&[0.0; 100], 44100, &[0, 60], &mut wav).unwrap();
+/// let cue = &wav[44 + 200..];
+/// assert_eq!(&cue[0..4], b"cue ");
+/// assert_eq!(u32::from_le_bytes(cue[4..8].try_into().unwrap()), 4 + 2 * 24);
+/// assert_eq!(u32::from_le_bytes(cue[8..12].try_into().unwrap()), 2);
+/// // The position of the second cue point.
+/// assert_eq!(u32::from_le_bytes(cue[12 + 24 + 4..12 + 24 + 8].try_into().unwrap()), 60);
+/// assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()) as usize, wav.len() - 8);
+/// ```
+///
+/// As in [`write_wav`], sizes and cue points that do not fit in 32 bits
+/// result in an error of kind [`std::io::ErrorKind::InvalidInput`]:
+///
+/// ```rust
+/// # #[cfg(target_pointer_width = "64")] {
+/// let mut wav = Vec::new();
+/// let error = jfxr::wav::write_wav_with_cues(&[0.0; 100], 44100, &[0, 1 << 32], &mut wav).unwrap_err();
+/// assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+/// assert!(wav.is_empty());
+/// # }
+/// ```
+pub fn write_wav_with_cues(samples: &[f64], sample_rate: u32, cues: &[usize], mut writer: impl Write) -> std::io::Result<()> {
+    let cue_size = cues
+        .len()
+        .checked_mul(24)
+        .and_then(|size| u32::try_from(size).ok())
+        .and_then(|size| size.checked_add(4 + 8))
+        .ok_or_else(|| too_large("cue list"))?
+        - 8;
+    let offsets = cues
+        .iter()
+        .map(|&offset| u32::try_from(offset).map_err(|_| too_large("cue point")))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    write_header(&mut writer, sample_rate, data_size(samples.len(), 8 + cue_size)?, 8 + cue_size)?;
+    write_samples(&mut writer, samples)?;
+    writer.write_all(b"cue ")?;
+    writer.write_all(&cue_size.to_le_bytes())?;
+    writer.write_all(&(offsets.len() as u32).to_le_bytes())?;
+    for (index, offset) in offsets.iter().enumerate() {
+        writer.write_all(&(index as u32 + 1).to_le_bytes())?; // Identifier
+        writer.write_all(&offset.to_le_bytes())?; // Position
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?; // Chunk start
+        writer.write_all(&0u32.to_le_bytes())?; // Block start
+        writer.write_all(&offset.to_le_bytes())?; // Sample offset
+    }
+    Ok(())
+}
+
+/// Writes a WAV file block by block, so that the whole sound never has to be
+/// in memory. The format is the same as that of [`write_wav`]. This pairs
+/// with [`crate::Synth::stream`]. If normalization is enabled, the stream
+/// generates the sound twice, once to find its peak, so streaming saves
+/// memory but not time:
+///
+/// ```rust
+/// let mut sound = jfxr::Sound::default();
+/// sound.sustain.0 = 0.3;
+/// sound.normalization.0 = false;
+/// let mut stream = jfxr::Synth::new(&sound).stream();
+/// let mut writer = jfxr::wav::StreamWriter::new(std::io::Cursor::new(Vec::new()), 44100).unwrap();
+/// while let Some(block) = stream.next_block() {
+///     writer.write_block(block).unwrap();
+/// }
+/// let streamed = writer.finalize().unwrap().into_inner();
+///
+/// let mut whole = Vec::new();
+/// jfxr::wav::write_wav(&jfxr::generate(&sound), 44100, &mut whole).unwrap();
+/// assert_eq!(streamed, whole);
+/// ```
+///
+/// The file does not have to start at the beginning of the writer:
+///
+/// ```rust
+/// use std::io::Write;
+/// let mut cursor = std::io::Cursor::new(Vec::new());
+/// cursor.write_all(b"prefix").unwrap();
+/// let mut writer = jfxr::wav::StreamWriter::new(cursor, 44100).unwrap();
+/// writer.write_block(&[0.5; 10]).unwrap();
+/// let written = writer.finalize().unwrap().into_inner();
+///
+/// let mut whole = b"prefix".to_vec();
+/// jfxr::wav::write_wav(&[0.5; 10], 44100, &mut whole).unwrap();
+/// assert_eq!(written, whole);
+/// ```
+pub struct StreamWriter<W: Write> {
+    writer: W,
+    data_size: u32,
+    /// Number of sample bytes written, which unlike `data_size` does not
+    /// saturate, so that the start of the file can be found again.
+    written: u64,
+}
+
+impl<W: Write> StreamWriter<W> {
+    /// Writes the header, with placeholders for the sizes.
+    pub fn new(mut writer: W, sample_rate: u32) -> std::io::Result<Self> {
+        write_header(&mut writer, sample_rate, u32::MAX, 0)?;
+        Ok(Self {
+            writer,
+            data_size: 0,
+            written: 0,
+        })
+    }
+
+    /// Writes a block of samples. Samples are clamped to the range -1 to 1.
+    pub fn write_block(&mut self, samples: &[f64]) -> std::io::Result<()> {
+        write_samples(&mut self.writer, samples)?;
+        let size = samples.len() as u64 * 2;
+        self.data_size = u32::try_from(size).map_or(u32::MAX, |size| self.data_size.saturating_add(size));
+        self.written += size;
+        Ok(())
+    }
+
+    /// Finishes writing without patching the sizes in the header, for
+    /// writers that cannot seek. The sizes are left at `0xffffffff`, which
+    /// most readers treat as "until the end of the file".
+    pub fn finish(mut self) -> std::io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write + std::io::Seek> StreamWriter<W> {
+    /// Finishes writing, and patches the sizes in the header. The header is
+    /// found relative to the current position, so the file may start
+    /// anywhere in the writer.
+    pub fn finalize(mut self) -> std::io::Result<W> {
+        use std::io::SeekFrom;
+        let end = self.writer.stream_position()?;
+        let start = end
+            .checked_sub(HEADER_SIZE as u64 + self.written)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "the writer is before the end of the WAV data"))?;
+        self.writer.seek(SeekFrom::Start(start + 4))?;
+        self.writer.write_all(&(HEADER_SIZE - 8).saturating_add(self.data_size).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(start + HEADER_SIZE as u64 - 4))?;
+        self.writer.write_all(&self.data_size.to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Renders a sound and writes it as a WAV file with the given sample rate.
+/// If the sample rate differs from that of the sound, the samples are
+/// resampled with [`crate::convert::ResampleQuality::WindowedSinc`].
+///
+/// ```rust
+/// let mut sound = jfxr::Sound::default();
+/// sound.sustain.0 = 0.3;
+/// let mut wav = Vec::new();
+/// jfxr::wav::render_to_wav(&sound, 22050, &mut wav).unwrap();
+/// assert_eq!(&wav[0..4], b"RIFF");
+/// ```
+///
+/// If the sound cannot be generated, an error of kind
+/// [`std::io::ErrorKind::InvalidInput`] wrapping the [`crate::SynthError`] is
+/// returned.
+pub fn render_to_wav(sound: &crate::sound::Sound, sample_rate: u32, writer: impl Write) -> std::io::Result<()> {
+    let samples = crate::try_generate(sound)
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidInput, error))?;
+    let samples = crate::convert::resample(&samples, sound.sample_rate.0, sample_rate as f64, crate::convert::ResampleQuality::WindowedSinc);
+    write_wav(&samples, sample_rate, writer)
+}
@@ -0,0 +1,37 @@
+//! Checks that the realtime paths of the synth do not allocate once they
+//! are prepared, with the counting allocator of [`jfxr::bench`] as the
+//! global allocator. Requires the `test-util` feature.
+
+use jfxr::bench::CountingAllocator;
+use jfxr::testing::assert_allocation_free;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn realtime_paths() {
+    for example in jfxr::sound::Example::ALL {
+        for normalization in [false, true] {
+            let mut sound = jfxr::Sound::example(*example);
+            sound.normalization.0 = normalization;
+
+            let mut stream = jfxr::Synth::new(&sound).stream();
+            while assert_allocation_free(&ALLOCATOR, "stream block", || stream.next_block().is_some()) {}
+
+            let mut synth = jfxr::Synth::new(&sound);
+            let mut out = [0.0f32; jfxr::synth::RENDER_QUANTUM];
+            synth.render_quantum(&mut out);
+            while assert_allocation_free(&ALLOCATOR, "render quantum", || synth.render_quantum(&mut out)) {}
+
+            let mut source = jfxr::PullSource::new(jfxr::Synth::new(&sound));
+            let mut chunk = [0.0f32; 333];
+            while assert_allocation_free(&ALLOCATOR, "pull source read", || source.read(&mut chunk)) > 0 {}
+        }
+    }
+}
+
+#[test]
+fn notices_allocations() {
+    let result = std::panic::catch_unwind(|| assert_allocation_free(&ALLOCATOR, "vector", || vec![0.0; 10]));
+    assert!(result.is_err());
+}
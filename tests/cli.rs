@@ -0,0 +1,104 @@
+//! Tests of the command line interface, which run the `jfxr` binary.
+//! Requires the `cli` feature.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn jfxr(args: &[&Path]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_jfxr")).args(args).output().unwrap()
+}
+
+/// Returns an empty directory for the files of one test.
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("jfxr-cli-{test}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/web-tool").join(name)
+}
+
+fn read_sound(path: &Path) -> jfxr::Sound {
+    jfxr::read_jfxr(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn apply_patch() {
+    let dir = temp_dir("apply-patch");
+    let patch = dir.join("house-style.json");
+    std::fs::write(&patch, r#"{"attack": 0.01, "amplification": 150}"#).unwrap();
+    let (coin, laser) = (dir.join("coin.jfxr"), dir.join("laser.jfxr"));
+    std::fs::copy(fixture("coin.jfxr"), &coin).unwrap();
+    std::fs::copy(fixture("laser.jfxr"), &laser).unwrap();
+
+    let output = jfxr(&[Path::new("apply-patch"), &patch, &coin, &laser]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    for (path, original) in [(&coin, "coin.jfxr"), (&laser, "laser.jfxr")] {
+        let sound = read_sound(path);
+        assert_eq!(sound.attack.0, 0.01);
+        assert_eq!(sound.amplification.0, 150.0);
+        // Other parameters are left alone.
+        let original = read_sound(&fixture(original));
+        assert_eq!(sound.frequency.0, original.frequency.0);
+        assert_eq!(original.diff(&sound).len(), 2);
+    }
+    assert!(!dir.join("coin.wav").exists());
+
+    let output = jfxr(&[Path::new("apply-patch"), &patch, &coin, Path::new("--render")]);
+    assert!(output.status.success());
+    assert!(dir.join("coin.wav").exists());
+
+    // A missing file fails, and missing arguments are a usage error.
+    let output = jfxr(&[Path::new("apply-patch"), &patch, &dir.join("missing.jfxr")]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot read"));
+    let output = jfxr(&[Path::new("apply-patch"), &patch]);
+    assert_eq!(output.status.code(), Some(2));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn validate() {
+    let validate = |args: &[&Path]| {
+        let output = jfxr(&[&[Path::new("validate")], args].concat());
+        (output.status.code(), String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    // Missing fields are only a note, which does not fail.
+    let (code, stdout) = validate(&[&fixture("explosion.jfxr")]);
+    assert_eq!(code, Some(0), "{stdout}");
+    assert!(stdout.contains("note: missing fields are set to their defaults"), "{stdout}");
+    assert!(stdout.ends_with("1 file, 0 errors, 0 warnings\n"), "{stdout}");
+
+    let (code, stdout) = validate(&[&fixture("powerup.jfxr")]);
+    assert_eq!(code, Some(3), "{stdout}");
+    assert!(stdout.contains("warning: field \"sampleRate\" is out of range: 48000 is clamped to 44100"), "{stdout}");
+
+    // Files that cannot be read or parsed are errors, and directories are
+    // searched for `.jfxr` files.
+    let dir = temp_dir("validate");
+    std::fs::write(dir.join("truncated.jfxr"), "{").unwrap();
+    std::fs::write(dir.join("unversioned.jfxr"), "{}").unwrap();
+    std::fs::write(dir.join("notes.txt"), "{").unwrap();
+    let (code, stdout) = validate(&[&dir, &dir.join("missing.jfxr")]);
+    assert_eq!(code, Some(1), "{stdout}");
+    assert!(stdout.contains("truncated.jfxr:\n    error: cannot parse file: invalid JSON"), "{stdout}");
+    assert!(stdout.contains("unversioned.jfxr:\n    error: cannot parse file: "), "{stdout}");
+    assert!(stdout.contains("missing.jfxr:\n    error: cannot read file: "), "{stdout}");
+    assert!(!stdout.contains("notes.txt"), "{stdout}");
+    assert!(stdout.ends_with("3 files, 3 errors, 0 warnings\n"), "{stdout}");
+
+    let (code, stdout) = validate(&[&fixture("powerup.jfxr"), &dir.join("truncated.jfxr"), Path::new("--format"), Path::new("json")]);
+    assert_eq!(code, Some(1), "{stdout}");
+    let report = json::parse(&stdout).unwrap();
+    assert_eq!(report["summary"], json::object! { "files": 2, "errors": 1, "warnings": 1 });
+    assert!(report["files"][0]["peak"].is_number());
+    assert_eq!(report["files"][1]["diagnostics"][0]["severity"], "error");
+    assert!(report["files"][1]["peak"].is_null());
+
+    assert_eq!(validate(&[]).0, Some(2));
+    assert_eq!(validate(&[&fixture("coin.jfxr"), Path::new("--format"), Path::new("xml")]).0, Some(2));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,48 @@
+//! Compares renders against the fixtures of [`jfxr::testing`], which pin the
+//! output of the synth. Requires the `test-util` and `deterministic`
+//! features.
+
+use jfxr::parameter::{Attack, EnumParameter, Normalization, Sustain};
+use jfxr::testing::sample_hash;
+
+#[test]
+fn example_hashes() {
+    for (example, hash) in jfxr::testing::EXAMPLE_HASHES {
+        let samples = jfxr::generate(&jfxr::Sound::example(*example));
+        assert_eq!(sample_hash(&samples), *hash, "{}", example.name());
+    }
+}
+
+#[test]
+fn ui_pack_hashes() {
+    let pack = jfxr::presets::ui_pack();
+    assert_eq!(pack.len(), jfxr::testing::UI_PACK_HASHES.len());
+    for ((name, sound), (expected_name, hash)) in pack.iter().zip(jfxr::testing::UI_PACK_HASHES) {
+        assert_eq!(name, expected_name);
+        assert_eq!(sample_hash(&jfxr::generate(sound)), *hash, "{name}");
+    }
+}
+
+#[test]
+fn convert_hashes() {
+    let outputs = jfxr::testing::convert_outputs();
+    assert_eq!(outputs.len(), jfxr::testing::CONVERT_HASHES.len());
+    for ((name, samples), (expected_name, hash)) in outputs.iter().zip(jfxr::testing::CONVERT_HASHES) {
+        assert_eq!(name, expected_name);
+        assert_eq!(sample_hash(samples), *hash, "{name}");
+    }
+}
+
+#[test]
+fn first_samples() {
+    for (waveform, first_samples) in jfxr::testing::FIRST_SAMPLES {
+        let sound = jfxr::Sound {
+            waveform: *waveform,
+            attack: Attack(0.0),
+            sustain: Sustain(0.1),
+            normalization: Normalization(false),
+            ..Default::default()
+        };
+        assert_eq!(jfxr::generate(&sound)[..3], *first_samples, "{}", waveform.name());
+    }
+}
@@ -0,0 +1,188 @@
+//! Checks of the invariants of [`jfxr::testing`] over every waveform, the
+//! grid of extreme sounds and the example sounds, and of the other
+//! properties that every render must have. Requires the `test-util`
+//! feature.
+
+use jfxr::parameter::{EnumParameter, Normalization, Waveform};
+use jfxr::sound::Example;
+use jfxr::synth::Precision;
+use jfxr::{Sound, Stage, Synth, SynthError};
+
+/// Returns a sound that runs every effect stage, with sweeps.
+fn effects_sound() -> Sound {
+    let mut sound = Sound::example(Example::Laser);
+    sound.flanger_offset.0 = 5.0;
+    sound.flanger_offset_sweep.0 = 5.0;
+    sound.flanger_feedback.0 = 50.0;
+    sound.chorus_voices.0 = 3;
+    sound.low_pass_cutoff.0 = 3000.0;
+    sound.low_pass_cutoff_sweep.0 = 2000.0;
+    sound.high_pass_cutoff.0 = 200.0;
+    sound.high_pass_cutoff_sweep.0 = 300.0;
+    sound.bit_crush.0 = 8;
+    sound.compression.0 = 0.7;
+    sound.reverb_amount.0 = 50.0;
+    sound.normalization.0 = true;
+    sound
+}
+
+/// Returns the example sounds, followed by [`effects_sound`].
+fn sounds() -> Vec<Sound> {
+    Example::ALL.iter().map(|example| Sound::example(*example)).chain([effects_sound()]).collect()
+}
+
+#[test]
+fn extreme_sounds() {
+    for sound in jfxr::testing::extreme_sounds() {
+        for waveform in Waveform::VALUES {
+            let sound = Sound { waveform: *waveform, ..sound.clone() };
+            let samples = jfxr::generate(&sound);
+            jfxr::testing::assert_finite(&samples);
+            // Without normalization, the tangent waveform reaches ±2, as it
+            // does upstream.
+            let peak = if sound.normalization.0 { 1.0 } else { 2.0 };
+            jfxr::testing::assert_peak_le(&samples, peak + 1e-9);
+            jfxr::testing::assert_deterministic(&sound);
+            jfxr::testing::assert_block_size_invariant(&sound);
+            jfxr::testing::assert_prime_invariant(&sound);
+            jfxr::testing::assert_checkpoint_invariant(&sound);
+        }
+    }
+}
+
+#[test]
+fn primed_halves() {
+    for sound in sounds() {
+        jfxr::testing::assert_prime_invariant(&sound);
+        let full = jfxr::generate(&sound);
+        let dry = Synth::new(&sound).generate_dry();
+        let half = full.len() / 2;
+        let mut synth = Synth::new(&sound);
+        synth.prime_with(&dry[..half]).unwrap();
+        assert!(synth.generate()[..half].iter().all(|sample| *sample == 0.0), "{}", sound.name);
+    }
+}
+
+#[test]
+fn primed_with_flanger_before_envelope() {
+    // The flanger before the envelope belongs to the dry sound.
+    let sound = effects_sound();
+    let order = [Stage::Generator, Stage::Flanger, Stage::Envelope, Stage::Chorus, Stage::BitCrush, Stage::LowPass, Stage::HighPass, Stage::Compress, Stage::Reverb, Stage::Normalize, Stage::Amplify];
+    let synth = || {
+        let mut synth = Synth::new(&sound);
+        synth.set_stage_order(&order).unwrap();
+        synth
+    };
+    let full = synth().generate();
+    let dry = synth().generate_dry();
+    assert_ne!(dry, Synth::new(&sound).generate_dry());
+    let half = full.len() / 2;
+    let mut primed = synth();
+    primed.prime_with(&dry[..half]).unwrap();
+    assert_eq!(primed.generate()[half..], full[half..]);
+}
+
+#[test]
+fn unprimable_stage_order() {
+    // Normalizing the dry sound needs all of it.
+    let sound = effects_sound();
+    let mut order = Stage::DEFAULT_ORDER.to_vec();
+    order.retain(|stage| *stage != Stage::Normalize);
+    order.insert(1, Stage::Normalize);
+    let mut synth = Synth::new(&sound);
+    synth.force_stage_order(&order).unwrap();
+    assert_eq!(synth.prime_with(&[0.0; 100]), Err(SynthError::UnprimableStage(Stage::Normalize)));
+    assert_eq!(synth.try_generate_block(), Err(SynthError::UnprimableStage(Stage::Normalize)));
+
+    // Without normalization, or with a level known in advance, the stage
+    // does not need the whole sound.
+    let unnormalized = Sound { normalization: Normalization(false), ..sound.clone() };
+    let mut synth = Synth::new(&unnormalized);
+    synth.force_stage_order(&order).unwrap();
+    assert_eq!(synth.prime_with(&[0.0; 100]), Ok(()));
+}
+
+#[test]
+fn checkpoints_in_every_precision() {
+    for sound in sounds() {
+        for precision in [Precision::F64, Precision::F32, Precision::Q15] {
+            let synth = || {
+                let mut synth = Synth::new(&sound);
+                synth.set_precision(precision);
+                synth.set_compatibility(jfxr::Compatibility::Improved);
+                synth.set_trim(Some(-40.0));
+                synth.set_block_size(777);
+                synth
+            };
+            let full = synth().generate();
+            let mut synth = synth();
+            synth.generate_block();
+            synth.generate_block();
+            let checkpoint = synth.checkpoint().unwrap();
+            while !synth.generate_block() {}
+            assert_eq!(synth.generated(), full);
+            synth.restore(&checkpoint);
+            assert_eq!(synth.generated().len(), 2 * 777);
+            assert_eq!(synth.generate(), full, "{} {precision:?}", sound.name);
+        }
+    }
+}
+
+#[test]
+fn concurrent_renders() {
+    let sound = Sound::example(Example::Explosion);
+    let expected = jfxr::testing::sample_hash(&jfxr::generate(&sound));
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..8).map(|_| scope.spawn(|| sound.render())).collect();
+        for handle in handles {
+            assert_eq!(jfxr::testing::sample_hash(&handle.join().unwrap()), expected);
+        }
+    });
+}
+
+#[test]
+fn reduced_precision() {
+    let render = |sound: &Sound, precision: Precision| {
+        let mut synth = Synth::new(sound);
+        synth.set_precision(precision);
+        synth.generate()
+    };
+
+    for example in Example::ALL {
+        let mut sound = Sound::example(*example);
+        sound.bit_crush.0 = 0;
+        let exact = render(&sound, Precision::F64);
+        let single = render(&sound, Precision::F32);
+        let fixed = render(&sound, Precision::Q15);
+        jfxr::testing::assert_finite(&single);
+        jfxr::testing::assert_finite(&fixed);
+        let level = jfxr::analysis::rms;
+        assert!((level(&single) / level(&exact) - 1.0).abs() < 1e-6, "{}", example.name());
+        // Fixed point stays within 0.01% of the level, unless a stage
+        // overshoots full scale before normalization brings the sound back
+        // down, as the sustain punch of the coin does; fixed point clips it.
+        let unnormalized = Sound { normalization: Normalization(false), ..sound.clone() };
+        if jfxr::analysis::peak(&render(&unnormalized, Precision::F64)) <= 1.0 {
+            assert!((level(&fixed) / level(&exact) - 1.0).abs() < 1e-4, "{}", example.name());
+        }
+        assert!(fixed.iter().all(|sample| (sample * 32768.0).fract() == 0.0));
+        // The arithmetic itself is reduced, not just the output.
+        assert!(exact.iter().map(|sample| Precision::Q15.quantize(*sample)).ne(fixed.iter().copied()));
+    }
+
+    // Fixed point saturates instead of wrapping around when a stage
+    // overshoots, here by amplifying a square wave fourfold.
+    let mut sound = Sound::example(Example::Blip);
+    sound.waveform = Waveform::Square;
+    sound.normalization.0 = false;
+    sound.amplification.0 = 400.0;
+    let exact = render(&sound, Precision::F64);
+    let fixed = render(&sound, Precision::Q15);
+    assert!(exact.iter().any(|sample| sample.abs() > 2.0));
+    jfxr::testing::assert_peak_le(&fixed, 1.0);
+    // The rounded phase moves a few edges of the square wave by a sample,
+    // which flips their sign, but wrapping around would flip most samples.
+    let loud: Vec<(&f64, &f64)> = exact.iter().zip(&fixed).filter(|(exact, _)| exact.abs() >= 0.5).collect();
+    let flipped = loud.iter().filter(|(exact, fixed)| exact.signum() != fixed.signum()).count();
+    assert!(flipped * 50 < loud.len(), "{flipped} of {}", loud.len());
+}
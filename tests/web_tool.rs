@@ -0,0 +1,47 @@
+//! Compares renders against WAV files exported by the web tool, with the
+//! "Export" button at <https://jfxr.frozenfractal.com/>, from the `.jfxr`
+//! files of the same name in `tests/web-tool`. Requires the `json` and `wav`
+//! features.
+
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/web-tool").join(name)
+}
+
+/// Returns the 16-bit samples in the `data` chunk of a WAV file.
+fn data_chunk(wav: &[u8]) -> Vec<i16> {
+    let mut chunks = &wav[12..];
+    while chunks.len() >= 8 {
+        let size = u32::from_le_bytes(chunks[4..8].try_into().unwrap()) as usize;
+        let body = &chunks[8..(8 + size).min(chunks.len())];
+        if &chunks[0..4] == b"data" {
+            return body.chunks_exact(2).map(|sample| i16::from_le_bytes([sample[0], sample[1]])).collect();
+        }
+        chunks = &chunks[(8 + size + size % 2).min(chunks.len())..];
+    }
+    panic!("no data chunk");
+}
+
+/// Renders the sound of `<name>.jfxr` and compares it with `<name>.wav`.
+/// Samples may differ by one step, as the web tool renders in single
+/// precision.
+fn assert_matches_web_tool(name: &str) {
+    let sound = jfxr::read_jfxr(&std::fs::read_to_string(fixture(&format!("{name}.jfxr"))).unwrap()).unwrap();
+    let expected = data_chunk(&std::fs::read(fixture(&format!("{name}.wav"))).unwrap());
+    let mut wav = Vec::new();
+    jfxr::wav::write_wav(&jfxr::generate(&sound), sound.sample_rate.0 as u32, &mut wav).unwrap();
+    let actual = data_chunk(&wav);
+    assert_eq!(actual.len(), expected.len(), "{name}");
+    for (i, (actual, expected)) in actual.iter().zip(&expected).enumerate() {
+        assert!((*actual as i32 - *expected as i32).abs() <= 1, "{name}: sample {i} is {actual}, not {expected}");
+    }
+}
+
+/// Three harmonics of a square wave with a duty sweep, where every harmonic
+/// has its own duty cycle.
+#[test]
+#[ignore = "needs tests/web-tool/square-harmonics.wav, exported by the web tool"]
+fn square_harmonics() {
+    assert_matches_web_tool("square-harmonics");
+}